@@ -7,8 +7,9 @@ use std::time::Instant;
 
 use ariadne::sources;
 use clap::{Parser, Subcommand};
-use terbium::{AstNode, AstToken, BcTransformer};
-use terbium_analyzer::{run_analysis, AnalyzerMessageKind, AnalyzerSet, Context};
+use terbium::loader::ModuleLoader;
+use terbium::{AstNode, AstToken, BcProgram};
+use terbium_analyzer::{run_analysis, AnalyzerMessage, AnalyzerMessageKind, AnalyzerSet, Context};
 use terbium_grammar::{ParseInterface, Source, Span};
 use terbium_interpreter::DefaultInterpreter;
 
@@ -41,7 +42,7 @@ enum Command {
     /// Disassembles the Terbium source code into Terbium bytecode.
     #[clap(arg_required_else_help = true)]
     Dis {
-        /// The input file containing Terbium source code.
+        /// The input file containing Terbium source code, or precompiled `.trbc` bytecode.
         #[clap(parse(from_os_str))]
         file: Option<PathBuf>,
 
@@ -53,9 +54,19 @@ enum Command {
         /// Not including this flag will output the bytecode in a readable format.
         ///
         /// Useful if you want to compile Terbium source code into bytecode and save
-        /// it in a separate file.
+        /// it in a separate `.trbc` file, which can be passed as the input file of
+        /// any command that runs bytecode.
         #[clap(short, long)]
         raw: bool,
+
+        /// Leave out the debug info mapping instructions back to the source code, which
+        /// makes the bytecode smaller, but runtime errors will not tell where they occurred.
+        #[clap(long)]
+        strip: bool,
+
+        /// Do not load the prelude (`builtin.trb`) before the program.
+        #[clap(long)]
+        no_prelude: bool,
     },
     /// Interprets the Terbium source code expression, pops the last object on the stack,
     /// and writes the object represented in repr/debug form into standard output.
@@ -64,13 +75,17 @@ enum Command {
     #[clap(arg_required_else_help = true)]
     #[clap(aliases(&["evaluate", "e"]))]
     Eval {
-        /// The input file containing Terbium source code.
+        /// The input file containing Terbium source code, or precompiled `.trbc` bytecode.
         #[clap(parse(from_os_str))]
         file: Option<PathBuf>,
 
         /// The direct source code to parse. Cannot be used with the file argument.
         #[clap(short, long)]
         code: Option<String>,
+
+        /// Do not load the prelude (`builtin.trb`) before the program.
+        #[clap(long)]
+        no_prelude: bool,
     },
     /// Analyzes the Terbium source code and checks for any potential runtime errors.
     #[clap(arg_required_else_help = true)]
@@ -88,14 +103,11 @@ enum Command {
 
 type PartialCache = Vec<(Source, String)>;
 
-fn run_ast<N>(
+fn read_input(
     file: Option<PathBuf>,
     code: Option<String>,
-) -> Result<(N, PartialCache), Box<dyn std::error::Error>>
-where
-    N: ParseInterface,
-{
-    let (code, src) = match (file, code) {
+) -> Result<(String, Source), Box<dyn std::error::Error>> {
+    Ok(match (file, code) {
         (Some(file), None) => (
             std::fs::read_to_string(file.clone())?,
             Source::from_path(file),
@@ -103,7 +115,17 @@ where
         (None, Some(code)) => (code, Source::default()),
         (Some(_), Some(_)) => return Err("must provide only one of file or code".into()),
         (None, None) => return Err("must provide one of file or code".into()),
-    };
+    })
+}
+
+fn run_ast<N>(
+    file: Option<PathBuf>,
+    code: Option<String>,
+) -> Result<(N, PartialCache), Box<dyn std::error::Error>>
+where
+    N: ParseInterface,
+{
+    let (code, src) = read_input(file, code)?;
 
     let s = vec![(src.clone(), code.clone())];
 
@@ -132,6 +154,18 @@ where
 
     let instant = Instant::now();
     let messages = run_analysis(&analyzers, ctx)?;
+
+    if report(messages, instant, &src) {
+        exit(-1);
+    }
+
+    // we can unwrap here since analysis unwraps for us
+    Ok(N::parse(tokens).unwrap())
+}
+
+/// Writes the analyzer messages and a summary into standard error.
+/// Returns whether any of the messages is an error.
+fn report(messages: Vec<AnalyzerMessage>, instant: Instant, cache: &[(Source, String)]) -> bool {
     #[allow(
         clippy::cast_precision_loss,
         reason = "The timing is provided on a best-effort basis, precision loss is tolerable."
@@ -158,7 +192,7 @@ where
             }
         }
 
-        message.write(sources(src.clone()), stderr());
+        message.write(sources(cache.to_vec()), stderr());
     }
 
     eprintln!("completed analysis in {} ms", elapsed);
@@ -173,12 +207,46 @@ where
         if error_count == 1 { "" } else { "s" },
     );
 
-    if should_exit {
+    should_exit
+}
+
+fn load(
+    file: Option<PathBuf>,
+    code: Option<String>,
+    prelude: bool,
+) -> Result<BcProgram, Box<dyn std::error::Error>> {
+    // Precompiled bytecode already contains the prelude it was compiled with
+    if let Some(ref file) = file {
+        if file.extension().is_some_and(|ext| ext == "trbc") {
+            return Ok(BcProgram::from_bytes(&std::fs::read(file)?)?);
+        }
+    }
+
+    let dir = match file {
+        Some(ref file) => file.parent().map(PathBuf::from).unwrap_or_default(),
+        None => std::env::current_dir()?,
+    };
+    let (code, src) = read_input(file, code)?;
+
+    let mut loader = ModuleLoader::default();
+    loader.prelude = prelude;
+
+    let instant = Instant::now();
+    let result = loader.load_main(src, code, &dir);
+    let cache = loader.sources().to_vec();
+
+    if report(loader.take_messages(), instant, &cache) {
+        exit(-1);
+    }
+    if let Err(e) = result {
+        e.write(&cache, stderr());
         exit(-1);
     }
 
-    // we can unwrap here since analysis unwraps for us
-    Ok(N::parse(tokens).unwrap())
+    let mut program = loader.program();
+    program.resolve();
+
+    Ok(program)
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -194,14 +262,17 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("{:?}", node);
             }
         }
-        Command::Dis { file, code, raw } => {
-            let body = analyze(file, code)?;
-
-            let mut transformer = BcTransformer::default();
-            transformer.interpret_body(None, body);
-
-            let mut program = transformer.program();
-            program.resolve();
+        Command::Dis {
+            file,
+            code,
+            raw,
+            strip,
+            no_prelude,
+        } => {
+            let mut program = load(file, code, !no_prelude)?;
+            if strip {
+                program.strip_debug_info();
+            }
 
             let mut stdout = std::io::stdout();
             if raw {
@@ -211,14 +282,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 program.dis(&mut stdout)?;
             }
         }
-        Command::Eval { file, code } => {
-            let body = analyze(file, code)?;
-
-            let mut transformer = BcTransformer::default();
-            transformer.interpret_body(None, body);
-
-            let mut program = transformer.program();
-            program.resolve();
+        Command::Eval {
+            file,
+            code,
+            no_prelude,
+        } => {
+            let program = load(file, code, !no_prelude)?;
 
             let mut interpreter = DefaultInterpreter::default();
             interpreter.run_bytecode(&program);
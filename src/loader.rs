@@ -0,0 +1,481 @@
+//! Resolves `require` statements to Terbium source files and lowers every required module
+//! into a single bytecode program.
+//!
+//! A require path is looked up in each search path in order, the first being the bundled
+//! `lib` directory followed by the paths listed in the `TERBIUM_PATH` environment variable.
+//! Relative paths (`.fs`) are looked up next to the requiring file instead. A path `a.b`
+//! resolves to the file `a/b.trb`, or to the package `a/b/lib.trb` if no such file exists.
+//!
+//! Every module is parsed, analyzed and lowered once. Its body runs before the code that first
+//! requires it, and the resulting module object is stored in a variable named after its
+//! canonical path, which is where the lowered `require` statements take their items from.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use ariadne::{sources, Label, Report, ReportKind};
+use terbium_analyzer::{run_analysis, AnalyzerMessage, AnalyzerMessageKind, AnalyzerSet, Context};
+use terbium_bytecode::{Interpreter as BcTransformer, Program};
+use terbium_grammar::{
+    Body, Error, Node, ParseInterface, RequireItem, RequireItems, RequirePath, Source, Span,
+    Spanned, Target, Token,
+};
+
+/// The environment variable holding additional search paths, separated like `PATH`.
+pub const PATH_VAR: &str = "TERBIUM_PATH";
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(PathBuf, std::io::Error),
+    Parse(Vec<Error>),
+    /// The module contains errors reported by the analyzer, see [`ModuleLoader::messages`].
+    Analysis(Source),
+    NotFound {
+        path: RequirePath,
+        span: Span,
+    },
+    NotExported {
+        name: String,
+        module: PathBuf,
+        span: Span,
+    },
+    /// The modules being loaded when the cycle was found, the first and last being the same.
+    Cycle(Vec<PathBuf>),
+    Unsupported(&'static str),
+}
+
+impl LoadError {
+    /// Write the error to the specified writer, using `cache` as the sources of the spans.
+    ///
+    /// # Panics
+    /// * Panic when writing to writer failed.
+    pub fn write(self, cache: &[(Source, String)], mut writer: impl Write) {
+        let (span, label) = match self {
+            Self::Parse(errors) => {
+                for error in errors {
+                    error.write(sources(cache.to_vec()), &mut writer);
+                }
+
+                return;
+            }
+            Self::NotFound { ref span, .. } => (span.clone(), "required here"),
+            Self::NotExported { ref span, .. } => (span.clone(), "not exported by the module"),
+            _ => return writeln!(writer, "error: {}", self).unwrap(),
+        };
+
+        Report::build(ReportKind::Error, span.src(), span.start())
+            .with_message(self.to_string())
+            .with_label(Label::new(span).with_message(label))
+            .finish()
+            .write(sources(cache.to_vec()), writer)
+            .unwrap();
+    }
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::Io(path, e) => write!(f, "could not read {}: {}", path.display(), e),
+            Self::Parse(errors) => write!(f, "{} syntax error(s)", errors.len()),
+            Self::Analysis(source) => {
+                write!(f, "could not compile {} due to analysis errors", source)
+            }
+            Self::NotFound { path, .. } => write!(f, "could not find module {}", path),
+            Self::NotExported { name, module, .. } => {
+                write!(f, "module {} does not export {}", module.display(), name)
+            }
+            Self::Cycle(chain) => write!(
+                f,
+                "cyclic require: {}",
+                chain
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            ),
+            Self::Unsupported(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Module {
+    /// The path the lowered code refers to the module by, see [`BcTransformer::module_var`].
+    pub name: RequirePath,
+    /// The canonical path of the module's source file.
+    pub path: PathBuf,
+    /// The names other modules may require from this module, in declaration order.
+    pub exports: Vec<String>,
+}
+
+pub struct ModuleLoader {
+    pub search_paths: Vec<PathBuf>,
+    /// Whether to load `builtin.trb` from the search paths and bind its exports everywhere.
+    pub prelude: bool,
+    pub analyzers: AnalyzerSet,
+    modules: HashMap<PathBuf, Module>,
+    loading: Vec<PathBuf>,
+    prelude_exports: Option<Vec<String>>,
+    sources: Vec<(Source, String)>,
+    messages: Vec<AnalyzerMessage>,
+    transformer: BcTransformer,
+}
+
+impl ModuleLoader {
+    /// Creates a loader looking up modules in `lib`, then in the paths listed in [`PATH_VAR`].
+    #[must_use]
+    pub fn new(lib: PathBuf) -> Self {
+        let mut search_paths = vec![lib];
+        if let Some(paths) = std::env::var_os(PATH_VAR) {
+            search_paths.extend(std::env::split_paths(&paths));
+        }
+
+        Self {
+            search_paths,
+            prelude: true,
+            analyzers: AnalyzerSet::default(),
+            modules: HashMap::new(),
+            loading: Vec::new(),
+            prelude_exports: None,
+            sources: Vec::new(),
+            messages: Vec::new(),
+            transformer: BcTransformer::default(),
+        }
+    }
+
+    /// The sources of every file read so far, for use as a diagnostic cache.
+    #[must_use]
+    pub fn sources(&self) -> &[(Source, String)] {
+        &self.sources
+    }
+
+    /// The analyzer messages of every module analyzed so far.
+    #[must_use]
+    pub fn messages(&self) -> &[AnalyzerMessage] {
+        &self.messages
+    }
+
+    pub fn take_messages(&mut self) -> Vec<AnalyzerMessage> {
+        std::mem::take(&mut self.messages)
+    }
+
+    #[must_use]
+    pub fn module(&self, path: &Path) -> Option<&Module> {
+        self.modules.get(path)
+    }
+
+    /// Loads the main program and every module it requires. Relative requires are resolved
+    /// from `dir`.
+    ///
+    /// # Errors
+    /// * The program or one of its modules could not be read, parsed, analyzed or resolved.
+    pub fn load_main(&mut self, source: Source, code: String, dir: &Path) -> Result<(), LoadError> {
+        self.load_prelude()?;
+
+        let (body, _) = self.prepare(source, code, dir)?;
+        self.transformer.interpret_body(None, body);
+
+        Ok(())
+    }
+
+    /// Returns the program built from everything loaded so far, along with the sources of
+    /// every file read as its debug info.
+    #[must_use]
+    pub fn program(self) -> Program {
+        let mut program = self.transformer.program();
+        for (src, code) in self.sources {
+            program.add_source(src, code);
+        }
+
+        program
+    }
+
+    fn load_prelude(&mut self) -> Result<(), LoadError> {
+        if !self.prelude || self.prelude_exports.is_some() {
+            return Ok(());
+        }
+
+        let path = RequirePath {
+            relative: false,
+            segments: vec!["builtin".to_string()],
+        };
+        let module = match self.find(Path::new(""), &path) {
+            Some(file) => self.load(&file)?,
+            None => {
+                self.prelude_exports = Some(Vec::new());
+                return Ok(());
+            }
+        };
+
+        self.transformer.interpret_node(
+            None,
+            Spanned::new(
+                Node::Require {
+                    items: RequireItems::Glob,
+                    from: Some(module.name),
+                },
+                Span::default(),
+            ),
+        );
+        self.prelude_exports = Some(module.exports);
+
+        Ok(())
+    }
+
+    /// Finds the file of the module at `path`, resolving relative paths from `dir`.
+    fn find(&self, dir: &Path, path: &RequirePath) -> Option<PathBuf> {
+        let roots = if path.relative {
+            vec![dir]
+        } else {
+            self.search_paths.iter().map(PathBuf::as_path).collect()
+        };
+
+        roots.into_iter().find_map(|root| {
+            let base = path
+                .segments
+                .iter()
+                .fold(root.to_path_buf(), |p, s| p.join(s));
+            let file = base.with_extension("trb");
+
+            if !path.segments.is_empty() && file.is_file() {
+                return Some(file);
+            }
+
+            let package = base.join("lib.trb");
+            package.is_file().then_some(package)
+        })
+    }
+
+    fn load(&mut self, file: &Path) -> Result<Module, LoadError> {
+        let path = file
+            .canonicalize()
+            .map_err(|e| LoadError::Io(file.to_path_buf(), e))?;
+
+        if let Some(module) = self.modules.get(&path) {
+            return Ok(module.clone());
+        }
+
+        if let Some(start) = self.loading.iter().position(|p| p == &path) {
+            let mut chain = self.loading[start..].to_vec();
+            chain.push(path);
+
+            return Err(LoadError::Cycle(chain));
+        }
+
+        self.loading.push(path.clone());
+        let module = self.load_uncached(path.clone());
+        self.loading.pop();
+
+        let module = module?;
+        self.modules.insert(path, module.clone());
+
+        Ok(module)
+    }
+
+    fn load_uncached(&mut self, path: PathBuf) -> Result<Module, LoadError> {
+        let code = std::fs::read_to_string(&path).map_err(|e| LoadError::Io(path.clone(), e))?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+        let (Body(body, _), exports) = self.prepare(Source::from_path(&path), code, dir)?;
+        let name = RequirePath {
+            relative: false,
+            segments: vec![path.display().to_string()],
+        };
+        self.transformer.interpret_module(&name, body, &exports);
+
+        Ok(Module {
+            name,
+            path,
+            exports,
+        })
+    }
+
+    /// Parses and analyzes the code, loading its requirements along the way.
+    /// Returns the body with its requires resolved, along with its exported names.
+    fn prepare(
+        &mut self,
+        source: Source,
+        code: String,
+        dir: &Path,
+    ) -> Result<(Body, Vec<String>), LoadError> {
+        self.sources.push((source.clone(), code.clone()));
+
+        let tokens =
+            Vec::<(Token, Span)>::from_string(source.clone(), code).map_err(LoadError::Parse)?;
+        if tokens.is_empty() {
+            return Ok((Body(Vec::new(), false), Vec::new()));
+        }
+
+        let Body(body, return_last) = Body::parse(tokens.clone()).map_err(LoadError::Parse)?;
+
+        let mut exports = Vec::new();
+        let mut modules = HashMap::new();
+        let mut resolved = Vec::with_capacity(body.len());
+
+        for node in body {
+            let (node, span) = node.into_node_span();
+
+            match node {
+                Node::Require { items, from } => {
+                    for (items, from) in
+                        self.resolve_require(dir, items, from, &span, &mut exports, &mut modules)?
+                    {
+                        resolved.push(Spanned::new(Node::Require { items, from }, span.clone()));
+                    }
+                }
+                node => {
+                    declared_names(&node, &mut exports);
+                    resolved.push(Spanned::new(node, span));
+                }
+            }
+        }
+
+        let mut ctx = Context::from_tokens(self.sources.clone(), tokens);
+        ctx.modules = modules;
+        for name in self.prelude_exports.iter().flatten() {
+            ctx.store_required(name.clone(), Span::default());
+        }
+
+        let messages = run_analysis(&self.analyzers, ctx).map_err(LoadError::Unsupported)?;
+        let failed = messages
+            .iter()
+            .any(|m| matches!(m.kind, AnalyzerMessageKind::Alert(k) if k.is_error()));
+        self.messages.extend(messages);
+
+        if failed {
+            return Err(LoadError::Analysis(source));
+        }
+
+        let mut seen = Vec::with_capacity(exports.len());
+        for name in exports {
+            if !name.starts_with('_') && !seen.contains(&name) {
+                seen.push(name);
+            }
+        }
+
+        Ok((Body(resolved, return_last), seen))
+    }
+
+    /// Loads the modules a require statement refers to, returning the equivalent requires
+    /// referring to the loaded modules by name.
+    fn resolve_require(
+        &mut self,
+        dir: &Path,
+        items: RequireItems,
+        from: Option<RequirePath>,
+        span: &Span,
+        bindings: &mut Vec<String>,
+        modules: &mut HashMap<String, Vec<String>>,
+    ) -> Result<Vec<(RequireItems, Option<RequirePath>)>, LoadError> {
+        let mut resolved = Vec::new();
+
+        let items = match (items, from) {
+            (RequireItems::Glob, Some(from)) => {
+                let module = self.require(dir, &from, span)?;
+
+                bindings.extend(module.exports.iter().cloned());
+                modules.insert(from.to_string(), module.exports);
+                resolved.push((RequireItems::Glob, Some(module.name)));
+
+                return Ok(resolved);
+            }
+            (RequireItems::Glob, None) => unreachable!("glob requires always specify a module"),
+            (RequireItems::Named(items), from) => {
+                items.into_iter().map(move |item| (item, from.clone()))
+            }
+        };
+
+        for (item, from) in items {
+            let binding = item.binding().to_string();
+            let mut path = from.clone().unwrap_or(RequirePath {
+                relative: false,
+                segments: Vec::new(),
+            });
+            path.segments.push(item.name.clone());
+
+            // Items of a package may name its submodules, which take precedence over its exports
+            let submodule = from.is_none() || self.find(dir, &path).is_some();
+
+            if submodule {
+                let module = self.require(dir, &path, span)?;
+
+                resolved.push((
+                    RequireItems::Named(vec![RequireItem {
+                        name: module.name.to_string(),
+                        alias: Some(binding.clone()),
+                    }]),
+                    None,
+                ));
+            } else {
+                let from = from.unwrap();
+                let module = self.require(dir, &from, span)?;
+
+                if !module.exports.contains(&item.name) {
+                    return Err(LoadError::NotExported {
+                        name: item.name,
+                        module: module.path,
+                        span: span.clone(),
+                    });
+                }
+
+                modules.insert(from.to_string(), module.exports);
+                resolved.push((RequireItems::Named(vec![item]), Some(module.name)));
+            }
+
+            bindings.push(binding);
+        }
+
+        Ok(resolved)
+    }
+
+    fn require(
+        &mut self,
+        dir: &Path,
+        path: &RequirePath,
+        span: &Span,
+    ) -> Result<Module, LoadError> {
+        let file = self.find(dir, path).ok_or_else(|| LoadError::NotFound {
+            path: path.clone(),
+            span: span.clone(),
+        })?;
+
+        self.load(&file)
+    }
+}
+
+impl Default for ModuleLoader {
+    /// Creates a loader looking up modules in the `lib` directory bundled with Terbium.
+    fn default() -> Self {
+        Self::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("lib"))
+    }
+}
+
+/// Collects the names a top-level statement declares.
+fn declared_names(node: &Node, names: &mut Vec<String>) {
+    fn target_names(target: &Target, names: &mut Vec<String>) {
+        match target {
+            Target::Ident(name) => names.push(name.clone()),
+            Target::Array(targets) => {
+                for target in targets {
+                    target_names(target.node(), names);
+                }
+            }
+            Target::Rest(target) => target_names(target.node(), names),
+            Target::Attr(..) | Target::Index(..) | Target::SelfRef => (),
+        }
+    }
+
+    match node {
+        Node::Declare { targets, .. } => {
+            for target in targets {
+                target_names(target.node(), names);
+            }
+        }
+        Node::Func { name, .. } | Node::Class { name, .. } | Node::Trait { name, .. } => {
+            names.push(name.clone());
+        }
+        _ => (),
+    }
+}
@@ -4,10 +4,16 @@
 
 pub mod util;
 
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use terbium_grammar::ast::{
+    SpannedField, SpannedGenericParam, SpannedMethod, SpannedNode, SpannedParam,
+};
 use terbium_grammar::{
-    Body, Expr, Node, Operator, ParseInterface, Source, Span, Spanned, Target, Token, TypeExpr,
+    bind_args, ArgumentError, ArrayLength, Body, CatchClause, Expr, Field, InterpolatedPart,
+    MatchArm, Method, Node, Operator, Param, ParamKind, ParamSpec, ParseInterface, Pattern,
+    RequireItems, Source, Span, Spanned, Target, Token, TypeExpr,
 };
 use util::to_snake_case;
 
@@ -342,6 +348,57 @@ impl AnalyzerMessage {
         )
     }
 
+    #[must_use]
+    pub fn invalid_cast(
+        span: Span,
+        value_ty: String,
+        value_span: Span,
+        target_ty: String,
+        target_span: Span,
+    ) -> Self {
+        Self::new(
+            AnalyzerMessageKind::Alert(AnalyzerKind::InvalidCasts),
+            span,
+            |report, color| {
+                report
+                    .with_message(format!("cannot cast {} to {}", value_ty, target_ty))
+                    .with_label(
+                        Label::new(value_span)
+                            .with_message(format!("this is of type {}", value_ty))
+                            .with_color(Color::Cyan)
+                            .with_order(0),
+                    )
+                    .with_label(
+                        Label::new(target_span)
+                            .with_message(format!("which can never be converted to {}", target_ty))
+                            .with_color(color)
+                            .with_order(1),
+                    )
+                    .with_help(
+                        "only objects of builtin types, or which define `op cast`, can be cast",
+                    )
+            },
+        )
+    }
+
+    #[must_use]
+    pub fn invalid_cast_target(span: Span, target_span: Span) -> Self {
+        Self::new(
+            AnalyzerMessageKind::Alert(AnalyzerKind::InvalidCasts),
+            span,
+            |report, color| {
+                report
+                    .with_message("invalid cast target")
+                    .with_label(
+                        Label::new(target_span)
+                            .with_message("this is not a builtin type")
+                            .with_color(color),
+                    )
+                    .with_help("only int, float, string and bool can be cast to")
+            },
+        )
+    }
+
     #[must_use]
     pub fn unbalanced_if_statement(
         span: Span,
@@ -406,6 +463,135 @@ impl AnalyzerMessage {
         )
     }
 
+    #[must_use]
+    pub fn unbalanced_match_arms(
+        span: Span,
+        first_span: Span,
+        first_ty: String,
+        second_span: Span,
+        second_ty: String,
+    ) -> Self {
+        Self::new(
+            AnalyzerMessageKind::Alert(AnalyzerKind::UnbalancedMatchArms),
+            span.clone(),
+            |report, color| {
+                report
+                    .with_message("return types of match arms are unbalanced")
+                    .with_label(
+                        Label::new(first_span)
+                            .with_message(format!("this resolves to {}", first_ty))
+                            .with_color(Color::Cyan)
+                            .with_order(0),
+                    )
+                    .with_label(
+                        Label::new(second_span)
+                            .with_message(format!(
+                                "this resolves to {}, which is incompatible with {}",
+                                second_ty, first_ty
+                            ))
+                            .with_color(color)
+                            .with_order(1),
+                    )
+                    .with_help("try adding semicolons or balancing the types")
+            },
+        )
+    }
+
+    #[must_use]
+    pub fn unbalanced_match_arms_no_else(span: Span, first_span: Span, first_ty: String) -> Self {
+        Self::new(
+            AnalyzerMessageKind::Alert(AnalyzerKind::UnbalancedMatchArms),
+            span.clone(),
+            |report, color| {
+                report
+                    .with_message("return types of match arms are unbalanced")
+                    .with_label(
+                        Label::new(first_span)
+                            .with_message(format!(
+                                "this resolves to {}, which is not null",
+                                first_ty
+                            ))
+                            .with_color(color)
+                            .with_order(0),
+                    )
+                    .with_label(
+                        Label::new(span)
+                            .with_message(
+                                "note that the lack of an `else` arm causes the possibility of null",
+                            )
+                            .with_color(color)
+                            .with_order(1),
+                    )
+                    .with_help("try adding an `else` arm or semicolons")
+            },
+        )
+    }
+
+    #[must_use]
+    pub fn unnecessary_null_check(
+        operator: &str,
+        ty: String,
+        span: Span,
+        subject_span: Span,
+    ) -> Self {
+        Self::new(
+            AnalyzerMessageKind::Alert(AnalyzerKind::UnnecessaryNullChecks),
+            span,
+            |report, color| {
+                report
+                    .with_message(format!("unnecessary use of `{}`", operator))
+                    .with_label(
+                        Label::new(subject_span)
+                            .with_message(format!("this resolves to {}, which is never null", ty))
+                            .with_color(color),
+                    )
+                    .with_help(format!("remove the `{}`", operator))
+            },
+        )
+    }
+
+    #[must_use]
+    pub fn destructuring_length_mismatch(
+        expected: usize,
+        has_rest: bool,
+        found: usize,
+        span: Span,
+        value_span: Span,
+    ) -> Self {
+        Self::new(
+            AnalyzerMessageKind::Alert(AnalyzerKind::DestructuringLengthMismatch),
+            span.clone(),
+            |report, color| {
+                let report = report
+                    .with_message("destructuring target does not match the length of its value")
+                    .with_label(
+                        Label::new(span)
+                            .with_message(format!(
+                                "this target takes {}{} items",
+                                if has_rest { "at least " } else { "" },
+                                expected,
+                            ))
+                            .with_color(color)
+                            .with_order(0),
+                    )
+                    .with_label(
+                        Label::new(value_span)
+                            .with_message(format!("but this value has {} items", found))
+                            .with_color(Color::Cyan)
+                            .with_order(1),
+                    );
+
+                if !has_rest && found > expected {
+                    report.with_help(
+                        "add a rest target such as `...rest` to take the remaining items",
+                    )
+                } else {
+                    report
+                }
+            },
+        )
+    }
+
     #[must_use]
     pub fn incompatible_types(
         span: Span,
@@ -445,6 +631,114 @@ impl AnalyzerMessage {
         )
     }
 
+    #[must_use]
+    pub fn missing_trait_methods(
+        class: &str,
+        r#trait: &str,
+        missing: Vec<(String, Span)>,
+        base_span: Span,
+        span: Span,
+    ) -> Self {
+        Self::new(
+            AnalyzerMessageKind::Alert(AnalyzerKind::MissingTraitMethods),
+            span.clone(),
+            |report, color| {
+                let names = missing
+                    .iter()
+                    .map(|(name, _)| format!("{:?}", name))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                let report = report
+                    .with_message(format!(
+                        "class {:?} does not implement all required methods of trait {:?}",
+                        class, r#trait,
+                    ))
+                    .with_label(
+                        Label::new(base_span)
+                            .with_message(format!("missing {} required by this trait", names))
+                            .with_color(color)
+                            .with_order(0),
+                    )
+                    .with_help("implement the missing methods in the class body");
+
+                missing
+                    .into_iter()
+                    .enumerate()
+                    .fold(report, |report, (i, (name, span))| {
+                        report.with_label(
+                            Label::new(span)
+                                .with_message(format!("{:?} is declared here", name))
+                                .with_color(Color::Cyan)
+                                .with_order(i as i32 + 1),
+                        )
+                    })
+            },
+        )
+    }
+
+    #[must_use]
+    pub fn loop_control_outside_loop(keyword: &str, label: Option<&str>, span: Span) -> Self {
+        Self::new(
+            AnalyzerMessageKind::Alert(AnalyzerKind::LoopControlOutsideLoop),
+            span.clone(),
+            |report, color| {
+                if let Some(label) = label {
+                    report
+                        .with_message(format!("no enclosing loop is labelled {:?}", label))
+                        .with_label(
+                            Label::new(span)
+                                .with_message(format!("this `{}` targets {:?}", keyword, label))
+                                .with_color(color),
+                        )
+                        .with_help(format!(
+                            "label the loop to target by prefixing it with `{}@`",
+                            label,
+                        ))
+                } else {
+                    report
+                        .with_message(format!("`{}` outside of a loop", keyword))
+                        .with_label(
+                            Label::new(span)
+                                .with_message(format!("cannot `{}` here", keyword))
+                                .with_color(color),
+                        )
+                        .with_help("`break` and `continue` can only be used inside a loop")
+                }
+            },
+        )
+    }
+
+    #[must_use]
+    pub fn invalid_arguments(
+        name: &str,
+        error: &ArgumentError,
+        span: Span,
+        declared_span: Span,
+    ) -> Self {
+        Self::new(
+            AnalyzerMessageKind::Alert(AnalyzerKind::InvalidArguments),
+            span.clone(),
+            |report, color| {
+                report
+                    .with_message(format!("invalid arguments for `{}`", name))
+                    .with_label(
+                        Label::new(span)
+                            .with_message(error.to_string())
+                            .with_color(color)
+                            .with_order(1),
+                    )
+                    .with_label(
+                        Label::new(declared_span)
+                            .with_message(format!("`{}` is declared here", name))
+                            .with_color(Color::Cyan)
+                            .with_order(0),
+                    )
+                    .with_help("check the parameters the function was declared with")
+            },
+        )
+    }
+
     pub fn uninferable_type(name: &str, span: Span) -> Self {
         Self::new(
             AnalyzerMessageKind::Alert(AnalyzerKind::UninferableTypes),
@@ -506,6 +800,17 @@ impl Display for PrimitiveType {
 }
 
 impl PrimitiveType {
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "int" => Self::Int,
+            "float" => Self::Float,
+            "string" => Self::String,
+            "bool" => Self::Bool,
+            _ => return None,
+        })
+    }
+
     pub fn get_unary_op_outcome(&self, op: Operator) -> Option<Type> {
         type Op = Operator;
 
@@ -538,7 +843,7 @@ impl PrimitiveType {
             ) => Type::Primitive(Self::Int),
             (
                 Self::Float | Self::Int,
-                Op::Add | Op::Sub | Op::Mul | Op::Div | Op::Pow | Op::Mod,
+                Op::Add | Op::Sub | Op::Mul | Op::Div | Op::TrueDiv | Op::Pow | Op::Mod,
                 Type::Primitive(Self::Float | Self::Int),
             ) => Type::Primitive(Self::Float),
             (
@@ -546,9 +851,20 @@ impl PrimitiveType {
                 Op::Lt | Op::Le | Op::Gt | Op::Ge | Op::Eq | Op::Ne,
                 Type::Primitive(Self::Float | Self::Int),
             ) => Type::Primitive(Self::Bool),
-            (Self::String, Op::Add | Op::Mul, Type::Primitive(Self::String)) => {
-                Type::Primitive(Self::String)
-            }
+            (Self::String, Op::Add, Type::Primitive(Self::String))
+            | (Self::String, Op::Mul, Type::Primitive(Self::Int)) => Type::Primitive(Self::String),
+            (
+                Self::String,
+                Op::Lt | Op::Le | Op::Gt | Op::Ge | Op::Eq | Op::Ne,
+                Type::Primitive(Self::String),
+            )
+            | (
+                Self::Bool,
+                Op::BitOr | Op::BitAnd | Op::BitXor | Op::Eq | Op::Ne,
+                Type::Primitive(Self::Bool),
+            )
+            | (_, Op::Or | Op::And, Type::Primitive(_)) => Type::Primitive(Self::Bool),
+            (Self::Int, Op::Range, Type::Primitive(Self::Int)) => Type::Range,
             _ => return None,
         })
     }
@@ -578,6 +894,14 @@ pub enum Type {
     Array(Box<Self>, Option<u32>),
     Tuple(Vec<Self>),
     Func(Vec<Type>, Box<Type>),
+    /// An integer range created with the `..` operator
+    Range,
+    /// A generic type parameter, substituted with its type argument when the function
+    /// declaring it is called.
+    Generic(String),
+    /// The type of an expression which never finishes evaluating, such as a call to a
+    /// function which always throws. It is compatible with every other type.
+    Never,
     Null,
     Any,
 
@@ -599,6 +923,8 @@ impl Type {
             (op, Self::And(a, b)) => a.get_unary_op_outcome(op).or(b.get_unary_op_outcome(op))?,
             (Operator::Not, _) => Self::Primitive(PrimitiveType::Bool),
             (_, t @ (Self::Any | Self::Unknown)) => t.clone(),
+            // TODO: check the operators supported by the bounds of the type parameter
+            (_, Self::Generic(_)) => Self::Any,
             _ => return None,
         })
     }
@@ -608,6 +934,12 @@ impl Type {
             (Self::Deferred(a), op, Self::Deferred(b)) => Self::Deferred(Box::new(
                 DeferredType::ApplyBinary(op, a.clone(), b.clone()),
             )),
+            (Self::Generic(_), _, _) | (_, _, Self::Generic(_)) => Self::Any,
+            (Self::Any, _, _) | (_, _, Self::Any) => Self::Any,
+            (Self::Unknown, _, _) | (_, _, Self::Unknown) => Self::Unknown,
+            // Any object can be compared with null
+            (Self::Null, Operator::Eq | Operator::Ne, _)
+            | (_, Operator::Eq | Operator::Ne, Self::Null) => Self::Primitive(PrimitiveType::Bool),
             (Self::Primitive(a), op, b) => a.get_binary_op_outcome(op, b)?,
             (Self::Union(box a, box b), op, c) | (c, op, Self::Union(box a, box b)) => a
                 .get_binary_op_outcome(op, c)
@@ -622,16 +954,31 @@ impl Type {
             (Self::Tuple(a), Operator::Add, Self::Tuple(b)) => {
                 Self::Tuple(a.clone().into_iter().chain(b.clone()).collect())
             }
-            (Self::Any, _, _) | (_, _, Self::Any) => Self::Any,
-            (Self::Unknown, _, _) | (_, _, Self::Unknown) => Self::Unknown,
             _ => return None,
         })
     }
 
+    /// Whether objects of this type may be cast to the primitive type. Casts which can only
+    /// fail at runtime, such as parsing a string as an int, are allowed.
+    pub fn can_cast_to(&self, target: PrimitiveType) -> bool {
+        match (self, target) {
+            (_, PrimitiveType::String | PrimitiveType::Bool) | (Self::Primitive(_), _) => true,
+            (Self::Union(box a, box b), target) => a.can_cast_to(target) && b.can_cast_to(target),
+            (Self::And(box a, box b), target) => a.can_cast_to(target) || b.can_cast_to(target),
+            (Self::Array(..) | Self::Tuple(_) | Self::Func(..) | Self::Range | Self::Null, _) => {
+                false
+            }
+            // Instances are typed as `any` and may define `op cast`
+            _ => true,
+        }
+    }
+
     pub fn is_compatible_with(&self, other: &Type) -> bool {
         match (self, other) {
-            (Self::Any, _) | (_, Self::Any) => true,
+            (Self::Any | Self::Never, _) | (_, Self::Any) => true,
             (Self::Primitive(a), Self::Primitive(b)) => a == b,
+            (Self::Range, Self::Range) | (Self::Null, Self::Null) => true,
+            (Self::Generic(a), Self::Generic(b)) => a == b,
             (Self::Union(box a, box b), other) => {
                 a.is_compatible_with(other) || b.is_compatible_with(other)
             }
@@ -644,10 +991,15 @@ impl Type {
             (Self::Tuple(a), Self::Tuple(b)) => {
                 a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.is_compatible_with(b))
             }
+            (Self::Array(box a, Some(len)), Self::Tuple(items)) => {
+                *len as usize == items.len() && items.iter().all(|b| a.is_compatible_with(b))
+            }
             (Self::Func(a, a_ret), Self::Func(b, b_ret)) => {
                 a_ret.is_compatible_with(b_ret)
                     && a.iter().zip(b).all(|(a, b)| a.is_compatible_with(b))
             }
+            (ty, Self::Union(box a, box b)) => ty.is_compatible_with(a) || ty.is_compatible_with(b),
+            (ty, Self::And(box a, box b)) => ty.is_compatible_with(a) && ty.is_compatible_with(b),
             _ => false,
         }
     }
@@ -681,6 +1033,110 @@ impl Type {
     pub fn is_strictly_unknown(&self) -> bool {
         self._is_unknown(true)
     }
+
+    /// Binds the type parameters within this type to the matching parts of `arg`, the type of
+    /// the argument given for it. Type parameters which are already bound are left as is.
+    pub fn infer_generics(&self, arg: &Self, bindings: &mut HashMap<String, Self>) {
+        match (self, arg) {
+            (Self::Generic(name), arg) => {
+                if !arg.is_unknown() {
+                    bindings.entry(name.clone()).or_insert_with(|| arg.clone());
+                }
+            }
+            (Self::Union(a, b) | Self::And(a, b), arg) => {
+                a.infer_generics(arg, bindings);
+                b.infer_generics(arg, bindings);
+            }
+            (Self::Array(a, _), Self::Array(b, _)) => a.infer_generics(b, bindings),
+            (Self::Array(a, _), Self::Tuple(items)) => {
+                for item in items {
+                    a.infer_generics(item, bindings);
+                }
+            }
+            (Self::Tuple(a), Self::Tuple(b)) => {
+                for (a, b) in a.iter().zip(b) {
+                    a.infer_generics(b, bindings);
+                }
+            }
+            (Self::Func(a, a_ret), Self::Func(b, b_ret)) => {
+                for (a, b) in a.iter().zip(b) {
+                    a.infer_generics(b, bindings);
+                }
+                a_ret.infer_generics(b_ret, bindings);
+            }
+            _ => (),
+        }
+    }
+
+    /// Substitutes every type parameter with its bound type argument, or `any` if it is unbound.
+    #[must_use]
+    pub fn substitute(&self, bindings: &HashMap<String, Self>) -> Self {
+        let sub = |ty: &Self| Box::new(ty.substitute(bindings));
+
+        match self {
+            Self::Generic(name) => bindings.get(name).cloned().unwrap_or(Self::Any),
+            Self::Union(a, b) => Self::Union(sub(a), sub(b)),
+            Self::And(a, b) => Self::And(sub(a), sub(b)),
+            Self::Array(ty, len) => Self::Array(sub(ty), *len),
+            Self::Tuple(items) => {
+                Self::Tuple(items.iter().map(|t| t.substitute(bindings)).collect())
+            }
+            Self::Func(params, ret) => Self::Func(
+                params.iter().map(|t| t.substitute(bindings)).collect(),
+                sub(ret),
+            ),
+            ty => ty.clone(),
+        }
+    }
+
+    /// Whether a value of this type could be null. Types which are not known yet are assumed
+    /// to be nullable.
+    #[must_use]
+    pub fn is_nullable(&self) -> bool {
+        match self {
+            Self::Null | Self::Any | Self::Generic(_) | Self::Deferred(_) | Self::Unknown => true,
+            Self::Union(a, b) => a.is_nullable() || b.is_nullable(),
+            Self::And(a, b) => a.is_nullable() && b.is_nullable(),
+            _ => false,
+        }
+    }
+
+    /// This type narrowed to exclude null, e.g. `?int` becomes `int`.
+    #[must_use]
+    pub fn without_null(&self) -> Self {
+        self.without(&Self::Null)
+    }
+
+    /// This type narrowed to exclude every member of `excluded`, e.g. `int | string` without
+    /// `int` becomes `string`.
+    #[must_use]
+    pub fn without(&self, excluded: &Self) -> Self {
+        match (self, excluded) {
+            (_, Self::Union(a, b)) => self.without(a).without(b),
+            (ty, excluded) if ty == excluded => Self::Never,
+            (Self::Union(a, b), excluded) => match (a.without(excluded), b.without(excluded)) {
+                (Self::Never, ty) | (ty, Self::Never) => ty,
+                (a, b) => Self::Union(Box::new(a), Box::new(b)),
+            },
+            (ty, _) => ty.clone(),
+        }
+    }
+
+    /// The type of the items yielded when iterating over this type.
+    #[must_use]
+    pub fn item_type(&self) -> Self {
+        match self {
+            Self::Array(ty, _) => *ty.clone(),
+            Self::Tuple(items) => items
+                .iter()
+                .cloned()
+                .reduce(|a, b| Self::Union(Box::new(a), Box::new(b)).flatten())
+                .unwrap_or(Self::Any),
+            Self::Range => Self::Primitive(PrimitiveType::Int),
+            Self::Deferred(_) | Self::Unknown => Self::Unknown,
+            _ => Self::Any,
+        }
+    }
 }
 
 impl Display for Type {
@@ -717,6 +1173,9 @@ impl Display for Type {
                     .join(", "),
                 ret,
             ),
+            Self::Range => write!(f, "range"),
+            Self::Generic(name) => f.write_str(name),
+            Self::Never => write!(f, "never"),
             Self::Null => write!(f, "null"),
             Self::Any => write!(f, "any"),
             Self::Deferred(_) => write!(f, "<unknown>"),
@@ -740,6 +1199,12 @@ pub struct MockScopeEntry {
     pub used: bool,
     pub mutated: bool,
     pub span: Span,
+    /// The signature of the function the variable was declared with, if any.
+    /// Calls to the variable are checked against it.
+    pub signature: Option<MockSignature>,
+    /// Whether the variable was bound by a `require`, in which case it may also name
+    /// a class or trait of the module it was required from.
+    pub required: bool,
 }
 
 impl MockScopeEntry {
@@ -752,6 +1217,8 @@ impl MockScopeEntry {
             used: false,
             mutated: false,
             span,
+            signature: None,
+            required: false,
         }
     }
 
@@ -776,6 +1243,24 @@ impl MockScopeEntry {
     }
 }
 
+/// The analyzer's view of the signature of a function.
+#[derive(Clone, Debug)]
+pub struct MockSignature {
+    pub params: Vec<ParamSpec>,
+    /// The generic type parameters of the function, each with the type its type argument
+    /// must be compatible with.
+    pub generics: Vec<(String, Type)>,
+}
+
+/// The analyzer's view of a declared trait.
+#[derive(Clone, Debug)]
+pub struct MockTrait {
+    pub name: String,
+    /// Methods without a default body, paired with the span of their signature.
+    pub required: Vec<(String, Span)>,
+    pub span: Span,
+}
+
 #[derive(Debug)]
 pub struct MockScope(pub HashMap<String, MockScopeEntry>);
 
@@ -802,6 +1287,20 @@ pub struct Context {
     pub ast: Node,
     pub messages: Vec<AnalyzerMessage>,
     pub scopes: Vec<MockScope>,
+    pub traits: HashMap<String, MockTrait>,
+    /// The names of the classes declared so far, including builtin classes such as `Error`.
+    pub classes: HashSet<String>,
+    /// The labels of the loops enclosing the node being visited, innermost last.
+    pub loops: Vec<Option<String>>,
+    /// The names of the generic type parameters in scope, innermost last.
+    pub type_params: Vec<String>,
+    /// The type `$` refers to within the methods of the class being analyzed.
+    pub self_ty: Option<Type>,
+    /// The exported names of already loaded modules, keyed by the path they are required from.
+    pub modules: HashMap<String, Vec<String>>,
+    /// The types of the variables narrowed by the `is` tests guarding the body being analyzed,
+    /// innermost last.
+    pub narrowed: RefCell<Vec<(String, Type)>>,
     pub cache: Vec<(Source, String)>,
 }
 
@@ -816,13 +1315,55 @@ impl Context {
             std::process::exit(-1)
         });
 
-        Self {
+        let mut ctx = Self {
             tokens,
             ast,
             messages: Vec::new(),
             scopes: vec![MockScope::new()],
+            traits: HashMap::new(),
+            classes: HashSet::new(),
+            loops: Vec::new(),
+            type_params: Vec::new(),
+            self_ty: None,
+            modules: HashMap::new(),
+            narrowed: RefCell::new(Vec::new()),
             cache,
+        };
+
+        for name in [
+            "Error",
+            "NullError",
+            "IndexError",
+            "ZeroDivisionError",
+            "ValueError",
+            "TypeError",
+            "StopIteration",
+            "AttributeError",
+            "SignatureError",
+        ] {
+            ctx.store_class(name.to_string(), Span::default());
+        }
+        // Implemented by every object that can be cast to the type argument
+        ctx.store_trait(
+            "Castable".to_string(),
+            MockTrait {
+                name: "Castable".to_string(),
+                required: Vec::new(),
+                span: Span::default(),
+            },
+        );
+        // The intrinsic functions the standard library wraps
+        for name in [
+            "__trb_internal_call_op",
+            "__trb_internal_has_op",
+            "__trb_internal_print",
+            "__trb_internal_floor_num",
+            "__trb_internal_name_of",
+            "__trb_internal_type_of",
+        ] {
+            ctx.store_required(name.to_string(), Span::default());
         }
+        ctx
     }
 
     #[must_use]
@@ -830,6 +1371,17 @@ impl Context {
         sources(self.cache.clone())
     }
 
+    /// The type `name` is narrowed to by the `is` tests guarding the body being analyzed, if any.
+    #[must_use]
+    pub fn narrowed_type(&self, name: &str) -> Option<Type> {
+        self.narrowed
+            .borrow()
+            .iter()
+            .rev()
+            .find(|(narrowed, _)| narrowed == name)
+            .map(|(_, ty)| ty.clone())
+    }
+
     #[must_use]
     pub fn is_top_level(&self) -> bool {
         self.scopes.len() == 1
@@ -849,6 +1401,16 @@ impl Context {
         self.locals_mut().0.insert(name, entry);
     }
 
+    /// Stores a name bound by a require. Its type is unknown, and it is never reported as unused.
+    pub fn store_required(&mut self, name: String, span: Span) {
+        let mut entry =
+            MockScopeEntry::new(name.clone(), Type::Any, ScopeEntryModifier::None, span);
+        entry.used = true;
+        entry.required = true;
+
+        self.store_var(name, entry);
+    }
+
     #[must_use]
     pub fn lookup_var(&self, name: &String) -> Option<&MockScopeEntry> {
         for scope in self.scopes.iter().rev() {
@@ -892,6 +1454,22 @@ impl Context {
         None
     }
 
+    pub fn store_trait(&mut self, name: String, r#trait: MockTrait) {
+        self.traits.insert(name, r#trait);
+    }
+
+    #[must_use]
+    pub fn lookup_trait(&self, name: &str) -> Option<&MockTrait> {
+        self.traits.get(name)
+    }
+
+    /// Stores a class, which can be referred to both as a type and as a value.
+    /// Like required names, classes are never reported as unused.
+    pub fn store_class(&mut self, name: String, span: Span) {
+        self.store_required(name.clone(), span);
+        self.classes.insert(name);
+    }
+
     pub fn enter_scope(&mut self) {
         self.scopes.push(MockScope::new());
     }
@@ -966,6 +1544,10 @@ pub enum AnalyzerKind {
     GlobalMutableVariables,
     /// [W006] Value of an if statement has unbalanced types
     UnbalancedIfStatements,
+    /// [W007] Value of a match expression has unbalanced types
+    UnbalancedMatchArms,
+    /// [W008] A value which can never be null was checked for null with `?.` or `!`
+    UnnecessaryNullChecks,
     /// [E001] An identifier (e.g. a variable) could not be found in the current scope
     UnresolvedIdentifiers,
     /// [E002] A variable declared as `const` was redeclared later on
@@ -978,6 +1560,16 @@ pub enum AnalyzerKind {
     IncompatibleTypes,
     /// [E006] The type could not be inferred
     UninferableTypes,
+    /// [E007] A class did not implement every required method of a trait it extends
+    MissingTraitMethods,
+    /// [E008] A `break` or `continue` was used outside of a loop, or with an unknown label
+    LoopControlOutsideLoop,
+    /// [E009] The arguments of a call do not match the parameters of the function
+    InvalidArguments,
+    /// [E010] A destructuring target takes a different amount of items than its value has
+    DestructuringLengthMismatch,
+    /// [E011] A value was cast to a type it can never be converted to
+    InvalidCasts,
 }
 
 impl AnalyzerKind {
@@ -997,15 +1589,22 @@ impl AnalyzerKind {
     pub const fn severity(&self) -> u8 {
         match self {
             Self::NonSnakeCase | Self::NonPascalCase | Self::NonAscii => 1,
-            Self::UnusedVariables | Self::UnnecessaryMutVariables => 2,
-            Self::UnbalancedIfStatements => 3,
+            Self::UnusedVariables | Self::UnnecessaryMutVariables | Self::UnnecessaryNullChecks => {
+                2
+            }
+            Self::UnbalancedIfStatements | Self::UnbalancedMatchArms => 3,
             Self::GlobalMutableVariables => 4,
             Self::UnresolvedIdentifiers
             | Self::RedeclaredConstVariables
             | Self::ReassignedImmutableVariables
             | Self::UnsupportedOperators
             | Self::IncompatibleTypes
-            | Self::UninferableTypes => 0,
+            | Self::UninferableTypes
+            | Self::MissingTraitMethods
+            | Self::LoopControlOutsideLoop
+            | Self::InvalidArguments
+            | Self::DestructuringLengthMismatch
+            | Self::InvalidCasts => 0,
         }
     }
 
@@ -1020,6 +1619,11 @@ impl AnalyzerKind {
             Self::UnnecessaryMutVariables | Self::UnsupportedOperators => 4,
             Self::GlobalMutableVariables | Self::IncompatibleTypes => 5,
             Self::UnbalancedIfStatements | Self::UninferableTypes => 6,
+            Self::UnbalancedMatchArms | Self::MissingTraitMethods => 7,
+            Self::LoopControlOutsideLoop | Self::UnnecessaryNullChecks => 8,
+            Self::InvalidArguments => 9,
+            Self::DestructuringLengthMismatch => 10,
+            Self::InvalidCasts => 11,
         }
     }
 
@@ -1060,6 +1664,13 @@ impl FromStr for AnalyzerKind {
             "incompatible-types" => Self::IncompatibleTypes,
             "uninferable-types" => Self::UninferableTypes,
             "unbalanced-if-statements" => Self::UnbalancedIfStatements,
+            "unbalanced-match-arms" => Self::UnbalancedMatchArms,
+            "unnecessary-null-checks" => Self::UnnecessaryNullChecks,
+            "missing-trait-methods" => Self::MissingTraitMethods,
+            "loop-control-outside-loop" => Self::LoopControlOutsideLoop,
+            "invalid-arguments" => Self::InvalidArguments,
+            "destructuring-length-mismatch" => Self::DestructuringLengthMismatch,
+            "invalid-casts" => Self::InvalidCasts,
             _ => return Err(format!("invalid analyzer {:?}", s)),
         })
     }
@@ -1081,6 +1692,13 @@ impl std::fmt::Display for AnalyzerKind {
             Self::IncompatibleTypes => "incompatible-types",
             Self::UninferableTypes => "uninferable-types",
             Self::UnbalancedIfStatements => "unbalanced-if-statements",
+            Self::UnbalancedMatchArms => "unbalanced-match-arms",
+            Self::UnnecessaryNullChecks => "unnecessary-null-checks",
+            Self::MissingTraitMethods => "missing-trait-methods",
+            Self::LoopControlOutsideLoop => "loop-control-outside-loop",
+            Self::InvalidArguments => "invalid-arguments",
+            Self::DestructuringLengthMismatch => "destructuring-length-mismatch",
+            Self::InvalidCasts => "invalid-casts",
         })
     }
 }
@@ -1117,6 +1735,13 @@ impl AnalyzerSet {
             A::IncompatibleTypes,
             A::UninferableTypes,
             A::UnbalancedIfStatements,
+            A::UnbalancedMatchArms,
+            A::UnnecessaryNullChecks,
+            A::MissingTraitMethods,
+            A::LoopControlOutsideLoop,
+            A::InvalidArguments,
+            A::DestructuringLengthMismatch,
+            A::InvalidCasts,
         ]))
     }
 
@@ -1159,8 +1784,10 @@ pub fn infer_type(
     Ok(match expr {
         Expr::Integer(_) => Type::Primitive(PrimitiveType::Int),
         Expr::Float(_) => Type::Primitive(PrimitiveType::Float),
-        Expr::String(_) => Type::Primitive(PrimitiveType::String),
+        Expr::String(_) | Expr::Interpolated(_) => Type::Primitive(PrimitiveType::String),
         Expr::Bool(_) => Type::Primitive(PrimitiveType::Bool),
+        Expr::Null => Type::Null,
+        Expr::SelfRef => ctx.self_ty.clone().unwrap_or(Type::Unknown),
         Expr::UnaryExpr { operator, value } => {
             let (op, op_span) = operator.node_span();
             let t = infer_type(analyzers, ctx, messages, value)?;
@@ -1203,23 +1830,33 @@ pub fn infer_type(
             }
         }
         Expr::If {
+            condition,
             body,
             else_if_bodies,
             else_body,
-            ..
         } => {
-            let mut bodies = else_if_bodies.iter().map(|b| &b.1).collect::<Vec<_>>();
-            bodies.insert(0, body);
+            let mut bodies = else_if_bodies
+                .iter()
+                .map(|(condition, body)| (Some(condition), body))
+                .collect::<Vec<_>>();
+            bodies.insert(0, (Some(condition), body));
 
             if let Some(else_body) = else_body {
-                bodies.push(else_body)
+                bodies.push((None, else_body))
             }
 
             let mut types = Vec::new();
+            let depth = ctx.narrowed.borrow().len();
 
-            for s in bodies {
+            for (condition, s) in bodies {
                 let (Body(nodes, return_last), body_span) = s.node_span();
 
+                let guarded = ctx.narrowed.borrow().len();
+                if let Some(condition) = condition {
+                    let narrowed = narrowings(ctx, condition.node(), true);
+                    ctx.narrowed.borrow_mut().extend(narrowed);
+                }
+
                 let ty = if *return_last {
                     if let Node::Expr(e) = nodes.last().unwrap().node() {
                         infer_type(analyzers, ctx, messages, e)?
@@ -1230,8 +1867,16 @@ pub fn infer_type(
                     Type::Null
                 };
 
+                // Later bodies are only reached when this condition is false
+                ctx.narrowed.borrow_mut().truncate(guarded);
+                if let Some(condition) = condition {
+                    let narrowed = narrowings(ctx, condition.node(), false);
+                    ctx.narrowed.borrow_mut().extend(narrowed);
+                }
+
                 types.push((ty, body_span));
             }
+            ctx.narrowed.borrow_mut().truncate(depth);
 
             let (target_type, first_span) = types.remove(0);
 
@@ -1271,9 +1916,140 @@ pub fn infer_type(
 
             accumulator
         }
-        Expr::While { .. } => {
-            // TODO: break statements
-            Type::Null
+        Expr::Match {
+            arms, else_body, ..
+        } => {
+            let mut types = Vec::new();
+
+            for arm in arms {
+                let (Body(nodes, return_last), body_span) = arm.body.node_span();
+
+                let ty = if *return_last {
+                    if let Node::Expr(e) = nodes.last().unwrap().node() {
+                        infer_type(analyzers, ctx, messages, e)?
+                    } else {
+                        Type::Null
+                    }
+                } else {
+                    Type::Null
+                };
+
+                types.push((ty, body_span));
+            }
+
+            if let Some(else_body) = else_body {
+                let (Body(nodes, return_last), body_span) = else_body.node_span();
+
+                let ty = if *return_last {
+                    if let Node::Expr(e) = nodes.last().unwrap().node() {
+                        infer_type(analyzers, ctx, messages, e)?
+                    } else {
+                        Type::Null
+                    }
+                } else {
+                    Type::Null
+                };
+
+                types.push((ty, body_span));
+            }
+
+            if types.is_empty() {
+                return Ok(Type::Null);
+            }
+
+            let (target_type, first_span) = types.remove(0);
+
+            let exhaustive = else_body.is_some()
+                || arms
+                    .iter()
+                    .any(|arm| arm.guard.is_none() && arm.pattern.is_irrefutable());
+
+            // Types depending on bindings of the arm can't be inferred yet
+            let known = |ty: &Type| !ty.is_unknown();
+
+            if analyzers.contains(&AnalyzerKind::UnbalancedMatchArms)
+                && !exhaustive
+                && known(&target_type)
+                && target_type != Type::Null
+            {
+                messages.push(AnalyzerMessage::unbalanced_match_arms_no_else(
+                    span.clone(),
+                    first_span.clone(),
+                    target_type.to_string(),
+                ));
+
+                return Ok(Type::Union(Box::new(target_type), Box::new(Type::Null)));
+            }
+
+            let mut accumulator = target_type.clone();
+            let mut unbalanced = false;
+
+            for (subject_type, subject_span) in types {
+                if analyzers.contains(&AnalyzerKind::UnbalancedMatchArms)
+                    && !unbalanced
+                    && known(&target_type)
+                    && known(&subject_type)
+                    && !target_type.is_compatible_with(&subject_type)
+                {
+                    messages.push(AnalyzerMessage::unbalanced_match_arms(
+                        span.clone(),
+                        first_span.clone(),
+                        target_type.to_string(),
+                        subject_span.clone(),
+                        subject_type.to_string(),
+                    ));
+                    unbalanced = true;
+                }
+
+                accumulator = Type::Union(Box::new(accumulator), Box::new(subject_type));
+            }
+
+            if exhaustive {
+                accumulator
+            } else {
+                Type::Union(Box::new(accumulator), Box::new(Type::Null))
+            }
+        }
+        Expr::While {
+            label,
+            condition,
+            body,
+        } => {
+            let mut types = Vec::new();
+            break_types(analyzers, ctx, messages, label, body, false, &mut types)?;
+
+            // A loop that never stops by itself can only evaluate to its break values
+            let infinite = condition.node() == &Expr::Bool(true);
+
+            types
+                .into_iter()
+                .chain((!infinite).then_some(Type::Null))
+                .reduce(|acc, ty| Type::Union(Box::new(acc), Box::new(ty)))
+                .unwrap_or(Type::Null)
+        }
+        Expr::For {
+            label,
+            target,
+            iterable,
+            body,
+        } => {
+            let depth = ctx.narrowed.borrow().len();
+
+            // Like catch bindings, the loop variable is given the item type the same way
+            // narrowed variables are, since it is only declared once the loop is visited
+            if let Target::Ident(name) = target.node() {
+                let ty = infer_type(analyzers, ctx, messages, iterable)?.item_type();
+                ctx.narrowed.borrow_mut().push((name.clone(), ty));
+            }
+
+            let mut types = Vec::new();
+            let found = break_types(analyzers, ctx, messages, label, body, false, &mut types);
+            ctx.narrowed.borrow_mut().truncate(depth);
+            found?;
+
+            types.into_iter().fold(Type::Null, |acc, ty| {
+                Type::Union(Box::new(acc), Box::new(ty))
+            })
         }
         Expr::Array(items) => {
             if items.is_empty() {
@@ -1317,7 +2093,9 @@ pub fn infer_type(
             Type::Array(Box::new(first_ty), Some(items.len() as u32))
         }
         Expr::Ident(s) => {
-            if let Some(entry) = ctx.lookup_var(s) {
+            if let Some(ty) = ctx.narrowed_type(s) {
+                ty
+            } else if let Some(entry) = ctx.lookup_var(s) {
                 if entry.ty.is_unknown() {
                     Type::Deferred(Box::new(DeferredType::TypeOf(entry.name.clone())))
                 } else {
@@ -1328,11 +2106,301 @@ pub fn infer_type(
                 Type::Unknown
             }
         }
+        Expr::Func {
+            params, return_ty, ..
+        } => signature_type(ctx, params, return_ty),
+        // The value of a finally body is discarded
+        Expr::Try { body, catches, .. } => {
+            let mut accumulator: Option<Type> = None;
+            let depth = ctx.narrowed.borrow().len();
+
+            for (body, clause) in std::iter::once((body, None))
+                .chain(catches.iter().map(|c| (&c.body, Some(c.node()))))
+            {
+                let Body(nodes, return_last) = body.node();
+
+                // The binding of a catch clause is only declared once the clause is visited,
+                // so it is given the caught type the same way narrowed variables are
+                if let Some(CatchClause {
+                    ty,
+                    binding: Some(binding),
+                    ..
+                }) = clause
+                {
+                    let ty = ty.clone().map_or(Type::Any, |ty| {
+                        resolve_type_expr(ctx, &mut Vec::new(), ty).0
+                    });
+                    ctx.narrowed.borrow_mut().push((binding.clone(), ty));
+                }
+
+                let ty = match nodes.last().map(Spanned::node) {
+                    Some(Node::Expr(e)) if *return_last => infer_type(analyzers, ctx, messages, e)?,
+                    _ => Type::Null,
+                };
+                ctx.narrowed.borrow_mut().truncate(depth);
+
+                accumulator = Some(match accumulator {
+                    Some(acc) => Type::Union(Box::new(acc), Box::new(ty)),
+                    None => ty,
+                });
+            }
+
+            accumulator.unwrap_or(Type::Null)
+        }
+        Expr::With { body, .. } => {
+            let Body(nodes, return_last) = body.node();
+
+            match nodes.last().map(Spanned::node) {
+                Some(Node::Expr(e)) if *return_last => infer_type(analyzers, ctx, messages, e)?,
+                _ => Type::Null,
+            }
+        }
+        Expr::Call {
+            value,
+            args,
+            kwargs,
+            optional,
+        } => {
+            let mut func = infer_type(analyzers, ctx, messages, value)?;
+            if *optional {
+                func = func.without_null();
+            }
+
+            // Arguments unpacked from splats are only bound at runtime
+            let splat = has_splats(args);
+            let instance = match signature_of(ctx, value.node()) {
+                Some(signature) if !splat => {
+                    instantiate_call(analyzers, ctx, &signature, &func, args, kwargs, span)?
+                }
+                _ => None,
+            };
+
+            let ret = match (instance, func) {
+                (Some((ret, _)), _) => ret,
+                (None, Type::Func(_, ret)) => ret.substitute(&HashMap::new()),
+                (None, Type::Any) => Type::Any,
+                _ => Type::Unknown,
+            };
+
+            // The call is skipped if the function is null
+            if *optional && !ret.is_unknown() {
+                Type::Union(Box::new(ret), Box::new(Type::Null))
+            } else {
+                ret
+            }
+        }
+        Expr::NonNull(value) => infer_type(analyzers, ctx, messages, value)?.without_null(),
+        // TODO: infer the types of attributes once classes are modeled
+        Expr::Attr { .. } => Type::Any,
+        Expr::Index {
+            subject,
+            index,
+            optional,
+        } => {
+            let subject = infer_type(analyzers, ctx, messages, subject)?.without_null();
+            let ty = match (&subject, index.node()) {
+                (Type::Tuple(items), Expr::Integer(i)) => usize::try_from(*i)
+                    .ok()
+                    .and_then(|i| items.get(i))
+                    .cloned()
+                    .unwrap_or(Type::Unknown),
+                (Type::Array(..) | Type::Tuple(_), _) => subject.item_type(),
+                (Type::Primitive(PrimitiveType::String), _) => subject,
+                _ => Type::Unknown,
+            };
+
+            if *optional && !ty.is_unknown() {
+                Type::Union(Box::new(ty), Box::new(Type::Null))
+            } else {
+                ty
+            }
+        }
+        Expr::Slice {
+            subject, optional, ..
+        } => {
+            let subject = infer_type(analyzers, ctx, messages, subject)?.without_null();
+            let ty = match subject {
+                Type::Array(..) | Type::Tuple(_) => {
+                    Type::Array(Box::new(subject.item_type()), None)
+                }
+                Type::Primitive(PrimitiveType::String) => subject,
+                _ => Type::Unknown,
+            };
+
+            if *optional && !ty.is_unknown() {
+                Type::Union(Box::new(ty), Box::new(Type::Null))
+            } else {
+                ty
+            }
+        }
+        Expr::Is { .. } | Expr::In { .. } => Type::Primitive(PrimitiveType::Bool),
+        Expr::Cast(value, ty) => {
+            let value_t = infer_type(analyzers, ctx, messages, value)?;
+
+            match cast_target(ty.node()) {
+                Some(target) if value_t.can_cast_to(target) => Type::Primitive(target),
+                Some(target) => {
+                    if analyzers.contains(&AnalyzerKind::InvalidCasts) {
+                        messages.push(AnalyzerMessage::invalid_cast(
+                            span.clone(),
+                            value_t.to_string(),
+                            value.span(),
+                            target.to_string(),
+                            ty.span(),
+                        ));
+                    }
+
+                    Type::Unknown
+                }
+                None => {
+                    if analyzers.contains(&AnalyzerKind::InvalidCasts) {
+                        messages.push(AnalyzerMessage::invalid_cast_target(
+                            span.clone(),
+                            ty.span(),
+                        ));
+                    }
+
+                    Type::Unknown
+                }
+            }
+        }
         _ => Type::Unknown,
     }
     .flatten())
 }
 
+/// Collects the types of the values of every `break` in `body` that exits the loop
+/// labelled `label`. `nested` is whether `body` belongs to a loop inside of that loop,
+/// in which case only breaks with a matching label are collected.
+fn break_types(
+    analyzers: &AnalyzerSet,
+    ctx: &Context,
+    messages: &mut Vec<AnalyzerMessage>,
+    label: &Option<String>,
+    body: &[Spanned<Node>],
+    nested: bool,
+    types: &mut Vec<Type>,
+) -> Result<(), &'static str> {
+    for node in body {
+        match node.node() {
+            Node::Break {
+                label: target,
+                value,
+            } if (target.is_none() && !nested) || (target.is_some() && target == label) => {
+                types.push(match value {
+                    Some(value) => infer_type(analyzers, ctx, messages, value)?,
+                    None => Type::Null,
+                });
+            }
+            Node::Expr(e) => match e.node() {
+                Expr::If {
+                    body: if_body,
+                    else_if_bodies,
+                    else_body,
+                    ..
+                } => {
+                    for body in std::iter::once(if_body)
+                        .chain(else_if_bodies.iter().map(|(_, body)| body))
+                        .chain(else_body)
+                    {
+                        break_types(analyzers, ctx, messages, label, &body.0, nested, types)?;
+                    }
+                }
+                Expr::Match {
+                    arms, else_body, ..
+                } => {
+                    for body in arms.iter().map(|arm| &arm.body).chain(else_body) {
+                        break_types(analyzers, ctx, messages, label, &body.0, nested, types)?;
+                    }
+                }
+                Expr::While { body, .. } | Expr::For { body, .. } => {
+                    break_types(analyzers, ctx, messages, label, body, true, types)?;
+                }
+                Expr::Try {
+                    body: try_body,
+                    catches,
+                    finally,
+                } => {
+                    for body in std::iter::once(try_body)
+                        .chain(catches.iter().map(|c| &c.body))
+                        .chain(finally)
+                    {
+                        break_types(analyzers, ctx, messages, label, &body.0, nested, types)?;
+                    }
+                }
+                Expr::With { body, .. } => {
+                    break_types(
+                        analyzers,
+                        ctx,
+                        messages,
+                        label,
+                        &body.node().0,
+                        nested,
+                        types,
+                    )?;
+                }
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// The types of the variables narrowed by the `is` tests in `condition` when it evaluates to
+/// `truthy`, e.g. `x is int` narrows `x` to `int` when true and excludes `int` from it when false.
+fn narrowings(ctx: &Context, condition: &Expr, truthy: bool) -> Vec<(String, Type)> {
+    match condition {
+        Expr::Is { value, ty, negated } => {
+            let name = match value.node() {
+                Expr::Ident(name) => name,
+                _ => return Vec::new(),
+            };
+            let tested = resolve_type_expr(ctx, &mut Vec::new(), ty.clone()).0;
+
+            // Classes are not modeled as types yet, so testing against them narrows nothing
+            if tested.is_unknown() || tested == Type::Any {
+                return Vec::new();
+            }
+
+            if truthy != *negated {
+                return vec![(name.clone(), tested)];
+            }
+
+            match ctx
+                .narrowed_type(name)
+                .or_else(|| ctx.lookup_var(name).map(|entry| entry.ty.clone()))
+            {
+                Some(ty) if !ty.is_unknown() => vec![(name.clone(), ty.without(&tested))],
+                _ => Vec::new(),
+            }
+        }
+        Expr::BinaryExpr { operator, lhs, rhs }
+            if matches!(
+                (operator.node(), truthy),
+                (Operator::And, true) | (Operator::Or, false)
+            ) =>
+        {
+            let mut narrowed = narrowings(ctx, lhs.node(), truthy);
+            narrowed.extend(narrowings(ctx, rhs.node(), truthy));
+            narrowed
+        }
+        Expr::UnaryExpr { operator, value } if operator.node() == &Operator::Not => {
+            narrowings(ctx, value.node(), !truthy)
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// The builtin type a cast expression converts to, if its target names one.
+fn cast_target(ty: &Expr) -> Option<PrimitiveType> {
+    match ty {
+        Expr::Ident(name) => PrimitiveType::from_name(name),
+        _ => None,
+    }
+}
+
 pub fn resolve_type_expr(
     ctx: &Context,
     messages: &mut Vec<AnalyzerMessage>,
@@ -1346,6 +2414,11 @@ pub fn resolve_type_expr(
             "float" => Type::Primitive(PrimitiveType::Float),
             "bool" => Type::Primitive(PrimitiveType::Bool),
             "string" => Type::Primitive(PrimitiveType::String),
+            "never" => Type::Never,
+            _ if ctx.type_params.contains(&s) => Type::Generic(s),
+            // TODO: model traits and classes as types
+            _ if ctx.lookup_trait(&s).is_some() || ctx.classes.contains(&s) => Type::Any,
+            _ if ctx.lookup_var(&s).is_some_and(|e| e.required) => Type::Any,
             _ => {
                 messages.push(AnalyzerMessage::unresolved_identifier(
                     &*s,
@@ -1379,6 +2452,12 @@ pub fn resolve_type_expr(
         TypeExpr::Array(ty, len) => {
             let ty = resolve_type_expr(ctx, messages, ty).0;
 
+            // Lengths named by constants are not checked
+            let len = match len {
+                Some(ArrayLength::Literal(len)) => Some(len),
+                Some(ArrayLength::Const(_)) | None => None,
+            };
+
             Type::Array(Box::new(ty), len)
         }
         TypeExpr::Tuple(items) => {
@@ -1389,82 +2468,643 @@ pub fn resolve_type_expr(
 
             Type::Tuple(items)
         }
-        _ => unimplemented!(),
+        // Type arguments are only checked for existence, since only traits take them so far
+        TypeExpr::Generic(subject, args) => {
+            for arg in args {
+                resolve_type_expr(ctx, messages, arg);
+            }
+
+            resolve_type_expr(ctx, messages, subject).0
+        }
+        TypeExpr::Func(params, ret) => {
+            let ret = match resolve_type_expr(ctx, messages, ret).0 {
+                Type::Unknown => Type::Any,
+                ret => ret,
+            };
+
+            // Functions taking any arguments can not be checked against a parameter list
+            match params {
+                Some(params) => Type::Func(
+                    params
+                        .into_iter()
+                        .map(|param| resolve_type_expr(ctx, messages, param).0)
+                        .collect(),
+                    Box::new(ret),
+                ),
+                None => Type::Any,
+            }
+        }
+        // Members of other modules are only checked for existence of the module itself
+        TypeExpr::Attr(subject, _) => match subject.node() {
+            TypeExpr::Ident(name) if ctx.lookup_var(name).is_none() => {
+                messages.push(AnalyzerMessage::unresolved_identifier(
+                    &**name,
+                    None,
+                    subject.span(),
+                ));
+
+                Type::Unknown
+            }
+            TypeExpr::Ident(_) => Type::Any,
+            _ => resolve_type_expr(ctx, messages, subject).0,
+        },
+        // TODO: model negated types
+        TypeExpr::Not(ty) => {
+            resolve_type_expr(ctx, messages, ty);
+
+            Type::Any
+        }
+        TypeExpr::SelfRef => ctx.self_ty.clone().unwrap_or(Type::Any),
     };
 
     (resolved, span)
 }
 
-#[allow(unused_variables, reason = "`analyzers` will be used later")]
-/// Analyzes the expression.
+/// Resolves the type of the value a parameter is bound to. Parameters without a type
+/// accept any object.
+pub fn resolve_param_type(
+    ctx: &Context,
+    messages: &mut Vec<AnalyzerMessage>,
+    param: &Param,
+) -> Type {
+    let ty = match resolve_type_expr(ctx, messages, param.ty.clone()).0 {
+        Type::Unknown => Type::Any,
+        ty => ty,
+    };
+
+    match param.kind {
+        ParamKind::Normal if param.optional => Type::Union(Box::new(ty), Box::new(Type::Null)),
+        ParamKind::Normal => ty,
+        ParamKind::Variadic => Type::Array(Box::new(ty), None),
+        // Each keyword argument is paired with its name
+        ParamKind::KeywordVariadic => Type::Array(
+            Box::new(Type::Tuple(vec![
+                Type::Primitive(PrimitiveType::String),
+                ty,
+            ])),
+            None,
+        ),
+    }
+}
+
+/// Analyzes the fields and methods of a class, trait or extend block, checking the default
+/// value of each field against its declared type and visiting each method body with `$`
+/// bound to `self_ty`.
 ///
 /// # Errors
 /// * The analyzer generated an error.
-pub fn visit_expr(
+fn visit_members(
     analyzers: &AnalyzerSet,
     ctx: &mut Context,
     messages: &mut Vec<AnalyzerMessage>,
-    expr: Spanned<Expr>,
-) -> Result<Type, &'static str> {
-    // infer_type provides us with operator checking
-    let ty = infer_type(analyzers, ctx, messages, &expr)?;
+    self_ty: Spanned<TypeExpr>,
+    fields: Vec<SpannedField>,
+    methods: Vec<SpannedMethod>,
+) -> Result<(), &'static str> {
+    for field in fields {
+        let (Field { ty, default, .. }, field_span) = field.into_node_span();
+        let (expected, ty_span) = resolve_type_expr(ctx, messages, ty);
+
+        if let Some(default) = default {
+            let default_span = default.span();
+            let inferred = visit_expr(analyzers, ctx, messages, default)?;
+
+            if !expected.is_unknown() && !inferred.is_compatible_with(&expected) {
+                messages.push(AnalyzerMessage::incompatible_types(
+                    field_span,
+                    default_span,
+                    inferred.to_string(),
+                    Some(ty_span),
+                    expected.to_string(),
+                ));
+            }
+        }
+    }
 
-    let span = expr.span();
-    let expr = expr.into_node();
+    let self_ty = resolve_type_expr(ctx, messages, self_ty).0;
+    let outer = ctx.self_ty.replace(self_ty);
 
-    match expr {
-        Expr::Ident(s) => match ctx.lookup_var_mut(&s) {
-            Some(e) => {
-                e.used = true;
-            }
-            None => {
-                let close_match = ctx.close_var_match(&s);
+    let result = methods
+        .into_iter()
+        .try_for_each(|method| visit_method(analyzers, ctx, messages, method));
+    ctx.self_ty = outer;
+    result
+}
 
-                messages.push(AnalyzerMessage::unresolved_identifier(
-                    &s,
-                    close_match,
-                    span,
-                ));
-            }
-        },
-        Expr::If {
-            condition,
-            body,
+/// Analyzes a method of a class or trait, visiting its body if it has one. The generic
+/// parameters of the enclosing class or trait must already be in scope, and `$` bound.
+///
+/// # Errors
+/// * The analyzer generated an error.
+fn visit_method(
+    analyzers: &AnalyzerSet,
+    ctx: &mut Context,
+    messages: &mut Vec<AnalyzerMessage>,
+    method: SpannedMethod,
+) -> Result<(), &'static str> {
+    let (
+        Method {
+            generics,
+            constraints,
+            params,
+            body,
+            return_last,
+            return_ty,
+            throws,
+            ..
+        },
+        span,
+    ) = method.into_node_span();
+
+    // Constraints only narrow the bounds of type parameters of the enclosing class or trait
+    let depth = ctx.type_params.len();
+    ctx.type_params.extend(
+        generics
+            .iter()
+            .chain(&constraints)
+            .map(|g| g.node().name.clone()),
+    );
+    for ty in generics
+        .into_iter()
+        .chain(constraints)
+        .flat_map(|g| g.into_node().bounds)
+        .chain(throws)
+    {
+        resolve_type_expr(ctx, messages, ty);
+    }
+
+    let result = match body {
+        Some(body) => visit_func(
+            analyzers,
+            ctx,
+            messages,
+            params,
+            body,
+            return_last,
+            return_ty,
+            span,
+        )
+        .map(drop),
+        None => {
+            resolve_type_expr(ctx, messages, return_ty);
+            for param in params {
+                resolve_param_type(ctx, messages, param.node());
+            }
+            Ok(())
+        }
+    };
+    ctx.type_params.truncate(depth);
+    result
+}
+
+/// Returns the type of a function as declared by its signature, without visiting its body.
+fn signature_type(ctx: &Context, params: &[SpannedParam], return_ty: &Spanned<TypeExpr>) -> Type {
+    // Unresolved types are reported when the function is visited
+    let params = params
+        .iter()
+        .map(|param| resolve_param_type(ctx, &mut Vec::new(), param.node()))
+        .collect();
+    let (ret, _) = resolve_type_expr(ctx, &mut Vec::new(), return_ty.clone());
+
+    Type::Func(
+        params,
+        Box::new(if ret.is_unknown() { Type::Any } else { ret }),
+    )
+}
+
+/// Declares a named function in the current scope. Functions are never reported as unused.
+fn declare_func(
+    ctx: &mut Context,
+    name: &str,
+    generics: &[SpannedGenericParam],
+    params: &[SpannedParam],
+    return_ty: &Spanned<TypeExpr>,
+    span: Span,
+) {
+    let depth = ctx.type_params.len();
+    ctx.type_params
+        .extend(generics.iter().map(|g| g.node().name.clone()));
+
+    // Unresolved bounds are reported when the function is visited
+    let bounds = generics
+        .iter()
+        .map(|g| {
+            let bound = g
+                .node()
+                .bounds
+                .iter()
+                .map(|bound| resolve_type_expr(ctx, &mut Vec::new(), bound.clone()).0)
+                .reduce(|a, b| Type::And(Box::new(a), Box::new(b)))
+                .unwrap_or(Type::Any);
+
+            (g.node().name.clone(), bound)
+        })
+        .collect();
+    let ty = signature_type(ctx, params, return_ty);
+    ctx.type_params.truncate(depth);
+
+    let mut entry = MockScopeEntry::new(name.to_string(), ty, ScopeEntryModifier::None, span);
+    entry.used = true;
+    entry.signature = Some(MockSignature {
+        params: params.iter().map(|p| p.node().spec()).collect(),
+        generics: bounds,
+    });
+
+    ctx.store_var(name.to_string(), entry);
+}
+
+/// Returns whether the arguments of a call contain splats.
+fn has_splats(args: &[Spanned<Expr>]) -> bool {
+    args.iter()
+        .any(|a| matches!(a.node(), Expr::Splat(_) | Expr::KeywordSplat(_)))
+}
+
+/// An argument or type argument of a call, with its type and the type it was expected to be.
+type Mismatch = (Span, Type, Type);
+
+/// Instantiates a call to a function with the given signature and type, inferring its type
+/// arguments from the types of the arguments.
+///
+/// Returns the return type of the call, along with every argument and type argument that is
+/// incompatible with the type it was expected to be. Calls with invalid arguments are not
+/// instantiated.
+///
+/// # Errors
+/// * The analyzer generated an error.
+fn instantiate_call(
+    analyzers: &AnalyzerSet,
+    ctx: &Context,
+    signature: &MockSignature,
+    func: &Type,
+    args: &[Spanned<Expr>],
+    kwargs: &[(String, Spanned<Expr>)],
+    span: &Span,
+) -> Result<Option<(Type, Vec<Mismatch>)>, &'static str> {
+    let (param_types, ret) = match func {
+        Type::Func(params, ret) => (params, ret),
+        _ => return Ok(None),
+    };
+
+    let keywords = kwargs.iter().map(|(name, _)| name).collect::<Vec<_>>();
+    let bound = match bind_args(&signature.params, args.len(), &keywords) {
+        Ok(bound) => bound,
+        Err(_) => return Ok(None),
+    };
+
+    let values = args
+        .iter()
+        .chain(kwargs.iter().map(|(_, arg)| arg))
+        .map(|arg| {
+            Ok((
+                arg.span(),
+                infer_type(analyzers, ctx, &mut Vec::new(), arg)?,
+            ))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Indices of the arguments, each paired with the type it is expected to be
+    let mut expected = Vec::with_capacity(values.len());
+    for ((param, ty), indices) in signature.params.iter().zip(param_types).zip(bound) {
+        let ty = match param.kind {
+            ParamKind::Normal => ty.clone(),
+            ParamKind::Variadic => ty.item_type(),
+            ParamKind::KeywordVariadic => match ty.item_type() {
+                Type::Tuple(mut pair) => pair.pop().unwrap_or(Type::Any),
+                ty => ty,
+            },
+        };
+
+        expected.extend(indices.into_iter().map(|i| (i, ty.clone())));
+    }
+
+    let mut bindings = HashMap::new();
+    for (i, ty) in &expected {
+        ty.infer_generics(&values[*i].1, &mut bindings);
+    }
+
+    let mut mismatches = Vec::new();
+    for (i, ty) in expected {
+        let (arg_span, arg_ty) = &values[i];
+        let ty = ty.substitute(&bindings);
+
+        if !arg_ty.is_unknown() && !arg_ty.is_compatible_with(&ty) {
+            mismatches.push((arg_span.clone(), arg_ty.clone(), ty));
+        }
+    }
+
+    for (name, bound) in &signature.generics {
+        if let Some(arg) = bindings.get(name) {
+            let bound = bound.substitute(&bindings);
+
+            if !bound.is_unknown() && !arg.is_compatible_with(&bound) {
+                mismatches.push((span.clone(), arg.clone(), bound));
+            }
+        }
+    }
+
+    Ok(Some((ret.substitute(&bindings), mismatches)))
+}
+
+#[allow(
+    clippy::too_many_arguments,
+    reason = "The parts of `Expr::Func` and `Node::Func`"
+)]
+/// Analyzes the parameters and body of a function, returning its type.
+///
+/// # Errors
+/// * The analyzer generated an error.
+fn visit_func(
+    analyzers: &AnalyzerSet,
+    ctx: &mut Context,
+    messages: &mut Vec<AnalyzerMessage>,
+    params: Vec<SpannedParam>,
+    mut body: Vec<SpannedNode>,
+    return_last: bool,
+    return_ty: Spanned<TypeExpr>,
+    span: Span,
+) -> Result<Type, &'static str> {
+    let show_span = return_ty.node() != &TypeExpr::Auto;
+    let (expected, ty_span) = resolve_type_expr(ctx, messages, return_ty);
+
+    // The body can not break out of loops enclosing the function
+    let loops = std::mem::take(&mut ctx.loops);
+    ctx.enter_scope();
+
+    let mut param_types = Vec::with_capacity(params.len());
+    for param in params {
+        let ty = resolve_param_type(ctx, messages, param.node());
+        let Param {
+            target, default, ..
+        } = param.into_node();
+
+        if let Some(default) = default {
+            visit_expr(analyzers, ctx, messages, default)?;
+        }
+
+        bind_target(analyzers, ctx, messages, target, ty.clone());
+        param_types.push(ty);
+    }
+
+    let last = if return_last { body.pop() } else { None };
+    visit_body(analyzers, ctx, messages, body)?;
+
+    let ret = match last.map(Spanned::into_node) {
+        Some(Node::Expr(value)) => {
+            let value_span = value.span();
+            let inferred = visit_expr(analyzers, ctx, messages, value)?;
+
+            if !expected.is_unknown() && !inferred.is_compatible_with(&expected) {
+                messages.push(AnalyzerMessage::incompatible_types(
+                    span,
+                    value_span,
+                    inferred.to_string(),
+                    if show_span { Some(ty_span) } else { None },
+                    expected.to_string(),
+                ));
+            }
+
+            inferred
+        }
+        _ => Type::Null,
+    };
+
+    ctx.exit_scope(analyzers, messages);
+    ctx.loops = loops;
+
+    // Types deferred to names declared within the body can no longer be resolved
+    let ret = if !expected.is_unknown() {
+        expected
+    } else if ret.is_unknown() {
+        Type::Any
+    } else {
+        ret
+    };
+
+    Ok(Type::Func(param_types, Box::new(ret)))
+}
+
+/// Returns the signature of the function the expression is known to refer to.
+fn signature_of(ctx: &Context, expr: &Expr) -> Option<MockSignature> {
+    match expr {
+        Expr::Func { params, .. } => Some(MockSignature {
+            params: params.iter().map(|p| p.node().spec()).collect(),
+            generics: Vec::new(),
+        }),
+        Expr::Ident(name) => ctx.lookup_var(name)?.signature.clone(),
+        _ => None,
+    }
+}
+
+/// Reports a null check with `operator` of a subject which can never be null.
+fn check_nullable(
+    analyzers: &AnalyzerSet,
+    ctx: &Context,
+    messages: &mut Vec<AnalyzerMessage>,
+    operator: &str,
+    subject: &Spanned<Expr>,
+    span: Span,
+) -> Result<(), &'static str> {
+    if !analyzers.contains(&AnalyzerKind::UnnecessaryNullChecks) {
+        return Ok(());
+    }
+
+    let ty = infer_type(analyzers, ctx, &mut Vec::new(), subject)?;
+    if !ty.is_nullable() && ty != Type::Never {
+        messages.push(AnalyzerMessage::unnecessary_null_check(
+            operator,
+            ty.to_string(),
+            span,
+            subject.span(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[allow(unused_variables, reason = "`analyzers` will be used later")]
+/// Analyzes the expression.
+///
+/// # Errors
+/// * The analyzer generated an error.
+pub fn visit_expr(
+    analyzers: &AnalyzerSet,
+    ctx: &mut Context,
+    messages: &mut Vec<AnalyzerMessage>,
+    expr: Spanned<Expr>,
+) -> Result<Type, &'static str> {
+    // infer_type provides us with operator checking
+    let ty = infer_type(analyzers, ctx, messages, &expr)?;
+
+    let span = expr.span();
+    let expr = expr.into_node();
+
+    match expr {
+        Expr::Ident(s) => match ctx.lookup_var_mut(&s) {
+            Some(e) => {
+                e.used = true;
+            }
+            None => {
+                let close_match = ctx.close_var_match(&s);
+
+                messages.push(AnalyzerMessage::unresolved_identifier(
+                    &s,
+                    close_match,
+                    span,
+                ));
+            }
+        },
+        Expr::If {
+            condition,
+            body,
             mut else_if_bodies,
             else_body,
         } => {
             else_if_bodies.insert(0, (condition, body));
+            let depth = ctx.narrowed.get_mut().len();
 
             for (condition, body) in else_if_bodies {
+                let truthy = narrowings(ctx, condition.node(), true);
+                let falsy = narrowings(ctx, condition.node(), false);
                 visit_expr(analyzers, ctx, messages, condition)?;
 
+                let guarded = ctx.narrowed.get_mut().len();
+                ctx.narrowed.get_mut().extend(truthy);
+
                 ctx.enter_scope();
-                for node in body.into_node().0 {
-                    visit_node(analyzers, ctx, messages, node)?;
-                }
+                visit_body(analyzers, ctx, messages, body.into_node().0)?;
                 ctx.exit_scope(analyzers, messages);
+
+                ctx.narrowed.get_mut().truncate(guarded);
+                ctx.narrowed.get_mut().extend(falsy);
             }
 
             if let Some(else_body) = else_body {
                 ctx.enter_scope();
-                for node in else_body.into_node().0 {
-                    visit_node(analyzers, ctx, messages, node)?;
+                visit_body(analyzers, ctx, messages, else_body.into_node().0)?;
+                ctx.exit_scope(analyzers, messages);
+            }
+            ctx.narrowed.get_mut().truncate(depth);
+        }
+        Expr::Match {
+            subject,
+            arms,
+            else_body,
+        } => {
+            let subject_ty = visit_expr(analyzers, ctx, messages, subject)?;
+
+            for arm in arms {
+                let MatchArm {
+                    pattern,
+                    guard,
+                    body,
+                } = arm.into_node();
+
+                ctx.enter_scope();
+                bind_pattern(analyzers, ctx, messages, pattern, subject_ty.clone())?;
+
+                if let Some(guard) = guard {
+                    visit_expr(analyzers, ctx, messages, guard)?;
                 }
+
+                visit_body(analyzers, ctx, messages, body.into_node().0)?;
+                ctx.exit_scope(analyzers, messages);
+            }
+
+            if let Some(else_body) = else_body {
+                ctx.enter_scope();
+                visit_body(analyzers, ctx, messages, else_body.into_node().0)?;
                 ctx.exit_scope(analyzers, messages);
             }
         }
-        Expr::While { condition, body } => {
+        Expr::For {
+            label,
+            target,
+            iterable,
+            body,
+        } => {
+            let ty = visit_expr(analyzers, ctx, messages, iterable)?;
+
+            ctx.enter_scope();
+            ctx.loops.push(label);
+            bind_target(analyzers, ctx, messages, target, ty.item_type());
+            visit_body(analyzers, ctx, messages, body)?;
+            ctx.loops.pop();
+            ctx.exit_scope(analyzers, messages);
+        }
+        Expr::While {
+            label,
+            condition,
+            body,
+        } => {
             visit_expr(analyzers, ctx, messages, condition)?;
 
             ctx.enter_scope();
-            for node in body {
-                visit_node(analyzers, ctx, messages, node)?;
-            }
+            ctx.loops.push(label);
+            visit_body(analyzers, ctx, messages, body)?;
+            ctx.loops.pop();
             ctx.exit_scope(analyzers, messages);
         }
         Expr::UnaryExpr { operator, value } => {
             visit_expr(analyzers, ctx, messages, value)?;
         }
+        Expr::Attr {
+            subject, optional, ..
+        } => {
+            if optional {
+                check_nullable(analyzers, ctx, messages, "?.", &subject, span)?;
+            }
+
+            visit_expr(analyzers, ctx, messages, subject)?;
+        }
+        Expr::NonNull(value) => {
+            check_nullable(analyzers, ctx, messages, "!", &value, span)?;
+            visit_expr(analyzers, ctx, messages, value)?;
+        }
+        Expr::Splat(value) | Expr::KeywordSplat(value) => {
+            visit_expr(analyzers, ctx, messages, value)?;
+        }
+        Expr::Index {
+            subject,
+            index,
+            optional,
+        } => {
+            if optional {
+                check_nullable(analyzers, ctx, messages, "?.", &subject, span)?;
+            }
+
+            visit_expr(analyzers, ctx, messages, subject)?;
+            visit_expr(analyzers, ctx, messages, index)?;
+        }
+        Expr::Slice {
+            subject,
+            start,
+            end,
+            optional,
+        } => {
+            if optional {
+                check_nullable(analyzers, ctx, messages, "?.", &subject, span)?;
+            }
+
+            visit_expr(analyzers, ctx, messages, subject)?;
+            for bound in start.into_iter().chain(end) {
+                visit_expr(analyzers, ctx, messages, bound)?;
+            }
+        }
+        Expr::Is { value, ty, .. } => {
+            visit_expr(analyzers, ctx, messages, value)?;
+            resolve_type_expr(ctx, messages, ty);
+        }
+        Expr::In { item, container } => {
+            visit_expr(analyzers, ctx, messages, item)?;
+            visit_expr(analyzers, ctx, messages, container)?;
+        }
+        Expr::Cast(value, ty) => {
+            visit_expr(analyzers, ctx, messages, value)?;
+
+            if cast_target(ty.node()).is_none() {
+                visit_expr(analyzers, ctx, messages, ty)?;
+            }
+        }
         Expr::BinaryExpr { operator, lhs, rhs } => {
             visit_expr(analyzers, ctx, messages, lhs)?;
             visit_expr(analyzers, ctx, messages, rhs)?;
@@ -1474,14 +3114,497 @@ pub fn visit_expr(
                 visit_expr(analyzers, ctx, messages, expr)?;
             }
         }
+        Expr::Interpolated(parts) => {
+            for part in parts {
+                if let InterpolatedPart::Value(value, _) = part {
+                    visit_expr(analyzers, ctx, messages, value)?;
+                }
+            }
+        }
+        Expr::Func {
+            params,
+            body,
+            return_last,
+            return_ty,
+        } => {
+            return visit_func(
+                analyzers,
+                ctx,
+                messages,
+                params,
+                body,
+                return_last,
+                return_ty,
+                span,
+            );
+        }
+        Expr::Call {
+            value,
+            args,
+            kwargs,
+            optional,
+        } => {
+            if optional {
+                check_nullable(analyzers, ctx, messages, "?.", &value, span.clone())?;
+            }
+
+            // Arguments unpacked from splats are only bound at runtime
+            let splat = has_splats(&args);
+
+            if let Some(signature) = signature_of(ctx, value.node()).filter(|_| !splat) {
+                let keywords = kwargs.iter().map(|(name, _)| name).collect::<Vec<_>>();
+
+                match bind_args(&signature.params, args.len(), &keywords) {
+                    Err(e) if analyzers.contains(&AnalyzerKind::InvalidArguments) => {
+                        let (name, declared_span) = match value.node() {
+                            Expr::Ident(name) => {
+                                (name.as_str(), ctx.lookup_var(name).map(|e| e.span.clone()))
+                            }
+                            _ => ("function", None),
+                        };
+
+                        messages.push(AnalyzerMessage::invalid_arguments(
+                            name,
+                            &e,
+                            span.clone(),
+                            declared_span.unwrap_or_else(|| value.span()),
+                        ));
+                    }
+                    Err(_) => (),
+                    Ok(_) => {
+                        let func = infer_type(analyzers, ctx, &mut Vec::new(), &value)?;
+                        let instance = instantiate_call(
+                            analyzers, ctx, &signature, &func, &args, &kwargs, &span,
+                        )?;
+
+                        for (arg_span, arg_ty, expected) in
+                            instance.map(|(_, m)| m).unwrap_or_default()
+                        {
+                            messages.push(AnalyzerMessage::incompatible_types(
+                                span.clone(),
+                                arg_span,
+                                arg_ty.to_string(),
+                                None,
+                                expected.to_string(),
+                            ));
+                        }
+                    }
+                }
+            }
+
+            visit_expr(analyzers, ctx, messages, value)?;
+            for arg in args
+                .into_iter()
+                .chain(kwargs.into_iter().map(|(_, arg)| arg))
+            {
+                visit_expr(analyzers, ctx, messages, arg)?;
+            }
+        }
+        Expr::Try {
+            body,
+            catches,
+            finally,
+        } => {
+            ctx.enter_scope();
+            visit_body(analyzers, ctx, messages, body.into_node().0)?;
+            ctx.exit_scope(analyzers, messages);
+
+            for clause in catches {
+                let (CatchClause { ty, binding, body }, clause_span) = clause.into_node_span();
+
+                if let Some(ty) = ty {
+                    resolve_type_expr(ctx, messages, ty);
+                }
+
+                ctx.enter_scope();
+                if let Some(binding) = binding {
+                    let target = Spanned::new(Target::Ident(binding), clause_span);
+                    bind_target(analyzers, ctx, messages, target, Type::Any);
+                }
+                visit_body(analyzers, ctx, messages, body.into_node().0)?;
+                ctx.exit_scope(analyzers, messages);
+            }
+
+            if let Some(finally) = finally {
+                ctx.enter_scope();
+                visit_body(analyzers, ctx, messages, finally.into_node().0)?;
+                ctx.exit_scope(analyzers, messages);
+            }
+        }
+        Expr::With { managers, body } => {
+            ctx.enter_scope();
+            for (manager, binding) in managers {
+                let manager_span = manager.span();
+                visit_expr(analyzers, ctx, messages, manager)?;
+
+                // TODO: the type returned by `op enter` of the manager
+                if let Some(binding) = binding {
+                    let target = Spanned::new(Target::Ident(binding), manager_span);
+                    bind_target(analyzers, ctx, messages, target, Type::Any);
+                }
+            }
+            visit_body(analyzers, ctx, messages, body.into_node().0)?;
+            ctx.exit_scope(analyzers, messages);
+        }
         _ => return Ok(ty),
     }
 
     Ok(ty)
 }
 
+/// Converts a target which is not destructuring into the expression which loads its value.
+fn target_expr(target: Spanned<Target>) -> Spanned<Expr> {
+    let (target, span) = target.into_node_span();
+
+    Spanned::new(
+        match target {
+            Target::Ident(name) => Expr::Ident(name),
+            Target::SelfRef => Expr::SelfRef,
+            Target::Attr(subject, attr) => Expr::Attr {
+                subject: target_expr(subject),
+                attr,
+                optional: false,
+            },
+            Target::Index(subject, index) => Expr::Index {
+                subject: target_expr(subject),
+                index,
+                optional: false,
+            },
+            Target::Array(_) | Target::Rest(_) => {
+                unreachable!("destructuring targets have no single value")
+            }
+        },
+        span,
+    )
+}
+
+/// Returns the type of each item of a destructuring target destructuring a value of type `ty`,
+/// where a rest item takes an array of the items left over. If the value has a known length
+/// which the items can not destructure, it is returned as well.
+fn destructured_types(targets: &[Spanned<Target>], ty: &Type) -> (Vec<Type>, Option<usize>) {
+    let rest = targets
+        .iter()
+        .position(|t| matches!(t.node(), Target::Rest(_)));
+    let fixed = targets.len() - usize::from(rest.is_some());
+
+    let len = match ty {
+        Type::Tuple(items) => Some(items.len()),
+        Type::Array(_, Some(len)) => Some(*len as usize),
+        _ => None,
+    };
+    let mismatch = len.filter(|&len| {
+        if rest.is_some() {
+            len < fixed
+        } else {
+            len != fixed
+        }
+    });
+
+    let types = (0..targets.len())
+        .map(|i| match (ty, rest) {
+            (Type::Tuple(items), Some(rest)) if i == rest && mismatch.is_none() => {
+                Type::Tuple(items[i..items.len() + i + 1 - targets.len()].to_vec())
+            }
+            (Type::Array(item, len), Some(rest)) if i == rest => Type::Array(
+                item.clone(),
+                len.map(|len| len.saturating_sub(fixed as u32)),
+            ),
+            (_, Some(rest)) if i == rest => Type::Array(Box::new(ty.item_type()), None),
+            (Type::Tuple(items), _) => {
+                // Items after a rest item are taken from the end
+                let pos = match rest {
+                    Some(rest) if i > rest => (items.len() + i).checked_sub(targets.len()),
+                    _ => Some(i),
+                };
+
+                pos.and_then(|pos| items.get(pos))
+                    .cloned()
+                    .unwrap_or(Type::Any)
+            }
+            (Type::Array(box item, _), _) => item.clone(),
+            _ => Type::Any,
+        })
+        .collect();
+
+    (types, mismatch)
+}
+
+/// Unwraps a rest item of a destructuring target into the target taking the rest.
+fn without_rest(target: Spanned<Target>) -> Spanned<Target> {
+    match target.into_node_span() {
+        (Target::Rest(target), _) => target,
+        (target, span) => Spanned::new(target, span),
+    }
+}
+
+/// Reports a destructuring target whose items can not destructure a value of type `ty`, then
+/// returns the type of each of its items.
+fn check_destructure(
+    analyzers: &AnalyzerSet,
+    messages: &mut Vec<AnalyzerMessage>,
+    targets: &[Spanned<Target>],
+    ty: &Type,
+    span: Span,
+    value_span: Span,
+) -> Vec<Type> {
+    let (types, mismatch) = destructured_types(targets, ty);
+
+    if let Some(found) = mismatch {
+        if analyzers.contains(&AnalyzerKind::DestructuringLengthMismatch) {
+            let has_rest = targets.iter().any(|t| matches!(t.node(), Target::Rest(_)));
+
+            messages.push(AnalyzerMessage::destructuring_length_mismatch(
+                targets.len() - usize::from(has_rest),
+                has_rest,
+                found,
+                span,
+                value_span,
+            ));
+        }
+    }
+
+    types
+}
+
+/// Checks an assignment of a value of type `ty` to the target, marking the variables it
+/// reassigns as mutated.
+///
+/// # Errors
+/// * The analyzer generated an error.
+fn assign_target(
+    analyzers: &AnalyzerSet,
+    ctx: &mut Context,
+    messages: &mut Vec<AnalyzerMessage>,
+    target: Spanned<Target>,
+    ty: Type,
+    span: Span,
+    value_span: Span,
+) -> Result<(), &'static str> {
+    let (target, tgt_span) = target.into_node_span();
+
+    match target {
+        Target::Ident(s) => {
+            let entry = match ctx.lookup_var_mut(&s) {
+                Some(entry) => entry,
+                None => {
+                    let close_match = ctx.close_var_match(&s);
+
+                    messages.push(AnalyzerMessage::unresolved_identifier(
+                        &s,
+                        close_match,
+                        tgt_span,
+                    ));
+                    return Ok(());
+                }
+            };
+
+            if entry.is_const() || !entry.is_mut() {
+                messages.push(AnalyzerMessage::reassigned_immutable_variable(
+                    &s,
+                    entry.span.clone(),
+                    span.clone(),
+                    entry.is_const(),
+                ));
+            }
+
+            entry.mutated = true;
+
+            if !ty.is_unknown() {
+                if entry.ty.is_unknown() {
+                    entry.ty = ty;
+                } else if !ty.is_compatible_with(&entry.ty) {
+                    messages.push(AnalyzerMessage::incompatible_types(
+                        span,
+                        value_span,
+                        ty.to_string(),
+                        None,
+                        entry.ty.to_string(),
+                    ));
+                }
+            }
+        }
+        Target::Array(targets) => {
+            let types = check_destructure(
+                analyzers,
+                messages,
+                &targets,
+                &ty,
+                tgt_span,
+                value_span.clone(),
+            );
+
+            for (target, ty) in targets.into_iter().zip(types) {
+                assign_target(
+                    analyzers,
+                    ctx,
+                    messages,
+                    without_rest(target),
+                    ty,
+                    span.clone(),
+                    value_span.clone(),
+                )?;
+            }
+        }
+        // Assigning to an attribute or item does not rebind the subject, which therefore
+        // does not have to be mutable
+        Target::Attr(..) | Target::Index(..) => {
+            let subject = target_expr(Spanned::new(target, tgt_span));
+
+            visit_expr(analyzers, ctx, messages, subject)?;
+        }
+        Target::SelfRef => unreachable!("`$` is only a target as a subject"),
+        Target::Rest(_) => unreachable!("rest targets are only parsed within arrays"),
+    }
+
+    Ok(())
+}
+
+/// Declares the identifiers of an immutable target, such as a `for` loop target,
+/// in the current scope.
+pub fn bind_target(
+    analyzers: &AnalyzerSet,
+    ctx: &mut Context,
+    messages: &mut Vec<AnalyzerMessage>,
+    target: Spanned<Target>,
+    ty: Type,
+) {
+    let (target, span) = target.into_node_span();
+
+    match target {
+        Target::Ident(name) => {
+            if analyzers.contains(&AnalyzerKind::NonSnakeCase) {
+                let snake = to_snake_case(&*name);
+
+                if name != snake {
+                    messages.push(AnalyzerMessage::non_snake_case(&name, snake, span.clone()));
+                }
+            }
+
+            ctx.store_var(
+                name.clone(),
+                MockScopeEntry::new(name, ty, ScopeEntryModifier::None, span),
+            );
+        }
+        Target::Array(targets) => {
+            let (types, _) = destructured_types(&targets, &ty);
+
+            for (target, ty) in targets.into_iter().zip(types) {
+                bind_target(analyzers, ctx, messages, without_rest(target), ty);
+            }
+        }
+        // Only the subjects of these are evaluated, nothing is declared
+        Target::Attr(..) | Target::Index(..) | Target::SelfRef => (),
+        Target::Rest(_) => unreachable!("rest targets are only parsed within arrays"),
+    }
+}
+
+/// Declares the identifiers bound by a match pattern in the current scope.
+///
+/// # Errors
+/// * The analyzer generated an error.
+pub fn bind_pattern(
+    analyzers: &AnalyzerSet,
+    ctx: &mut Context,
+    messages: &mut Vec<AnalyzerMessage>,
+    pattern: Spanned<Pattern>,
+    ty: Type,
+) -> Result<(), &'static str> {
+    let (pattern, span) = pattern.into_node_span();
+
+    match pattern {
+        Pattern::Literal(value) => {
+            visit_expr(analyzers, ctx, messages, value)?;
+        }
+        Pattern::Ident(name) => {
+            if name == "_" {
+                return Ok(());
+            }
+
+            if analyzers.contains(&AnalyzerKind::NonSnakeCase) {
+                let snake = to_snake_case(&*name);
+
+                if name != snake {
+                    messages.push(AnalyzerMessage::non_snake_case(&name, snake, span.clone()));
+                }
+            }
+
+            ctx.store_var(
+                name.clone(),
+                MockScopeEntry::new(name, ty, ScopeEntryModifier::None, span),
+            );
+        }
+        Pattern::Array(items) => {
+            for (i, item) in items.into_iter().enumerate() {
+                let ty = match &ty {
+                    Type::Tuple(items) => items.get(i).cloned().unwrap_or(Type::Any),
+                    Type::Array(box ty, _) => ty.clone(),
+                    _ => Type::Any,
+                };
+
+                bind_pattern(analyzers, ctx, messages, item, ty)?;
+            }
+        }
+        Pattern::Alt(alts) => {
+            for alt in alts {
+                bind_pattern(analyzers, ctx, messages, alt, ty.clone())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 #[allow(clippy::missing_panics_doc, reason = "todo!()")]
 #[allow(clippy::too_many_lines, reason = "Should refactor later.")]
+/// Declares the functions, classes and traits of a body before any of its nodes are visited,
+/// so that classes may extend traits declared after them and functions may be called before
+/// they are declared.
+fn hoist_declarations(ctx: &mut Context, nodes: &[SpannedNode]) {
+    for node in nodes {
+        match node.node() {
+            Node::Func {
+                name,
+                generics,
+                params,
+                return_ty,
+                ..
+            } => declare_func(ctx, name, generics, params, return_ty, node.span()),
+            Node::Class { name, .. } => ctx.store_class(name.clone(), node.span()),
+            Node::Trait { name, methods, .. } => ctx.store_trait(
+                name.clone(),
+                MockTrait {
+                    name: name.clone(),
+                    required: methods
+                        .iter()
+                        .filter(|m| m.body.is_none())
+                        .map(|m| (m.name.clone(), m.span()))
+                        .collect(),
+                    span: node.span(),
+                },
+            ),
+            _ => (),
+        }
+    }
+}
+
+/// Analyzes the nodes of a body, after hoisting its declarations.
+///
+/// # Errors
+/// * The analyzer generated an error.
+fn visit_body(
+    analyzers: &AnalyzerSet,
+    ctx: &mut Context,
+    messages: &mut Vec<AnalyzerMessage>,
+    nodes: Vec<SpannedNode>,
+) -> Result<(), &'static str> {
+    hoist_declarations(ctx, &nodes);
+
+    for node in nodes {
+        visit_node(analyzers, ctx, messages, node)?;
+    }
+    Ok(())
+}
+
 /// Analyzes the node.
 ///
 /// # Errors
@@ -1496,11 +3619,7 @@ pub fn visit_node(
     let node = node.into_node();
 
     match node {
-        Node::Module(m) => {
-            for node in m {
-                visit_node(analyzers, ctx, messages, node)?;
-            }
-        }
+        Node::Module(m) => visit_body(analyzers, ctx, messages, m)?,
         Node::Declare {
             targets,
             ty,
@@ -1510,16 +3629,18 @@ pub fn visit_node(
         } => {
             type DeferEntry = (String, Type, Span);
 
+            #[allow(clippy::too_many_arguments, reason = "Threads the declaration through")]
             fn recur(
+                analyzers: &AnalyzerSet,
                 ctx: &Context,
                 messages: &mut Vec<AnalyzerMessage>,
                 target: Target,
                 span: Span,
                 tgt_span: Span,
+                value_span: Span,
                 ty: Type,
                 deferred: &mut Vec<DeferEntry>,
             ) {
-                #[allow(clippy::match_wildcard_for_single_variants, reason = "todo!()")]
                 match target {
                     Target::Ident(s) => {
                         if let Some(entry) = ctx.lookup_var(&s) {
@@ -1535,34 +3656,37 @@ pub fn visit_node(
                         deferred.push((s, ty, tgt_span));
                     }
                     Target::Array(targets) => {
-                        for (i, target) in targets.into_iter().enumerate() {
-                            let (target, tgt_span) = target.into_node_span();
+                        let types = check_destructure(
+                            analyzers,
+                            messages,
+                            &targets,
+                            &ty,
+                            tgt_span,
+                            value_span.clone(),
+                        );
+
+                        for (target, ty) in targets.into_iter().zip(types) {
+                            let (target, tgt_span) = without_rest(target).into_node_span();
 
                             recur(
+                                analyzers,
                                 ctx,
                                 messages,
                                 target,
                                 span.clone(),
-                                tgt_span.clone(),
-                                match &ty {
-                                    Type::Tuple(items) => items[i].clone(),
-                                    Type::Array(box ty, _) => ty.clone(),
-                                    _ => Type::Any,
-                                },
+                                tgt_span,
+                                value_span.clone(),
+                                ty,
                                 deferred,
                             );
                         }
                     }
-                    _ => todo!(),
+                    // e.g. let $x = 1; stores into a field instead of declaring a variable
+                    Target::Attr(..) | Target::Index(..) | Target::SelfRef => (),
+                    Target::Rest(_) => unreachable!("rest targets are only parsed within arrays"),
                 };
             }
 
-            // Assume there can only be one target
-            let (target, tgt_span) = targets
-                .first()
-                .ok_or("multiple declaration targets unsupported")?
-                .node_span();
-
             let modifier = match (r#mut, r#const) {
                 (true, false) => ScopeEntryModifier::Mut,
                 (false, true) => ScopeEntryModifier::Const,
@@ -1581,6 +3705,7 @@ pub fn visit_node(
             let (ty, ty_span) = resolve_type_expr(ctx, messages, ty);
 
             let value_span = value.span();
+            let signature = signature_of(ctx, value.node());
             let inferred = visit_expr(analyzers, ctx, messages, value)?;
 
             let ty = if ty.is_unknown() {
@@ -1589,7 +3714,7 @@ pub fn visit_node(
                 if !inferred.is_compatible_with(&ty) {
                     messages.push(AnalyzerMessage::incompatible_types(
                         span.clone(),
-                        value_span,
+                        value_span.clone(),
                         inferred.to_string(),
                         if show_span { Some(ty_span) } else { None },
                         ty.to_string(),
@@ -1601,15 +3726,21 @@ pub fn visit_node(
 
             let mut deferred = Vec::<DeferEntry>::new();
 
-            recur(
-                ctx,
-                messages,
-                target.clone(),
-                span.clone(),
-                tgt_span.clone(),
-                ty,
-                &mut deferred,
-            );
+            for target in targets.iter().cloned() {
+                let (target, tgt_span) = target.into_node_span();
+
+                recur(
+                    analyzers,
+                    ctx,
+                    messages,
+                    target,
+                    span.clone(),
+                    tgt_span,
+                    value_span.clone(),
+                    ty.clone(),
+                    &mut deferred,
+                );
+            }
 
             for (name, ty, tgt_span) in deferred {
                 if analyzers.contains(&AnalyzerKind::NonSnakeCase) {
@@ -1624,83 +3755,263 @@ pub fn visit_node(
                     }
                 }
 
-                ctx.store_var(
-                    name.clone(),
-                    MockScopeEntry::new(name, ty, modifier, span.clone()),
-                );
+                let mut entry = MockScopeEntry::new(name.clone(), ty, modifier, span.clone());
+                if targets
+                    .iter()
+                    .any(|t| matches!(t.node(), Target::Ident(n) if n == &name))
+                {
+                    entry.signature = signature.clone();
+                }
+
+                ctx.store_var(name, entry);
             }
         }
         Node::Assign { targets, value } => {
-            // Assume there can only be one target
-            let (target, tgt_span) = targets
-                .first()
-                .ok_or("multiple assignment targets unsupported")?
-                .node_span();
-
-            #[allow(clippy::match_wildcard_for_single_variants, reason = "todo!()")]
-            match target {
-                Target::Ident(s) => {
-                    let has_entry = ctx.lookup_var(s).is_some();
-
-                    if has_entry {
-                        {
-                            let entry = ctx.lookup_var(s).unwrap();
+            let value_span = value.span();
+            let ty = visit_expr(analyzers, ctx, messages, value)?;
+
+            for target in targets {
+                assign_target(
+                    analyzers,
+                    ctx,
+                    messages,
+                    target,
+                    ty.clone(),
+                    span.clone(),
+                    value_span.clone(),
+                )?;
+            }
+        }
+        // Analyzed as the assignment it is shorthand for, so that it is checked as a mutation
+        Node::CompoundAssign {
+            target,
+            operator,
+            value,
+        } => {
+            let value_span = target.span().merge(value.span());
+            let value = Expr::BinaryExpr {
+                operator,
+                lhs: target_expr(target.clone()),
+                rhs: value,
+            };
 
-                            if entry.is_const() || !entry.is_mut() {
-                                messages.push(AnalyzerMessage::reassigned_immutable_variable(
-                                    s,
-                                    entry.span.clone(),
-                                    span.clone(),
-                                    entry.is_const(),
-                                ));
-                            }
-                        }
+            visit_node(
+                analyzers,
+                ctx,
+                messages,
+                Spanned::new(
+                    Node::Assign {
+                        targets: vec![target],
+                        value: Spanned::new(value, value_span),
+                    },
+                    span,
+                ),
+            )?;
+        }
+        Node::Class {
+            name,
+            generics,
+            bases,
+            fields,
+            methods,
+            ..
+        } => {
+            ctx.store_class(name.clone(), span.clone());
 
-                        let value_span = value.span();
-                        let ty = visit_expr(analyzers, ctx, messages, value)?;
-                        let entry = ctx.lookup_var_mut(s).unwrap();
-
-                        entry.mutated = true;
-
-                        if !ty.is_unknown() {
-                            if entry.ty.is_unknown() {
-                                entry.ty = ty;
-                            } else {
-                                if !ty.is_compatible_with(&entry.ty) {
-                                    messages.push(AnalyzerMessage::incompatible_types(
-                                        span,
-                                        value_span,
-                                        ty.to_string(),
-                                        None,
-                                        entry.ty.to_string(),
-                                    ));
-                                }
-                            }
+            if analyzers.contains(&AnalyzerKind::MissingTraitMethods) {
+                let implemented = methods
+                    .iter()
+                    .map(|m| m.name.as_str())
+                    .collect::<HashSet<_>>();
+
+                for base in &bases {
+                    let base_name = match base.node() {
+                        TypeExpr::Ident(s) => s,
+                        TypeExpr::Generic(subject, _) => match subject.node() {
+                            TypeExpr::Ident(s) => s,
+                            _ => continue,
+                        },
+                        _ => continue,
+                    };
+
+                    if let Some(r#trait) = ctx.lookup_trait(base_name) {
+                        let missing = r#trait
+                            .required
+                            .iter()
+                            .filter(|(m, _)| !implemented.contains(m.as_str()))
+                            .cloned()
+                            .collect::<Vec<_>>();
+
+                        if !missing.is_empty() {
+                            messages.push(AnalyzerMessage::missing_trait_methods(
+                                &name,
+                                &r#trait.name,
+                                missing,
+                                base.span(),
+                                span.clone(),
+                            ));
                         }
-                    } else {
-                        let close_match = ctx.close_var_match(s);
-
-                        messages.push(AnalyzerMessage::unresolved_identifier(
-                            s,
-                            close_match,
-                            tgt_span.clone(),
-                        ));
-                        return Ok(());
                     }
                 }
-                Target::Array(_) => return Err("array assignments unsupported"),
-                _ => todo!(),
             }
+
+            // The generic parameters of the class are in scope for its bases and members
+            let depth = ctx.type_params.len();
+            ctx.type_params
+                .extend(generics.iter().map(|g| g.node().name.clone()));
+            for ty in generics
+                .into_iter()
+                .flat_map(|g| g.into_node().bounds)
+                .chain(bases)
+            {
+                resolve_type_expr(ctx, messages, ty);
+            }
+
+            let self_ty = Spanned::new(TypeExpr::Ident(name), span);
+            let result = visit_members(analyzers, ctx, messages, self_ty, fields, methods);
+            ctx.type_params.truncate(depth);
+            result?;
+        }
+        Node::Func {
+            name,
+            generics,
+            params,
+            body,
+            return_last,
+            return_ty,
+            throws,
+        } => {
+            if analyzers.contains(&AnalyzerKind::NonSnakeCase) {
+                let snake = to_snake_case(&*name);
+
+                if name != snake {
+                    messages.push(AnalyzerMessage::non_snake_case(&name, snake, span.clone()));
+                }
+            }
+
+            // Declared before the body is visited, so that the function can call itself
+            declare_func(ctx, &name, &generics, &params, &return_ty, span.clone());
+
+            let depth = ctx.type_params.len();
+            ctx.type_params
+                .extend(generics.iter().map(|g| g.node().name.clone()));
+            for ty in generics
+                .into_iter()
+                .flat_map(|g| g.into_node().bounds)
+                .chain(throws)
+            {
+                resolve_type_expr(ctx, messages, ty);
+            }
+
+            let result = visit_func(
+                analyzers,
+                ctx,
+                messages,
+                params,
+                body,
+                return_last,
+                return_ty,
+                span,
+            );
+            ctx.type_params.truncate(depth);
+            result?;
         }
+        // Traits are registered when their enclosing body is visited
+        Node::Trait {
+            name,
+            generics,
+            fields,
+            methods,
+            ..
+        } => {
+            let depth = ctx.type_params.len();
+            ctx.type_params
+                .extend(generics.iter().map(|g| g.node().name.clone()));
+            for ty in generics.into_iter().flat_map(|g| g.into_node().bounds) {
+                resolve_type_expr(ctx, messages, ty);
+            }
+
+            let self_ty = Spanned::new(TypeExpr::Ident(name), span);
+            let result = visit_members(analyzers, ctx, messages, self_ty, fields, methods);
+            ctx.type_params.truncate(depth);
+            result?;
+        }
+        Node::Extend {
+            r#trait,
+            target,
+            methods,
+        } => {
+            resolve_type_expr(ctx, messages, r#trait);
+            visit_members(analyzers, ctx, messages, target, Vec::new(), methods)?;
+        }
+        Node::Break { label, value } => {
+            check_loop_control(analyzers, ctx, messages, "break", label, span);
+
+            if let Some(value) = value {
+                visit_expr(analyzers, ctx, messages, value)?;
+            }
+        }
+        Node::Continue(label) => {
+            check_loop_control(analyzers, ctx, messages, "continue", label, span);
+        }
+        Node::Return(value) => {
+            if let Some(value) = value {
+                visit_expr(analyzers, ctx, messages, value)?;
+            }
+        }
+        Node::Throw(value) => {
+            visit_expr(analyzers, ctx, messages, value)?;
+        }
+        Node::Require { items, from } => match items {
+            RequireItems::Named(items) => {
+                for item in items {
+                    ctx.store_required(item.binding().to_string(), span.clone());
+                }
+            }
+            // Names bound by a glob require are only known once the module is loaded
+            RequireItems::Glob => {
+                let exports = from
+                    .as_ref()
+                    .and_then(|from| ctx.modules.get(&from.to_string()))
+                    .cloned()
+                    .unwrap_or_default();
+
+                for name in exports {
+                    ctx.store_required(name, span.clone());
+                }
+            }
+        },
         Node::Expr(expr) => {
             visit_expr(analyzers, ctx, messages, expr)?;
         }
-        _ => unimplemented!(),
     }
 
     Ok(())
 }
 
+/// Reports a `break` or `continue` that has no enclosing loop it could target.
+fn check_loop_control(
+    analyzers: &AnalyzerSet,
+    ctx: &Context,
+    messages: &mut Vec<AnalyzerMessage>,
+    keyword: &str,
+    label: Option<String>,
+    span: Span,
+) {
+    let found = match &label {
+        Some(_) => ctx.loops.contains(&label),
+        None => !ctx.loops.is_empty(),
+    };
+
+    if analyzers.contains(&AnalyzerKind::LoopControlOutsideLoop) && !found {
+        messages.push(AnalyzerMessage::loop_control_outside_loop(
+            keyword,
+            label.as_deref(),
+            span,
+        ));
+    }
+}
+
 /// Analyze the given context.
 ///
 /// # Errors
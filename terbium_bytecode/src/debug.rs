@@ -0,0 +1,132 @@
+//! Debug info mapping the instructions of a [`Program`] back to the source code they were
+//! lowered from.
+//!
+//! The span of an instruction only holds character offsets into its source file, so a program
+//! also keeps the line table of each file it was lowered from, which is enough to tell the line
+//! and column of an offset once the source itself is gone, e.g. in precompiled bytecode.
+
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use super::{AddrRepr, Program};
+use terbium_grammar::Source;
+
+/// The lines of a source file which a [`Program`] was lowered from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceFile {
+    pub(crate) src: Source,
+    /// The character offset each line starts at, the first always being 0.
+    pub(crate) line_starts: Vec<usize>,
+    /// The source code itself. This is not serialized, so it is only known for programs
+    /// lowered in this process.
+    pub(crate) text: Option<String>,
+}
+
+impl SourceFile {
+    #[must_use]
+    pub fn new(src: Source, text: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(
+                text.chars()
+                    .enumerate()
+                    .filter(|(_, c)| *c == '\n')
+                    .map(|(i, _)| i + 1),
+            )
+            .collect();
+
+        Self {
+            src,
+            line_starts,
+            text: Some(text),
+        }
+    }
+
+    #[must_use]
+    pub fn src(&self) -> Source {
+        self.src.clone()
+    }
+
+    /// The line and column of the character at `offset`, both starting at 1.
+    #[must_use]
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+
+        (line, offset - self.line_starts[line - 1] + 1)
+    }
+
+    /// The source code of the file. Programs read from bytes read it from the path of the
+    /// source instead, if it still exists.
+    #[must_use]
+    pub fn text(&self) -> Option<Cow<'_, str>> {
+        match self.text {
+            Some(ref text) => Some(Cow::Borrowed(text)),
+            None => std::fs::read_to_string(self.src.to_path())
+                .ok()
+                .map(Cow::Owned),
+        }
+    }
+}
+
+/// A position in the source code of a [`Program`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub src: Source,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.src.to_path().display(),
+            self.line,
+            self.column
+        )
+    }
+}
+
+impl Program {
+    /// Records the source code of a file this program is lowered from, so that the spans
+    /// pointing into it can be resolved to lines. Replaces any source previously added for
+    /// the same file.
+    pub fn add_source(&mut self, src: Source, text: String) {
+        self.sources.retain(|file| file.src != src);
+        self.sources.push(SourceFile::new(src, text));
+    }
+
+    pub fn sources(&self) -> impl Iterator<Item = &SourceFile> {
+        self.sources.iter()
+    }
+
+    #[must_use]
+    pub fn source(&self, src: &Source) -> Option<&SourceFile> {
+        self.sources.iter().find(|file| &file.src == src)
+    }
+
+    /// Where the instruction at `addr` starts in the source code, or `None` if it has no
+    /// span or its source was never added.
+    #[must_use]
+    pub fn location(&self, addr: AddrRepr) -> Option<Location> {
+        let span = self.inner.get(addr)?.span.as_ref()?;
+        let (line, column) = self.source(&span.src())?.line_col(span.start());
+
+        Some(Location {
+            src: span.src(),
+            line,
+            column,
+        })
+    }
+
+    /// Removes the spans, names and sources of every instruction, so that the program
+    /// serializes without debug info.
+    pub fn strip_debug_info(&mut self) {
+        self.sources.clear();
+
+        for instr in &mut self.inner {
+            instr.span = None;
+            instr.name = None;
+        }
+    }
+}
@@ -0,0 +1,800 @@
+//! The `.trbc` container which a [`Program`] is serialized to.
+//!
+//! Every number is little-endian, and every integer operand is an unsigned LEB128 varint.
+//! A file is laid out as:
+//!
+//! - The magic number [`MAGIC`], then the [`FORMAT_VERSION`] as a `u16`, then a byte of flags
+//!   telling whether the debug section is present
+//! - The constant pool, as the amount of constants followed by each constant: a byte naming
+//!   its kind, then its value. Strings are their length followed by their UTF-8 bytes, integers
+//!   are a `u128` and floats are the bits of an `f64`. Each distinct constant is stored once.
+//! - The handler table, as the amount of handlers followed by the target of each
+//! - The code, as the amount of instructions followed by each instruction: its opcode (see
+//!   [`Instruction::to_instr_id`]) then its operands
+//! - The debug section, which is made of three tables:
+//!   - The sources, as the amount of source files followed by the path of each, then the amount
+//!     of lines in it followed by the distance between the start of each line and the last
+//!   - The spans, as the amount of instructions with a span followed by the distance of each
+//!     from the last one with a span, then the path of its source and its start and end offsets
+//!   - The names, laid out like the spans, each instruction being followed by its name
+//!
+//! Strings (including paths and names), floats and integers which do not fit in a `u64` are
+//! written as their index in the constant pool.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::path::PathBuf;
+
+use super::{
+    Addr, EqComparableFloat, Handler, Instruction, Program, RichInstruction, SourceFile, TypeTag,
+};
+use terbium_grammar::{Align, FormatSpec, ParamKind, ParamSpec, Source, Span};
+
+/// The bytes every `.trbc` file starts with.
+pub const MAGIC: [u8; 4] = *b"TRBC";
+
+/// The version of the format written by [`Program::bytes`]. Only files of this version can be
+/// read back, so it is bumped whenever the layout or an opcode changes.
+pub const FORMAT_VERSION: u16 = 2;
+
+const STRING_CONSTANT: u8 = 0;
+const INT_CONSTANT: u8 = 1;
+const FLOAT_CONSTANT: u8 = 2;
+
+const DEBUG_INFO: u8 = 0b1;
+
+/// An error encountered while reading a [`Program`] from bytes. Each offset is the position
+/// in the input at which the malformed value starts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input does not start with [`MAGIC`], so it is not Terbium bytecode.
+    InvalidMagic,
+    /// The input was written in a format version other than [`FORMAT_VERSION`].
+    UnsupportedVersion(u16),
+    /// The input ended in the middle of a value, e.g. because the file was truncated.
+    UnexpectedEof(usize),
+    /// A varint encodes a number too large for its operand.
+    VarintOverflow(usize),
+    /// A byte which is not the opcode of any instruction.
+    InvalidOpcode { opcode: u8, offset: usize },
+    /// An index into the constant pool which is out of bounds, or refers to a constant of
+    /// the wrong kind.
+    InvalidConstant { index: usize, offset: usize },
+    /// A string in the constant pool which is not valid UTF-8.
+    InvalidUtf8(usize),
+    /// An operand outside of the values its instruction accepts, e.g. a jump to an address
+    /// past the end of the code.
+    InvalidOperand { reason: &'static str, offset: usize },
+    /// Bytes left over after the last instruction.
+    TrailingBytes(usize),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::InvalidMagic => write!(f, "not a Terbium bytecode file (missing magic number)"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported bytecode format version {} (expected version {})",
+                version, FORMAT_VERSION,
+            ),
+            Self::UnexpectedEof(offset) => write!(
+                f,
+                "unexpected end of bytecode at offset {}, the file may be truncated",
+                offset,
+            ),
+            Self::VarintOverflow(offset) => write!(f, "varint at offset {} is too large", offset),
+            Self::InvalidOpcode { opcode, offset } => {
+                write!(f, "invalid opcode 0x{:02x} at offset {}", opcode, offset)
+            }
+            Self::InvalidConstant { index, offset } => write!(
+                f,
+                "constant {} referred to at offset {} does not exist or is of the wrong kind",
+                index, offset,
+            ),
+            Self::InvalidUtf8(offset) => {
+                write!(f, "string at offset {} is not valid UTF-8", offset)
+            }
+            Self::InvalidOperand { reason, offset } => write!(f, "{} at offset {}", reason, offset),
+            Self::TrailingBytes(offset) => {
+                write!(f, "unexpected trailing bytes at offset {}", offset)
+            }
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Constant {
+    String(String),
+    Int(u128),
+    Float(EqComparableFloat),
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u128) {
+    loop {
+        #[allow(clippy::cast_possible_truncation)] // Only the lowest 7 bits are kept
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            return bytes.push(byte);
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+#[derive(Default)]
+struct Encoder {
+    constants: Vec<Constant>,
+    indices: HashMap<Constant, usize>,
+    code: Vec<u8>,
+}
+
+impl Encoder {
+    fn varint(&mut self, value: usize) {
+        write_varint(&mut self.code, value as u128);
+    }
+
+    /// Adds the constant to the pool unless it is already there, returning its index.
+    fn intern(&mut self, constant: Constant) -> usize {
+        if let Some(&index) = self.indices.get(&constant) {
+            return index;
+        }
+
+        self.constants.push(constant.clone());
+        self.indices.insert(constant, self.constants.len() - 1);
+        self.constants.len() - 1
+    }
+
+    fn string(&mut self, s: &str) {
+        let index = self.intern(Constant::String(s.to_string()));
+        self.varint(index);
+    }
+
+    fn addr(&mut self, addr: Addr) {
+        match addr {
+            Addr::Absolute(p) => self.varint(p),
+            _ => panic!("procedures must be resolved prior to conversion"),
+        }
+    }
+
+    fn format_spec(&mut self, spec: &FormatSpec) {
+        let align = match spec.align {
+            None => 0_u8,
+            Some(Align::Left) => 1,
+            Some(Align::Center) => 2,
+            Some(Align::Right) => 3,
+        };
+        let flags = align
+            | u8::from(spec.width.is_some()) << 2
+            | u8::from(spec.precision.is_some()) << 3
+            | u8::from(spec.repr) << 4;
+
+        self.varint(spec.fill as usize);
+        self.code.push(flags);
+        for n in spec.width.into_iter().chain(spec.precision) {
+            self.varint(n);
+        }
+    }
+
+    fn param_spec(&mut self, spec: &ParamSpec) {
+        let kind = match spec.kind {
+            ParamKind::Normal => 0_u8,
+            ParamKind::Variadic => 1,
+            ParamKind::KeywordVariadic => 2,
+        };
+        let flags = u8::from(spec.keyword_only)
+            | u8::from(spec.default) << 1
+            | u8::from(spec.optional) << 2;
+
+        self.string(&spec.name);
+        self.code.extend_from_slice(&[kind, flags]);
+    }
+
+    fn instruction(&mut self, instr: &RichInstruction) {
+        type I = Instruction;
+
+        self.code.push(instr.inner.to_instr_id());
+
+        match &instr.inner {
+            // The lowest bit tells inline integers apart from indices into the constant pool
+            I::LoadInt(i) => match u64::try_from(*i) {
+                Ok(i) => write_varint(&mut self.code, u128::from(i) << 1),
+                Err(_) => {
+                    let index = self.intern(Constant::Int(*i));
+                    write_varint(&mut self.code, (index as u128) << 1 | 1);
+                }
+            },
+            I::LoadFloat(f) => {
+                let index = self.intern(Constant::Float(*f));
+                self.varint(index);
+            }
+            I::LoadString(s) | I::MakeOp(s) | I::LoadOp(s) => self.string(s),
+            I::LoadBool(b) => self.code.push(u8::from(*b)),
+            I::IsType(tag) | I::Cast(tag) => self.code.push(*tag as u8),
+            I::LoadVar(i)
+            | I::Load(i)
+            | I::LoadFrame(i)
+            | I::Store(i)
+            | I::StoreMutVar(i)
+            | I::StoreConstVar(i)
+            | I::StoreVar(i)
+            | I::AssignVar(i)
+            | I::InitVar(i)
+            | I::CaptureVar(i)
+            | I::MakeArray(i)
+            | I::LoadAttr(i)
+            | I::StoreAttr(i)
+            | I::MakeModule(i)
+            | I::BuildString(i)
+            | I::MatchArray(i)
+            | I::EnterTry(i)
+            | I::MakeMethod(i) => self.varint(*i),
+            I::FormatValue(spec) => self.format_spec(spec),
+            I::MakeParam(spec) => self.param_spec(spec),
+            I::CallFunc(args, kwargs) | I::CallFuncUnpacked(args, kwargs) => {
+                self.varint(*args);
+                self.varint(*kwargs);
+            }
+            I::MakeClass(name, bases) => {
+                self.string(name);
+                self.varint(*bases);
+            }
+            I::Jump(a) | I::JumpIf(a) | I::JumpIfNotNull(a) | I::MakeFunc(a) => self.addr(*a),
+            I::JumpIfElse(a, b) => {
+                self.addr(*a);
+                self.addr(*b);
+            }
+            _ => (),
+        }
+    }
+
+    fn path(&mut self, src: &Source) {
+        self.string(&src.to_path().to_string_lossy());
+    }
+
+    /// Writes the entries of the instructions for which `entry` is `Some`, each preceded by
+    /// its distance from the last.
+    fn table<'a, T: 'a>(
+        &mut self,
+        instructions: &'a [RichInstruction],
+        entry: impl Fn(&'a RichInstruction) -> Option<T>,
+        mut write: impl FnMut(&mut Self, T),
+    ) {
+        let entries = instructions
+            .iter()
+            .enumerate()
+            .filter_map(|(addr, instr)| Some((addr, entry(instr)?)))
+            .collect::<Vec<_>>();
+        let mut last = 0;
+
+        self.varint(entries.len());
+        for (addr, entry) in entries {
+            self.varint(addr - last);
+            write(self, entry);
+            last = addr;
+        }
+    }
+
+    fn debug_info(&mut self, program: &Program) {
+        self.varint(program.sources.len());
+        for file in &program.sources {
+            self.path(&file.src);
+            self.varint(file.line_starts.len());
+
+            let mut last = 0;
+            for &start in &file.line_starts {
+                self.varint(start - last);
+                last = start;
+            }
+        }
+
+        self.table(
+            &program.inner,
+            |instr| instr.span.as_ref(),
+            |this, span| {
+                this.path(&span.src());
+                this.varint(span.start());
+                this.varint(span.end());
+            },
+        );
+        self.table(
+            &program.inner,
+            |instr| instr.name.as_ref(),
+            |this, name| {
+                this.string(name);
+            },
+        );
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    constants: Vec<Constant>,
+    /// The amount of instructions in the code, which addresses must be less than.
+    code_len: usize,
+    handlers: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(DecodeError::UnexpectedEof(self.pos))?;
+        let taken = &self.bytes[self.pos..end];
+
+        self.pos = end;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn varint_u128(&mut self) -> Result<u128, DecodeError> {
+        let start = self.pos;
+        let mut value = 0_u128;
+
+        for shift in (0..128).step_by(7) {
+            let byte = self.byte()?;
+            let bits = u128::from(byte & 0x7f);
+
+            if shift > 0 && bits >> (128 - shift) != 0 {
+                break;
+            }
+            value |= bits << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(DecodeError::VarintOverflow(start))
+    }
+
+    fn varint(&mut self) -> Result<usize, DecodeError> {
+        let start = self.pos;
+
+        usize::try_from(self.varint_u128()?).map_err(|_| DecodeError::VarintOverflow(start))
+    }
+
+    fn invalid(&self, reason: &'static str, offset: usize) -> DecodeError {
+        DecodeError::InvalidOperand { reason, offset }
+    }
+
+    fn constant(&mut self) -> Result<Constant, DecodeError> {
+        let kind_offset = self.pos;
+
+        Ok(match self.byte()? {
+            STRING_CONSTANT => {
+                let len = self.varint()?;
+                let start = self.pos;
+                let bytes = self.take(len)?;
+
+                Constant::String(
+                    String::from_utf8(bytes.to_vec())
+                        .map_err(|_| DecodeError::InvalidUtf8(start))?,
+                )
+            }
+            INT_CONSTANT => Constant::Int(u128::from_le_bytes(self.array()?)),
+            FLOAT_CONSTANT => Constant::Float(f64::from_le_bytes(self.array()?).into()),
+            _ => return Err(self.invalid("unknown constant kind", kind_offset)),
+        })
+    }
+
+    /// Reads the distance of an entry of a debug table from the last, returning its address.
+    fn entry_addr(&mut self, last: usize) -> Result<usize, DecodeError> {
+        let offset = self.pos;
+
+        match last.checked_add(self.varint()?) {
+            Some(addr) if addr < self.code_len => Ok(addr),
+            _ => Err(self.invalid("address past the end of the code", offset)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, DecodeError> {
+        let offset = self.pos;
+        let index = self.varint()?;
+
+        match self.constants.get(index) {
+            Some(Constant::String(s)) => Ok(s.clone()),
+            _ => Err(DecodeError::InvalidConstant { index, offset }),
+        }
+    }
+
+    fn int(&mut self) -> Result<u128, DecodeError> {
+        let offset = self.pos;
+        let operand = self.varint_u128()?;
+
+        if operand & 1 == 0 {
+            return Ok(operand >> 1);
+        }
+
+        let index = usize::try_from(operand >> 1).unwrap_or(usize::MAX);
+        match self.constants.get(index) {
+            Some(Constant::Int(i)) => Ok(*i),
+            _ => Err(DecodeError::InvalidConstant { index, offset }),
+        }
+    }
+
+    fn float(&mut self) -> Result<EqComparableFloat, DecodeError> {
+        let offset = self.pos;
+        let index = self.varint()?;
+
+        match self.constants.get(index) {
+            Some(Constant::Float(f)) => Ok(*f),
+            _ => Err(DecodeError::InvalidConstant { index, offset }),
+        }
+    }
+
+    fn addr(&mut self) -> Result<Addr, DecodeError> {
+        let offset = self.pos;
+
+        match self.varint()? {
+            addr if addr < self.code_len => Ok(Addr::Absolute(addr)),
+            _ => Err(self.invalid("address past the end of the code", offset)),
+        }
+    }
+
+    fn flags(&mut self, valid: u8) -> Result<u8, DecodeError> {
+        let offset = self.pos;
+
+        match self.byte()? {
+            flags if flags & !valid == 0 => Ok(flags),
+            _ => Err(self.invalid("unknown flags", offset)),
+        }
+    }
+
+    fn type_tag(&mut self) -> Result<TypeTag, DecodeError> {
+        let offset = self.pos;
+        let tag = self.byte()?;
+
+        TypeTag::from_byte(tag).ok_or_else(|| self.invalid("unknown type tag", offset))
+    }
+
+    fn format_spec(&mut self) -> Result<FormatSpec, DecodeError> {
+        let offset = self.pos;
+        let fill = u32::try_from(self.varint()?)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.invalid("invalid fill character", offset))?;
+        let flags = self.flags(0b1_1111)?;
+
+        Ok(FormatSpec {
+            fill,
+            align: match flags & 0b11 {
+                0 => None,
+                1 => Some(Align::Left),
+                2 => Some(Align::Center),
+                _ => Some(Align::Right),
+            },
+            width: if flags & 0b100 == 0 {
+                None
+            } else {
+                Some(self.varint()?)
+            },
+            precision: if flags & 0b1000 == 0 {
+                None
+            } else {
+                Some(self.varint()?)
+            },
+            repr: flags & 0b1_0000 != 0,
+        })
+    }
+
+    fn param_spec(&mut self) -> Result<ParamSpec, DecodeError> {
+        let name = self.string()?;
+        let offset = self.pos;
+        let kind = match self.byte()? {
+            0 => ParamKind::Normal,
+            1 => ParamKind::Variadic,
+            2 => ParamKind::KeywordVariadic,
+            _ => return Err(self.invalid("unknown parameter kind", offset)),
+        };
+        let flags = self.flags(0b111)?;
+
+        Ok(ParamSpec {
+            name,
+            kind,
+            keyword_only: flags & 0b1 != 0,
+            default: flags & 0b10 != 0,
+            optional: flags & 0b100 != 0,
+        })
+    }
+
+    fn path(&mut self) -> Result<Source, DecodeError> {
+        Ok(Source::from_path(PathBuf::from(self.string()?)))
+    }
+
+    /// Reads the debug section, attaching the spans and names to the instructions.
+    fn debug_info(
+        &mut self,
+        instructions: &mut [RichInstruction],
+    ) -> Result<Vec<SourceFile>, DecodeError> {
+        let mut sources = Vec::new();
+        for _ in 0..self.varint()? {
+            let src = self.path()?;
+            let table_offset = self.pos;
+            let mut line_starts = Vec::new();
+            let mut last = 0_usize;
+
+            for _ in 0..self.varint()? {
+                let offset = self.pos;
+                last = last
+                    .checked_add(self.varint()?)
+                    .ok_or(DecodeError::VarintOverflow(offset))?;
+                line_starts.push(last);
+            }
+
+            if line_starts.first() != Some(&0) {
+                return Err(self.invalid("line table does not start at offset 0", table_offset));
+            }
+            sources.push(SourceFile {
+                src,
+                line_starts,
+                text: None,
+            });
+        }
+
+        let mut last = 0;
+        for _ in 0..self.varint()? {
+            last = self.entry_addr(last)?;
+
+            let src = self.path()?;
+            let start = self.varint()?;
+            let end = self.varint()?;
+            instructions[last].span = Some(Span::from_range(src, start..end));
+        }
+
+        let mut last = 0;
+        for _ in 0..self.varint()? {
+            last = self.entry_addr(last)?;
+            instructions[last].name = Some(self.string()?);
+        }
+
+        Ok(sources)
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn instruction(&mut self) -> Result<RichInstruction, DecodeError> {
+        type I = Instruction;
+
+        let offset = self.pos;
+        let instr = match self.byte()? {
+            0 => I::LoadInt(self.int()?),
+            1 => I::LoadFloat(self.float()?),
+            2 => I::LoadString(self.string()?),
+            3 => match self.byte()? {
+                0 => I::LoadBool(false),
+                1 => I::LoadBool(true),
+                _ => return Err(self.invalid("invalid bool", offset + 1)),
+            },
+            4 => I::Load(self.varint()?),
+            5 => I::UnOpPos,
+            6 => I::UnOpNeg,
+            7 => I::BinOpAdd,
+            8 => I::BinOpSub,
+            9 => I::BinOpMul,
+            10 => I::BinOpDiv,
+            11 => I::BinOpTrueDiv,
+            12 => I::BinOpPow,
+            13 => I::BinOpBitOr,
+            14 => I::BinOpBitXor,
+            15 => I::BinOpBitAnd,
+            16 => I::UnOpBitNot,
+            17 => I::OpEq,
+            18 => I::OpNe,
+            19 => I::OpLt,
+            20 => I::OpLe,
+            21 => I::OpGt,
+            22 => I::OpGe,
+            23 => I::OpLogicalOr,
+            24 => I::OpLogicalAnd,
+            25 => I::OpLogicalNot,
+            26 => I::LoadVar(self.varint()?),
+            27 => I::StoreVar(self.varint()?),
+            28 => I::StoreMutVar(self.varint()?),
+            29 => I::StoreConstVar(self.varint()?),
+            30 => I::MakeFunc(self.addr()?),
+            31 => I::CallFunc(self.varint()?, self.varint()?),
+            32 => I::Jump(self.addr()?),
+            33 => I::JumpIf(self.addr()?),
+            34 => I::JumpIfElse(self.addr()?, self.addr()?),
+            35 => I::Pop,
+            36 => I::Ret,
+            37 => I::RetNull,
+            38 => I::Halt,
+            39 => I::LoadFrame(self.varint()?),
+            40 => I::Store(self.varint()?),
+            41 => I::EnterScope,
+            42 => I::ExitScope,
+            43 => I::AssignVar(self.varint()?),
+            44 => I::MakeArray(self.varint()?),
+            45 => I::Index,
+            46 => I::MatchArray(self.varint()?),
+            47 => I::BinOpRange,
+            48 => I::GetIter,
+            49 => I::IterNext,
+            50 => I::LoadNull,
+            51 => I::LoadAttr(self.varint()?),
+            52 => I::UnpackModule,
+            53 => I::MakeModule(self.varint()?),
+            54 => I::BuildString(self.varint()?),
+            55 => I::FormatValue(self.format_spec()?),
+            56 => I::CaptureVar(self.varint()?),
+            57 => I::MakeParam(self.param_spec()?),
+            58 => I::MakeClass(self.string()?, self.varint()?),
+            59 => I::IsInstance,
+            60 => {
+                let handler_offset = self.pos;
+
+                match self.varint()? {
+                    handler if handler < self.handlers => I::EnterTry(handler),
+                    _ => return Err(self.invalid("unknown handler", handler_offset)),
+                }
+            }
+            61 => I::Throw,
+            62 => I::MakeMethod(self.varint()?),
+            63 => I::MakeOp(self.string()?),
+            64 => I::LoadOp(self.string()?),
+            65 => I::JumpIfNotNull(self.addr()?),
+            66 => I::AssertNonNull,
+            67 => I::Slice,
+            68 => I::StoreIndex,
+            69 => I::StoreAttr(self.varint()?),
+            70 => I::IsType(self.type_tag()?),
+            71 => I::Contains,
+            72 => I::BinOpMod,
+            73 => I::BinOpLShift,
+            74 => I::BinOpRShift,
+            75 => I::Cast(self.type_tag()?),
+            76 => I::InitVar(self.varint()?),
+            77 => I::CallFuncUnpacked(self.varint()?, self.varint()?),
+            opcode => return Err(DecodeError::InvalidOpcode { opcode, offset }),
+        };
+
+        Ok(instr.into())
+    }
+}
+
+impl Program {
+    /// Serializes this program into the `.trbc` format described in the [`format`](self)
+    /// module. The debug section is only written if the program has debug info, see
+    /// [`Program::strip_debug_info`].
+    ///
+    /// # Panics
+    /// - The program has not been resolved
+    #[must_use]
+    pub fn bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::default();
+
+        write_varint(&mut encoder.code, self.inner.len() as u128);
+        for instr in &self.inner {
+            encoder.instruction(instr);
+        }
+
+        let has_debug_info = !self.sources.is_empty()
+            || self
+                .inner
+                .iter()
+                .any(|instr| instr.span.is_some() || instr.name.is_some());
+        if has_debug_info {
+            encoder.debug_info(self);
+        }
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.push(if has_debug_info { DEBUG_INFO } else { 0 });
+
+        write_varint(&mut bytes, encoder.constants.len() as u128);
+        for constant in &encoder.constants {
+            match constant {
+                Constant::String(s) => {
+                    bytes.push(STRING_CONSTANT);
+                    write_varint(&mut bytes, s.len() as u128);
+                    bytes.extend_from_slice(s.as_bytes());
+                }
+                Constant::Int(i) => {
+                    bytes.push(INT_CONSTANT);
+                    bytes.extend_from_slice(&i.to_le_bytes());
+                }
+                Constant::Float(f) => {
+                    bytes.push(FLOAT_CONSTANT);
+                    bytes.extend_from_slice(&f.0.to_le_bytes());
+                }
+            }
+        }
+
+        write_varint(&mut bytes, self.handlers.len() as u128);
+        for handler in &self.handlers {
+            match handler.target {
+                Addr::Absolute(p) => write_varint(&mut bytes, p as u128),
+                _ => panic!("procedures must be resolved prior to conversion"),
+            }
+        }
+
+        bytes.extend(encoder.code);
+        bytes
+    }
+
+    /// Reads a program serialized by [`Program::bytes`].
+    ///
+    /// # Errors
+    /// - The bytes are not a `.trbc` file of the current [`FORMAT_VERSION`]
+    /// - The file is truncated or otherwise malformed, see [`DecodeError`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        if !bytes.starts_with(&MAGIC) {
+            return Err(DecodeError::InvalidMagic);
+        }
+
+        let mut decoder = Decoder {
+            bytes,
+            pos: MAGIC.len(),
+            constants: Vec::new(),
+            code_len: 0,
+            handlers: 0,
+        };
+
+        let version = u16::from_le_bytes(decoder.array()?);
+        if version != FORMAT_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        let flags = decoder.flags(DEBUG_INFO)?;
+
+        for _ in 0..decoder.varint()? {
+            let constant = decoder.constant()?;
+            decoder.constants.push(constant);
+        }
+
+        // Targets are only checked once the length of the code is known
+        let mut targets = Vec::new();
+        for _ in 0..decoder.varint()? {
+            targets.push((decoder.pos, decoder.varint()?));
+        }
+        decoder.handlers = targets.len();
+
+        decoder.code_len = decoder.varint()?;
+        let handlers = targets
+            .into_iter()
+            .map(|(offset, target)| match target {
+                target if target < decoder.code_len => Ok(Handler {
+                    target: Addr::Absolute(target),
+                }),
+                _ => Err(decoder.invalid("address past the end of the code", offset)),
+            })
+            .collect::<Result<_, _>>()?;
+
+        // The length is not trusted for preallocation, as it may be corrupt
+        let mut instructions = Vec::new();
+        for _ in 0..decoder.code_len {
+            instructions.push(decoder.instruction()?);
+        }
+
+        let sources = if flags & DEBUG_INFO == 0 {
+            Vec::new()
+        } else {
+            decoder.debug_info(&mut instructions)?
+        };
+
+        if decoder.pos < bytes.len() {
+            return Err(DecodeError::TrailingBytes(decoder.pos));
+        }
+
+        Ok(Self {
+            inner: instructions,
+            procedures: Vec::new(),
+            handlers,
+            sources,
+        })
+    }
+}
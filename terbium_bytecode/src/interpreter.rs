@@ -3,9 +3,17 @@
 
 use std::collections::HashMap;
 
-use super::{Addr, AddrRepr, Instruction, Program, RichInstruction};
-use terbium_grammar::ast::Target;
-use terbium_grammar::{Body, Expr, Node, Operator, Span, Spanned};
+use super::{
+    Addr, AddrRepr, Instruction, Program, RichInstruction, TypeTag, BUILTIN_CLASSES, BUILTIN_FUNCS,
+};
+use terbium_grammar::ast::{
+    SpannedCatchClause, SpannedExpr, SpannedMatchArm, SpannedMethod, SpannedParam, SpannedPattern,
+    SpannedTypeExpr, Target,
+};
+use terbium_grammar::{
+    ArrayLength, Body, CatchClause, Expr, InterpolatedPart, MatchArm, Method, Node, Operator,
+    Param, ParamKind, Pattern, RequireItem, RequireItems, RequirePath, Span, Spanned, TypeExpr,
+};
 
 // Contrary to assumption, this does not take into account scope and in reality
 // it's just a super basic string-interner, in a way.
@@ -19,90 +27,1617 @@ pub struct IdentLookup {
 
 impl IdentLookup {
     pub fn new() -> Self {
-        Self {
+        let mut lookup = Self {
             inner: HashMap::new(),
             increment: 0,
+        };
+
+        for name in BUILTIN_CLASSES.into_iter().chain(BUILTIN_FUNCS) {
+            lookup.get(name.to_string());
+        }
+        lookup
+    }
+
+    pub fn get(&mut self, ident: String) -> usize {
+        *self.inner.entry(ident).or_insert_with(|| {
+            let old = self.increment;
+            self.increment += 1;
+
+            old
+        })
+    }
+}
+
+impl Default for IdentLookup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Interpreter {
+    program: Program,
+    lookup: IdentLookup,
+    hidden_count: usize,
+    /// How many scopes enclose the code currently being interpreted.
+    scope_depth: usize,
+    /// The loops enclosing the code currently being interpreted, innermost last.
+    loops: Vec<Loop>,
+    /// The cleanups of the regions enclosing the code currently being interpreted, innermost last.
+    cleanups: Vec<Cleanup>,
+}
+
+type MaybeProc = Option<AddrRepr>;
+
+/// A loop which `break` and `continue` can target.
+#[derive(Clone)]
+struct Loop {
+    label: Option<String>,
+    /// Where `break` jumps to, with the value of the loop on top of the stack.
+    exit: Addr,
+    /// Where `continue` jumps to.
+    next: Addr,
+    /// The scope depth outside of the body of the loop.
+    depth: usize,
+}
+
+/// Code which runs whenever control leaves a region of code, including by `return`,
+/// `break` or `continue`.
+#[derive(Clone)]
+struct Cleanup {
+    /// The scope depth the code runs at, once every scope entered within the region is left.
+    depth: usize,
+    kind: CleanupKind,
+}
+
+#[derive(Clone)]
+enum CleanupKind {
+    /// Calls `op exit` of the context manager stored in this variable.
+    Exit(usize),
+    /// Runs the `finally` body of a try expression.
+    Finally(Body),
+}
+
+/// A condition tested while matching a `match` arm.
+/// Interpreting a condition always pushes a boolean to the stack.
+enum Condition {
+    /// The object stored in field 0 is an array with exactly field 1 elements.
+    ArrayLen(usize, usize),
+    /// The element at index field 1 of the array stored in field 0 matches the pattern.
+    Element(usize, usize, SpannedPattern),
+    /// The object stored in field 0 matches the pattern.
+    Pattern(usize, SpannedPattern),
+    /// The guard of an arm is truthy.
+    Guard(Spanned<Expr>),
+}
+
+/// Adds the name to `names` if it is not in there already.
+fn add_name(names: &mut Vec<String>, name: &str) {
+    if !names.iter().any(|n| n == name) {
+        names.push(name.to_string());
+    }
+}
+
+fn collect_target_names(target: &Target, names: &mut Vec<String>) {
+    match target {
+        Target::Ident(s) => add_name(names, s),
+        Target::Array(targets) => {
+            for target in targets {
+                collect_target_names(target.node(), names);
+            }
+        }
+        Target::Attr(subject, _) | Target::Rest(subject) => {
+            collect_target_names(subject.node(), names);
+        }
+        Target::Index(subject, index) => {
+            collect_target_names(subject.node(), names);
+            collect_expr_names(index.node(), names);
+        }
+        Target::SelfRef => add_name(names, "$"),
+    }
+}
+
+fn collect_body_names(body: &[Spanned<Node>], names: &mut Vec<String>) {
+    for node in body {
+        collect_node_names(node.node(), names);
+    }
+}
+
+fn collect_func_names(params: &[SpannedParam], body: &[Spanned<Node>], names: &mut Vec<String>) {
+    for param in params {
+        if let Some(default) = &param.node().default {
+            collect_expr_names(default.node(), names);
+        }
+    }
+
+    collect_body_names(body, names);
+}
+
+/// Collects the names of the variables referred to by the expression, including those
+/// referred to by functions nested within it.
+///
+/// Names of variables declared within the expression are collected as well, since whether a
+/// name refers to an enclosing variable is only known at runtime.
+fn collect_expr_names(expr: &Expr, names: &mut Vec<String>) {
+    match expr {
+        Expr::Ident(s) => add_name(names, s),
+        Expr::Interpolated(parts) => {
+            for part in parts {
+                if let InterpolatedPart::Value(value, _) = part {
+                    collect_expr_names(value.node(), names);
+                }
+            }
+        }
+        Expr::Array(items) => {
+            for item in items {
+                collect_expr_names(item.node(), names);
+            }
+        }
+        Expr::Cast(lhs, rhs) | Expr::BinaryExpr { lhs, rhs, .. } => {
+            collect_expr_names(lhs.node(), names);
+            collect_expr_names(rhs.node(), names);
+        }
+        Expr::UnaryExpr { value, .. }
+        | Expr::Attr { subject: value, .. }
+        | Expr::NonNull(value)
+        | Expr::Splat(value)
+        | Expr::KeywordSplat(value) => {
+            collect_expr_names(value.node(), names);
+        }
+        Expr::Index { subject, index, .. } => {
+            collect_expr_names(subject.node(), names);
+            collect_expr_names(index.node(), names);
+        }
+        Expr::Is { value, ty, .. } => {
+            collect_expr_names(value.node(), names);
+            collect_type_names(ty.node(), names);
+        }
+        Expr::In { item, container } => {
+            collect_expr_names(item.node(), names);
+            collect_expr_names(container.node(), names);
+        }
+        Expr::Slice {
+            subject,
+            start,
+            end,
+            ..
+        } => {
+            collect_expr_names(subject.node(), names);
+
+            for bound in start.iter().chain(end) {
+                collect_expr_names(bound.node(), names);
+            }
+        }
+        Expr::Call {
+            value,
+            args,
+            kwargs,
+            ..
+        } => {
+            collect_expr_names(value.node(), names);
+
+            for arg in args.iter().chain(kwargs.iter().map(|(_, arg)| arg)) {
+                collect_expr_names(arg.node(), names);
+            }
+        }
+        Expr::If {
+            condition,
+            body,
+            else_if_bodies,
+            else_body,
+        } => {
+            collect_expr_names(condition.node(), names);
+            collect_body_names(&body.node().0, names);
+
+            for (condition, body) in else_if_bodies {
+                collect_expr_names(condition.node(), names);
+                collect_body_names(&body.node().0, names);
+            }
+            if let Some(else_body) = else_body {
+                collect_body_names(&else_body.node().0, names);
+            }
+        }
+        Expr::While {
+            condition, body, ..
+        } => {
+            collect_expr_names(condition.node(), names);
+            collect_body_names(body, names);
+        }
+        Expr::For { iterable, body, .. } => {
+            collect_expr_names(iterable.node(), names);
+            collect_body_names(body, names);
+        }
+        Expr::Match {
+            subject,
+            arms,
+            else_body,
+        } => {
+            collect_expr_names(subject.node(), names);
+
+            for arm in arms {
+                if let Some(guard) = &arm.node().guard {
+                    collect_expr_names(guard.node(), names);
+                }
+                collect_body_names(&arm.node().body.node().0, names);
+            }
+            if let Some(else_body) = else_body {
+                collect_body_names(&else_body.node().0, names);
+            }
+        }
+        Expr::Func { params, body, .. } => collect_func_names(params, body, names),
+        Expr::Try {
+            body,
+            catches,
+            finally,
+        } => {
+            collect_body_names(&body.node().0, names);
+
+            for clause in catches {
+                let CatchClause { ty, binding, body } = clause.node();
+
+                if let Some(ty) = ty {
+                    collect_type_names(ty.node(), names);
+                }
+                if let Some(binding) = binding {
+                    add_name(names, binding);
+                }
+                collect_body_names(&body.node().0, names);
+            }
+            if let Some(finally) = finally {
+                collect_body_names(&finally.node().0, names);
+            }
+        }
+        Expr::With { managers, body } => {
+            for (manager, binding) in managers {
+                collect_expr_names(manager.node(), names);
+
+                if let Some(binding) = binding {
+                    add_name(names, binding);
+                }
+            }
+            collect_body_names(&body.node().0, names);
+        }
+        Expr::SelfRef => add_name(names, "$"),
+        Expr::Integer(_) | Expr::Float(_) | Expr::String(_) | Expr::Bool(_) | Expr::Null => (),
+    }
+}
+
+/// Collects the names of the classes referred to by a type, e.g. in a `catch` clause.
+fn collect_type_names(ty: &TypeExpr, names: &mut Vec<String>) {
+    match ty {
+        TypeExpr::Ident(s) => add_name(names, s),
+        TypeExpr::Attr(ty, _)
+        | TypeExpr::Generic(ty, _)
+        | TypeExpr::Nullable(ty)
+        | TypeExpr::Not(ty) => collect_type_names(ty.node(), names),
+        TypeExpr::Union(lhs, rhs) | TypeExpr::And(lhs, rhs) => {
+            collect_type_names(lhs.node(), names);
+            collect_type_names(rhs.node(), names);
+        }
+        _ => (),
+    }
+}
+
+fn collect_node_names(node: &Node, names: &mut Vec<String>) {
+    match node {
+        Node::Module(body) => collect_body_names(body, names),
+        Node::Func { params, body, .. } => collect_func_names(params, body, names),
+        Node::Expr(e) | Node::Declare { value: e, .. } => collect_expr_names(e.node(), names),
+        Node::Assign { targets, value } => {
+            for target in targets {
+                collect_target_names(target.node(), names);
+            }
+            collect_expr_names(value.node(), names);
+        }
+        Node::CompoundAssign { target, value, .. } => {
+            collect_target_names(target.node(), names);
+            collect_expr_names(value.node(), names);
+        }
+        Node::Return(Some(e)) | Node::Throw(e) | Node::Break { value: Some(e), .. } => {
+            collect_expr_names(e.node(), names);
+        }
+        Node::Class { bases, methods, .. } => {
+            for base in bases {
+                collect_type_names(base.node(), names);
+            }
+            collect_method_names(methods, names);
+        }
+        Node::Trait { methods, .. } => collect_method_names(methods, names),
+        Node::Extend {
+            target, methods, ..
+        } => {
+            collect_type_names(target.node(), names);
+            collect_method_names(methods, names);
+        }
+        _ => (),
+    }
+}
+
+fn collect_method_names(methods: &[SpannedMethod], names: &mut Vec<String>) {
+    for method in methods {
+        let Method { params, body, .. } = method.node();
+
+        collect_func_names(params, body.as_deref().unwrap_or_default(), names);
+    }
+}
+
+fn binary_instruction(operator: &Operator) -> Instruction {
+    match operator {
+        Operator::Add => Instruction::BinOpAdd,
+        Operator::Sub => Instruction::BinOpSub,
+        Operator::Mul => Instruction::BinOpMul,
+        Operator::Div => Instruction::BinOpDiv,
+        Operator::TrueDiv => Instruction::BinOpTrueDiv,
+        Operator::Mod => Instruction::BinOpMod,
+        Operator::Pow => Instruction::BinOpPow,
+        Operator::Eq => Instruction::OpEq,
+        Operator::Ne => Instruction::OpNe,
+        Operator::Lt => Instruction::OpLt,
+        Operator::Le => Instruction::OpLe,
+        Operator::Gt => Instruction::OpGt,
+        Operator::Ge => Instruction::OpGe,
+        Operator::Or => Instruction::OpLogicalOr,
+        Operator::And => Instruction::OpLogicalAnd,
+        Operator::BitOr => Instruction::BinOpBitOr,
+        Operator::BitXor => Instruction::BinOpBitXor,
+        Operator::BitAnd => Instruction::BinOpBitAnd,
+        Operator::BitLShift => Instruction::BinOpLShift,
+        Operator::BitRShift => Instruction::BinOpRShift,
+        Operator::Range => Instruction::BinOpRange,
+        Operator::Not | Operator::BitNot => unreachable!("{} is unary", operator),
+    }
+}
+
+impl Interpreter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            program: Program::default(),
+            lookup: IdentLookup::default(),
+            hidden_count: 0,
+            scope_depth: 0,
+            loops: Vec::new(),
+            cleanups: Vec::new(),
+        }
+    }
+
+    pub fn reset_program(&mut self) {
+        self.program = Program::default();
+    }
+
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)] // destructors cannot be evaluated at compile-time E0493
+    pub fn program(self) -> Program {
+        self.program
+    }
+
+    pub fn push(&mut self, procedure: MaybeProc, instr: Instruction) {
+        self.program.push(
+            procedure,
+            RichInstruction {
+                inner: instr,
+                span: None,
+                name: None,
+            },
+        );
+    }
+
+    pub fn push_rich(&mut self, procedure: MaybeProc, instr: RichInstruction) {
+        self.program.push(procedure, instr);
+    }
+
+    pub fn push_spanned(&mut self, procedure: MaybeProc, instr: Instruction, span: Span) {
+        self.push_rich(procedure, RichInstruction::spanned(instr, span));
+    }
+
+    /// Loads the attribute of the object on top of the stack, naming it for error messages.
+    fn push_load_attr(&mut self, procedure: MaybeProc, attr: String, span: Span) {
+        let key = self.lookup.get(attr.clone());
+
+        self.push_rich(
+            procedure,
+            RichInstruction {
+                inner: Instruction::LoadAttr(key),
+                span: Some(span),
+                name: Some(attr),
+            },
+        );
+    }
+
+    pub fn push_return(&mut self, procedure: AddrRepr, return_last: bool) {
+        self.push(
+            Some(procedure),
+            if return_last {
+                Instruction::Ret
+            } else {
+                Instruction::RetNull
+            },
+        );
+    }
+
+    pub fn push_enter_scope(&mut self, procedure: MaybeProc) {
+        self.push(procedure, Instruction::EnterScope);
+        self.scope_depth += 1;
+    }
+
+    pub fn push_exit_scope(&mut self, procedure: MaybeProc) {
+        self.push(procedure, Instruction::ExitScope);
+        self.scope_depth -= 1;
+    }
+
+    /// Enters a scope guarded by a new handler, which sends caught errors to `target`.
+    fn push_enter_try(&mut self, procedure: MaybeProc, target: Addr, span: Span) {
+        let handler = self.program.add_handler(target);
+
+        self.push_spanned(procedure, Instruction::EnterTry(handler), span);
+        self.scope_depth += 1;
+    }
+
+    /// Returns the address `n` instructions after the next one pushed to the procedure.
+    fn addr_after(&self, procedure: MaybeProc, n: usize) -> Addr {
+        match self.program.next_addr(procedure) {
+            Addr::Absolute(i) => Addr::Absolute(i + n),
+            Addr::Offset(p, i) => Addr::Offset(p, i + n),
+            Addr::Procedure(_) => unreachable!(),
         }
     }
 
-    pub fn get(&mut self, ident: String) -> usize {
-        *self.inner.entry(ident).or_insert_with(|| {
-            let old = self.increment;
-            self.increment += 1;
+    /// Returns the key of a new variable which cannot be named from source code.
+    fn hidden_var(&mut self) -> usize {
+        self.hidden_count += 1;
+
+        self.lookup.get(format!("${}", self.hidden_count))
+    }
+
+    /// Returns the key of the variable holding the object a method was called on.
+    fn self_var(&mut self) -> usize {
+        self.lookup.get("$".to_string())
+    }
+
+    /// Leaves every scope entered since `depth`, running the cleanups of the regions
+    /// being left along the way, innermost first.
+    fn push_leave(&mut self, proc: MaybeProc, depth: usize) {
+        let scope_depth = self.scope_depth;
+        let cleanups = self.cleanups.clone();
+
+        while let Some(cleanup) = self.cleanups.pop_if(|c| c.depth >= depth) {
+            for _ in cleanup.depth..self.scope_depth {
+                self.push(proc, Instruction::ExitScope);
+            }
+            // Cleanups run outside of their own region
+            self.scope_depth = cleanup.depth;
+            self.interpret_cleanup(proc, cleanup.kind);
+        }
+        for _ in depth..self.scope_depth {
+            self.push(proc, Instruction::ExitScope);
+        }
+
+        self.scope_depth = scope_depth;
+        self.cleanups = cleanups;
+    }
+
+    fn interpret_cleanup(&mut self, proc: MaybeProc, cleanup: CleanupKind) {
+        match cleanup {
+            CleanupKind::Exit(manager) => {
+                self.push(proc, Instruction::LoadVar(manager));
+                self.push(proc, Instruction::LoadOp("exit".to_string()));
+                self.push(proc, Instruction::CallFunc(0, 0));
+            }
+            CleanupKind::Finally(body) => {
+                self.push_enter_scope(proc);
+                self.interpret_body_value(proc, body);
+                self.push_exit_scope(proc);
+            }
+        }
+        self.push(proc, Instruction::Pop);
+    }
+
+    /// Returns the key of the variable holding the module loaded from the given path.
+    pub fn module_var(&mut self, path: &RequirePath) -> usize {
+        self.lookup.get(format!("<module {}>", path))
+    }
+
+    fn store_binding(&mut self, proc: MaybeProc, item: &RequireItem, span: Span) {
+        let name = item.binding().to_string();
+        let key = self.lookup.get(name.clone());
+
+        self.push_rich(
+            proc,
+            RichInstruction {
+                inner: Instruction::StoreVar(key),
+                span: Some(span),
+                name: Some(name),
+            },
+        );
+    }
+
+    /// Binds the required items in the current scope, taking them from the variables
+    /// holding loaded modules.
+    fn interpret_require(
+        &mut self,
+        proc: MaybeProc,
+        items: RequireItems,
+        from: Option<RequirePath>,
+        span: Span,
+    ) {
+        match (items, from) {
+            (RequireItems::Named(items), None) => {
+                for item in items {
+                    let module = self.module_var(&RequirePath {
+                        relative: false,
+                        segments: vec![item.name.clone()],
+                    });
+
+                    self.push(proc, Instruction::LoadVar(module));
+                    self.store_binding(proc, &item, span.clone());
+                }
+            }
+            (RequireItems::Named(items), Some(from)) => {
+                let module = self.module_var(&from);
+
+                for item in items {
+                    self.push(proc, Instruction::LoadVar(module));
+                    self.push_load_attr(proc, item.name.clone(), span.clone());
+                    self.store_binding(proc, &item, span.clone());
+                }
+            }
+            (RequireItems::Glob, Some(from)) => {
+                let module = self.module_var(&from);
+
+                self.push(proc, Instruction::LoadVar(module));
+                self.push_spanned(proc, Instruction::UnpackModule, span);
+            }
+            (RequireItems::Glob, None) => unreachable!("glob requires always specify a module"),
+        }
+    }
+
+    /// Pushes whether all conditions hold, without testing those after the first that fails.
+    fn interpret_all(&mut self, proc: MaybeProc, conditions: Vec<Condition>) {
+        let mut conditions = conditions.into_iter();
+
+        match conditions.next() {
+            Some(first) => self.interpret_condition(proc, first),
+            None => return self.push(proc, Instruction::LoadBool(true)),
+        }
+
+        let rest = conditions.collect::<Vec<_>>();
+        if rest.is_empty() {
+            return;
+        }
+
+        let cont = self.addr_after(proc, 1);
+
+        let then_proc = self.program.create_procedure();
+        self.interpret_all(Some(then_proc), rest);
+        self.push(Some(then_proc), Instruction::Jump(cont));
+
+        let else_proc = self.program.create_procedure();
+        self.push(Some(else_proc), Instruction::LoadBool(false));
+        self.push(Some(else_proc), Instruction::Jump(cont));
+
+        self.push(
+            proc,
+            Instruction::JumpIfElse(Addr::Procedure(then_proc), Addr::Procedure(else_proc)),
+        );
+    }
+
+    /// Pushes whether any of the patterns match, without testing those after the first that does.
+    fn interpret_any(&mut self, proc: MaybeProc, subject: usize, patterns: Vec<SpannedPattern>) {
+        let mut patterns = patterns.into_iter();
+
+        match patterns.next() {
+            Some(first) => self.interpret_condition(proc, Condition::Pattern(subject, first)),
+            None => return self.push(proc, Instruction::LoadBool(false)),
+        }
+
+        let rest = patterns.collect::<Vec<_>>();
+        if rest.is_empty() {
+            return;
+        }
+
+        let cont = self.addr_after(proc, 1);
+
+        let then_proc = self.program.create_procedure();
+        self.push(Some(then_proc), Instruction::LoadBool(true));
+        self.push(Some(then_proc), Instruction::Jump(cont));
+
+        let else_proc = self.program.create_procedure();
+        self.interpret_any(Some(else_proc), subject, rest);
+        self.push(Some(else_proc), Instruction::Jump(cont));
+
+        self.push(
+            proc,
+            Instruction::JumpIfElse(Addr::Procedure(then_proc), Addr::Procedure(else_proc)),
+        );
+    }
+
+    fn interpret_condition(&mut self, proc: MaybeProc, condition: Condition) {
+        match condition {
+            Condition::ArrayLen(subject, len) => {
+                self.push(proc, Instruction::LoadVar(subject));
+                self.push(proc, Instruction::MatchArray(len));
+            }
+            Condition::Element(subject, index, pattern) => {
+                let element = self.hidden_var();
+
+                self.push(proc, Instruction::LoadVar(subject));
+                self.push(proc, Instruction::LoadInt(index as u128));
+                self.push(proc, Instruction::Index);
+                self.push(proc, Instruction::StoreVar(element));
+                self.interpret_condition(proc, Condition::Pattern(element, pattern));
+            }
+            Condition::Pattern(subject, pattern) => {
+                let (pattern, span) = pattern.into_node_span();
+
+                match pattern {
+                    Pattern::Literal(value) => {
+                        self.push(proc, Instruction::LoadVar(subject));
+                        self.interpret_expr(proc, value);
+                        self.push_spanned(proc, Instruction::OpEq, span);
+                    }
+                    Pattern::Ident(name) if name == "_" => {
+                        self.push(proc, Instruction::LoadBool(true));
+                    }
+                    Pattern::Ident(name) => {
+                        let key = self.lookup.get(name.clone());
+
+                        self.push(proc, Instruction::LoadVar(subject));
+                        self.push_rich(
+                            proc,
+                            RichInstruction {
+                                inner: Instruction::StoreVar(key),
+                                span: Some(span),
+                                name: Some(name),
+                            },
+                        );
+                        self.push(proc, Instruction::LoadBool(true));
+                    }
+                    Pattern::Array(items) => {
+                        let mut conditions = vec![Condition::ArrayLen(subject, items.len())];
+                        conditions.extend(
+                            items
+                                .into_iter()
+                                .enumerate()
+                                .map(|(i, item)| Condition::Element(subject, i, item)),
+                        );
+
+                        self.interpret_all(proc, conditions);
+                    }
+                    Pattern::Alt(alts) => self.interpret_any(proc, subject, alts),
+                }
+            }
+            Condition::Guard(guard) => self.interpret_expr(proc, guard),
+        }
+    }
+
+    /// Stores the object on top of the stack into the target, destructuring arrays. Items after
+    /// a rest target are indexed from the end, and the rest target takes a slice of the items
+    /// between.
+    fn store_target(
+        &mut self,
+        proc: MaybeProc,
+        target: Spanned<Target>,
+        store: fn(usize) -> Instruction,
+    ) {
+        let (target, span) = target.into_node_span();
+
+        match target {
+            Target::Ident(s) => {
+                let key = self.lookup.get(s.clone());
+
+                self.push_rich(
+                    proc,
+                    RichInstruction {
+                        inner: store(key),
+                        span: Some(span),
+                        name: Some(s),
+                    },
+                );
+            }
+            Target::SelfRef => {
+                let key = self.self_var();
+                self.push_spanned(proc, store(key), span);
+            }
+            Target::Array(targets) => {
+                let key = self.hidden_var();
+                self.push(proc, Instruction::StoreVar(key));
+
+                let len = targets.len();
+                let rest = targets
+                    .iter()
+                    .position(|t| matches!(t.node(), Target::Rest(_)));
+
+                for (i, target) in targets.into_iter().enumerate() {
+                    self.push(proc, Instruction::LoadVar(key));
+
+                    match (rest, target.into_node_span()) {
+                        (_, (Target::Rest(target), rest_span)) => {
+                            self.push(proc, Instruction::LoadInt(i as u128));
+                            self.push_end_offset(proc, len - i - 1);
+                            self.push_spanned(proc, Instruction::Slice, rest_span);
+                            self.store_target(proc, target, store);
+                        }
+                        (Some(rest), (target, target_span)) if i > rest => {
+                            self.push_end_offset(proc, len - i);
+                            self.push_spanned(proc, Instruction::Index, span.clone());
+                            self.store_target(proc, Spanned::new(target, target_span), store);
+                        }
+                        (_, (target, target_span)) => {
+                            self.push(proc, Instruction::LoadInt(i as u128));
+                            self.push_spanned(proc, Instruction::Index, span.clone());
+                            self.store_target(proc, Spanned::new(target, target_span), store);
+                        }
+                    }
+                }
+            }
+            Target::Rest(_) => unreachable!("rest targets are only parsed within arrays"),
+            Target::Attr(subject, attr) => {
+                self.load_target(proc, subject);
+
+                let key = self.lookup.get(attr);
+                self.push_spanned(proc, Instruction::StoreAttr(key), span);
+            }
+            Target::Index(subject, index) => {
+                self.load_target(proc, subject);
+                self.interpret_expr(proc, index);
+
+                // Discard the result of `op set_index`
+                self.push_spanned(proc, Instruction::StoreIndex, span);
+                self.push(proc, Instruction::Pop);
+            }
+        }
+    }
+
+    /// Applies the operator to the target and the value, then stores the result back into the
+    /// target. The subject and index of the target are stored in hidden variables so that they
+    /// are only evaluated once.
+    fn interpret_compound_assign(
+        &mut self,
+        proc: MaybeProc,
+        target: Spanned<Target>,
+        operator: Spanned<Operator>,
+        value: Spanned<Expr>,
+    ) {
+        let (target, span) = target.into_node_span();
+        let (operator, op_span) = operator.into_node_span();
+
+        match target {
+            Target::Ident(_) | Target::SelfRef => {
+                let target = Spanned::new(target, span);
+
+                self.load_target(proc, target.clone());
+                self.interpret_expr(proc, value);
+                self.push_spanned(proc, binary_instruction(&operator), op_span);
+                self.store_target(proc, target, Instruction::AssignVar);
+            }
+            Target::Attr(subject, attr) => {
+                let key = self.lookup.get(attr.clone());
+                let subject_var = self.hidden_var();
+
+                self.load_target(proc, subject);
+                self.push(proc, Instruction::StoreVar(subject_var));
+
+                self.push(proc, Instruction::LoadVar(subject_var));
+                self.push_load_attr(proc, attr, span.clone());
+                self.interpret_expr(proc, value);
+                self.push_spanned(proc, binary_instruction(&operator), op_span);
+
+                self.push(proc, Instruction::LoadVar(subject_var));
+                self.push_spanned(proc, Instruction::StoreAttr(key), span);
+            }
+            Target::Index(subject, index) => {
+                let subject_var = self.hidden_var();
+                let index_var = self.hidden_var();
+
+                self.load_target(proc, subject);
+                self.push(proc, Instruction::StoreVar(subject_var));
+                self.interpret_expr(proc, index);
+                self.push(proc, Instruction::StoreVar(index_var));
+
+                self.push(proc, Instruction::LoadVar(subject_var));
+                self.push(proc, Instruction::LoadVar(index_var));
+                self.push_spanned(proc, Instruction::Index, span.clone());
+                self.interpret_expr(proc, value);
+                self.push_spanned(proc, binary_instruction(&operator), op_span);
+
+                self.push(proc, Instruction::LoadVar(subject_var));
+                self.push(proc, Instruction::LoadVar(index_var));
+                self.push_spanned(proc, Instruction::StoreIndex, span);
+                self.push(proc, Instruction::Pop);
+            }
+            Target::Array(_) | Target::Rest(_) => {
+                unreachable!("destructuring targets are rejected by the parser")
+            }
+        }
+    }
+
+    /// Stores the object on top of the stack into each of the targets, from left to right.
+    fn store_targets(
+        &mut self,
+        proc: MaybeProc,
+        targets: Vec<Spanned<Target>>,
+        store: fn(usize) -> Instruction,
+    ) {
+        if targets.len() == 1 {
+            let target = targets.into_iter().next().unwrap();
+
+            return self.store_target(proc, target, store);
+        }
+
+        let key = self.hidden_var();
+        self.push(proc, Instruction::StoreVar(key));
+
+        for target in targets {
+            self.push(proc, Instruction::LoadVar(key));
+            self.store_target(proc, target, store);
+        }
+    }
+
+    /// Pushes the negative index `offset` items from the end, or null (an open end) when
+    /// `offset` is zero.
+    fn push_end_offset(&mut self, proc: MaybeProc, offset: usize) {
+        if offset == 0 {
+            self.push(proc, Instruction::LoadNull);
+        } else {
+            self.push(proc, Instruction::LoadInt(offset as u128));
+            self.push(proc, Instruction::UnOpNeg);
+        }
+    }
+
+    /// Pushes the object the target refers to, such as the subject of an attribute target.
+    fn load_target(&mut self, proc: MaybeProc, target: Spanned<Target>) {
+        let (target, span) = target.into_node_span();
+
+        match target {
+            Target::Ident(s) => {
+                let key = self.lookup.get(s);
+                self.push_spanned(proc, Instruction::LoadVar(key), span);
+            }
+            Target::SelfRef => {
+                let key = self.self_var();
+                self.push_spanned(proc, Instruction::LoadVar(key), span);
+            }
+            Target::Attr(subject, attr) => {
+                self.load_target(proc, subject);
+                self.push_load_attr(proc, attr, span);
+            }
+            Target::Index(subject, index) => {
+                self.load_target(proc, subject);
+                self.interpret_expr(proc, index);
+                self.push_spanned(proc, Instruction::Index, span);
+            }
+            Target::Array(_) | Target::Rest(_) => {
+                unreachable!("destructuring targets are never loaded")
+            }
+        }
+    }
+
+    /// Tests the given arms in order and runs the body of the first one that matches,
+    /// falling back to `else_body`, then jumps to `cont` with the value of that body.
+    /// Each arm is tested in its own scope so that its bindings are only visible to its
+    /// guard and body.
+    fn interpret_match_arms(
+        &mut self,
+        proc: AddrRepr,
+        arms: &mut impl Iterator<Item = SpannedMatchArm>,
+        else_body: Option<Spanned<Body>>,
+        subject: usize,
+        cont: Addr,
+    ) {
+        if let Some(arm) = arms.next() {
+            let (
+                MatchArm {
+                    pattern,
+                    guard,
+                    body,
+                },
+                span,
+            ) = arm.into_node_span();
+
+            self.push_enter_scope(Some(proc));
+
+            let mut conditions = vec![Condition::Pattern(subject, pattern)];
+            if let Some(guard) = guard {
+                conditions.push(Condition::Guard(guard));
+            }
+            self.interpret_all(Some(proc), conditions);
+
+            let body_proc = self.program.create_procedure();
+            self.interpret_body_value(Some(body_proc), body.into_node());
+            self.push_exit_scope(Some(body_proc));
+            self.push(Some(body_proc), Instruction::Jump(cont));
+
+            // The scope of this arm was already accounted for when leaving it from its body
+            let next_proc = self.program.create_procedure();
+            self.push(Some(next_proc), Instruction::ExitScope);
+            self.interpret_match_arms(next_proc, arms, else_body, subject, cont);
+
+            self.push_spanned(
+                Some(proc),
+                Instruction::JumpIfElse(Addr::Procedure(body_proc), Addr::Procedure(next_proc)),
+                span,
+            );
+        } else if let Some(else_body) = else_body {
+            self.interpret_block(proc, else_body.into_node(), cont);
+        } else {
+            self.push(Some(proc), Instruction::LoadNull);
+            self.push(Some(proc), Instruction::Jump(cont));
+        }
+    }
+
+    /// Interprets the body of a loop in its own scope, jumping to the next iteration
+    /// once it finishes. If `target` is given, the object on top of the stack is stored
+    /// into it first.
+    fn interpret_loop_body(
+        &mut self,
+        proc: AddrRepr,
+        r#loop: Loop,
+        target: Option<Spanned<Target>>,
+        body: Vec<Spanned<Node>>,
+    ) {
+        let next = r#loop.next;
+        self.loops.push(r#loop);
+
+        self.push_enter_scope(Some(proc));
+        if let Some(target) = target {
+            self.store_target(Some(proc), target, Instruction::StoreVar);
+        }
+        self.interpret_body_no_return(Some(proc), body);
+        self.push_exit_scope(Some(proc));
+        self.push(Some(proc), Instruction::Jump(next));
+
+        self.loops.pop();
+    }
+
+    /// Leaves every scope entered since the start of the targeted loop's body,
+    /// running any cleanups on the way, then jumps to the address chosen from the loop.
+    fn interpret_loop_control(
+        &mut self,
+        proc: MaybeProc,
+        label: Option<String>,
+        addr: fn(&Loop) -> Addr,
+        span: Span,
+    ) {
+        let r#loop = self
+            .loops
+            .iter()
+            .rev()
+            .find(|l| label.is_none() || l.label == label)
+            .cloned();
+
+        // The analyzer reports these, but lowering must not fail on them
+        let r#loop = match r#loop {
+            Some(r#loop) => r#loop,
+            None => {
+                let message = match label {
+                    Some(label) => format!("no enclosing loop is labelled {:?}", label),
+                    None => "break or continue outside of a loop".to_string(),
+                };
+
+                return self.push_throw_builtin(proc, "Error", message, span);
+            }
+        };
+
+        self.push_leave(proc, r#loop.depth);
+        self.push_spanned(proc, Instruction::Jump(addr(&r#loop)), span);
+    }
+
+    /// Throws an instance of the builtin error class with this name, holding the message.
+    /// Used for code which can be lowered but never run, most of which the analyzer reports.
+    fn push_throw_builtin(&mut self, proc: MaybeProc, class: &str, message: String, span: Span) {
+        let key = self.lookup.get(class.to_string());
+
+        self.push(proc, Instruction::LoadVar(key));
+        self.push(proc, Instruction::LoadString(message));
+        self.push_spanned(proc, Instruction::CallFunc(1, 0), span.clone());
+        self.push_spanned(proc, Instruction::Throw, span);
+    }
+
+    /// Pushes the class referred to by the type, e.g. `IOError` or `fs.IOError`.
+    fn interpret_class(&mut self, proc: MaybeProc, ty: SpannedTypeExpr) {
+        let (ty, span) = ty.into_node_span();
+
+        match ty {
+            TypeExpr::Ident(name) => {
+                let key = self.lookup.get(name.clone());
+
+                self.push_rich(
+                    proc,
+                    RichInstruction {
+                        inner: Instruction::LoadVar(key),
+                        span: Some(span),
+                        name: Some(name),
+                    },
+                );
+            }
+            TypeExpr::Attr(subject, attr) => {
+                self.interpret_class(proc, subject);
+                self.push_load_attr(proc, attr, span);
+            }
+            // Type arguments are only checked by the analyzer
+            TypeExpr::Generic(subject, _) => self.interpret_class(proc, subject),
+            // e.g. `catch ?IOError`, which has no class to test against
+            _ => self.push_throw_builtin(
+                proc,
+                "TypeError",
+                "only classes can be referred to at runtime".to_string(),
+                span,
+            ),
+        }
+    }
+
+    /// Pushes whether the value stored in `subject` is of the given type.
+    fn interpret_type_test(&mut self, proc: MaybeProc, subject: usize, ty: SpannedTypeExpr) {
+        let (ty, span) = ty.into_node_span();
+
+        match ty {
+            TypeExpr::Union(lhs, rhs) => {
+                self.interpret_type_test(proc, subject, lhs);
+                self.interpret_type_test(proc, subject, rhs);
+                self.push(proc, Instruction::OpLogicalOr);
+            }
+            TypeExpr::And(lhs, rhs) => {
+                self.interpret_type_test(proc, subject, lhs);
+                self.interpret_type_test(proc, subject, rhs);
+                self.push(proc, Instruction::OpLogicalAnd);
+            }
+            TypeExpr::Nullable(ty) => {
+                self.interpret_type_test(proc, subject, ty);
+                self.push(proc, Instruction::LoadVar(subject));
+                self.push(proc, Instruction::IsType(TypeTag::Null));
+                self.push(proc, Instruction::OpLogicalOr);
+            }
+            TypeExpr::Not(ty) => {
+                self.interpret_type_test(proc, subject, ty);
+                self.push(proc, Instruction::OpLogicalNot);
+            }
+            TypeExpr::Null => {
+                self.push(proc, Instruction::LoadVar(subject));
+                self.push(proc, Instruction::IsType(TypeTag::Null));
+            }
+            TypeExpr::Any | TypeExpr::Auto => self.push(proc, Instruction::LoadBool(true)),
+            // Element types are only checked by the analyzer
+            TypeExpr::Array(_, None) => {
+                self.push(proc, Instruction::LoadVar(subject));
+                self.push(proc, Instruction::IsType(TypeTag::Array));
+            }
+            TypeExpr::Array(_, Some(len)) => {
+                self.push(proc, Instruction::LoadVar(subject));
+                match len {
+                    ArrayLength::Literal(len) => {
+                        self.push(proc, Instruction::MatchArray(len as usize));
+                    }
+                    // Lengths named by constants are only checked by the analyzer
+                    ArrayLength::Const(_) => self.push(proc, Instruction::IsType(TypeTag::Array)),
+                }
+            }
+            TypeExpr::Tuple(items) => {
+                self.push(proc, Instruction::LoadVar(subject));
+                self.push(proc, Instruction::MatchArray(items.len()));
+            }
+            TypeExpr::Ident(ref name) if name == "never" => {
+                self.push(proc, Instruction::LoadBool(false));
+            }
+            TypeExpr::Ident(ref name) if TypeTag::from_name(name).is_some() => {
+                let tag = TypeTag::from_name(name).unwrap();
+
+                self.push(proc, Instruction::LoadVar(subject));
+                self.push(proc, Instruction::IsType(tag));
+            }
+            ty => {
+                self.push(proc, Instruction::LoadVar(subject));
+                self.interpret_class(proc, Spanned::new(ty, span.clone()));
+                self.push_spanned(proc, Instruction::IsInstance, span);
+            }
+        }
+    }
+
+    /// Interprets the body of a try expression along with its `catch` clauses, leaving the
+    /// value of whichever body finished last on the stack.
+    ///
+    /// Errors caught by the handler guarding the body are stored into a new scope, then tested
+    /// against the type of each clause in order. An error that no clause catches is thrown again.
+    fn interpret_try_catch(
+        &mut self,
+        proc: MaybeProc,
+        body: Body,
+        catches: Vec<SpannedCatchClause>,
+        span: Span,
+    ) {
+        let dispatch_proc = self.program.create_procedure();
+        self.push_enter_try(proc, Addr::Procedure(dispatch_proc), span.clone());
+        self.interpret_body_value(proc, body);
+        self.push_exit_scope(proc);
+
+        let cont = self.program.next_addr(proc);
+        let error = self.hidden_var();
+        let depth = self.scope_depth;
+
+        self.push_enter_scope(Some(dispatch_proc));
+        self.push(Some(dispatch_proc), Instruction::StoreVar(error));
+
+        for clause in catches {
+            let (CatchClause { ty, binding, body }, clause_span) = clause.into_node_span();
+            let clause_proc = self.program.create_procedure();
+
+            match ty {
+                Some(ty) => {
+                    // e.g. catch IOError | ValueError tests against each class in turn
+                    let mut classes = vec![ty];
+                    while let Some(ty) = classes.pop() {
+                        let (ty, ty_span) = ty.into_node_span();
+
+                        if let TypeExpr::Union(lhs, rhs) = ty {
+                            classes.push(rhs);
+                            classes.push(lhs);
+                            continue;
+                        }
+
+                        self.push(Some(dispatch_proc), Instruction::LoadVar(error));
+                        self.interpret_class(Some(dispatch_proc), Spanned::new(ty, ty_span));
+                        self.push(Some(dispatch_proc), Instruction::IsInstance);
+                        self.push_spanned(
+                            Some(dispatch_proc),
+                            Instruction::JumpIf(Addr::Procedure(clause_proc)),
+                            clause_span.clone(),
+                        );
+                    }
+                }
+                None => self.push_spanned(
+                    Some(dispatch_proc),
+                    Instruction::Jump(Addr::Procedure(clause_proc)),
+                    clause_span,
+                ),
+            }
+
+            if let Some(binding) = binding {
+                let key = self.lookup.get(binding.clone());
+
+                self.push(Some(clause_proc), Instruction::LoadVar(error));
+                self.push_rich(
+                    Some(clause_proc),
+                    RichInstruction {
+                        inner: Instruction::StoreVar(key),
+                        span: Some(span.clone()),
+                        name: Some(binding),
+                    },
+                );
+            }
+            self.interpret_body_value(Some(clause_proc), body.into_node());
+            self.push_exit_scope(Some(clause_proc));
+            self.push(Some(clause_proc), Instruction::Jump(cont));
+
+            // Only one clause runs, so each of them leaves the scope entered by the dispatch
+            self.scope_depth = depth + 1;
+        }
+
+        self.push(Some(dispatch_proc), Instruction::LoadVar(error));
+        self.push_spanned(Some(dispatch_proc), Instruction::Throw, span);
+        self.scope_depth = depth;
+    }
+
+    /// Interprets a try expression. A `finally` body runs once the rest of the expression
+    /// finishes, whether by returning or breaking out of it as well, and before an error
+    /// thrown from it is sent to any enclosing handler.
+    fn interpret_try(
+        &mut self,
+        proc: MaybeProc,
+        body: Body,
+        catches: Vec<SpannedCatchClause>,
+        finally: Option<Spanned<Body>>,
+        span: Span,
+    ) {
+        let finally = match finally {
+            Some(finally) => finally.into_node(),
+            None => return self.interpret_try_catch(proc, body, catches, span),
+        };
+
+        let finally_proc = self.program.create_procedure();
+        self.cleanups.push(Cleanup {
+            depth: self.scope_depth,
+            kind: CleanupKind::Finally(finally.clone()),
+        });
+        self.push_enter_try(proc, Addr::Procedure(finally_proc), span.clone());
+        if catches.is_empty() {
+            self.interpret_body_value(proc, body);
+        } else {
+            self.interpret_try_catch(proc, body, catches, span.clone());
+        }
+        self.push_exit_scope(proc);
+        self.cleanups.pop();
+
+        // The value of the expression stays below the value of the finally body
+        self.push_enter_scope(proc);
+        self.interpret_body_value(proc, finally.clone());
+        self.push_exit_scope(proc);
+        self.push(proc, Instruction::Pop);
+
+        let error = self.hidden_var();
+        self.push_enter_scope(Some(finally_proc));
+        self.push(Some(finally_proc), Instruction::StoreVar(error));
+        self.interpret_body_value(Some(finally_proc), finally);
+        self.push(Some(finally_proc), Instruction::Pop);
+        self.push(Some(finally_proc), Instruction::LoadVar(error));
+        self.push_spanned(Some(finally_proc), Instruction::Throw, span);
+        // The scope is left by unwinding to the next handler
+        self.scope_depth -= 1;
+    }
+
+    /// Interprets the function into a new procedure, then pushes a function object calling it.
+    ///
+    /// The procedure starts by storing the arguments the function was called with into its
+    /// parameters, then runs the body in its own scope and returns its value. Default values of
+    /// parameters are evaluated once, when the function object is created.
+    fn interpret_func(
+        &mut self,
+        proc: MaybeProc,
+        params: Vec<SpannedParam>,
+        body: Body,
+        span: Span,
+    ) {
+        let mut captures = Vec::new();
+        collect_func_names(&params, &body.0, &mut captures);
+
+        // Parameters shadow any variable of the same name
+        for param in &params {
+            let mut bound = Vec::new();
+            collect_target_names(param.node().target.node(), &mut bound);
+            captures.retain(|name| !bound.contains(name));
+        }
+
+        let func_proc = self.program.create_procedure();
+        let mut targets = Vec::with_capacity(params.len());
+        self.push_spanned(
+            proc,
+            Instruction::MakeFunc(Addr::Procedure(func_proc)),
+            span.clone(),
+        );
+
+        for param in params {
+            let param_span = param.span();
+            let spec = param.node().spec();
+            let Param {
+                target, default, ..
+            } = param.into_node();
+
+            if let Some(default) = default {
+                self.interpret_expr(proc, default);
+            }
+            self.push_spanned(proc, Instruction::MakeParam(spec), param_span);
+            targets.push(target);
+        }
+
+        for name in captures {
+            let key = self.lookup.get(name.clone());
+
+            self.push_rich(
+                proc,
+                RichInstruction {
+                    inner: Instruction::CaptureVar(key),
+                    span: Some(span.clone()),
+                    name: Some(name),
+                },
+            );
+        }
+
+        // The body can not break out of loops enclosing the function
+        let loops = std::mem::take(&mut self.loops);
+        let cleanups = std::mem::take(&mut self.cleanups);
+        let scope_depth = std::mem::replace(&mut self.scope_depth, 0);
+
+        // Arguments are pushed in order, so the last one is on top of the stack
+        for target in targets.into_iter().rev() {
+            self.store_target(Some(func_proc), target, Instruction::StoreVar);
+        }
+        self.interpret_body(Some(func_proc), body);
+
+        self.loops = loops;
+        self.cleanups = cleanups;
+        self.scope_depth = scope_depth;
+    }
+
+    /// Interprets a chain of attribute accesses, calls and indexing such as `a?.b.c()[0]`,
+    /// leaving its value on the stack.
+    ///
+    /// Once an optional link finds null, the rest of the chain is skipped and the null is
+    /// left as the value of the whole chain.
+    fn interpret_chain(&mut self, proc: MaybeProc, expr: Spanned<Expr>) {
+        let mut skips = Vec::new();
+        let last = self.interpret_link(proc, expr, &mut skips);
+
+        if skips.is_empty() {
+            return;
+        }
+
+        // Nothing is pushed to `proc` after its first optional link, which falls through
+        // to where the chain continues
+        let cont = self.program.next_addr(proc);
+        for skip in skips.into_iter().skip(1).chain(std::iter::once(last)) {
+            self.push(skip, Instruction::Jump(cont));
+        }
+    }
+
+    /// Interprets a link of a chain, returning the procedure the rest of the chain is
+    /// interpreted in. Procedures which fall through once an optional link finds null are
+    /// added to `skips`.
+    fn interpret_link(
+        &mut self,
+        proc: MaybeProc,
+        expr: Spanned<Expr>,
+        skips: &mut Vec<MaybeProc>,
+    ) -> MaybeProc {
+        let span = expr.span();
+
+        match expr.into_node() {
+            Expr::Attr {
+                subject,
+                attr,
+                optional,
+            } => {
+                let proc = self.interpret_subject(proc, subject, optional, span.clone(), skips);
+
+                self.push_load_attr(proc, attr, span);
+                proc
+            }
+            Expr::Call {
+                value,
+                args,
+                kwargs,
+                optional,
+            } => {
+                let proc = self.interpret_subject(proc, value, optional, span.clone(), skips);
+
+                if args
+                    .iter()
+                    .any(|a| matches!(a.node(), Expr::Splat(_) | Expr::KeywordSplat(_)))
+                {
+                    let counts = self.interpret_unpacked_args(proc, args, kwargs);
 
-            old
-        })
-    }
-}
+                    self.push_spanned(
+                        proc,
+                        Instruction::CallFuncUnpacked(counts.0, counts.1),
+                        span,
+                    );
+                } else {
+                    let counts = (args.len(), kwargs.len());
 
-impl Default for IdentLookup {
-    fn default() -> Self {
-        Self::new()
-    }
-}
+                    for arg in args {
+                        self.interpret_expr(proc, arg);
+                    }
+                    for (name, arg) in kwargs {
+                        self.push(proc, Instruction::LoadString(name));
+                        self.interpret_expr(proc, arg);
+                    }
 
-pub struct Interpreter {
-    program: Program,
-    lookup: IdentLookup,
-}
+                    self.push_spanned(proc, Instruction::CallFunc(counts.0, counts.1), span);
+                }
+                proc
+            }
+            Expr::Index {
+                subject,
+                index,
+                optional,
+            } => {
+                let proc = self.interpret_subject(proc, subject, optional, span.clone(), skips);
 
-type MaybeProc = Option<AddrRepr>;
+                self.interpret_expr(proc, index);
+                self.push_spanned(proc, Instruction::Index, span);
+                proc
+            }
+            Expr::Slice {
+                subject,
+                start,
+                end,
+                optional,
+            } => {
+                let proc = self.interpret_subject(proc, subject, optional, span.clone(), skips);
 
-impl Interpreter {
-    #[must_use]
-    pub fn new() -> Self {
-        Self {
-            program: Program::default(),
-            lookup: IdentLookup::default(),
+                // Omitted bounds are null
+                for bound in [start, end] {
+                    match bound {
+                        Some(bound) => self.interpret_expr(proc, bound),
+                        None => self.push(proc, Instruction::LoadNull),
+                    }
+                }
+                self.push_spanned(proc, Instruction::Slice, span);
+                proc
+            }
+            e => {
+                self.interpret_expr(proc, Spanned::new(e, span));
+                proc
+            }
         }
     }
 
-    pub fn reset_program(&mut self) {
-        self.program = Program::default();
-    }
+    /// Pushes the arguments of a call with splats as arrays to be concatenated by
+    /// `CallFuncUnpacked`, returning the amount of positional and keyword arrays.
+    ///
+    /// Runs of positional arguments are collected into one array each, and keyword arguments
+    /// into `[name, value]` arrays. Keyword splats are evaluated after the keyword arguments.
+    fn interpret_unpacked_args(
+        &mut self,
+        proc: MaybeProc,
+        args: Vec<SpannedExpr>,
+        kwargs: Vec<(String, SpannedExpr)>,
+    ) -> (usize, usize) {
+        let (keyword_splats, args): (Vec<_>, Vec<_>) = args
+            .into_iter()
+            .partition(|a| matches!(a.node(), Expr::KeywordSplat(_)));
+        let mut counts = (0, 0);
+        let mut run = 0;
 
-    #[must_use]
-    #[allow(clippy::missing_const_for_fn)] // destructors cannot be evaluated at compile-time E0493
-    pub fn program(self) -> Program {
-        self.program
+        for arg in args {
+            let (arg, span) = arg.into_node_span();
+
+            if let Expr::Splat(value) = arg {
+                if run > 0 {
+                    self.push(proc, Instruction::MakeArray(run));
+                    counts.0 += 1;
+                    run = 0;
+                }
+                self.interpret_expr(proc, value);
+                counts.0 += 1;
+            } else {
+                self.interpret_expr(proc, SpannedExpr::new(arg, span));
+                run += 1;
+            }
+        }
+        if run > 0 {
+            self.push(proc, Instruction::MakeArray(run));
+            counts.0 += 1;
+        }
+
+        if !kwargs.is_empty() {
+            let len = kwargs.len();
+
+            for (name, arg) in kwargs {
+                self.push(proc, Instruction::LoadString(name));
+                self.interpret_expr(proc, arg);
+                self.push(proc, Instruction::MakeArray(2));
+            }
+            self.push(proc, Instruction::MakeArray(len));
+            counts.1 += 1;
+        }
+        for splat in keyword_splats {
+            if let Expr::KeywordSplat(value) = splat.into_node() {
+                self.interpret_expr(proc, value);
+                counts.1 += 1;
+            }
+        }
+
+        counts
     }
 
-    pub fn push(&mut self, procedure: MaybeProc, instr: Instruction) {
-        self.program.push(
-            procedure,
-            RichInstruction {
-                inner: instr,
-                span: None,
-                name: None,
-            },
+    /// Interprets the subject of a link, skipping the rest of the chain if the link is
+    /// optional and the subject is null.
+    fn interpret_subject(
+        &mut self,
+        proc: MaybeProc,
+        subject: Spanned<Expr>,
+        optional: bool,
+        span: Span,
+        skips: &mut Vec<MaybeProc>,
+    ) -> MaybeProc {
+        let proc = self.interpret_link(proc, subject, skips);
+        if !optional {
+            return proc;
+        }
+
+        let rest = self.program.create_procedure();
+        self.push_spanned(
+            proc,
+            Instruction::JumpIfNotNull(Addr::Procedure(rest)),
+            span,
         );
-    }
+        skips.push(proc);
 
-    pub fn push_rich(&mut self, procedure: MaybeProc, instr: RichInstruction) {
-        self.program.push(procedure, instr);
+        Some(rest)
     }
 
-    pub fn push_spanned(&mut self, procedure: MaybeProc, instr: Instruction, span: Span) {
-        self.push_rich(procedure, RichInstruction::spanned(instr, span));
-    }
+    /// Interprets the body of a with expression once the context managers from `managers`
+    /// onwards are entered, leaving its value on the stack.
+    ///
+    /// Each manager is stored into a new scope, where its `op exit` is called once control
+    /// leaves the rest of the expression in any way, including by throwing an error.
+    fn interpret_with(
+        &mut self,
+        proc: MaybeProc,
+        mut managers: std::vec::IntoIter<(Spanned<Expr>, Option<String>)>,
+        body: Body,
+        span: Span,
+    ) {
+        let (manager, binding) = match managers.next() {
+            Some(manager) => manager,
+            None => return self.interpret_body_value(proc, body),
+        };
+        let manager_span = manager.span();
+        let key = self.hidden_var();
 
-    pub fn push_return(&mut self, procedure: AddrRepr, return_last: bool) {
-        self.push(
-            Some(procedure),
-            if return_last {
-                Instruction::Ret
-            } else {
-                Instruction::RetNull
-            },
+        self.push_enter_scope(proc);
+        self.interpret_expr(proc, manager);
+        self.push(proc, Instruction::StoreVar(key));
+        self.push(proc, Instruction::LoadVar(key));
+        self.push_spanned(
+            proc,
+            Instruction::LoadOp("enter".to_string()),
+            manager_span.clone(),
         );
-    }
+        self.push_spanned(proc, Instruction::CallFunc(0, 0), manager_span);
+        match binding {
+            Some(binding) => {
+                let binding_key = self.lookup.get(binding.clone());
+
+                self.push_rich(
+                    proc,
+                    RichInstruction {
+                        inner: Instruction::StoreVar(binding_key),
+                        span: Some(span.clone()),
+                        name: Some(binding),
+                    },
+                );
+            }
+            None => self.push(proc, Instruction::Pop),
+        }
+
+        let exit_proc = self.program.create_procedure();
+        self.cleanups.push(Cleanup {
+            depth: self.scope_depth,
+            kind: CleanupKind::Exit(key),
+        });
+        self.push_enter_try(proc, Addr::Procedure(exit_proc), span.clone());
+        self.interpret_with(proc, managers, body, span.clone());
+        self.push_exit_scope(proc);
+        self.cleanups.pop();
+
+        // The value of the expression stays below the value returned by the manager
+        self.interpret_cleanup(proc, CleanupKind::Exit(key));
+        self.push_exit_scope(proc);
 
-    pub fn push_enter_scope(&mut self, procedure: AddrRepr) {
-        self.push(Some(procedure), Instruction::EnterScope);
+        // The error stays below the value returned by the manager until it is thrown again
+        self.interpret_cleanup(Some(exit_proc), CleanupKind::Exit(key));
+        self.push_spanned(Some(exit_proc), Instruction::Throw, span);
     }
 
-    pub fn push_exit_scope(&mut self, procedure: AddrRepr) {
-        self.push(Some(procedure), Instruction::ExitScope);
+    /// Interprets the methods of a class into functions taking the object they are called on
+    /// before any other argument, then adds them to the class on top of the stack.
+    fn interpret_methods(&mut self, proc: MaybeProc, methods: Vec<SpannedMethod>) {
+        for method in methods {
+            let (method, span) = method.into_node_span();
+            let Method {
+                name,
+                mut params,
+                body,
+                return_last,
+                op,
+                ..
+            } = method;
+            // Required methods of traits are implemented by the classes deriving from them
+            let Some(body) = body else {
+                continue;
+            };
+
+            params.insert(
+                0,
+                Spanned::new(
+                    Param {
+                        target: Spanned::new(Target::SelfRef, span.clone()),
+                        ty: Spanned::new(TypeExpr::SelfRef, span.clone()),
+                        default: None,
+                        kind: ParamKind::Normal,
+                        keyword_only: false,
+                        optional: false,
+                    },
+                    span.clone(),
+                ),
+            );
+            self.interpret_func(proc, params, Body(body, return_last), span.clone());
+
+            let instr = if op {
+                Instruction::MakeOp(name)
+            } else {
+                Instruction::MakeMethod(self.lookup.get(name))
+            };
+            self.push_spanned(proc, instr, span);
+        }
     }
 
     #[allow(clippy::too_many_lines)] // Should probably refactor it later
@@ -115,7 +1650,27 @@ impl Interpreter {
                 Instruction::LoadInt(i), span,
             ),
             Expr::Bool(b) => self.push_spanned(proc, Instruction::LoadBool(b), span),
+            Expr::Null => self.push_spanned(proc, Instruction::LoadNull, span),
             Expr::String(s) => self.push_spanned(proc, Instruction::LoadString(s), span),
+            Expr::Interpolated(parts) => {
+                let count = parts.len();
+
+                for part in parts {
+                    match part {
+                        InterpolatedPart::Literal(s) => self.push(proc, Instruction::LoadString(s)),
+                        InterpolatedPart::Value(value, spec) => {
+                            let value_span = value.span();
+                            self.interpret_expr(proc, value);
+
+                            if !spec.is_plain() {
+                                self.push_spanned(proc, Instruction::FormatValue(spec), value_span);
+                            }
+                        }
+                    }
+                }
+
+                self.push_spanned(proc, Instruction::BuildString(count), span);
+            }
             Expr::Float(f) => self.push_spanned(
                 proc,
                 Instruction::LoadFloat(
@@ -148,29 +1703,44 @@ impl Interpreter {
                 self.interpret_expr(proc, lhs);
                 self.interpret_expr(proc, rhs);
 
-                self.push_spanned(
-                    proc,
-                    match operator.node() {
-                        Operator::Add => Instruction::BinOpAdd,
-                        Operator::Sub => Instruction::BinOpSub,
-                        Operator::Mul => Instruction::BinOpMul,
-                        Operator::Div => Instruction::BinOpDiv,
-                        Operator::Eq => Instruction::OpEq,
-                        Operator::Ne => Instruction::OpNe,
-                        Operator::Lt => Instruction::OpLt,
-                        Operator::Le => Instruction::OpLe,
-                        Operator::Gt => Instruction::OpGt,
-                        Operator::Ge => Instruction::OpGe,
-                        Operator::Or => Instruction::OpLogicalOr,
-                        Operator::And => Instruction::OpLogicalAnd,
-                        Operator::BitOr => Instruction::BinOpBitOr,
-                        Operator::BitXor => Instruction::BinOpBitXor,
-                        Operator::BitAnd => Instruction::BinOpBitAnd,
-                        Operator::Range => todo!(),
-                        _ => unimplemented!(),
-                    },
-                    span,
-                );
+                self.push_spanned(proc, binary_instruction(operator.node()), span);
+            }
+            Expr::Is { value, ty, negated } => {
+                let subject = self.hidden_var();
+
+                self.interpret_expr(proc, value);
+                self.push(proc, Instruction::StoreVar(subject));
+                self.interpret_type_test(proc, subject, ty);
+
+                if negated {
+                    self.push_spanned(proc, Instruction::OpLogicalNot, span);
+                }
+            }
+            Expr::In { item, container } => {
+                self.interpret_expr(proc, item);
+                self.interpret_expr(proc, container);
+
+                self.push_spanned(proc, Instruction::Contains, span);
+            }
+            Expr::Cast(value, ty) => {
+                let tag = match ty.node() {
+                    Expr::Ident(name) => TypeTag::from_name(name),
+                    _ => None,
+                };
+
+                self.interpret_expr(proc, value);
+                match tag {
+                    Some(tag) => self.push_spanned(proc, Instruction::Cast(tag), span),
+                    None => {
+                        self.push(proc, Instruction::Pop);
+                        self.push_throw_builtin(
+                            proc,
+                            "TypeError",
+                            "only int, float, string and bool can be cast to".to_string(),
+                            span,
+                        );
+                    }
+                }
             }
             Expr::Ident(ident) => {
                 let var = self.lookup.get(ident.clone());
@@ -187,81 +1757,207 @@ impl Interpreter {
                 mut else_if_bodies,
                 else_body,
             } => {
-                if else_if_bodies.is_empty() {
-                    self.interpret_expr(proc, condition);
+                self.interpret_expr(proc, condition);
+                let cont = self.addr_after(proc, 1);
 
-                    let then_proc = self.program.create_procedure();
-                    self.interpret_body_scoped(then_proc, body.into_node());
+                let then_proc = self.program.create_procedure();
+                self.interpret_block(then_proc, body.into_node(), cont);
 
+                let else_proc = self.program.create_procedure();
+                if else_if_bodies.is_empty() {
                     if let Some(else_body) = else_body {
-                        let else_proc = self.program.create_procedure();
-                        self.interpret_body_scoped(else_proc, else_body.into_node());
-
-                        self.push_spanned(
-                            proc,
-                            Instruction::JumpIfElse(
-                                Addr::Procedure(then_proc),
-                                Addr::Procedure(else_proc),
-                            ),
-                            span,
-                        );
-                        return;
+                        self.interpret_block(else_proc, else_body.into_node(), cont);
+                    } else {
+                        self.push(Some(else_proc), Instruction::LoadNull);
+                        self.push(Some(else_proc), Instruction::Jump(cont));
                     }
+                } else {
+                    // An else if chain is the same as an if nested in the else body
+                    let (condition, body) = else_if_bodies.remove(0);
 
-                    self.push_spanned(proc, Instruction::JumpIf(Addr::Procedure(then_proc)), span);
-                    return;
+                    self.interpret_expr(
+                        Some(else_proc),
+                        Spanned::new(
+                            Expr::If {
+                                condition,
+                                body,
+                                else_if_bodies,
+                                else_body,
+                            },
+                            span.clone(),
+                        ),
+                    );
+                    self.push(Some(else_proc), Instruction::Jump(cont));
                 }
 
-                else_if_bodies.insert(0, (condition, body));
+                self.push_spanned(
+                    proc,
+                    Instruction::JumpIfElse(Addr::Procedure(then_proc), Addr::Procedure(else_proc)),
+                    span,
+                );
+            }
+            Expr::Match {
+                subject,
+                arms,
+                else_body,
+            } => {
+                let key = self.hidden_var();
 
-                let mut last_parent = proc;
-                let mut last_else = self.program.create_procedure();
+                self.push_enter_scope(proc);
+                self.interpret_expr(proc, subject);
+                self.push(proc, Instruction::StoreVar(key));
+                let cont = self.addr_after(proc, 1);
 
-                for (cond, body) in else_if_bodies {
-                    let Body(body, return_last) = body.into_node();
+                let arms_proc = self.program.create_procedure();
+                self.interpret_match_arms(arms_proc, &mut arms.into_iter(), else_body, key, cont);
+                self.push_spanned(proc, Instruction::Jump(Addr::Procedure(arms_proc)), span);
+                self.push_exit_scope(proc);
+            }
+            Expr::Splat(_) | Expr::KeywordSplat(_) => {
+                unreachable!("splats are only parsed as arguments of calls")
+            }
+            Expr::Array(items) => {
+                let len = items.len();
 
-                    self.interpret_expr(last_parent, cond);
+                for item in items {
+                    self.interpret_expr(proc, item);
+                }
 
-                    let then_proc = self.program.create_procedure();
-                    self.interpret_body_scoped(then_proc, Body(body, return_last));
+                self.push_spanned(proc, Instruction::MakeArray(len), span);
+            }
+            Expr::For {
+                label,
+                target,
+                iterable,
+                body,
+            } => {
+                self.push_enter_scope(proc);
 
-                    self.push_spanned(
-                        last_parent,
-                        Instruction::JumpIfElse(
-                            Addr::Procedure(then_proc),
-                            Addr::Procedure(last_else),
-                        ),
-                        span.clone(),
-                    );
-                    // If last_parent is None, Halt instruction should be inserted automatically.
-                    if let Some(p) = last_parent {
-                        self.push_return(p, return_last);
+                let body_proc = self.program.create_procedure();
+
+                match iterable.into_node_span() {
+                    // Fast path: count through integer ranges without creating an iterator
+                    (
+                        Expr::BinaryExpr {
+                            operator,
+                            lhs: start,
+                            rhs: end,
+                        },
+                        _,
+                    ) if operator.node() == &Operator::Range => {
+                        let counter = self.hidden_var();
+                        let end_key = self.hidden_var();
+
+                        self.interpret_expr(proc, start);
+                        self.push(proc, Instruction::StoreMutVar(counter));
+                        self.interpret_expr(proc, end);
+                        self.push(proc, Instruction::StoreVar(end_key));
+
+                        let loc = self.program.next_addr(proc);
+                        self.push(proc, Instruction::LoadVar(counter));
+                        self.push(proc, Instruction::LoadVar(end_key));
+                        self.push(proc, Instruction::OpLt);
+
+                        // Kept out of the body so that `continue` still increments the counter
+                        let step_proc = self.program.create_procedure();
+                        self.push(Some(step_proc), Instruction::LoadVar(counter));
+                        self.push(Some(step_proc), Instruction::LoadInt(1));
+                        self.push(Some(step_proc), Instruction::BinOpAdd);
+                        self.push(Some(step_proc), Instruction::AssignVar(counter));
+                        self.push(Some(step_proc), Instruction::Jump(loc));
+
+                        self.push(Some(body_proc), Instruction::LoadVar(counter));
+                        self.interpret_loop_body(
+                            body_proc,
+                            Loop {
+                                label,
+                                exit: self.addr_after(proc, 2),
+                                next: Addr::Procedure(step_proc),
+                                depth: self.scope_depth,
+                            },
+                            Some(target),
+                            body,
+                        );
                     }
+                    (iterable, iterable_span) => {
+                        let iter = self.hidden_var();
 
-                    last_parent = Some(last_else);
-                    last_else = self.program.create_procedure();
-                }
-                self.program.pop_procedure();
+                        self.interpret_expr(proc, Spanned::new(iterable, iterable_span.clone()));
+                        self.push_spanned(proc, Instruction::GetIter, iterable_span);
+                        self.push(proc, Instruction::StoreVar(iter));
 
-                if let Some(else_body) = else_body {
-                    self.interpret_body_scoped(last_parent.unwrap_or_else(
-                        || unreachable!("last_parent is always an if or else if block and never the top level")
-                    ), else_body.into_node());
-                } else {
-                    self.push(last_parent, Instruction::RetNull);
+                        let loc = self.program.next_addr(proc);
+                        self.push(proc, Instruction::LoadVar(iter));
+                        self.push(proc, Instruction::IterNext);
+
+                        self.interpret_loop_body(
+                            body_proc,
+                            Loop {
+                                label,
+                                exit: self.addr_after(proc, 2),
+                                next: loc,
+                                depth: self.scope_depth,
+                            },
+                            Some(target),
+                            body,
+                        );
+                    }
                 }
+
+                // A loop that runs out of items evaluates to null, otherwise to its break value
+                self.push_spanned(proc, Instruction::JumpIf(Addr::Procedure(body_proc)), span);
+                self.push(proc, Instruction::LoadNull);
+                self.push_exit_scope(proc);
             }
-            Expr::While { condition, body } => {
+            Expr::While {
+                label,
+                condition,
+                body,
+            } => {
                 let loc = self.program.next_addr(proc);
                 self.interpret_expr(proc, condition);
 
                 let body_proc = self.program.create_procedure();
-                self.interpret_body_scoped_no_return(body_proc, body);
+                self.interpret_loop_body(
+                    body_proc,
+                    Loop {
+                        label,
+                        exit: self.addr_after(proc, 2),
+                        next: loc,
+                        depth: self.scope_depth,
+                    },
+                    None,
+                    body,
+                );
 
-                self.push(Some(body_proc), Instruction::Jump(loc));
-                self.push(proc, Instruction::JumpIf(Addr::Procedure(body_proc)));
+                self.push_spanned(proc, Instruction::JumpIf(Addr::Procedure(body_proc)), span);
+                self.push(proc, Instruction::LoadNull);
+            }
+            Expr::Func {
+                params,
+                body,
+                return_last,
+                ..
+            } => self.interpret_func(proc, params, Body(body, return_last), span),
+            e @ (Expr::Attr { .. } | Expr::Call { .. } | Expr::Index { .. } | Expr::Slice { .. }) => {
+                self.interpret_chain(proc, Spanned::new(e, span));
+            }
+            Expr::NonNull(value) => {
+                self.interpret_expr(proc, value);
+                self.push_spanned(proc, Instruction::AssertNonNull, span);
+            }
+            Expr::Try {
+                body,
+                catches,
+                finally,
+            } => self.interpret_try(proc, body.into_node(), catches, finally, span),
+            Expr::With { managers, body } => {
+                self.interpret_with(proc, managers.into_iter(), body.into_node(), span);
+            }
+            Expr::SelfRef => {
+                let key = self.self_var();
+                self.push_spanned(proc, Instruction::LoadVar(key), span);
             }
-            _ => todo!(),
         }
     }
 
@@ -270,16 +1966,96 @@ impl Interpreter {
         let node = node.into_node();
 
         match node {
-            Node::Expr(e) => self.interpret_expr(proc, e),
+            Node::Expr(e) => {
+                self.interpret_expr(proc, e);
+                self.push(proc, Instruction::Pop);
+            }
             Node::Module(m) => self.interpret_body(proc, Body(m, false)),
             Node::Return(e) => {
+                let depth = self.cleanups.first().map_or(self.scope_depth, |c| c.depth);
+
                 if let Some(e) = e {
                     self.interpret_expr(proc, e);
+                    self.push_leave(proc, depth);
                     self.push_spanned(proc, Instruction::Ret, span);
                 } else {
+                    self.push_leave(proc, depth);
                     self.push_spanned(proc, Instruction::RetNull, span);
                 }
             }
+            Node::Throw(e) => {
+                self.interpret_expr(proc, e);
+                self.push_spanned(proc, Instruction::Throw, span);
+            }
+            // TODO: fields, casts and default operators are only checked by the analyzer until
+            // instances are initialized by their constructor
+            Node::Class {
+                name,
+                bases,
+                methods,
+                ..
+            } => {
+                let key = self.lookup.get(name.clone());
+
+                // Builtin classes are declared by the standard library, e.g. `StopIteration` in
+                // `std.iter`, which binds the builtin class so that the errors thrown by the
+                // runtime are instances of it. Its bases are those of the builtin class.
+                if BUILTIN_CLASSES.contains(&name.as_str()) {
+                    self.push_spanned(proc, Instruction::LoadVar(key), span.clone());
+                } else {
+                    let len = bases.len();
+                    for base in bases {
+                        self.interpret_class(proc, base);
+                    }
+                    self.push_spanned(
+                        proc,
+                        Instruction::MakeClass(name.clone(), len),
+                        span.clone(),
+                    );
+                }
+                self.interpret_methods(proc, methods);
+
+                self.push_rich(
+                    proc,
+                    RichInstruction {
+                        inner: Instruction::StoreVar(key),
+                        span: Some(span),
+                        name: Some(name),
+                    },
+                );
+            }
+            // The name was declared when entering the body, so the function captures itself and
+            // any function declared alongside it, see `interpret_body_no_return`
+            Node::Func {
+                name,
+                params,
+                body,
+                return_last,
+                ..
+            } => {
+                self.interpret_func(proc, params, Body(body, return_last), span.clone());
+
+                let key = self.lookup.get(name.clone());
+                self.push_rich(
+                    proc,
+                    RichInstruction {
+                        inner: Instruction::InitVar(key),
+                        span: Some(span),
+                        name: Some(name),
+                    },
+                );
+            }
+            Node::Break { label, value } => {
+                if let Some(value) = value {
+                    self.interpret_expr(proc, value);
+                } else {
+                    self.push(proc, Instruction::LoadNull);
+                }
+
+                self.interpret_loop_control(proc, label, |l| l.exit, span);
+            }
+            Node::Continue(label) => self.interpret_loop_control(proc, label, |l| l.next, span),
+            Node::Require { items, from } => self.interpret_require(proc, items, from, span),
             // TODO: maybe we can check const and mut at runtime, but those should be caught by the analyzer
             Node::Declare {
                 targets,
@@ -290,76 +2066,130 @@ impl Interpreter {
             } => {
                 self.interpret_expr(proc, value);
 
-                // TODO: currently we assume only one target
-                let target = targets.first().unwrap();
-
-                if let Target::Ident(s) = target.node() {
-                    let key = self.lookup.get(s.clone());
+                let store = if r#mut {
+                    Instruction::StoreMutVar
+                } else if r#const {
+                    Instruction::StoreConstVar
+                } else {
+                    Instruction::StoreVar
+                };
 
-                    self.push_rich(
-                        proc,
-                        RichInstruction {
-                            inner: if r#mut {
-                                Instruction::StoreMutVar(key)
-                            } else if r#const {
-                                Instruction::StoreConstVar(key)
-                            } else {
-                                Instruction::StoreVar(key)
-                            },
-                            span: Some(span),
-                            name: Some(s.clone()),
-                        },
-                    );
-                }
+                self.store_targets(proc, targets, store);
             }
             Node::Assign { targets, value } => {
                 self.interpret_expr(proc, value);
+                self.store_targets(proc, targets, Instruction::AssignVar);
+            }
+            Node::CompoundAssign {
+                target,
+                operator,
+                value,
+            } => self.interpret_compound_assign(proc, target, operator, value),
+            // Traits are classes whose default methods are inherited by the classes deriving
+            // from them
+            Node::Trait { name, methods, .. } => {
+                let key = self.lookup.get(name.clone());
 
-                // TODO: currently we assume only one target
-                let target = targets.first().unwrap();
-
-                if let Target::Ident(s) = target.node() {
-                    let key = self.lookup.get(s.clone());
-
-                    self.push_rich(
-                        proc,
-                        RichInstruction {
-                            inner: Instruction::AssignVar(key),
-                            span: Some(span),
-                            name: Some(s.clone()),
-                        },
-                    );
-                }
+                self.push_spanned(proc, Instruction::MakeClass(name.clone(), 0), span.clone());
+                self.interpret_methods(proc, methods);
+                self.push_rich(
+                    proc,
+                    RichInstruction {
+                        inner: Instruction::StoreVar(key),
+                        span: Some(span),
+                        name: Some(name),
+                    },
+                );
+            }
+            // The methods are added to the class or trait being extended
+            Node::Extend {
+                target, methods, ..
+            } => {
+                self.interpret_class(proc, target);
+                self.interpret_methods(proc, methods);
+                self.push_spanned(proc, Instruction::Pop, span);
             }
-            _ => todo!(),
         }
     }
 
+    /// Interprets the body, first declaring the names of the functions it declares as null so
+    /// that functions can call themselves and each other however they are nested.
     pub fn interpret_body_no_return(&mut self, proc: MaybeProc, body: Vec<Spanned<Node>>) {
+        for node in &body {
+            if let Node::Func { name, .. } = node.node() {
+                let key = self.lookup.get(name.clone());
+
+                self.push(proc, Instruction::LoadNull);
+                self.push_rich(
+                    proc,
+                    RichInstruction {
+                        inner: Instruction::StoreVar(key),
+                        span: Some(node.span()),
+                        name: Some(name.clone()),
+                    },
+                );
+            }
+        }
+
         for node in body {
             self.interpret_node(proc, node);
         }
     }
 
-    pub fn interpret_body(&mut self, proc: MaybeProc, Body(body, return_last): Body) {
+    /// Interprets the body, leaving the value of its last expression (or null) on the stack.
+    pub fn interpret_body_value(&mut self, proc: MaybeProc, Body(mut body, return_last): Body) {
+        let last = if return_last { body.pop() } else { None };
+
         self.interpret_body_no_return(proc, body);
 
+        match last.map(Spanned::into_node) {
+            Some(Node::Expr(e)) => self.interpret_expr(proc, e),
+            Some(_) => unreachable!("only expressions are returned implicitly"),
+            None => self.push(proc, Instruction::LoadNull),
+        }
+    }
+
+    pub fn interpret_body(&mut self, proc: MaybeProc, body: Body) {
+        self.interpret_body_value(proc, body);
+
         if let Some(proc) = proc {
-            self.push_return(proc, return_last);
+            self.push_return(proc, true);
         } else {
             self.push(None, Instruction::Halt);
         }
     }
 
-    pub fn interpret_body_scoped_no_return(&mut self, proc: AddrRepr, body: Vec<Spanned<Node>>) {
-        self.push_enter_scope(proc);
-        self.interpret_body_no_return(Some(proc), body);
-        self.push_exit_scope(proc);
+    /// Interprets the body in its own scope, then jumps to `cont` with its value on the stack.
+    pub fn interpret_block(&mut self, proc: AddrRepr, body: Body, cont: Addr) {
+        self.push_enter_scope(Some(proc));
+        self.interpret_body_value(Some(proc), body);
+        self.push_exit_scope(Some(proc));
+        self.push(Some(proc), Instruction::Jump(cont));
     }
 
-    pub fn interpret_body_scoped(&mut self, proc: AddrRepr, Body(body, return_last): Body) {
-        self.interpret_body_scoped_no_return(proc, body);
-        self.push_return(proc, return_last);
+    /// Runs the body of a module in its own scope and stores the module object built from
+    /// its exported names in the variable returned by [`Self::module_var`] for `name`.
+    pub fn interpret_module(
+        &mut self,
+        name: &RequirePath,
+        body: Vec<Spanned<Node>>,
+        exports: &[String],
+    ) {
+        self.push_enter_scope(None);
+        self.interpret_body_no_return(None, body);
+
+        for export in exports {
+            let key = self.lookup.get(export.clone());
+
+            self.push(None, Instruction::LoadInt(key as u128));
+            self.push(None, Instruction::LoadVar(key));
+        }
+
+        self.push_exit_scope(None);
+        self.push(None, Instruction::MakeModule(exports.len()));
+
+        let module = self.module_var(name);
+        self.push(None, Instruction::StoreVar(module));
     }
 }
 
@@ -6,21 +6,99 @@
 #![allow(clippy::missing_panics_doc)]
 #![allow(clippy::missing_errors_doc)]
 
+mod debug;
+mod format;
 mod interpreter;
 mod util;
 
 use std::collections::HashMap;
 use std::io::Write;
-use std::mem::size_of;
-use std::path::PathBuf;
-use std::str::FromStr;
 
+pub use debug::{Location, SourceFile};
+pub use format::{DecodeError, FORMAT_VERSION, MAGIC};
 pub use interpreter::Interpreter;
-use terbium_grammar::{Source, Span};
+use terbium_grammar::{FormatSpec, ParamSpec, Span};
 pub use util::EqComparableFloat;
 
 pub type AddrRepr = usize;
 
+/// Classes bound in the global scope before any program runs. Transformers look up these names
+/// before any other, so the key of each class is its index here.
+pub const BUILTIN_CLASSES: [&str; 9] = [
+    "Error",
+    "NullError",
+    "IndexError",
+    "ZeroDivisionError",
+    "ValueError",
+    "TypeError",
+    "StopIteration",
+    "AttributeError",
+    "SignatureError",
+];
+
+/// Intrinsic functions bound in the global scope after the builtin classes, which the standard
+/// library wraps. The key of each function is its index here offset by the amount of builtin
+/// classes.
+pub const BUILTIN_FUNCS: [&str; 6] = [
+    "__trb_internal_call_op",
+    "__trb_internal_has_op",
+    "__trb_internal_print",
+    "__trb_internal_floor_num",
+    "__trb_internal_name_of",
+    "__trb_internal_type_of",
+];
+
+/// A builtin type which [`Instruction::IsType`] tests objects against, or which
+/// [`Instruction::Cast`] converts them to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TypeTag {
+    Int,
+    Float,
+    String,
+    Bool,
+    Null,
+    Array,
+}
+
+impl TypeTag {
+    /// The tag of the builtin type with this name in a type expression, e.g. `int`.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "int" => Self::Int,
+            "float" => Self::Float,
+            "string" => Self::String,
+            "bool" => Self::Bool,
+            _ => return None,
+        })
+    }
+
+    /// The name of this type, as written in a type expression.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::String => "string",
+            Self::Bool => "bool",
+            Self::Null => "null",
+            Self::Array => "array",
+        }
+    }
+
+    const fn from_byte(b: u8) -> Option<Self> {
+        Some(match b {
+            0 => Self::Int,
+            1 => Self::Float,
+            2 => Self::String,
+            3 => Self::Bool,
+            4 => Self::Null,
+            5 => Self::Array,
+            _ => return None,
+        })
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialOrd, PartialEq, Eq, Hash)]
 pub enum Addr {
     Absolute(AddrRepr),
@@ -35,6 +113,7 @@ pub enum Instruction {
     LoadFloat(EqComparableFloat),
     LoadString(String),
     LoadBool(bool),
+    LoadNull,
     Load(usize),
     LoadFrame(usize),
 
@@ -50,13 +129,17 @@ pub enum Instruction {
     BinOpSub,
     BinOpMul,
     BinOpDiv,
-    BinOpTrueDiv, // Div keeps type, while TrueDiv doesn't. e.g. Div(5, 2) -> 2 but TrueDiv(5, 2) -> 2.5
+    BinOpTrueDiv, // Div keeps type, while TrueDiv doesn't. e.g. Div(5, 2) -> 2 but TrueDiv(5, 2) -> 2.5. Div floors, e.g. Div(-5, 2) -> -3
+    BinOpMod,     // Takes the sign of the divisor, so that Div(a, b) * b + Mod(a, b) == a
     BinOpPow,
+    BinOpRange,
 
     BinOpBitOr,
     BinOpBitXor,
     BinOpBitAnd,
-    UnOpBitNot, // Unary
+    BinOpLShift,
+    BinOpRShift, // Arithmetic, e.g. RShift(-8, 1) -> -4
+    UnOpBitNot,  // Unary
 
     // Logical Operations
     OpEq,
@@ -75,15 +158,56 @@ pub enum Instruction {
     StoreMutVar(usize),
     StoreConstVar(usize),
     AssignVar(usize),
+    InitVar(usize), // Sets the variable declared earlier in the current scope to the popped value, including through any cell it was captured into
     LoadVar(usize),
 
+    // Arrays
+    MakeArray(usize), // Field 0 is the amount of items to take from the stack as elements
+    Index, // Pops an index, then pushes the item of the popped object at that index, calling its `op index` if it defines one
+    Slice, // Pops the end and start of a slice, each null if omitted, then pushes that slice of the popped object
+    StoreIndex, // Pops an index and an object, then sets its item at that index to the popped value. Pushes the result of `op set_index` if the object defines it, or null otherwise
+    MatchArray(usize), // Pushes whether the popped object is an array with exactly this many elements
+
+    // Modules
+    MakeModule(usize), // Field 0 is the amount of exports to take from the stack, each an integer key followed by its value
+    LoadAttr(usize),
+    StoreAttr(usize), // Pops an object, then sets its attribute with this key to the popped value
+    UnpackModule,     // Stores every name exported by the popped module into the current scope
+
+    // Strings
+    BuildString(usize), // Field 0 is the amount of items to take from the stack, each converted to a string
+    FormatValue(FormatSpec),
+
+    // Iterators
+    GetIter,
+    IterNext, // Pushes the next item followed by `true`, or only `false` if the iterator is exhausted
+
     // Functions
-    MakeFunc(usize), // Field 0 is the amount of items to take from the stack as parameters
-    CallFunc(usize), // Field 0 is the number of items
+    MakeFunc(Addr),         // Field 0 is the address of the body of the function
+    MakeParam(ParamSpec), // Adds a parameter to the function on top of the stack, popping its default value first if it has one
+    CaptureVar(usize), // Captures the variable by reference into the function on top of the stack
+    CallFunc(usize, usize), // Field 0 is the amount of positional arguments, field 1 the amount of keyword arguments, each a string followed by its value. The function is popped after them
+    CallFuncUnpacked(usize, usize), // Like `CallFunc`, but takes arrays that are concatenated into the arguments: field 0 is the amount of arrays of positional arguments, field 1 the amount of arrays of keyword arguments, each a `[name, value]` array
+
+    // Classes
+    MakeClass(String, usize), // Field 1 is the amount of base classes to take from the stack
+    MakeMethod(usize), // Adds the popped function as a method to the class on top of the stack
+    MakeOp(String), // Adds the popped function as the named operator overload to the class on top of the stack
+    LoadOp(String), // Pushes the named operator overload of the popped object, bound to it
+    IsInstance, // Pops a class, then pushes whether the popped object is an instance of it or of one of its subclasses
+    IsType(TypeTag), // Pushes whether the popped object is of this builtin type
+    Contains, // Pops a container, then pushes whether the popped item is in it, calling its `op contains` if it defines one
+    Cast(TypeTag), // Pushes the popped object converted to this builtin type, calling its `op cast` with the name of the type if it defines one
+
+    // Exceptions
+    EnterTry(usize), // Enters a scope guarded by the handler with this index until the scope is left
+    Throw, // Unwinds to the innermost handler, then jumps to it with the popped error on top of the stack
+    AssertNonNull, // Throws a NullError if the object on top of the stack is null, leaving it otherwise
 
     Jump(Addr),
     JumpIf(Addr),
     JumpIfElse(Addr, Addr),
+    JumpIfNotNull(Addr), // Does not pop the object it checks
 
     Pop,
     Ret,
@@ -103,29 +227,6 @@ impl Instruction {
         }
     }
 
-    #[must_use]
-    pub fn size(&self) -> usize {
-        1_usize
-            + match self {
-                Self::LoadInt(_) => size_of::<u128>(),
-                Self::LoadFloat(_) => size_of::<f64>(),
-                Self::LoadString(s) => s.len(), // FIXME: String length might exceed 255 (`u8::MAX`)
-                Self::LoadBool(_) => 1,
-                Self::LoadVar(_)
-                | Self::StoreVar(_)
-                | Self::StoreMutVar(_)
-                | Self::StoreConstVar(_)
-                | Self::MakeFunc(_)
-                | Self::Load(_)
-                | Self::Store(_)
-                | Self::LoadFrame(_)
-                | Self::AssignVar(_) => size_of::<usize>(),
-                Self::Jump(_) | Self::JumpIf(_) => size_of::<AddrRepr>(),
-                Self::JumpIfElse(_, _) => size_of::<AddrRepr>() * 2,
-                _ => 0,
-            }
-    }
-
     #[must_use]
     pub const fn to_instr_id(&self) -> u8 {
         match self {
@@ -160,7 +261,7 @@ impl Instruction {
             Self::StoreMutVar(_) => 28,
             Self::StoreConstVar(_) => 29,
             Self::MakeFunc(_) => 30,
-            Self::CallFunc(_) => 31,
+            Self::CallFunc(_, _) => 31,
             Self::Jump(_) => 32,
             Self::JumpIf(_) => 33,
             Self::JumpIfElse(_, _) => 34,
@@ -173,6 +274,40 @@ impl Instruction {
             Self::EnterScope => 41,
             Self::ExitScope => 42,
             Self::AssignVar(_) => 43,
+            Self::MakeArray(_) => 44,
+            Self::Index => 45,
+            Self::MatchArray(_) => 46,
+            Self::BinOpRange => 47,
+            Self::GetIter => 48,
+            Self::IterNext => 49,
+            Self::LoadNull => 50,
+            Self::LoadAttr(_) => 51,
+            Self::UnpackModule => 52,
+            Self::MakeModule(_) => 53,
+            Self::BuildString(_) => 54,
+            Self::FormatValue(_) => 55,
+            Self::CaptureVar(_) => 56,
+            Self::MakeParam(_) => 57,
+            Self::MakeClass(_, _) => 58,
+            Self::IsInstance => 59,
+            Self::EnterTry(_) => 60,
+            Self::Throw => 61,
+            Self::MakeMethod(_) => 62,
+            Self::MakeOp(_) => 63,
+            Self::LoadOp(_) => 64,
+            Self::JumpIfNotNull(_) => 65,
+            Self::AssertNonNull => 66,
+            Self::Slice => 67,
+            Self::StoreIndex => 68,
+            Self::StoreAttr(_) => 69,
+            Self::IsType(_) => 70,
+            Self::Contains => 71,
+            Self::BinOpMod => 72,
+            Self::BinOpLShift => 73,
+            Self::BinOpRShift => 74,
+            Self::Cast(_) => 75,
+            Self::InitVar(_) => 76,
+            Self::CallFuncUnpacked(_, _) => 77,
         }
     }
 }
@@ -226,45 +361,20 @@ impl RichInstruction {
     }
 }
 
+/// An exception handler, which [`Instruction::EnterTry`] installs by its index in the
+/// handler table of a [`Program`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Handler {
+    /// Where an error caught by the handler is sent, on top of the stack.
+    pub target: Addr,
+}
+
 #[derive(Debug)]
 pub struct Program {
     inner: Vec<RichInstruction>,
     procedures: Vec<Vec<RichInstruction>>,
-}
-
-pub(crate) fn read_ne_u128(input: &mut &[u8]) -> u128 {
-    let (int_bytes, rest) = input.split_at(std::mem::size_of::<u128>());
-    *input = rest;
-    u128::from_ne_bytes(int_bytes.try_into().unwrap())
-}
-
-pub(crate) fn read_ne_f64(input: &mut &[u8]) -> f64 {
-    let (int_bytes, rest) = input.split_at(std::mem::size_of::<f64>());
-    *input = rest;
-    f64::from_ne_bytes(int_bytes.try_into().unwrap())
-}
-
-pub(crate) fn read_ne_usize(input: &mut &[u8]) -> usize {
-    let (int_bytes, rest) = input.split_at(std::mem::size_of::<usize>());
-    *input = rest;
-    usize::from_ne_bytes(int_bytes.try_into().unwrap())
-}
-
-macro_rules! progress {
-    ($ptr:ident, $e:expr) => {{
-        $ptr += 1;
-        $e
-    }};
-}
-
-macro_rules! parse_usize {
-    ($ptr:ident, $bytes:expr, $member:ident) => {{
-        $ptr += 1 + size_of::<usize>();
-        Instruction::$member(
-            $crate::read_ne_usize(&mut &$bytes[($ptr - ::std::mem::size_of::<usize>())..$ptr])
-                .into(),
-        )
-    }};
+    handlers: Vec<Handler>,
+    sources: Vec<SourceFile>,
 }
 
 impl Program {
@@ -273,6 +383,8 @@ impl Program {
         Self {
             inner: Vec::new(),
             procedures: Vec::new(),
+            handlers: Vec::new(),
+            sources: Vec::new(),
         }
     }
 
@@ -290,6 +402,18 @@ impl Program {
         std::mem::drop(self.procedures.pop());
     }
 
+    /// Adds a handler sending caught errors to `target`, returning its index.
+    pub fn add_handler(&mut self, target: Addr) -> usize {
+        self.handlers.push(Handler { target });
+
+        self.handlers.len() - 1
+    }
+
+    #[must_use]
+    pub fn handler(&self, index: usize) -> &Handler {
+        self.handlers.get(index).expect("no handler there")
+    }
+
     pub fn push(&mut self, procedure: Option<AddrRepr>, instr: RichInstruction) {
         if let Some(procedure) = procedure {
             return self
@@ -369,10 +493,16 @@ impl Program {
                         Instruction::JumpIf(addr) => {
                             Instruction::JumpIf(Self::resolve_addr(&lookup, *addr))
                         }
+                        Instruction::JumpIfNotNull(addr) => {
+                            Instruction::JumpIfNotNull(Self::resolve_addr(&lookup, *addr))
+                        }
                         Instruction::JumpIfElse(a, b) => Instruction::JumpIfElse(
                             Self::resolve_addr(&lookup, *a),
                             Self::resolve_addr(&lookup, *b),
                         ),
+                        Instruction::MakeFunc(addr) => {
+                            Instruction::MakeFunc(Self::resolve_addr(&lookup, *addr))
+                        }
                         o => o.clone(), // TODO: don't clone
                     },
                     span: span.clone(),
@@ -381,78 +511,30 @@ impl Program {
             )
             .collect();
 
-        self
-    }
-
-    #[must_use]
-    pub fn bytes(&self) -> Vec<u8> {
-        type I = Instruction;
-        let mut bytes = Vec::new();
-
-        for RichInstruction {
-            inner: instr,
-            span,
-            name,
-        } in self.inner()
-        {
-            bytes.push(instr.to_instr_id());
-
-            match instr {
-                I::LoadInt(i) => bytes.extend_from_slice(&i.to_ne_bytes()),
-                I::LoadFloat(f) => bytes.extend_from_slice(&f.0.to_ne_bytes()),
-                I::LoadString(s) => {
-                    bytes.extend_from_slice(&s.len().to_ne_bytes());
-                    bytes.extend_from_slice(s.as_bytes());
-                }
-                I::LoadBool(b) => bytes.extend_from_slice(&[if *b { 0 } else { 1 }]),
-                I::LoadVar(i)
-                | I::Load(i)
-                | I::Store(i)
-                | I::StoreMutVar(i)
-                | I::StoreConstVar(i)
-                | I::StoreVar(i)
-                | I::AssignVar(i)
-                | I::MakeFunc(i) => bytes.extend_from_slice(&i.to_ne_bytes()),
-                I::Jump(a) | I::JumpIf(a) => match a {
-                    Addr::Absolute(p) => bytes.extend_from_slice(&p.to_ne_bytes()),
-                    _ => panic!("procedures must be resolved prior to conversion"),
-                },
-                I::JumpIfElse(a, b) => match (a, b) {
-                    (Addr::Absolute(a), Addr::Absolute(b)) => {
-                        bytes.extend_from_slice(&[a.to_ne_bytes(), b.to_ne_bytes()].concat());
-                    }
-                    _ => panic!("procedures must be resolved prior to conversion"),
-                },
-                _ => (),
-            }
-
-            let span_bytes = span.clone().map(|s| {
-                let path = s.src().to_path();
-                let lossy = path.to_string_lossy();
-                let src_bytes = lossy.as_bytes();
-
-                [
-                    [1_u8].as_slice(),
-                    s.start().to_ne_bytes().as_slice(),
-                    s.end().to_ne_bytes().as_slice(),
-                    src_bytes.len().to_ne_bytes().as_slice(),
-                    src_bytes,
-                ]
-                .concat()
-            });
-            bytes.extend_from_slice(&*span_bytes.unwrap_or_else(|| vec![0_u8]));
-
-            let name_bytes = name.clone().map(|s| s.as_bytes().to_vec());
-            bytes.extend_from_slice(&name_bytes.clone().map_or(0, |n| n.len()).to_ne_bytes());
-            bytes.extend_from_slice(&*name_bytes.unwrap_or_default());
+        for handler in &mut self.handlers {
+            handler.target = Self::resolve_addr(&lookup, handler.target);
         }
 
-        bytes
+        self
     }
 
+    /// Writes the disassembly of this program. Each line of source code the program has debug
+    /// info for is written before the first instruction lowered from it.
     pub fn dis(&self, w: &mut impl Write) -> std::io::Result<()> {
         type I = Instruction;
         let pad_length = self.inner.len().saturating_sub(1).to_string().len();
+        let texts = self
+            .sources
+            .iter()
+            .map(SourceFile::text)
+            .collect::<Vec<_>>();
+        let mut current_line = None;
+
+        for (i, handler) in self.handlers.iter().enumerate() {
+            if let Addr::Absolute(target) = handler.target {
+                writeln!(w, "handler {} -> {}", i, target)?;
+            }
+        }
 
         for (
             j,
@@ -461,30 +543,90 @@ impl Program {
             },
         ) in self.inner().enumerate()
         {
+            if let Some(Location { src, line, .. }) = self.location(j) {
+                if current_line.as_ref() != Some(&(src.clone(), line)) {
+                    let text = self
+                        .sources
+                        .iter()
+                        .position(|file| file.src == src)
+                        .and_then(|i| texts[i].as_deref())
+                        .and_then(|text| text.lines().nth(line - 1));
+
+                    write!(w, "# {}:{}", src.to_path().display(), line)?;
+                    if let Some(text) = text {
+                        write!(w, ": {}", text.trim())?;
+                    }
+                    writeln!(w)?;
+
+                    current_line = Some((src, line));
+                }
+            }
+
             write!(w, "{:01$} | ", j, pad_length)?;
             match instr {
                 I::LoadInt(i) => write!(w, "load_int {}", i)?,
                 I::LoadFloat(f) => write!(w, "load_float {}", f.0)?,
                 I::LoadString(s) => write!(w, "load_string {:?}", s)?,
                 I::LoadBool(b) => write!(w, "load_bool {:?}", b)?,
+                I::LoadNull => write!(w, "load_null")?,
                 I::Load(i) => write!(w, "load {}", i)?,
                 I::LoadVar(i) => write!(w, "load_var {}", i)?,
+                I::LoadFrame(i) => write!(w, "load_frame {}", i)?,
                 I::Store(i) => write!(w, "store {}", i)?,
                 I::StoreVar(i) => write!(w, "store_var {}", i)?,
                 I::StoreMutVar(i) => write!(w, "store_mut_var {}", i)?,
                 I::StoreConstVar(i) => write!(w, "store_const_var {}", i)?,
                 I::AssignVar(i) => write!(w, "assign_var {}", i)?,
+                I::InitVar(i) => write!(w, "init_var {}", i)?,
+                I::MakeArray(i) => write!(w, "make_array {}", i)?,
+                I::Index => write!(w, "index")?,
+                I::Slice => write!(w, "slice")?,
+                I::StoreIndex => write!(w, "store_index")?,
+                I::MatchArray(i) => write!(w, "match_array {}", i)?,
+                I::LoadAttr(i) => write!(w, "load_attr {}", i)?,
+                I::StoreAttr(i) => write!(w, "store_attr {}", i)?,
+                I::UnpackModule => write!(w, "unpack_module")?,
+                I::MakeModule(i) => write!(w, "make_module {}", i)?,
+                I::BuildString(i) => write!(w, "build_string {}", i)?,
+                I::FormatValue(spec) => write!(w, "format_value {:?}", spec)?,
+                I::MakeFunc(Addr::Absolute(addr)) => write!(w, "make_func {}", addr)?,
+                I::MakeParam(spec) => write!(w, "make_param {:?}", spec)?,
+                I::CaptureVar(i) => write!(w, "capture_var {}", i)?,
+                I::CallFunc(args, kwargs) => write!(w, "call_func {} {}", args, kwargs)?,
+                I::CallFuncUnpacked(args, kwargs) => {
+                    write!(w, "call_func_unpacked {} {}", args, kwargs)?;
+                }
+                I::MakeClass(name, bases) => write!(w, "make_class {:?} {}", name, bases)?,
+                I::IsInstance => write!(w, "is_instance")?,
+                I::IsType(tag) => write!(w, "is_type {:?}", tag)?,
+                I::Contains => write!(w, "contains")?,
+                I::Cast(tag) => write!(w, "cast {:?}", tag)?,
+                I::EnterTry(i) => write!(w, "enter_try {}", i)?,
+                I::Throw => write!(w, "throw")?,
+                I::AssertNonNull => write!(w, "assert_non_null")?,
+                I::MakeMethod(i) => write!(w, "make_method {}", i)?,
+                I::MakeOp(name) => write!(w, "make_op {:?}", name)?,
+                I::LoadOp(name) => write!(w, "load_op {:?}", name)?,
+                I::BinOpRange => write!(w, "bin_range")?,
+                I::GetIter => write!(w, "get_iter")?,
+                I::IterNext => write!(w, "iter_next")?,
                 I::Jump(Addr::Absolute(addr)) => write!(w, "jump {}", addr)?,
                 I::JumpIf(Addr::Absolute(addr)) => write!(w, "jump_if {}", addr)?,
+                I::JumpIfNotNull(Addr::Absolute(addr)) => write!(w, "jump_if_not_null {}", addr)?,
                 I::JumpIfElse(Addr::Absolute(a), Addr::Absolute(b)) => {
                     write!(w, "jump_if_else {} {}", a, b)?;
                 }
+                I::UnOpPos => write!(w, "un_pos")?,
+                I::UnOpNeg => write!(w, "un_neg")?,
                 I::BinOpAdd => write!(w, "bin_add")?,
                 I::BinOpSub => write!(w, "bin_sub")?,
                 I::BinOpMul => write!(w, "bin_mul")?,
                 I::BinOpDiv => write!(w, "bin_div")?,
                 I::BinOpTrueDiv => write!(w, "bin_truediv")?,
+                I::BinOpMod => write!(w, "bin_mod")?,
                 I::BinOpPow => write!(w, "bin_pow")?,
+                I::BinOpLShift => write!(w, "bin_lshift")?,
+                I::BinOpRShift => write!(w, "bin_rshift")?,
                 I::BinOpBitOr => write!(w, "bin_bit_or")?,
                 I::BinOpBitXor => write!(w, "bin_bit_xor")?,
                 I::UnOpBitNot => write!(w, "bin_bit_not")?,
@@ -516,151 +658,6 @@ impl Program {
 
         Ok(())
     }
-
-    #[must_use]
-    #[allow(clippy::match_same_arms)] // Remove when unimplemented!() are all implemented
-    #[allow(clippy::too_many_lines)] // Should refactor later
-    pub fn from_bytes(bytes: &[u8]) -> Self {
-        type I = Instruction;
-
-        let mut ptr = 0;
-        let mut instructions = Vec::<RichInstruction>::new();
-
-        while let Some(b) = bytes.get(ptr) {
-            let instr = match b {
-                0 => {
-                    ptr += 1 + size_of::<u128>();
-                    I::LoadInt(read_ne_u128(&mut &bytes[(ptr - 16)..ptr]))
-                }
-                1 => {
-                    ptr += 1 + size_of::<f64>();
-                    I::LoadFloat(read_ne_f64(&mut &bytes[(ptr - 8)..ptr]).into())
-                }
-                2 => {
-                    let size = size_of::<usize>();
-                    ptr += 1 + size;
-                    let len = read_ne_usize(&mut &bytes[(ptr - size)..ptr]);
-
-                    ptr += 1 + len;
-                    I::LoadString(String::from_utf8(Vec::from(&bytes[(ptr - len)..ptr])).unwrap())
-                }
-                3 => {
-                    ptr += 1 + size_of::<bool>();
-                    I::LoadBool(bytes[ptr - 1] == 0)
-                }
-                4 => parse_usize!(ptr, bytes, Load),
-                5 => progress!(ptr, I::UnOpPos),
-                6 => progress!(ptr, I::UnOpNeg),
-                7 => progress!(ptr, I::BinOpAdd),
-                8 => progress!(ptr, I::BinOpSub),
-                9 => progress!(ptr, I::BinOpMul),
-                10 => progress!(ptr, I::BinOpDiv),
-                11 => progress!(ptr, I::BinOpTrueDiv),
-                12 => progress!(ptr, I::BinOpPow),
-                13 => progress!(ptr, I::BinOpBitOr),
-                14 => progress!(ptr, I::BinOpBitXor),
-                15 => progress!(ptr, I::BinOpBitAnd),
-                16 => progress!(ptr, I::UnOpBitNot),
-                17 => progress!(ptr, I::OpEq),
-                18 => progress!(ptr, I::OpNe),
-                19 => progress!(ptr, I::OpLt),
-                20 => progress!(ptr, I::OpLe),
-                21 => progress!(ptr, I::OpGt),
-                22 => progress!(ptr, I::OpGe),
-                23 => progress!(ptr, I::OpLogicalOr),
-                24 => progress!(ptr, I::OpLogicalAnd),
-                25 => progress!(ptr, I::OpLogicalNot),
-                26 => parse_usize!(ptr, bytes, LoadVar),
-                27 => parse_usize!(ptr, bytes, StoreVar),
-                28 => parse_usize!(ptr, bytes, StoreMutVar),
-                29 => parse_usize!(ptr, bytes, StoreConstVar),
-                30 => unimplemented!(),
-                31 => unimplemented!(),
-                32 => {
-                    ptr += 1 + size_of::<AddrRepr>();
-                    I::Jump(Addr::Absolute(read_ne_usize(
-                        &mut &bytes[(ptr - size_of::<AddrRepr>())..ptr],
-                    )))
-                }
-                33 => {
-                    ptr += 1 + size_of::<AddrRepr>();
-                    I::JumpIf(Addr::Absolute(read_ne_usize(
-                        &mut &bytes[(ptr - size_of::<AddrRepr>())..ptr],
-                    )))
-                }
-                34 => {
-                    ptr += 1 + size_of::<AddrRepr>();
-                    let first = Addr::Absolute(read_ne_usize(
-                        &mut &bytes[(ptr - size_of::<AddrRepr>())..ptr],
-                    ));
-
-                    ptr += size_of::<AddrRepr>();
-                    I::JumpIfElse(
-                        first,
-                        Addr::Absolute(read_ne_usize(
-                            &mut &bytes[(ptr - size_of::<AddrRepr>())..ptr],
-                        )),
-                    )
-                }
-                35 => progress!(ptr, I::Pop),
-                36 => progress!(ptr, I::Ret),
-                37 => progress!(ptr, I::RetNull),
-                38 => progress!(ptr, I::Halt),
-                39 => unimplemented!(),
-                40 => parse_usize!(ptr, bytes, Store),
-                41 => progress!(ptr, I::EnterScope),
-                42 => progress!(ptr, I::ExitScope),
-                43 => parse_usize!(ptr, bytes, AssignVar),
-                b => panic!("invalid byte 0x{:0x} at position {}", b, ptr),
-            };
-
-            let size = size_of::<usize>();
-
-            let span = if bytes[ptr] == 1 {
-                ptr += 1 + size;
-                let start = read_ne_usize(&mut &bytes[(ptr - size)..ptr]);
-
-                ptr += 1 + size;
-                let end = read_ne_usize(&mut &bytes[(ptr - size)..ptr]);
-
-                ptr += 1 + size;
-                let src_len = read_ne_usize(&mut &bytes[(ptr - size)..ptr]);
-
-                ptr += 1 + src_len;
-                let path =
-                    PathBuf::from_str(&String::from_utf8_lossy(&bytes[(ptr - src_len)..ptr]))
-                        .unwrap();
-
-                let src = path.as_path();
-                let src = Source::from_path(src);
-
-                Some(Span::from_range(src, start..end))
-            } else {
-                None
-            };
-
-            ptr += 1 + size;
-            let string_len = read_ne_usize(&mut &bytes[(ptr - size)..ptr]);
-
-            ptr += 1 + string_len;
-            let name = if string_len > 0 {
-                Some(String::from_utf8(Vec::from(&bytes[(ptr - string_len)..ptr])).unwrap())
-            } else {
-                None
-            };
-
-            instructions.push(RichInstruction {
-                inner: instr,
-                span,
-                name,
-            });
-        }
-
-        Self {
-            inner: instructions,
-            procedures: Vec::new(),
-        }
-    }
 }
 
 impl FromIterator<RichInstruction> for Program {
@@ -668,6 +665,8 @@ impl FromIterator<RichInstruction> for Program {
         Self {
             inner: iter.into_iter().collect(),
             procedures: Vec::new(),
+            handlers: Vec::new(),
+            sources: Vec::new(),
         }
     }
 }
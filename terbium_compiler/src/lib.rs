@@ -10,8 +10,8 @@ use inkwell::{
     IntPredicate,
     module::Module,
     passes::PassManager,
-    types::{BasicType, VectorType, FloatMathType},
-    values::{AnyValue, BasicValue, BasicValueEnum, FunctionValue, PointerValue},
+    types::{BasicType, VectorType, FloatMathType, FunctionType},
+    values::{AnyValue, BasicValue, BasicValueEnum, FloatValue, FunctionValue, IntValue, PointerValue},
 };
 
 #[derive(Debug)]
@@ -47,6 +47,38 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
         compiler.optimize().map(|_| compiler.fn_value())
     }
 
+    /// Compiles the expression into a function named `name`, which takes no arguments and
+    /// returns the bits of the value of the expression as an `i64`. Floats are bitcast and
+    /// booleans are zero-extended.
+    pub fn compile_expr(
+        ctx: &'ctx Context,
+        builder: &'a Builder<'ctx>,
+        fpm: &'a PassManager<FunctionValue<'ctx>>,
+        module: &'a Module<'ctx>,
+        name: &str,
+        expr: &Spanned<Expr>,
+    ) -> Result<FunctionValue<'ctx>, &'static str> {
+        let mut compiler = Self {
+            ctx,
+            builder,
+            fpm,
+            module,
+            fn_val: None,
+        };
+
+        let i64_type = ctx.i64_type();
+        compiler.prepare_function(name, i64_type.fn_type(&[], false));
+
+        let bits = match compiler.eval_expr(expr)? {
+            BasicValueEnum::IntValue(i) => builder.build_int_z_extend_or_bit_cast(i, i64_type, "tmpbits"),
+            BasicValueEnum::FloatValue(f) => builder.build_bitcast(f, i64_type, "tmpbits").into_int_value(),
+            _ => return Err("only integers, floats and booleans can be returned"),
+        };
+        builder.build_return(Some(&bits));
+
+        compiler.optimize().map(|_| compiler.fn_value())
+    }
+
     #[inline]
     pub fn fn_value(&self) -> FunctionValue<'ctx> {
         self.fn_val.expect("expected fn_val.is_some()")
@@ -120,73 +152,518 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
                         // Analyzer should've caught this
                         _ => unreachable!(),
                     }
-                    Operator::Not => match value {
-                        BasicValueEnum::IntValue(i) => if i.is_const() {
-                            i.const_select(
-                                self.ctx.bool_type().const_zero(),
-                                self.ctx.bool_type().const_all_ones(),
-                            )
+                    Operator::Not => {
+                        let truthy = self.build_truthy(value)?;
+
+                        self.builder.build_not(truthy, "tmpnot")
                             .as_basic_value_enum()
-                        } else {
-                            self.builder.build_not(i, "tmpnot")
-                                .as_basic_value_enum()
-                        }
-                        BasicValueEnum::FloatValue(f) => if f.is_const() {
-                            f.const_compare(FloatPredicate::UEQ, self.ctx.f64_type().const_zero())
-                                .const_select(
-                                    self.ctx.bool_type().const_zero(),
-                                    self.ctx.bool_type().const_all_ones(),
-                                )
+                    }
+                    Operator::BitNot => match value {
+                        BasicValueEnum::IntValue(i) if !is_bool(i) => {
+                            self.builder.build_not(i, "tmpbitnot")
                                 .as_basic_value_enum()
-                        } else {
-                            self.builder.build_float_compare(
-                                FloatPredicate::UEQ,
-                                f,
-                                self.ctx.f64_type().const_zero(),
-                                "tmpnot",
-                            )
-                            .as_basic_value_enum()
                         }
-                        _ => todo!(),
+                        _ => return Err("unsupported operand for ~"),
                     }
-                    Operator::BitNot => todo!(),
                     _ => unreachable!(),
                 }
             }
             Expr::BinaryExpr{ operator, lhs, rhs } => {
                 let (operator, span) = operator.node_span();
 
+                // Strings only exist as constants, so operations on them are folded
+                if let Some(s) = fold_string(expr) {
+                    return Ok(self.ctx.const_string(s.as_bytes(), false).as_basic_value_enum());
+                }
+                if let (Some(lhs), Some(rhs)) = (fold_string(lhs.node()), fold_string(rhs.node())) {
+                    return self.compare_strings(*operator, &lhs, &rhs);
+                }
+
                 let left = self.eval_expr(lhs)?;
                 let right = self.eval_expr(rhs)?;
 
-                match operator {
-                    Operator::Add => {
-                        match (left.as_basic_value_enum(), right.as_basic_value_enum()) {
-                            (BasicValueEnum::IntValue(lhs), BasicValueEnum::IntValue(rhs)) => {
-                                self.builder.build_int_add(lhs, rhs, "tmpintadd")
-                                    .as_basic_value_enum()
-                            }
-                            (BasicValueEnum::FloatValue(lhs), BasicValueEnum::FloatValue(rhs)) => {
-                                self.builder.build_float_add(lhs, rhs, "tmpfloatadd")
-                                    .as_basic_value_enum()
-                            }
-                            (BasicValueEnum::FloatValue(f), BasicValueEnum::IntValue(i))
-                            | (BasicValueEnum::IntValue(i), BasicValueEnum::FloatValue(f)) => {
-                                let int = self.builder.build_signed_int_to_float(i, self.ctx.f64_type(), "tmpintfloatconv");
-
-                                self.builder.build_float_add(int, f, "tmpfloatadd")
-                                    .as_basic_value_enum()
-                            }
-                            _ => todo!()
+                match (*operator, left, right) {
+                    (Operator::Or | Operator::And, lhs, rhs) => {
+                        let lhs = self.build_truthy(lhs)?;
+                        let rhs = self.build_truthy(rhs)?;
+
+                        if *operator == Operator::Or {
+                            self.builder.build_or(lhs, rhs, "tmpor").as_basic_value_enum()
+                        } else {
+                            self.builder.build_and(lhs, rhs, "tmpand").as_basic_value_enum()
+                        }
+                    }
+                    (op, BasicValueEnum::IntValue(lhs), BasicValueEnum::IntValue(rhs)) => {
+                        if is_bool(lhs) && is_bool(rhs) {
+                            self.build_bool_op(op, lhs, rhs)?
+                        } else {
+                            self.build_int_op(op, lhs, rhs)?
                         }
                     }
+                    (op, BasicValueEnum::FloatValue(lhs), BasicValueEnum::FloatValue(rhs)) => {
+                        self.build_float_op(op, lhs, rhs)?
+                    }
+                    (op, BasicValueEnum::FloatValue(lhs), BasicValueEnum::IntValue(rhs)) => {
+                        let rhs = self.build_int_to_float(rhs);
+
+                        self.build_float_op(op, lhs, rhs)?
+                    }
+                    (op, BasicValueEnum::IntValue(lhs), BasicValueEnum::FloatValue(rhs)) => {
+                        let lhs = self.build_int_to_float(lhs);
+
+                        self.build_float_op(op, lhs, rhs)?
+                    }
+                    // Custom operator overloading
                     _ => todo!(),
                 }
             }
+            Expr::Cast(value, ty) => {
+                let target = match ty.node() {
+                    Expr::Ident(name) => name.as_str(),
+                    _ => return Err("only builtin types can be cast to"),
+                };
+
+                // Strings only exist as constants, so casts to and from them are folded
+                if let Some(s) = fold_string(expr) {
+                    return Ok(self.ctx.const_string(s.as_bytes(), false).as_basic_value_enum());
+                }
+                if let Some(s) = fold_string(value.node()) {
+                    return self.cast_string(&s, target);
+                }
+
+                match (target, self.eval_expr(value)?) {
+                    ("int", BasicValueEnum::IntValue(i)) => if is_bool(i) {
+                        self.builder.build_int_z_extend(i, self.ctx.i64_type(), "tmpcast")
+                            .as_basic_value_enum()
+                    } else {
+                        i.as_basic_value_enum()
+                    },
+                    ("int", BasicValueEnum::FloatValue(f)) => self.build_float_to_int(f)?.as_basic_value_enum(),
+                    ("float", BasicValueEnum::IntValue(i)) => if is_bool(i) {
+                        self.builder.build_unsigned_int_to_float(i, self.ctx.f64_type(), "tmpcast")
+                            .as_basic_value_enum()
+                    } else {
+                        self.build_int_to_float(i).as_basic_value_enum()
+                    },
+                    ("float", f @ BasicValueEnum::FloatValue(_)) => f,
+                    ("bool", value) => self.build_truthy(value)?.as_basic_value_enum(),
+                    ("string", _) => return Err("only constants can be cast to string"),
+                    _ => return Err("unsupported cast"),
+                }
+            }
             _ => todo!(),
         })
     }
 
+    /// Casts a constant string to the builtin type named by `target`, parsing it as a number if
+    /// the type is numeric.
+    fn cast_string(&self, s: &str, target: &str) -> Result<BasicValueEnum<'ctx>, &'static str> {
+        Ok(match target {
+            "int" => {
+                let i = s.parse::<i64>().map_err(|_| "string is not a valid int")?;
+
+                self.ctx.i64_type().const_int(i as u64, true).as_basic_value_enum()
+            }
+            "float" => {
+                let f = s.parse::<f64>().map_err(|_| "string is not a valid float")?;
+
+                self.ctx.f64_type().const_float(f).as_basic_value_enum()
+            }
+            "bool" => self.ctx.bool_type().const_int(u64::from(!s.is_empty()), false)
+                .as_basic_value_enum(),
+            _ => return Err("unsupported cast"),
+        })
+    }
+
+    /// Builds an operation on two integers, following the semantics of the interpreter, e.g.
+    /// division floors and the remainder takes the sign of the divisor. Arithmetic wraps on
+    /// overflow.
+    fn build_int_op(
+        &self,
+        op: Operator,
+        lhs: IntValue<'ctx>,
+        rhs: IntValue<'ctx>,
+    ) -> Result<BasicValueEnum<'ctx>, &'static str> {
+        Ok(match op {
+            Operator::Add => self.builder.build_int_add(lhs, rhs, "tmpadd").as_basic_value_enum(),
+            Operator::Sub => self.builder.build_int_sub(lhs, rhs, "tmpsub").as_basic_value_enum(),
+            Operator::Mul => self.builder.build_int_mul(lhs, rhs, "tmpmul").as_basic_value_enum(),
+            Operator::Div => {
+                let (quotient, remainder) = self.build_div_rem(lhs, rhs)?;
+                let floored = self.builder.build_int_sub(
+                    quotient,
+                    lhs.get_type().const_int(1, false),
+                    "tmpfloor",
+                );
+
+                self.builder.build_select(
+                    self.build_needs_floor(remainder, rhs),
+                    floored,
+                    quotient,
+                    "tmpdiv",
+                )
+            }
+            Operator::TrueDiv => self.builder.build_float_div(
+                self.build_int_to_float(lhs),
+                self.build_int_to_float(rhs),
+                "tmpdiv",
+            ).as_basic_value_enum(),
+            Operator::Mod => {
+                let (_, remainder) = self.build_div_rem(lhs, rhs)?;
+                let floored = self.builder.build_int_add(remainder, rhs, "tmpfloor");
+
+                self.builder.build_select(
+                    self.build_needs_floor(remainder, rhs),
+                    floored,
+                    remainder,
+                    "tmpmod",
+                )
+            }
+            Operator::Pow => self.build_int_pow(lhs, rhs)?.as_basic_value_enum(),
+            Operator::BitOr => self.builder.build_or(lhs, rhs, "tmpor").as_basic_value_enum(),
+            Operator::BitXor => self.builder.build_xor(lhs, rhs, "tmpxor").as_basic_value_enum(),
+            Operator::BitAnd => self.builder.build_and(lhs, rhs, "tmpand").as_basic_value_enum(),
+            Operator::BitLShift => self.builder.build_left_shift(lhs, rhs, "tmpshl").as_basic_value_enum(),
+            Operator::BitRShift => self.builder.build_right_shift(lhs, rhs, true, "tmpshr").as_basic_value_enum(),
+            Operator::Eq
+            | Operator::Ne
+            | Operator::Lt
+            | Operator::Le
+            | Operator::Gt
+            | Operator::Ge => {
+                let predicate = match op {
+                    Operator::Eq => IntPredicate::EQ,
+                    Operator::Ne => IntPredicate::NE,
+                    Operator::Lt => IntPredicate::SLT,
+                    Operator::Le => IntPredicate::SLE,
+                    Operator::Gt => IntPredicate::SGT,
+                    _ => IntPredicate::SGE,
+                };
+
+                self.builder.build_int_compare(predicate, lhs, rhs, "tmpcmp")
+                    .as_basic_value_enum()
+            }
+            _ => return Err("unsupported operator for integers"),
+        })
+    }
+
+    /// Builds the truncated quotient and remainder of a division. Dividing by zero traps, since
+    /// it is undefined behavior in LLVM, and is rejected while compiling if the divisor is a
+    /// constant. Dividing the minimum integer by -1 wraps like the other operations do.
+    fn build_div_rem(
+        &self,
+        lhs: IntValue<'ctx>,
+        rhs: IntValue<'ctx>,
+    ) -> Result<(IntValue<'ctx>, IntValue<'ctx>), &'static str> {
+        let ty = rhs.get_type();
+
+        // TODO: throw ZeroDivisionError like the interpreter does once exceptions are compiled
+        if rhs.get_zero_extended_constant() == Some(0) {
+            return Err("division by zero");
+        }
+        let is_zero = self.builder.build_int_compare(IntPredicate::EQ, rhs, ty.const_zero(), "tmpiszero");
+        self.build_trap_if(is_zero);
+
+        // Overflowing divisions are undefined behavior as well, so -1 is handled separately
+        let minus_one = ty.const_all_ones();
+        let is_minus_one = self.builder.build_int_compare(IntPredicate::EQ, rhs, minus_one, "tmpisneg");
+        let divisor = self.builder.build_select(is_minus_one, ty.const_int(1, false), rhs, "tmpdivisor")
+            .into_int_value();
+
+        let quotient = self.builder.build_int_signed_div(lhs, divisor, "tmpdiv");
+        let quotient = self.builder.build_select(
+            is_minus_one,
+            self.builder.build_int_neg(lhs, "tmpneg"),
+            quotient,
+            "tmpdiv",
+        ).into_int_value();
+        let remainder = self.builder.build_int_signed_rem(lhs, divisor, "tmprem");
+
+        Ok((quotient, remainder))
+    }
+
+    /// Builds an integer raised to an integer power by repeated squaring, wrapping on overflow.
+    /// Results of negative exponents are truncated towards zero, e.g. `2 ** -1` is `0`, and
+    /// raising zero to a negative power traps like dividing by zero does.
+    fn build_int_pow(&self, base: IntValue<'ctx>, exponent: IntValue<'ctx>) -> Result<IntValue<'ctx>, &'static str> {
+        let ty = base.get_type();
+        let (zero, one) = (ty.const_zero(), ty.const_int(1, false));
+
+        let is_negative = self.builder.build_int_compare(IntPredicate::SLT, exponent, zero, "tmpisneg");
+        let is_zero = self.builder.build_int_compare(IntPredicate::EQ, base, zero, "tmpiszero");
+
+        // TODO: throw ZeroDivisionError like the interpreter does once exceptions are compiled
+        if let (Some(0), Some(exponent)) = (base.get_zero_extended_constant(), exponent.get_sign_extended_constant()) {
+            if exponent < 0 {
+                return Err("zero raised to a negative power");
+            }
+        }
+        self.build_trap_if(self.builder.build_and(is_negative, is_zero, "tmpinvalid"));
+
+        let function = self.fn_value();
+        let entry = self.builder.get_insert_block().unwrap();
+        let header = self.ctx.append_basic_block(function, "powloop");
+        let body = self.ctx.append_basic_block(function, "powbody");
+        let exit = self.ctx.append_basic_block(function, "powexit");
+        self.builder.build_unconditional_branch(header);
+
+        self.builder.position_at_end(header);
+        let result = self.builder.build_phi(ty, "tmpresult");
+        let square = self.builder.build_phi(ty, "tmpsquare");
+        let remaining = self.builder.build_phi(ty, "tmpremaining");
+        let (result_value, square_value, remaining_value) = (
+            result.as_basic_value().into_int_value(),
+            square.as_basic_value().into_int_value(),
+            remaining.as_basic_value().into_int_value(),
+        );
+
+        let done = self.builder.build_int_compare(IntPredicate::SLE, remaining_value, zero, "tmpdone");
+        self.builder.build_conditional_branch(done, exit, body);
+
+        self.builder.position_at_end(body);
+        let odd = self.builder.build_int_compare(
+            IntPredicate::NE,
+            self.builder.build_and(remaining_value, one, "tmpbit"),
+            zero,
+            "tmpodd",
+        );
+        let next_result = self.builder.build_select(
+            odd,
+            self.builder.build_int_mul(result_value, square_value, "tmpmul"),
+            result_value,
+            "tmpresult",
+        ).into_int_value();
+        let next_square = self.builder.build_int_mul(square_value, square_value, "tmpsquare");
+        let next_remaining = self.builder.build_right_shift(remaining_value, one, true, "tmpremaining");
+        self.builder.build_unconditional_branch(header);
+
+        result.add_incoming(&[(&one, entry), (&next_result, body)]);
+        square.add_incoming(&[(&base, entry), (&next_square, body)]);
+        remaining.add_incoming(&[(&exponent, entry), (&next_remaining, body)]);
+
+        // Only 1 and -1 raised to a negative power are not truncated to zero
+        self.builder.position_at_end(exit);
+        let minus_one = ty.const_all_ones();
+        let is_odd = self.builder.build_int_compare(
+            IntPredicate::NE,
+            self.builder.build_and(exponent, one, "tmpbit"),
+            zero,
+            "tmpodd",
+        );
+        let minus_one_pow = self.builder.build_select(is_odd, minus_one, one, "tmppow").into_int_value();
+        let truncated = self.builder.build_select(
+            self.builder.build_int_compare(IntPredicate::EQ, base, one, "tmpisone"),
+            one,
+            self.builder.build_select(
+                self.builder.build_int_compare(IntPredicate::EQ, base, minus_one, "tmpisneg"),
+                minus_one_pow,
+                zero,
+                "tmppow",
+            ).into_int_value(),
+            "tmppow",
+        ).into_int_value();
+
+        Ok(self.builder.build_select(is_negative, truncated, result_value, "tmppow").into_int_value())
+    }
+
+    /// Traps if the condition holds, continuing to build in a new block otherwise.
+    fn build_trap_if(&self, condition: IntValue<'ctx>) {
+        let function = self.fn_value();
+        let trap_block = self.ctx.append_basic_block(function, "trap");
+        let cont_block = self.ctx.append_basic_block(function, "cont");
+        self.builder.build_conditional_branch(condition, trap_block, cont_block);
+
+        self.builder.position_at_end(trap_block);
+        let trap = self.module.get_function("llvm.trap").unwrap_or_else(|| {
+            self.module.add_function("llvm.trap", self.ctx.void_type().fn_type(&[], false), None)
+        });
+        self.builder.build_call(trap, &[], "");
+        self.builder.build_unreachable();
+
+        self.builder.position_at_end(cont_block);
+    }
+
+    /// Builds whether the result of a truncating division should be floored, which is when it
+    /// has a remainder with a different sign than the divisor.
+    fn build_needs_floor(&self, remainder: IntValue<'ctx>, rhs: IntValue<'ctx>) -> IntValue<'ctx> {
+        let zero = rhs.get_type().const_zero();
+
+        let inexact = self.builder.build_int_compare(IntPredicate::NE, remainder, zero, "tmpinexact");
+        let signs = self.builder.build_xor(
+            self.builder.build_int_compare(IntPredicate::SLT, remainder, zero, "tmpneg"),
+            self.builder.build_int_compare(IntPredicate::SLT, rhs, zero, "tmpneg"),
+            "tmpsigns",
+        );
+
+        self.builder.build_and(inexact, signs, "tmpfloor")
+    }
+
+    fn build_float_op(
+        &self,
+        op: Operator,
+        lhs: FloatValue<'ctx>,
+        rhs: FloatValue<'ctx>,
+    ) -> Result<BasicValueEnum<'ctx>, &'static str> {
+        Ok(match op {
+            Operator::Add => self.builder.build_float_add(lhs, rhs, "tmpadd").as_basic_value_enum(),
+            Operator::Sub => self.builder.build_float_sub(lhs, rhs, "tmpsub").as_basic_value_enum(),
+            Operator::Mul => self.builder.build_float_mul(lhs, rhs, "tmpmul").as_basic_value_enum(),
+            Operator::Div | Operator::TrueDiv => {
+                self.builder.build_float_div(lhs, rhs, "tmpdiv").as_basic_value_enum()
+            }
+            Operator::Mod => {
+                let zero = rhs.get_type().const_zero();
+                let remainder = self.builder.build_float_rem(lhs, rhs, "tmprem");
+                let floored = self.builder.build_float_add(remainder, rhs, "tmpfloor");
+
+                let inexact = self.builder.build_float_compare(FloatPredicate::UNE, remainder, zero, "tmpinexact");
+                let signs = self.builder.build_xor(
+                    self.builder.build_float_compare(FloatPredicate::OLT, remainder, zero, "tmpneg"),
+                    self.builder.build_float_compare(FloatPredicate::OLT, rhs, zero, "tmpneg"),
+                    "tmpsigns",
+                );
+
+                self.builder.build_select(
+                    self.builder.build_and(inexact, signs, "tmpfloor"),
+                    floored,
+                    remainder,
+                    "tmpmod",
+                )
+            }
+            Operator::Pow => self.build_pow(lhs, rhs).as_basic_value_enum(),
+            Operator::Eq
+            | Operator::Ne
+            | Operator::Lt
+            | Operator::Le
+            | Operator::Gt
+            | Operator::Ge => {
+                let predicate = match op {
+                    Operator::Eq => FloatPredicate::OEQ,
+                    Operator::Ne => FloatPredicate::UNE,
+                    Operator::Lt => FloatPredicate::OLT,
+                    Operator::Le => FloatPredicate::OLE,
+                    Operator::Gt => FloatPredicate::OGT,
+                    _ => FloatPredicate::OGE,
+                };
+
+                self.builder.build_float_compare(predicate, lhs, rhs, "tmpcmp")
+                    .as_basic_value_enum()
+            }
+            _ => return Err("unsupported operator for floats"),
+        })
+    }
+
+    fn build_bool_op(
+        &self,
+        op: Operator,
+        lhs: IntValue<'ctx>,
+        rhs: IntValue<'ctx>,
+    ) -> Result<BasicValueEnum<'ctx>, &'static str> {
+        Ok(match op {
+            Operator::BitOr => self.builder.build_or(lhs, rhs, "tmpor").as_basic_value_enum(),
+            Operator::BitXor => self.builder.build_xor(lhs, rhs, "tmpxor").as_basic_value_enum(),
+            Operator::BitAnd => self.builder.build_and(lhs, rhs, "tmpand").as_basic_value_enum(),
+            Operator::Eq => self.builder.build_int_compare(IntPredicate::EQ, lhs, rhs, "tmpcmp")
+                .as_basic_value_enum(),
+            Operator::Ne => self.builder.build_int_compare(IntPredicate::NE, lhs, rhs, "tmpcmp")
+                .as_basic_value_enum(),
+            _ => return Err("unsupported operator for booleans"),
+        })
+    }
+
+    fn compare_strings(&self, op: Operator, lhs: &str, rhs: &str) -> Result<BasicValueEnum<'ctx>, &'static str> {
+        let result = match op {
+            Operator::Eq => lhs == rhs,
+            Operator::Ne => lhs != rhs,
+            Operator::Lt => lhs < rhs,
+            Operator::Le => lhs <= rhs,
+            Operator::Gt => lhs > rhs,
+            Operator::Ge => lhs >= rhs,
+            _ => return Err("unsupported operator for strings"),
+        };
+
+        Ok(self.ctx.bool_type().const_int(u64::from(result), false).as_basic_value_enum())
+    }
+
+    /// Builds whether the value is truthy, which for numbers is whether they are not zero.
+    fn build_truthy(&self, value: BasicValueEnum<'ctx>) -> Result<IntValue<'ctx>, &'static str> {
+        Ok(match value {
+            BasicValueEnum::IntValue(i) => self.builder.build_int_compare(
+                IntPredicate::NE,
+                i,
+                i.get_type().const_zero(),
+                "tmptruthy",
+            ),
+            BasicValueEnum::FloatValue(f) => self.builder.build_float_compare(
+                FloatPredicate::UNE,
+                f,
+                f.get_type().const_zero(),
+                "tmptruthy",
+            ),
+            _ => return Err("only numbers and booleans can be tested for truthiness"),
+        })
+    }
+
+    /// Builds the conversion of a float to an integer, truncating towards zero like the
+    /// interpreter. Infinite and NaN floats trap where the interpreter throws `ValueError`, and
+    /// are rejected while compiling if the float is a constant. Floats out of range saturate.
+    fn build_float_to_int(&self, f: FloatValue<'ctx>) -> Result<IntValue<'ctx>, &'static str> {
+        // TODO: throw ValueError like the interpreter does once exceptions are compiled
+        if let Some((value, _)) = f.get_constant() {
+            if !value.is_finite() {
+                return Err("cannot cast an infinite or NaN float to int");
+            }
+        }
+
+        // Subtracting a float from itself only gives NaN if it is infinite or NaN
+        let difference = self.builder.build_float_sub(f, f, "tmpdiff");
+        let is_finite = self.builder.build_float_compare(
+            FloatPredicate::OEQ,
+            difference,
+            f.get_type().const_zero(),
+            "tmpfinite",
+        );
+        self.build_trap_if(self.builder.build_not(is_finite, "tmpinfinite"));
+
+        // Unlike fptosi, the saturating intrinsic is defined for floats out of range
+        let i64_type = self.ctx.i64_type();
+        let name = "llvm.fptosi.sat.i64.f64";
+        let fptosi = self.module.get_function(name).unwrap_or_else(|| {
+            self.module.add_function(
+                name,
+                i64_type.fn_type(&[self.ctx.f64_type().into()], false),
+                None,
+            )
+        });
+
+        Ok(self.builder.build_call(fptosi, &[f.into()], "tmpcast")
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_int_value())
+    }
+
+    fn build_int_to_float(&self, i: IntValue<'ctx>) -> FloatValue<'ctx> {
+        self.builder.build_signed_int_to_float(i, self.ctx.f64_type(), "tmpintfloatconv")
+    }
+
+    fn build_pow(&self, lhs: FloatValue<'ctx>, rhs: FloatValue<'ctx>) -> FloatValue<'ctx> {
+        let f64_type = self.ctx.f64_type();
+        let pow = self.module.get_function("llvm.pow.f64").unwrap_or_else(|| {
+            self.module.add_function(
+                "llvm.pow.f64",
+                f64_type.fn_type(&[f64_type.into(), f64_type.into()], false),
+                None,
+            )
+        });
+
+        self.builder.build_call(pow, &[lhs.into(), rhs.into()], "tmppow")
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_float_value()
+    }
+
     pub fn compile_node(&mut self, node: &Spanned<Node>) -> Result<(), &'static str> {
         let (node, span) = node.node_span();
 
@@ -239,8 +716,12 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
     pub fn prepare(&mut self) {
         let fn_type = self.ctx.i32_type().fn_type(&[], false);
 
+        self.prepare_function(Self::ENTRYPOINT_FN_NAME, fn_type);
+    }
+
+    fn prepare_function(&mut self, name: &str, fn_type: FunctionType<'ctx>) {
         self.fn_val = Some(
-            self.module.add_function(Self::ENTRYPOINT_FN_NAME, fn_type, None)
+            self.module.add_function(name, fn_type, None)
         );
 
         let entry = self.ctx.append_basic_block(self.fn_value(), "entry");
@@ -262,4 +743,32 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
     }
 }
 
+/// Whether the integer is a boolean, which are represented as integers of a single bit.
+fn is_bool(i: IntValue) -> bool {
+    i.get_type().get_bit_width() == 1
+}
+
+/// Evaluates a string expression made only of literals, e.g. `"ab" + "c" * 2 + 1::string`.
+fn fold_string(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::String(s) => Some(s.clone()),
+        Expr::BinaryExpr { operator, lhs, rhs } => match (operator.node(), rhs.node()) {
+            (Operator::Add, _) => Some(fold_string(lhs.node())? + &fold_string(rhs.node())?),
+            (Operator::Mul, Expr::Integer(n)) => {
+                Some(fold_string(lhs.node())?.repeat(usize::try_from(*n).ok()?))
+            }
+            _ => None,
+        },
+        Expr::Cast(value, ty) if matches!(ty.node(), Expr::Ident(name) if name == "string") => {
+            match value.node() {
+                Expr::Integer(i) => Some(i.to_string()),
+                Expr::Float(f) => Some(f.parse::<f64>().ok()?.to_string()),
+                Expr::Bool(b) => Some(b.to_string()),
+                value => fold_string(value),
+            }
+        }
+        _ => None,
+    }
+}
+
 pub type EntrypointFunction = unsafe extern "C" fn() -> i32;
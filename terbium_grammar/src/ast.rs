@@ -1,6 +1,10 @@
-use super::token::{get_lexer, Bracket, Keyword, Literal, Operator, StringLiteral, Token};
+use super::token::{
+    get_lexer, split_interpolated, Bracket, Keyword, Literal, Operator, Segment, StringLiteral,
+    Token,
+};
 use super::{Error, Source, Span, Spanned};
 
+use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::path::Path;
 
 use chumsky::prelude::*;
@@ -12,15 +16,28 @@ pub type SpannedNode = Spanned<Node>;
 pub type SpannedBody = Spanned<Body>;
 pub type SpannedTarget = Spanned<Target>;
 pub type SpannedParam = Spanned<Param>;
+pub type SpannedGenericParam = Spanned<GenericParam>;
 pub type SpannedTypeExpr = Spanned<TypeExpr>;
+pub type SpannedField = Spanned<Field>;
+pub type SpannedMethod = Spanned<Method>;
+pub type SpannedCast = Spanned<Cast>;
+pub type SpannedPattern = Spanned<Pattern>;
+pub type SpannedMatchArm = Spanned<MatchArm>;
+pub type SpannedCatchClause = Spanned<CatchClause>;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Expr {
     Integer(u128),
     Float(String), // See token.rs for why this is a String
     String(String),
+    /// Given $"{a} + {b:>4} = {a + b:?}", the literal and embedded expression parts in order.
+    Interpolated(Vec<InterpolatedPart>),
     Bool(bool),
+    Null,
     Ident(String),
+    /// The `$` expression, which refers to the instance of the enclosing class.
+    /// `$field` is parsed as an `Attr` of `SelfRef`.
+    SelfRef,
     Array(Vec<SpannedExpr>),
     Cast(SpannedExpr, SpannedExpr),
     UnaryExpr {
@@ -32,11 +49,53 @@ pub enum Expr {
         lhs: SpannedExpr,
         rhs: SpannedExpr,
     },
-    Attr(SpannedExpr, String),
+    /// `optional` is whether the attribute was accessed with `?.`, in which case the rest of
+    /// the chain is skipped and evaluates to null if the subject is null, e.g. `a?.b.c`.
+    Attr {
+        subject: SpannedExpr,
+        attr: String,
+        optional: bool,
+    },
+    /// `optional` is whether the function was called with `?.`, e.g. `f?.(x)`.
+    ///
+    /// `args` may contain `Splat`s, and `KeywordSplat`s after every positional argument.
     Call {
         value: SpannedExpr,
         args: Vec<SpannedExpr>,
         kwargs: Vec<(String, SpannedExpr)>,
+        optional: bool,
+    },
+    /// e.g. the `*args` of `f(*args)`, which passes each item of `args` as a positional
+    /// argument. Only valid as an argument of a call.
+    Splat(SpannedExpr),
+    /// e.g. the `**kwargs` of `f(**kwargs)`, which passes each `[name, value]` pair of `kwargs`
+    /// as a keyword argument. Only valid as an argument of a call.
+    KeywordSplat(SpannedExpr),
+    // e.g. x.y!, which throws a NullError if x.y is null
+    NonNull(SpannedExpr),
+    // e.g. a[i], a?.[i]
+    Index {
+        subject: SpannedExpr,
+        index: SpannedExpr,
+        optional: bool,
+    },
+    /// e.g. `a[1..3]`. Either end may be omitted, e.g. `a[..-1]` or `a[2..]`.
+    Slice {
+        subject: SpannedExpr,
+        start: Option<SpannedExpr>,
+        end: Option<SpannedExpr>,
+        optional: bool,
+    },
+    // e.g. x is int, x is not string | null
+    Is {
+        value: SpannedExpr,
+        ty: SpannedTypeExpr,
+        negated: bool,
+    },
+    // e.g. item in items, which calls `op contains` of items if it defines one
+    In {
+        item: SpannedExpr,
+        container: SpannedExpr,
     },
     If {
         condition: SpannedExpr,
@@ -44,10 +103,140 @@ pub enum Expr {
         else_if_bodies: Vec<(SpannedExpr, SpannedBody)>,
         else_body: Option<SpannedBody>,
     },
+    // e.g. outer@ while x { ... } is labelled "outer" so that nested loops can target it
     While {
+        label: Option<String>,
         condition: SpannedExpr,
         body: Vec<SpannedNode>,
     },
+    // e.g. for [i, item] in items.enumerate() { ... }
+    For {
+        label: Option<String>,
+        target: SpannedTarget,
+        iterable: SpannedExpr,
+        body: Vec<SpannedNode>,
+    },
+    Match {
+        subject: SpannedExpr,
+        arms: Vec<SpannedMatchArm>,
+        else_body: Option<SpannedBody>,
+    },
+    // e.g. (a, b: int) -> int => a + b, or item => item * 2
+    Func {
+        params: Vec<SpannedParam>,
+        body: Vec<SpannedNode>,
+        return_last: bool,
+        return_ty: SpannedTypeExpr,
+    },
+    // e.g. try { f() } catch IOError as e { g(e) } finally { h() }
+    Try {
+        body: SpannedBody,
+        catches: Vec<SpannedCatchClause>,
+        finally: Option<SpannedBody>,
+    },
+    // e.g. with File("a.txt", read = true) as f, File("b.txt", write = true) as g { ... }
+    With {
+        managers: Vec<(SpannedExpr, Option<String>)>,
+        body: SpannedBody,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum InterpolatedPart {
+    Literal(String),
+    Value(SpannedExpr, FormatSpec),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// How to format a value embedded into an interpolated string, written after a colon
+/// as `[[fill]align][width][.precision][?]`, e.g. `{x:*^10.2}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatSpec {
+    pub fill: char,
+    /// Defaults to right alignment for numbers and left alignment for anything else.
+    pub align: Option<Align>,
+    pub width: Option<usize>,
+    /// The number of digits after the decimal point of floats, or the maximum length of strings.
+    pub precision: Option<usize>,
+    /// Whether to use the representation of the value, as written by `{x:?}`.
+    pub repr: bool,
+}
+
+impl FormatSpec {
+    /// Whether the value is formatted the same as when it is converted to a string.
+    #[must_use]
+    pub fn is_plain(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+impl Default for FormatSpec {
+    fn default() -> Self {
+        Self {
+            fill: ' ',
+            align: None,
+            width: None,
+            precision: None,
+            repr: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pattern {
+    /// Given 1, -2.5, "one" or true, this becomes Literal(Integer(1)), etc.
+    ///
+    /// Matches if the subject is equal to the literal.
+    Literal(SpannedExpr),
+    /// Given x, this becomes Ident("x").
+    ///
+    /// Always matches and binds the subject to the identifier.
+    /// The identifier `_` matches without binding anything.
+    Ident(String),
+    /// Given [a, 0], this becomes Array([Ident(a), Literal(Integer(0))]).
+    ///
+    /// Matches an array of exactly as many elements, each matching its pattern.
+    Array(Vec<SpannedPattern>),
+    /// Given 1 | 2, this becomes Alt([Literal(Integer(1)), Literal(Integer(2))]).
+    ///
+    /// Matches if any of the alternatives match, tried from left to right.
+    Alt(Vec<SpannedPattern>),
+}
+
+impl Pattern {
+    /// Whether this pattern matches any subject, e.g. a lone identifier.
+    #[must_use]
+    pub fn is_irrefutable(&self) -> bool {
+        match self {
+            Self::Ident(_) => true,
+            Self::Alt(alts) => alts.iter().any(|p| p.is_irrefutable()),
+            _ => false,
+        }
+    }
+}
+
+/// A `catch` clause of a try expression, e.g. `catch IOError | ValueError as e { ... }`
+///
+/// A clause without a type catches every error.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CatchClause {
+    pub ty: Option<SpannedTypeExpr>,
+    pub binding: Option<String>,
+    pub body: SpannedBody,
+}
+
+/// An arm of a match expression, e.g. `[x, y] if x > y -> x`
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchArm {
+    pub pattern: SpannedPattern,
+    pub guard: Option<SpannedExpr>,
+    pub body: SpannedBody,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -82,11 +271,12 @@ pub enum TypeExpr {
     /// Only types that are not compatible with T will be compatible with !T.
     Not(SpannedTypeExpr),
     /// Given T[], this becomes Array(Ident(T), None).
-    /// Given T[n], where n is u32, this becomes Array(Ident(T), Some(n)).
+    /// Given T[n], this becomes Array(Ident(T), Some(n)), where n is either a u32 literal
+    /// or the name of a constant.
     ///
     /// This represents an Array of T, and if n is specified, an array with
     /// such capacity.
-    Array(SpannedTypeExpr, Option<u32>),
+    Array(SpannedTypeExpr, Option<ArrayLength>),
     /// Given [A, B], this becomes Tuple([Ident(A), Ident(B)]).
     ///
     /// A tuple is an array with an exact number of elements but with
@@ -105,6 +295,22 @@ pub enum TypeExpr {
     /// The `any` type. It is compatible with any other type and any other type is
     /// compatible with it, including `null`.
     Any,
+    /// The `$` type, which refers to the type of the enclosing class.
+    SelfRef,
+    /// Given (A, B) -> R, this becomes Func(Some([Ident(A), Ident(B)]), Ident(R)).
+    /// Given (...) -> R, this becomes Func(None, Ident(R)).
+    ///
+    /// The type of a function taking arguments of the given types and returning R.
+    /// Without a parameter list, the function may take any arguments.
+    Func(Option<Vec<SpannedTypeExpr>>, SpannedTypeExpr),
+}
+
+/// The length of a fixed size array type, e.g. the `4` of `int[4]` or the `n` of `int[n]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArrayLength {
+    Literal(u32),
+    /// The name of a constant, e.g. a static constant field of the enclosing class.
+    Const(String),
 }
 
 pub trait ParseInterface {
@@ -154,24 +360,319 @@ pub enum Target {
     Ident(String),
     Array(Vec<SpannedTarget>),
     Attr(SpannedTarget, String), // Invalid as a parameter in a declaration statement or parameter.
+    Index(SpannedTarget, SpannedExpr), // Only valid in an assignment, e.g. a[i] = value;
+    Rest(SpannedTarget),         // Only valid as an item of an Array target, e.g. [head, ...tail]
+    SelfRef, // Only valid as the subject of an Attr target, e.g. $field = value;
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ParamKind {
+    /// A parameter bound to a single argument, e.g. `a: int = 1`
+    Normal,
+    /// Collects every remaining positional argument into an array, e.g. `*args`
+    Variadic,
+    /// Collects every keyword argument which matched no other parameter, e.g. `**kwargs`
+    KeywordVariadic,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Param {
-    target: SpannedTarget,
-    ty: SpannedTypeExpr,
-    default: Option<SpannedExpr>,
+    pub target: SpannedTarget,
+    /// `TypeExpr::Auto` if the type of the parameter is inferred.
+    pub ty: SpannedTypeExpr,
+    pub default: Option<SpannedExpr>,
+    pub kind: ParamKind,
+    /// Whether the parameter can only be given as a keyword argument, which is the case for
+    /// parameters declared after `*` or a variadic parameter.
+    pub keyword_only: bool,
+    /// Whether the parameter was declared with `?`, in which case it is null when omitted.
+    pub optional: bool,
+}
+
+impl Param {
+    /// The name keyword arguments are matched against, or `None` if the target destructures.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        match self.target.node() {
+            Target::Ident(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn spec(&self) -> ParamSpec {
+        ParamSpec {
+            name: self.name().unwrap_or_default().to_string(),
+            kind: self.kind,
+            keyword_only: self.keyword_only,
+            default: self.default.is_some(),
+            optional: self.optional,
+        }
+    }
+}
+
+/// What matters about a parameter when binding the arguments of a call to it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParamSpec {
+    /// Empty if the parameter can not be given as a keyword argument.
+    pub name: String,
+    pub kind: ParamKind,
+    pub keyword_only: bool,
+    pub default: bool,
+    pub optional: bool,
+}
+
+impl ParamSpec {
+    /// Whether every call must give an argument for this parameter.
+    #[must_use]
+    pub fn is_required(&self) -> bool {
+        self.kind == ParamKind::Normal && !self.default && !self.optional
+    }
+}
+
+/// Why the arguments of a call could not be bound to the parameters of a function.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArgumentError {
+    TooManyArguments { expected: usize, given: usize },
+    MissingArgument(String),
+    UnknownKeyword(String),
+    DuplicateArgument(String),
+}
+
+impl Display for ArgumentError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::TooManyArguments { expected, given } => write!(
+                f,
+                "expected at most {} positional argument{}, but {} were given",
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                given,
+            ),
+            Self::MissingArgument(name) => write!(f, "missing argument for parameter `{}`", name),
+            Self::UnknownKeyword(name) => write!(f, "unknown keyword argument `{}`", name),
+            Self::DuplicateArgument(name) => {
+                write!(f, "multiple arguments given for parameter `{}`", name)
+            }
+        }
+    }
+}
+
+/// Binds the arguments of a call to the parameters of a function, given the amount of
+/// positional arguments and the names of the keyword arguments in order.
+///
+/// Returns the indices of the arguments bound to each parameter, where keyword arguments
+/// are indexed after every positional argument. A normal parameter without an argument
+/// takes its default value.
+///
+/// # Errors
+/// * There are too many positional arguments, or a required parameter was not given one.
+/// * A keyword argument matches no parameter, or a parameter was given more than one argument.
+pub fn bind_args(
+    params: &[ParamSpec],
+    positional: usize,
+    keywords: &[impl AsRef<str>],
+) -> Result<Vec<Vec<usize>>, ArgumentError> {
+    let mut bound = vec![Vec::new(); params.len()];
+    let mut next = 0;
+
+    for (i, param) in params.iter().enumerate() {
+        match param.kind {
+            ParamKind::Normal if !param.keyword_only && next < positional => {
+                bound[i].push(next);
+                next += 1;
+            }
+            ParamKind::Variadic => {
+                bound[i].extend(next..positional);
+                next = positional;
+            }
+            _ => (),
+        }
+    }
+
+    if next < positional {
+        return Err(ArgumentError::TooManyArguments {
+            expected: next,
+            given: positional,
+        });
+    }
+
+    for (i, keyword) in keywords.iter().enumerate() {
+        let keyword = keyword.as_ref();
+        let index = positional + i;
+
+        if let Some(p) = params
+            .iter()
+            .position(|p| p.kind == ParamKind::Normal && p.name == keyword)
+        {
+            if !bound[p].is_empty() {
+                return Err(ArgumentError::DuplicateArgument(keyword.to_string()));
+            }
+            bound[p].push(index);
+        } else if let Some(p) = params
+            .iter()
+            .position(|p| p.kind == ParamKind::KeywordVariadic)
+        {
+            bound[p].push(index);
+        } else {
+            return Err(ArgumentError::UnknownKeyword(keyword.to_string()));
+        }
+    }
+
+    if let Some((param, _)) = params
+        .iter()
+        .zip(&bound)
+        .find(|(param, bound)| param.is_required() && bound.is_empty())
+    {
+        return Err(ArgumentError::MissingArgument(param.name.clone()));
+    }
+
+    Ok(bound)
+}
+
+/// A generic type parameter of a function or class, e.g. `T` or `I: Iterator<T>`
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenericParam {
+    pub name: String,
+    /// Types the type argument must be compatible with, declared either in the list of generic
+    /// parameters or in a `where` clause.
+    pub bounds: Vec<SpannedTypeExpr>,
+    /// Operators the type argument must define along with their types, declared in a `where`
+    /// clause, e.g. `where T @ add: (T) -> T`.
+    pub ops: Vec<(String, SpannedTypeExpr)>,
+}
+
+/// The path of a required module, e.g. `.io` or `std.io`.
+///
+/// A relative path is resolved from the package of the module requiring it,
+/// in which case `.` on its own refers to that package.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RequirePath {
+    pub relative: bool,
+    pub segments: Vec<String>,
+}
+
+impl Display for RequirePath {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        if self.relative {
+            f.write_str(".")?;
+        }
+
+        f.write_str(&self.segments.join("."))
+    }
+}
+
+/// A name brought into scope by a require statement, e.g. `repr as _repr`
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequireItem {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+impl RequireItem {
+    /// The name this item is bound to in the requiring module.
+    #[must_use]
+    pub fn binding(&self) -> &str {
+        self.alias.as_ref().unwrap_or(&self.name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RequireItems {
+    /// Given require a, b as c from x, this becomes Named([a, b as c])
+    Named(Vec<RequireItem>),
+    /// Given require * from x, this becomes Glob.
+    ///
+    /// Binds every name exported by the module.
+    Glob,
+}
+
+/// A field declared in the body of a class, e.g. `private inner: InnerFile;`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub ty: SpannedTypeExpr,
+    pub default: Option<SpannedExpr>,
+    pub private: bool,
+    pub r#static: bool,
+    pub r#const: bool,
+}
+
+/// A method declared in the body of a class or trait.
+///
+/// Methods declared with `op` are operator overloads (e.g. `op constructor`),
+/// while methods declared with `func` (or with no keyword at all) are regular methods.
+///
+/// `body` is `None` for a required trait method, which is a bare signature ending in `;`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Method {
+    pub name: String,
+    pub generics: Vec<SpannedGenericParam>,
+    /// Bounds a `where` clause puts on the type parameters of the enclosing class or trait,
+    /// which only have to hold for this method, e.g. `sum() -> T where T @ add: (T) -> T`.
+    pub constraints: Vec<SpannedGenericParam>,
+    pub params: Vec<SpannedParam>,
+    pub body: Option<Vec<SpannedNode>>,
+    pub return_last: bool,
+    pub return_ty: SpannedTypeExpr,
+    pub throws: Vec<SpannedTypeExpr>,
+    pub private: bool,
+    pub r#static: bool,
+    pub op: bool,
+}
+
+/// A conversion declared in the body of a class or trait, e.g. `cast int { $inner }`.
+///
+/// With `from`, this instead converts an object of `ty` into the enclosing type, e.g.
+/// `cast from int(value) { $(value) }`. `param` is bound to the object being converted
+/// in that case, or to the type converted to otherwise.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cast {
+    pub ty: SpannedTypeExpr,
+    pub from: bool,
+    pub param: Option<String>,
+    pub body: Vec<SpannedNode>,
+    pub return_last: bool,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Node {
     Module(Vec<SpannedNode>),
+    // e.g. func first<T>(items: T[]) -> T throws IndexError where T: Display { items[0] }
     Func {
         name: String,
+        generics: Vec<SpannedGenericParam>,
         params: Vec<SpannedParam>,
         body: Vec<SpannedNode>,
         return_last: bool,
         return_ty: SpannedTypeExpr,
+        throws: Vec<SpannedTypeExpr>,
+    },
+    // e.g. class Foo(Bar) { private x: int; op constructor() {} }
+    Class {
+        name: String,
+        generics: Vec<SpannedGenericParam>,
+        bases: Vec<SpannedTypeExpr>,
+        fields: Vec<SpannedField>,
+        methods: Vec<SpannedMethod>,
+        casts: Vec<SpannedCast>,
+        /// Operators implemented in terms of others, e.g. `default op iadd;` uses `op add`
+        default_ops: Vec<Spanned<String>>,
+    },
+    // e.g. trait Iterator<T> { op next() -> T; skip(n: int) -> $ { ... } }
+    Trait {
+        name: String,
+        generics: Vec<SpannedGenericParam>,
+        fields: Vec<SpannedField>,
+        methods: Vec<SpannedMethod>,
+        casts: Vec<SpannedCast>,
+        default_ops: Vec<Spanned<String>>,
+    },
+    // e.g. extend Joinable<string, string> for Iterator<string> { join(sep: string) -> string { ... } }
+    Extend {
+        r#trait: SpannedTypeExpr,
+        target: SpannedTypeExpr,
+        methods: Vec<SpannedMethod>,
     },
     Expr(SpannedExpr),
     // e.g. x.y = z becomes Assign { target: Attr(Ident("x"), "y"), value: Ident("z"), .. }
@@ -186,8 +687,28 @@ pub enum Node {
         targets: Vec<SpannedTarget>,
         value: SpannedExpr,
     },
+    // e.g. a[i] += 1; evaluates a and i only once
+    CompoundAssign {
+        target: SpannedTarget,
+        operator: Spanned<Operator>,
+        value: SpannedExpr,
+    },
     Return(Option<SpannedExpr>),
-    Require(Vec<String>), // TODO: require y from x; require * from x
+    // e.g. throw IOError("file not found");
+    Throw(SpannedExpr),
+    // e.g. break@outer item; exits the loop labelled "outer" with item as its value
+    Break {
+        label: Option<String>,
+        value: Option<SpannedExpr>,
+    },
+    // e.g. continue@outer;
+    Continue(Option<String>),
+    // e.g. require print, println from .io;
+    Require {
+        items: RequireItems,
+        // None when the items are modules themselves, e.g. require std;
+        from: Option<RequirePath>,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -269,6 +790,149 @@ impl ParseInterface for Body {
 pub trait CommonParser<T> = Parser<Token, T, Error = Error> + Clone;
 pub type RecursiveParser<'a, T> = Recursive<'a, Token, T, Error>;
 
+/// A member declared in the body of a class or trait.
+enum Member {
+    Field(SpannedField),
+    Method(SpannedMethod),
+    Cast(SpannedCast),
+    DefaultOp(Spanned<String>),
+}
+
+/// The fields, methods, casts and default operators of a class or trait, in that order.
+type Members = (
+    Vec<SpannedField>,
+    Vec<SpannedMethod>,
+    Vec<SpannedCast>,
+    Vec<Spanned<String>>,
+);
+
+fn split_members(members: Vec<Member>) -> Members {
+    let mut split: Members = Default::default();
+
+    for member in members {
+        match member {
+            Member::Field(field) => split.0.push(field),
+            Member::Method(method) => split.1.push(method),
+            Member::Cast(cast) => split.2.push(cast),
+            Member::DefaultOp(op) => split.3.push(op),
+        }
+    }
+
+    split
+}
+
+/// A single link of a postfix chain, e.g. the `?.b` in `a?.b`.
+#[derive(Clone)]
+enum Postfix {
+    Attr(String, bool),
+    Call(Vec<SpannedExpr>, Vec<(String, SpannedExpr)>, bool),
+    Index(SpannedExpr, bool),
+    Slice(Option<SpannedExpr>, Option<SpannedExpr>, bool),
+    NonNull,
+}
+
+/// The right-hand side of a type test or membership test, e.g. `is int` or `in items`.
+#[derive(Clone)]
+enum Test {
+    Is(SpannedTypeExpr, bool),
+    In(SpannedExpr),
+}
+
+/// A type along with whether it ended in a `>>` which also closed the generic argument list
+/// enclosing it. The lexer can not tell `>>` closing nested generics from a right shift.
+type OwingTypeExpr = (SpannedTypeExpr, bool);
+
+/// Closes a generic argument list, returning whether the enclosing list was closed as well.
+fn generic_close() -> impl Parser<Token, bool, Error = Error> + Clone {
+    just(Token::Operator(Operator::Gt))
+        .to(false)
+        .or(just(Token::Operator(Operator::BitRShift)).to(true))
+}
+
+/// Consumes nothing, producing the value.
+fn consumed<O: Clone>(value: O) -> impl Parser<Token, O, Error = Error> + Clone {
+    Parser::<Token, ()>::to(empty(), value)
+}
+
+/// Rejects a type ending in a `>>` which closes a generic argument list that was never opened.
+#[allow(clippy::result_large_err)] // Parser errors are returned unboxed throughout chumsky
+fn not_owing((ty, owing): OwingTypeExpr, span: Span) -> Result<SpannedTypeExpr, Error> {
+    if owing {
+        Err(Error::custom(
+            span,
+            "unexpected `>>` closing a generic argument list",
+        ))
+    } else {
+        Ok(ty)
+    }
+}
+
+/// Folds the operands of a binary type operator, of which only the last may end in a `>>`
+/// closing the enclosing generic argument list.
+fn fold_owing(
+    lhs: OwingTypeExpr,
+    rhs: Vec<OwingTypeExpr>,
+    emit: &mut dyn FnMut(Error),
+    f: impl Fn(SpannedTypeExpr, SpannedTypeExpr) -> SpannedTypeExpr,
+) -> OwingTypeExpr {
+    rhs.into_iter().fold(lhs, |(lhs, owing), (rhs, rhs_owing)| {
+        if owing {
+            emit(Error::custom(
+                lhs.span(),
+                "unexpected `>>` closing a generic argument list",
+            ));
+        }
+
+        (f(lhs, rhs), rhs_owing)
+    })
+}
+
+/// A clause of a `where` clause: the type parameter it bounds, the operator of it that is
+/// bounded if any, and the bound itself.
+type WhereClause = ((Spanned<String>, Option<String>), SpannedTypeExpr);
+
+/// Adds the bounds of a `where` clause to the generic parameters they constrain.
+/// Clauses naming an operator, e.g. `T @ add: (T) -> T`, bound that operator of the parameter.
+fn bound_generics(
+    mut generics: Vec<SpannedGenericParam>,
+    clauses: Vec<WhereClause>,
+    emit: &mut dyn FnMut(Error),
+) -> Vec<SpannedGenericParam> {
+    for ((name, op), bound) in clauses {
+        match generics.iter_mut().find(|g| &g.node().name == name.node()) {
+            Some(generic) => match op {
+                Some(op) => generic.node_mut().ops.push((op, bound)),
+                None => generic.node_mut().bounds.push(bound),
+            },
+            None => emit(Error::custom(
+                name.span(),
+                format!(
+                    "`where` clause bounds unknown type parameter `{}`",
+                    name.node()
+                ),
+            )),
+        }
+    }
+
+    generics
+}
+
+/// Reports the `where` clauses of methods which bound a type parameter that is not declared by
+/// the enclosing class or trait.
+fn check_constraints(methods: &[SpannedMethod], generics: &[&str], emit: &mut dyn FnMut(Error)) {
+    for constraint in methods.iter().flat_map(|m| &m.node().constraints) {
+        if !generics.contains(&constraint.node().name.as_str()) {
+            emit(Error::custom(
+                constraint.span(),
+                format!(
+                    "`where` clause bounds unknown type parameter `{}`",
+                    constraint.node().name
+                ),
+            ));
+        }
+    }
+}
+
 pub fn nested_parser<'a, T: 'a>(
     parser: impl CommonParser<T> + 'a,
     delimiter: Bracket,
@@ -305,7 +969,12 @@ pub fn nested_parser<'a, T: 'a>(
 #[allow(clippy::too_many_lines, clippy::missing_panics_doc)]
 pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
     recursive(|body: Recursive<Token, SpannedBody, Error>| {
-        let ty = recursive(|ty: Recursive<Token, SpannedTypeExpr, Error>| {
+        // Each type is parsed along with whether it ended in a `>>` closing both its last
+        // generic argument list and the one enclosing it, e.g. the `Iterator<T>>` of
+        // `<I: Iterator<T>>`. Only the enclosing argument list may accept such a type.
+        let owing_ty = recursive(|owing_ty: Recursive<Token, OwingTypeExpr, Error>| {
+            let ty = owing_ty.clone().try_map(not_owing).boxed();
+
             let ident = select! {
                 Token::Identifier(s) => match s.as_str() {
                     "any" => TypeExpr::Any,
@@ -317,52 +986,48 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
             .map_with_span(Spanned::new)
             .labelled("type identifier");
 
-            let array = ty
-                .clone()
-                .then(
-                    select!(Token::Literal(Literal::Integer(i)) => i)
-                        .or_not()
-                        .delimited_by(
-                            just(Token::StartBracket(Bracket::Bracket)),
-                            just(Token::StartBracket(Bracket::Bracket)),
-                        ),
-                )
-                .try_map(|(t, n), span| {
-                    Ok(Spanned::new(
-                        TypeExpr::Array(
-                            t,
-                            if let Some(n) = n {
-                                Some(n.try_into().map_err(|_| {
-                                    Error::custom(span.clone(), "fixed array length is too large")
-                                })?)
-                            } else {
-                                None
-                            },
-                        ),
-                        span,
-                    ))
-                });
-
             let tuple = ty
                 .clone()
                 .separated_by(just::<_, Token, Error>(Token::Comma))
                 .allow_trailing()
                 .delimited_by(
                     just(Token::StartBracket(Bracket::Bracket)),
-                    just(Token::StartBracket(Bracket::Bracket)),
+                    just(Token::EndBracket(Bracket::Bracket)),
                 )
                 .map_with_span(|a, span| Spanned::new(TypeExpr::Tuple(a), span));
 
+            // e.g. (int, int) -> int, (...) -> any
+            let func = just(Token::Ellipsis)
+                .to(None)
+                .or(ty
+                    .clone()
+                    .separated_by(just::<_, Token, Error>(Token::Comma))
+                    .allow_trailing()
+                    .map(Some))
+                .delimited_by(
+                    just(Token::StartBracket(Bracket::Paren)),
+                    just(Token::EndBracket(Bracket::Paren)),
+                )
+                .then_ignore(just(Token::Arrow))
+                .then(ty.clone())
+                .map_with_span(|(params, ret), span| {
+                    Spanned::new(TypeExpr::Func(params, ret), span)
+                })
+                .boxed();
+
             let atom = choice((
                 tuple,
                 ident,
+                just(Token::Dollar).map_with_span(|_, span| Spanned::new(TypeExpr::SelfRef, span)),
+                // Function types go before parenthesized types, which would otherwise consume
+                // their parameters
+                func,
                 ty.clone()
                     .delimited_by(
                         just(Token::StartBracket(Bracket::Paren)),
                         just(Token::EndBracket(Bracket::Paren)),
                     )
                     .boxed(),
-                array,
             ));
 
             let attr = atom
@@ -385,29 +1050,93 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
             let generic = attr
                 .clone()
                 .then(
-                    ty.clone()
-                        .separated_by(just::<_, Token, _>(Token::Comma))
-                        .allow_trailing()
-                        .delimited_by(
-                            just(Token::Operator(Operator::Lt)),
-                            just(Token::Operator(Operator::Gt)),
+                    just(Token::Operator(Operator::Lt))
+                        .ignore_then(
+                            ty.clone()
+                                .then_ignore(just::<_, Token, _>(Token::Comma))
+                                .repeated(),
                         )
+                        .then(owing_ty.clone().or_not().then_with(|last| {
+                            match last {
+                                // The `>>` ending the last argument already closed this list
+                                Some((last, true)) => consumed((Some(last), false)).boxed(),
+                                last => generic_close()
+                                    .map(move |owing| (last.clone().map(|(t, _)| t), owing))
+                                    .boxed(),
+                            }
+                        }))
                         .or_not(),
                 )
                 .map_with_span(|(subject, params), span| match params {
-                    Some(params) => Spanned::new(TypeExpr::Generic(subject, params), span),
-                    None => subject,
+                    Some((mut params, (last, owing))) => {
+                        params.extend(last);
+
+                        (
+                            Spanned::new(TypeExpr::Generic(subject, params), span),
+                            owing,
+                        )
+                    }
+                    None => (subject, false),
+                })
+                .boxed();
+
+            // e.g. int[], int[3][], int[n]
+            let dimensions = select! {
+                Token::Literal(Literal::Integer(i)) => Ok(i),
+                Token::Identifier(name) => Err(name),
+            }
+            .or_not()
+            .delimited_by(
+                just(Token::StartBracket(Bracket::Bracket)),
+                just(Token::EndBracket(Bracket::Bracket)),
+            )
+            .try_map(|n, span: Span| {
+                let n = match n {
+                    Some(Ok(n)) => Some(ArrayLength::Literal(n.try_into().map_err(|_| {
+                        Error::custom(span.clone(), "fixed array length is too large")
+                    })?)),
+                    Some(Err(name)) => Some(ArrayLength::Const(name)),
+                    None => None,
+                };
+
+                Ok((n, span))
+            })
+            .repeated();
+            let array = generic
+                .clone()
+                .then_with(move |(t, owing)| {
+                    // Nothing of this type can follow a `>>` closing the enclosing list
+                    if owing {
+                        return consumed((t, true)).boxed();
+                    }
+
+                    dimensions
+                        .clone()
+                        .map(move |dimensions| {
+                            let t = dimensions.into_iter().fold(t.clone(), |t, (n, span)| {
+                                let span = t.span().merge(span);
+
+                                Spanned::new(TypeExpr::Array(t, n), span)
+                            });
+
+                            (t, false)
+                        })
+                        .boxed()
                 })
                 .boxed();
 
             let nullable = just(Token::Question)
                 .map_with_span(Spanned::<Token>::new)
                 .repeated()
-                .then(generic.clone())
-                .foldr(|op, t| {
-                    let span = op.span().merge(t.span());
+                .then(array)
+                .map(|(ops, (t, owing))| {
+                    let t = ops.into_iter().rev().fold(t, |t, op| {
+                        let span = op.span().merge(t.span());
 
-                    Spanned::new(TypeExpr::Nullable(t), span)
+                        Spanned::new(TypeExpr::Nullable(t), span)
+                    });
+
+                    (t, owing)
                 })
                 .boxed();
 
@@ -415,10 +1144,14 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
                 .map_with_span(Spanned::<Token>::new)
                 .repeated()
                 .then(nullable.clone())
-                .foldr(|op, t| {
-                    let span = op.span().merge(t.span());
+                .map(|(ops, (t, owing))| {
+                    let t = ops.into_iter().rev().fold(t, |t, op| {
+                        let span = op.span().merge(t.span());
+
+                        Spanned::new(TypeExpr::Not(t), span)
+                    });
 
-                    Spanned::new(TypeExpr::Not(t), span)
+                    (t, owing)
                 })
                 .boxed();
 
@@ -429,10 +1162,12 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
                         .ignore_then(not)
                         .repeated(),
                 )
-                .foldl(|lhs, rhs| {
-                    let span = lhs.span().merge(rhs.span());
+                .validate(|(lhs, rhs), _, emit| {
+                    fold_owing(lhs, rhs, emit, |lhs, rhs| {
+                        let span = lhs.span().merge(rhs.span());
 
-                    Spanned::new(TypeExpr::And(lhs, rhs), span)
+                        Spanned::new(TypeExpr::And(lhs, rhs), span)
+                    })
                 })
                 .boxed();
 
@@ -442,57 +1177,283 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
                         .ignore_then(and)
                         .repeated(),
                 )
-                .foldl(|lhs, rhs| {
-                    let span = lhs.span().merge(rhs.span());
+                .validate(|(lhs, rhs), _, emit| {
+                    fold_owing(lhs, rhs, emit, |lhs, rhs| {
+                        let span = lhs.span().merge(rhs.span());
 
-                    Spanned::new(TypeExpr::Union(lhs, rhs), span)
+                        Spanned::new(TypeExpr::Union(lhs, rhs), span)
+                    })
                 })
                 .boxed()
         });
+        let ty = owing_ty.clone().try_map(not_owing).boxed();
 
-        let e = recursive(|e: Recursive<Token, SpannedExpr, Error>| {
-            let literal = select! {
-                Token::Literal(lit) => match lit {
-                    Literal::Integer(i) => Expr::Integer(i),
-                    Literal::Float(f) => Expr::Float(f),
-                    Literal::String(s) => match s {
-                        StringLiteral::String(s) => Expr::String(s),
-                        _ => unreachable!(),
-                    },
-                }
-            }
-            .map_with_span(SpannedExpr::new)
-            .labelled("literal");
-
-            let ident = select! {
-                Token::Identifier(s) => match s.as_str() {
-                    "true" => Expr::Bool(true),
-                    "false" => Expr::Bool(false),
-                    _ => Expr::Ident(s),
-                },
-            }
-            .map_with_span(SpannedExpr::new)
-            .labelled("identifier");
-
-            let array = e
-                .clone()
-                .separated_by(just::<_, Token, Error>(Token::Comma))
+        // TODO: support more targets
+        let target = recursive(|t| {
+            select!(
+                Token::Identifier(i) => Target::Ident(i),
+            )
+            .map_with_span(Spanned::new)
+            .or(just(Token::Dollar)
+                .map_with_span(|_, span| Spanned::new(Target::SelfRef, span))
+                .then(select!(Token::Identifier(i) => i))
+                .map_with_span(|(subject, attr), span| {
+                    Spanned::new(Target::Attr(subject, attr), span)
+                }))
+            .or(just(Token::Ellipsis)
+                .or_not()
+                .then(t)
+                .map_with_span(|(rest, target), span| match rest {
+                    Some(_) => Spanned::new(Target::Rest(target), span),
+                    None => target,
+                })
+                .separated_by(just::<_, Token, _>(Token::Comma))
                 .allow_trailing()
+                .at_least(1)
                 .delimited_by(
                     just(Token::StartBracket(Bracket::Bracket)),
                     just(Token::EndBracket(Bracket::Bracket)),
                 )
-                .map_with_span(|a, span| Spanned::new(Expr::Array(a), span));
+                .validate(|targets: Vec<SpannedTarget>, _, emit| {
+                    for extra in targets
+                        .iter()
+                        .filter(|t| matches!(t.node(), Target::Rest(_)))
+                        .skip(1)
+                    {
+                        emit(Error::custom(
+                            extra.span(),
+                            "a destructuring target can only have one rest target",
+                        ));
+                    }
 
-            let if_stmt = just::<_, Token, Error>(Token::Keyword(Keyword::If))
-                .ignore_then(e.clone())
-                .then(body.clone().delimited_by(
-                    just(Token::StartBracket(Bracket::Brace)),
-                    just(Token::EndBracket(Bracket::Brace)),
-                ))
-                .then(
-                    just::<_, Token, _>(Token::Keyword(Keyword::Else))
-                        .ignore_then(just(Token::Keyword(Keyword::If)))
+                    targets
+                })
+                .map_with_span(|a, span| Spanned::new(Target::Array(a), span)))
+        });
+
+        // Parameters are also parsed within expressions, by anonymous functions
+        let params = |e: Recursive<'a, Token, SpannedExpr, Error>| {
+            let annotation = || {
+                just(Token::Colon)
+                    .ignore_then(ty.clone())
+                    .or_not()
+                    .map_with_span(|t, span| {
+                        t.unwrap_or_else(|| Spanned::new(TypeExpr::Auto, span))
+                    })
+            };
+
+            let normal = just::<_, Token, _>(Token::Question)
+                .or_not()
+                .then(target.clone())
+                .then(annotation())
+                .then(just(Token::Assign).ignore_then(e).or_not())
+                .map(|(((optional, target), ty), default)| {
+                    Some(Param {
+                        target,
+                        ty,
+                        default,
+                        kind: ParamKind::Normal,
+                        keyword_only: false,
+                        optional: optional.is_some(),
+                    })
+                });
+
+            // A lone `*` takes no arguments, it only marks the parameters after it as keyword-only
+            let variadic = just(Token::Operator(Operator::Pow))
+                .to(ParamKind::KeywordVariadic)
+                .or(just(Token::Operator(Operator::Mul)).to(ParamKind::Variadic))
+                .then(
+                    select!(Token::Identifier(i) => Target::Ident(i))
+                        .map_with_span(Spanned::new)
+                        .then(annotation())
+                        .or_not(),
+                )
+                .try_map(|(kind, param), span| match (kind, param) {
+                    (_, Some((target, ty))) => Ok(Some(Param {
+                        target,
+                        ty,
+                        default: None,
+                        kind,
+                        keyword_only: false,
+                        optional: false,
+                    })),
+                    (ParamKind::Variadic, None) => Ok(None),
+                    _ => Err(Error::custom(span, "expected a parameter name after `**`")),
+                });
+
+            variadic
+                .or(normal)
+                .map_with_span(|param, span| (param, span))
+                .separated_by(just(Token::Comma))
+                .allow_trailing()
+                .delimited_by(
+                    just(Token::StartBracket(Bracket::Paren)),
+                    just(Token::EndBracket(Bracket::Paren)),
+                )
+                .validate(|items: Vec<(Option<Param>, Span)>, _, emit| {
+                    let mut params: Vec<SpannedParam> = Vec::with_capacity(items.len());
+                    let mut keyword_only = false;
+                    let mut seen_default = false;
+
+                    for (param, span) in items {
+                        if matches!(
+                            params.last(),
+                            Some(p) if p.node().kind == ParamKind::KeywordVariadic
+                        ) {
+                            emit(Error::custom(
+                                span.clone(),
+                                "no parameters can follow a keyword-variadic parameter",
+                            ));
+                        }
+
+                        let mut param = match param {
+                            Some(param) => param,
+                            None => {
+                                if keyword_only {
+                                    emit(Error::custom(span, "`*` can only be given once"));
+                                }
+                                keyword_only = true;
+                                continue;
+                            }
+                        };
+
+                        match param.kind {
+                            ParamKind::Normal if keyword_only => param.keyword_only = true,
+                            ParamKind::Normal => {
+                                if param.default.is_some() || param.optional {
+                                    seen_default = true;
+                                } else if seen_default {
+                                    emit(Error::custom(
+                                        span.clone(),
+                                        "a required parameter can not follow an optional one",
+                                    ));
+                                }
+                            }
+                            ParamKind::Variadic => {
+                                if keyword_only {
+                                    emit(Error::custom(span.clone(), "`*` can only be given once"));
+                                }
+                                keyword_only = true;
+                            }
+                            ParamKind::KeywordVariadic => (),
+                        }
+
+                        if param.optional && param.default.is_some() {
+                            emit(Error::custom(
+                                span.clone(),
+                                "an optional parameter can not have a default value",
+                            ));
+                        }
+
+                        params.push(Spanned::new(param, span));
+                    }
+
+                    params
+                })
+        };
+
+        let e = recursive(|e: Recursive<Token, SpannedExpr, Error>| {
+            let literal = select! {
+                Token::Literal(Literal::Integer(i)) => Expr::Integer(i),
+                Token::Literal(Literal::Float(f)) => Expr::Float(f),
+                Token::Literal(Literal::String(
+                    StringLiteral::String(s) | StringLiteral::RawString(s)
+                )) => Expr::String(s),
+            }
+            .map_with_span(SpannedExpr::new)
+            .labelled("literal");
+
+            let interpolated = select! {
+                Token::Literal(Literal::String(StringLiteral::InterpolatedString(s))) => s,
+            }
+            .map_with_span(|raw, span| (raw, span))
+            .validate({
+                let e = e.clone();
+
+                move |(raw, span): (String, Span), _, emit| {
+                    let segments = split_interpolated(&raw, &span).unwrap_or_else(|errors| {
+                        errors.into_iter().for_each(&mut *emit);
+                        Vec::new()
+                    });
+
+                    let parts = segments
+                        .into_iter()
+                        .filter_map(|segment| match segment {
+                            Segment::Literal(s) => Some(InterpolatedPart::Literal(s)),
+                            Segment::Value { tokens, spec, span } => match e
+                                .clone()
+                                .then_ignore(end())
+                                .parse(Stream::<_, Span, _>::from_iter(
+                                    Span::single(span.src(), span.end()),
+                                    tokens.into_iter(),
+                                )) {
+                                Ok(value) => Some(InterpolatedPart::Value(value, spec)),
+                                Err(errors) => {
+                                    errors.into_iter().for_each(&mut *emit);
+                                    None
+                                }
+                            },
+                        })
+                        .collect();
+
+                    SpannedExpr::new(Expr::Interpolated(parts), span)
+                }
+            })
+            .labelled("interpolated string");
+
+            let ident = select! {
+                Token::Identifier(s) => match s.as_str() {
+                    "true" => Expr::Bool(true),
+                    "false" => Expr::Bool(false),
+                    "null" => Expr::Null,
+                    _ => Expr::Ident(s),
+                },
+            }
+            .map_with_span(SpannedExpr::new)
+            .labelled("identifier");
+
+            let spanned_op = |o: Token, span: Span| -> SpannedOperator {
+                match o {
+                    Token::Operator(op) => SpannedOperator::new(op, span),
+                    _ => unreachable!(),
+                }
+            };
+
+            let self_ref = just::<_, Token, Error>(Token::Dollar)
+                .map_with_span(|_, span| SpannedExpr::new(Expr::SelfRef, span))
+                .then(select!(Token::Identifier(s) => s).or_not())
+                .map_with_span(|(subject, attr), span| match attr {
+                    Some(attr) => SpannedExpr::new(
+                        Expr::Attr {
+                            subject,
+                            attr,
+                            optional: false,
+                        },
+                        span,
+                    ),
+                    None => subject,
+                })
+                .labelled("self reference");
+
+            let array = e
+                .clone()
+                .separated_by(just::<_, Token, Error>(Token::Comma))
+                .allow_trailing()
+                .delimited_by(
+                    just(Token::StartBracket(Bracket::Bracket)),
+                    just(Token::EndBracket(Bracket::Bracket)),
+                )
+                .map_with_span(|a, span| Spanned::new(Expr::Array(a), span));
+
+            let if_stmt = just::<_, Token, Error>(Token::Keyword(Keyword::If))
+                .ignore_then(e.clone())
+                .then(body.clone().delimited_by(
+                    just(Token::StartBracket(Bracket::Brace)),
+                    just(Token::EndBracket(Bracket::Brace)),
+                ))
+                .then(
+                    just::<_, Token, _>(Token::Keyword(Keyword::Else))
+                        .ignore_then(just(Token::Keyword(Keyword::If)))
                         .ignore_then(e.clone())
                         .then(body.clone().delimited_by(
                             just(Token::StartBracket(Bracket::Brace)),
@@ -520,15 +1481,23 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
                     )
                 });
 
-            let while_stmt = just::<_, Token, Error>(Token::Keyword(Keyword::While))
-                .ignore_then(e.clone())
+            let label = select!(Token::Identifier(s) => s)
+                .then_ignore(just(Token::At))
+                .labelled("loop label");
+
+            let while_stmt = label
+                .clone()
+                .or_not()
+                .then_ignore(just::<_, Token, Error>(Token::Keyword(Keyword::While)))
+                .then(e.clone())
                 .then(body.clone().delimited_by(
                     just(Token::StartBracket(Bracket::Brace)),
                     just(Token::EndBracket(Bracket::Brace)),
                 ))
-                .map_with_span(|(condition, body), span| {
+                .map_with_span(|((label, condition), body), span| {
                     SpannedExpr::new(
                         Expr::While {
+                            label,
                             condition,
                             body: body.into_node().0,
                         },
@@ -536,9 +1505,245 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
                     )
                 });
 
+            let pattern = recursive(|p: Recursive<Token, SpannedPattern, Error>| {
+                let literal = just(Token::Operator(Operator::Sub))
+                    .map_with_span(spanned_op)
+                    .or_not()
+                    .then(literal.or(ident.try_map(|e, span| match e.node() {
+                        Expr::Bool(_) | Expr::Null => Ok(e),
+                        _ => Err(Error::custom(span, "expected literal")),
+                    })))
+                    .map_with_span(|(operator, value), span| {
+                        let value = match operator {
+                            Some(operator) => {
+                                SpannedExpr::new(Expr::UnaryExpr { operator, value }, span.clone())
+                            }
+                            None => value,
+                        };
+
+                        SpannedPattern::new(Pattern::Literal(value), span)
+                    });
+
+                let ident = select!(Token::Identifier(s) => Pattern::Ident(s))
+                    .map_with_span(SpannedPattern::new);
+
+                let array = p
+                    .separated_by(just::<_, Token, Error>(Token::Comma))
+                    .allow_trailing()
+                    .delimited_by(
+                        just(Token::StartBracket(Bracket::Bracket)),
+                        just(Token::EndBracket(Bracket::Bracket)),
+                    )
+                    .map_with_span(|a, span| SpannedPattern::new(Pattern::Array(a), span));
+
+                let atom = choice((literal, ident, array));
+
+                atom.clone()
+                    .then(
+                        just(Token::Operator(Operator::BitOr))
+                            .ignore_then(atom)
+                            .repeated(),
+                    )
+                    .map_with_span(|(first, rest), span| {
+                        if rest.is_empty() {
+                            first
+                        } else {
+                            let mut alts = rest;
+                            alts.insert(0, first);
+
+                            SpannedPattern::new(Pattern::Alt(alts), span)
+                        }
+                    })
+            })
+            .labelled("pattern");
+
+            let arm_body = body
+                .clone()
+                .delimited_by(
+                    just(Token::StartBracket(Bracket::Brace)),
+                    just(Token::EndBracket(Bracket::Brace)),
+                )
+                .or(e.clone().map(|e| {
+                    let span = e.span();
+
+                    Spanned::new(
+                        Body(vec![Spanned::new(Node::Expr(e), span.clone())], true),
+                        span,
+                    )
+                }));
+
+            let func = select!(Token::Identifier(i) => Target::Ident(i))
+                .map_with_span(|target, span: Span| {
+                    let param = Param {
+                        target: Spanned::new(target, span.clone()),
+                        ty: Spanned::new(TypeExpr::Auto, span.clone()),
+                        default: None,
+                        kind: ParamKind::Normal,
+                        keyword_only: false,
+                        optional: false,
+                    };
+
+                    (vec![Spanned::new(param, span)], None)
+                })
+                .or(params(e.clone()).then(just(Token::Arrow).ignore_then(ty.clone()).or_not()))
+                .then_ignore(just(Token::FatArrow))
+                .then(arm_body.clone())
+                .map_with_span(|((params, return_ty), body), span| {
+                    let Body(body, return_last) = body.into_node();
+
+                    SpannedExpr::new(
+                        Expr::Func {
+                            params,
+                            body,
+                            return_last,
+                            return_ty: return_ty
+                                .unwrap_or_else(|| Spanned::new(TypeExpr::Auto, span.clone())),
+                        },
+                        span,
+                    )
+                })
+                .boxed();
+
+            let for_stmt = label
+                .or_not()
+                .then_ignore(just::<_, Token, Error>(Token::Keyword(Keyword::For)))
+                .then(target.clone())
+                .then_ignore(just(Token::Keyword(Keyword::In)))
+                .then(e.clone())
+                .then(body.clone().delimited_by(
+                    just(Token::StartBracket(Bracket::Brace)),
+                    just(Token::EndBracket(Bracket::Brace)),
+                ))
+                .map_with_span(|(((label, target), iterable), body), span| {
+                    SpannedExpr::new(
+                        Expr::For {
+                            label,
+                            target,
+                            iterable,
+                            body: body.into_node().0,
+                        },
+                        span,
+                    )
+                });
+
+            let match_stmt = just::<_, Token, Error>(Token::Keyword(Keyword::Match))
+                .ignore_then(e.clone())
+                .then(
+                    pattern
+                        .then(
+                            just(Token::Keyword(Keyword::If))
+                                .ignore_then(e.clone())
+                                .or_not(),
+                        )
+                        .then_ignore(just(Token::Arrow))
+                        .then(arm_body.clone())
+                        .map_with_span(|((pattern, guard), body), span| {
+                            SpannedMatchArm::new(
+                                MatchArm {
+                                    pattern,
+                                    guard,
+                                    body,
+                                },
+                                span,
+                            )
+                        })
+                        .separated_by(just(Token::Comma))
+                        .allow_trailing()
+                        .then(
+                            just(Token::Keyword(Keyword::Else))
+                                .ignore_then(arm_body)
+                                .then_ignore(just(Token::Comma).or_not())
+                                .or_not(),
+                        )
+                        .delimited_by(
+                            just(Token::StartBracket(Bracket::Brace)),
+                            just(Token::EndBracket(Bracket::Brace)),
+                        ),
+                )
+                .map_with_span(|(subject, (arms, else_body)), span| {
+                    SpannedExpr::new(
+                        Expr::Match {
+                            subject,
+                            arms,
+                            else_body,
+                        },
+                        span,
+                    )
+                });
+
+            let block = || {
+                body.clone().delimited_by(
+                    just(Token::StartBracket(Bracket::Brace)),
+                    just(Token::EndBracket(Bracket::Brace)),
+                )
+            };
+
+            let try_stmt = just::<_, Token, Error>(Token::Keyword(Keyword::Try))
+                .ignore_then(block())
+                .then(
+                    just(Token::Keyword(Keyword::Catch))
+                        .ignore_then(ty.clone().or_not())
+                        .then(
+                            just(Token::Keyword(Keyword::As))
+                                .ignore_then(select!(Token::Identifier(i) => i))
+                                .or_not(),
+                        )
+                        .then(block())
+                        .map_with_span(|((ty, binding), body), span| {
+                            SpannedCatchClause::new(CatchClause { ty, binding, body }, span)
+                        })
+                        .repeated(),
+                )
+                .then(
+                    just(Token::Keyword(Keyword::Finally))
+                        .ignore_then(block())
+                        .or_not(),
+                )
+                .try_map(|((body, catches), finally), span| {
+                    if catches.is_empty() && finally.is_none() {
+                        return Err(Error::custom(
+                            span,
+                            "expected `catch` or `finally` after the body of `try`",
+                        ));
+                    }
+
+                    Ok(SpannedExpr::new(
+                        Expr::Try {
+                            body,
+                            catches,
+                            finally,
+                        },
+                        span,
+                    ))
+                });
+
+            let with_stmt = just::<_, Token, Error>(Token::Keyword(Keyword::With))
+                .ignore_then(
+                    e.clone()
+                        .then(
+                            just(Token::Keyword(Keyword::As))
+                                .ignore_then(select!(Token::Identifier(i) => i))
+                                .or_not(),
+                        )
+                        .separated_by(just(Token::Comma))
+                        .at_least(1),
+                )
+                .then(block())
+                .map_with_span(|(managers, body), span| {
+                    SpannedExpr::new(Expr::With { managers, body }, span)
+                });
+
             let atom = choice((
                 literal,
+                interpolated,
+                // Loops go before identifiers, which would otherwise consume their label
+                while_stmt,
+                for_stmt,
+                // Functions go before identifiers and parenthesized expressions, which
+                // would otherwise consume their parameters
+                func,
                 ident,
+                self_ref,
                 e.clone()
                     .delimited_by(
                         just(Token::StartBracket(Bracket::Paren)),
@@ -546,67 +1751,153 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
                     )
                     .boxed(),
                 if_stmt,
-                while_stmt,
+                match_stmt,
+                try_stmt,
+                with_stmt,
                 array,
             ))
             .boxed();
 
-            let attr = atom
-                .clone()
-                .then(
-                    just::<_, Token, Error>(Token::Dot)
-                        .ignore_then(ident)
-                        .repeated(),
+            // e.g. x, key = x, *args, **kwargs
+            let arg = choice((
+                just(Token::Operator(Operator::Pow))
+                    .ignore_then(e.clone())
+                    .map_with_span(|value, span| {
+                        (None, SpannedExpr::new(Expr::KeywordSplat(value), span))
+                    }),
+                just(Token::Operator(Operator::Mul))
+                    .ignore_then(e.clone())
+                    .map_with_span(|value, span| {
+                        (None, SpannedExpr::new(Expr::Splat(value), span))
+                    }),
+                select!(Token::Identifier(name) => name)
+                    .then_ignore(just(Token::Assign))
+                    .or_not()
+                    .then(e.clone()),
+            ));
+
+            let args = arg
+                .separated_by(just::<_, Token, Error>(Token::Comma))
+                .allow_trailing()
+                .delimited_by(
+                    just(Token::StartBracket(Bracket::Paren)),
+                    just(Token::EndBracket(Bracket::Paren)),
                 )
-                .foldl(|expr, ident| {
-                    let span = expr.span();
+                .validate(|items, _, emit| {
+                    let mut args = Vec::new();
+                    let mut kwargs: Vec<(String, SpannedExpr)> = Vec::new();
+
+                    for (name, value) in items {
+                        let keyword_splat = args.last().is_some_and(|a: &SpannedExpr| {
+                            matches!(a.node(), Expr::KeywordSplat(_))
+                        });
+
+                        match name {
+                            Some(name) => kwargs.push((name, value)),
+                            None if matches!(value.node(), Expr::KeywordSplat(_)) => {
+                                args.push(value);
+                            }
+                            None if !kwargs.is_empty() || keyword_splat => emit(Error::custom(
+                                value.span(),
+                                "positional arguments can not follow keyword arguments",
+                            )),
+                            None => args.push(value),
+                        }
+                    }
 
-                    SpannedExpr::new(
-                        Expr::Attr(
-                            expr,
-                            match ident.node() {
-                                Expr::Ident(s) => s.to_string(),
-                                Expr::Bool(b) => b.to_string(),
-                                _ => unreachable!(),
-                            },
-                        ),
-                        span.merge(ident.span()),
+                    (args, kwargs)
+                });
+
+            let attr = select!(Token::Identifier(s) => s).labelled("attribute");
+
+            // e.g. [i], [1..3], [..n], [1..]
+            let subscript = choice((
+                just(Token::Operator(Operator::Range))
+                    .ignore_then(e.clone().or_not())
+                    .map(|end| Postfix::Slice(None, end, false)),
+                e.clone()
+                    .then(
+                        just(Token::Operator(Operator::Range))
+                            .ignore_then(e.clone().or_not())
+                            .or_not(),
                     )
-                })
-                .boxed();
+                    .map(|(index, end)| match (index.into_node_span(), end) {
+                        ((index, span), Some(end)) => {
+                            Postfix::Slice(Some(Spanned::new(index, span)), end, false)
+                        }
+                        ((Expr::BinaryExpr { operator, lhs, rhs }, _), None)
+                            if operator.node() == &Operator::Range =>
+                        {
+                            Postfix::Slice(Some(lhs), Some(rhs), false)
+                        }
+                        ((index, span), None) => Postfix::Index(Spanned::new(index, span), false),
+                    }),
+            ))
+            .delimited_by(
+                just(Token::StartBracket(Bracket::Bracket)),
+                just(Token::EndBracket(Bracket::Bracket)),
+            );
+
+            // e.g. a.b, a?.b, f(x), f?.(x), a[i], a?.[i], a!
+            let postfix = choice((
+                just(Token::Dot)
+                    .ignore_then(attr)
+                    .map(|attr| Postfix::Attr(attr, false)),
+                just(Token::Question)
+                    .then(just(Token::Dot))
+                    .ignore_then(choice((
+                        attr.map(|attr| Postfix::Attr(attr, true)),
+                        args.clone()
+                            .map(|(args, kwargs)| Postfix::Call(args, kwargs, true)),
+                        subscript.clone().map(|subscript| match subscript {
+                            Postfix::Index(index, _) => Postfix::Index(index, true),
+                            Postfix::Slice(start, end, _) => Postfix::Slice(start, end, true),
+                            _ => unreachable!(),
+                        }),
+                    ))),
+                args.map(|(args, kwargs)| Postfix::Call(args, kwargs, false)),
+                subscript,
+                just(Token::Operator(Operator::Not)).to(Postfix::NonNull),
+            ))
+            .map_with_span(|postfix, span: Span| (postfix, span));
 
-            let call = attr
+            let call = atom
                 .clone()
-                .then(
-                    e.clone()
-                        .separated_by(just::<_, Token, Error>(Token::Comma))
-                        .allow_trailing()
-                        .delimited_by(
-                            just(Token::StartBracket(Bracket::Paren)),
-                            just(Token::EndBracket(Bracket::Paren)),
-                        )
-                        .or_not(),
-                )
-                .map_with_span(|(expr, args), span| match args {
-                    Some(args) => SpannedExpr::new(
-                        Expr::Call {
-                            value: expr,
-                            args,
-                            kwargs: vec![],
+                .then(postfix.repeated())
+                .foldl(|expr, (postfix, span)| {
+                    let span = expr.span().merge(span);
+
+                    SpannedExpr::new(
+                        match postfix {
+                            Postfix::Attr(attr, optional) => Expr::Attr {
+                                subject: expr,
+                                attr,
+                                optional,
+                            },
+                            Postfix::Call(args, kwargs, optional) => Expr::Call {
+                                value: expr,
+                                args,
+                                kwargs,
+                                optional,
+                            },
+                            Postfix::Index(index, optional) => Expr::Index {
+                                subject: expr,
+                                index,
+                                optional,
+                            },
+                            Postfix::Slice(start, end, optional) => Expr::Slice {
+                                subject: expr,
+                                start,
+                                end,
+                                optional,
+                            },
+                            Postfix::NonNull => Expr::NonNull(expr),
                         },
                         span,
-                    ),
-                    None => expr,
+                    )
                 })
                 .boxed();
 
-            let spanned_op = |o: Token, span: Span| -> SpannedOperator {
-                match o {
-                    Token::Operator(op) => SpannedOperator::new(op, span),
-                    _ => unreachable!(),
-                }
-            };
-
             let unary = just(Token::Operator(Operator::Sub))
                 .or(just(Token::Operator(Operator::Add)))
                 .or(just(Token::Operator(Operator::Not)))
@@ -651,6 +1942,7 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
 
             let op = just(Token::Operator(Operator::Mul))
                 .or(just(Token::Operator(Operator::Div)))
+                .or(just(Token::Operator(Operator::TrueDiv)))
                 .or(just(Token::Operator(Operator::Mod)))
                 .map_with_span(spanned_op);
             let binary_product = binary_pow
@@ -676,6 +1968,57 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
                 })
                 .boxed();
 
+            let op = just(Token::Operator(Operator::BitLShift))
+                .or(just(Token::Operator(Operator::BitRShift)))
+                .map_with_span(spanned_op);
+            let binary_shift = binary_sum
+                .clone()
+                .then(op.then(binary_sum).repeated())
+                .foldl(|lhs, (operator, rhs)| {
+                    let span = lhs.span().merge(rhs.span());
+
+                    Spanned::new(Expr::BinaryExpr { operator, lhs, rhs }, span)
+                })
+                .boxed();
+
+            // `not` is only a keyword directly after `is`
+            let test = just(Token::Keyword(Keyword::Is))
+                .ignore_then(just(Token::Identifier("not".to_string())).or_not())
+                .then(ty.clone())
+                .map(|(not, ty)| Test::Is(ty, not.is_some()))
+                .or(just(Token::Keyword(Keyword::In))
+                    .ignore_then(binary_shift.clone())
+                    .map(Test::In));
+            let binary_test = binary_shift
+                .clone()
+                .then(test.repeated())
+                .foldl(|lhs, test| match test {
+                    Test::Is(ty, negated) => {
+                        let span = lhs.span().merge(ty.span());
+
+                        Spanned::new(
+                            Expr::Is {
+                                value: lhs,
+                                ty,
+                                negated,
+                            },
+                            span,
+                        )
+                    }
+                    Test::In(container) => {
+                        let span = lhs.span().merge(container.span());
+
+                        Spanned::new(
+                            Expr::In {
+                                item: lhs,
+                                container,
+                            },
+                            span,
+                        )
+                    }
+                })
+                .boxed();
+
             let op = just(Token::Operator(Operator::Eq))
                 .or(just(Token::Operator(Operator::Ne)))
                 .or(just(Token::Operator(Operator::Lt)))
@@ -683,9 +2026,9 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
                 .or(just(Token::Operator(Operator::Le)))
                 .or(just(Token::Operator(Operator::Ge)))
                 .map_with_span(spanned_op);
-            let binary_cmp = binary_sum
+            let binary_cmp = binary_test
                 .clone()
-                .then(op.then(binary_sum).repeated())
+                .then(op.then(binary_test).repeated())
                 .foldl(|lhs, (operator, rhs)| {
                     let span = lhs.span().merge(rhs.span());
 
@@ -727,7 +2070,7 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
                 .or(just(Token::Operator(Operator::BitOr)))
                 .or(just(Token::Operator(Operator::BitXor)))
                 .map_with_span(spanned_op);
-            binary_logical_or
+            let binary_bitwise = binary_logical_or
                 .clone()
                 .then(op.then(binary_logical_or).repeated())
                 .foldl(|lhs, (operator, rhs)| {
@@ -735,37 +2078,75 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
 
                     Spanned::new(Expr::BinaryExpr { operator, lhs, rhs }, span)
                 })
+                .boxed();
+
+            // Ranges bind the loosest and do not chain, e.g. 0..n + 1 is 0..(n + 1)
+            binary_bitwise
+                .clone()
+                .then(
+                    just(Token::Operator(Operator::Range))
+                        .map_with_span(spanned_op)
+                        .then(binary_bitwise)
+                        .or_not(),
+                )
+                .map(|(lhs, rhs)| match rhs {
+                    Some((operator, rhs)) => {
+                        let span = lhs.span().merge(rhs.span());
+
+                        Spanned::new(Expr::BinaryExpr { operator, lhs, rhs }, span)
+                    }
+                    None => lhs,
+                })
                 .boxed()
         });
 
-        let require = just::<_, Token, _>(Token::Keyword(Keyword::Require))
-            .ignore_then(
-                select! {
-                    Token::Identifier(i) => i,
+        let require_path = just::<_, Token, _>(Token::Dot)
+            .or_not()
+            .then(select!(Token::Identifier(i) => i).separated_by(just::<_, Token, _>(Token::Dot)))
+            .try_map(|(dot, segments), span| {
+                if dot.is_none() && segments.is_empty() {
+                    return Err(Error::custom(span, "expected a module path"));
                 }
+
+                Ok(RequirePath {
+                    relative: dot.is_some(),
+                    segments,
+                })
+            })
+            .labelled("module path");
+
+        let require_items = just::<_, Token, _>(Token::Operator(Operator::Mul))
+            .to(RequireItems::Glob)
+            .or(select!(Token::Identifier(i) => i)
+                .then(
+                    just::<_, Token, _>(Token::Keyword(Keyword::As))
+                        .ignore_then(select!(Token::Identifier(i) => i))
+                        .or_not(),
+                )
+                .map(|(name, alias)| RequireItem { name, alias })
                 .separated_by(just::<_, Token, _>(Token::Comma))
                 .allow_trailing()
-                .at_least(1),
+                .at_least(1)
+                .map(RequireItems::Named));
+
+        let require = just::<_, Token, _>(Token::Keyword(Keyword::Require))
+            .ignore_then(require_items)
+            .then(
+                just::<_, Token, _>(Token::Keyword(Keyword::From))
+                    .ignore_then(require_path)
+                    .or_not(),
             )
             .then_ignore(just::<_, Token, _>(Token::Semicolon))
-            .map_with_span(|n, span| Spanned::new(Node::Require(n), span));
+            .try_map(|(items, from), span| {
+                if items == RequireItems::Glob && from.is_none() {
+                    return Err(Error::custom(
+                        span,
+                        "`require *` must specify a module to require from",
+                    ));
+                }
 
-        // TODO: support more targets
-        let target = recursive(|t| {
-            select!(
-                Token::Identifier(i) => Target::Ident(i),
-            )
-            .map_with_span(Spanned::new)
-            .or(t
-                .separated_by(just::<_, Token, _>(Token::Comma))
-                .allow_trailing()
-                .at_least(1)
-                .delimited_by(
-                    just(Token::StartBracket(Bracket::Bracket)),
-                    just(Token::EndBracket(Bracket::Bracket)),
-                )
-                .map_with_span(|a, span| Spanned::new(Target::Array(a), span)))
-        });
+                Ok(Spanned::new(Node::Require { items, from }, span))
+            });
 
         let declare = just::<_, Token, _>(Token::Keyword(Keyword::Let))
             .or(just(Token::Keyword(Keyword::Const)))
@@ -784,9 +2165,16 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
                     )
                     .then_ignore(just::<_, Token, _>(Token::Assign)),
             )
+            // e.g. let a: int = b = 0; declares both a and b
+            .then(
+                target
+                    .clone()
+                    .then_ignore(just::<_, Token, _>(Token::Assign))
+                    .repeated(),
+            )
             .then(e.clone())
             .then_ignore(just::<_, Token, _>(Token::Semicolon))
-            .try_map(|((modifiers, (target, ty)), expr), span| {
+            .try_map(|(((modifiers, (target, ty)), chained), expr), span| {
                 let (modifier, is_mut) = modifiers.node();
 
                 let r#mut = is_mut.is_some();
@@ -798,7 +2186,7 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
 
                 Ok(Spanned::new(
                     Node::Declare {
-                        targets: vec![target],
+                        targets: std::iter::once(target).chain(chained).collect(),
                         ty,
                         value: expr,
                         r#mut,
@@ -808,8 +2196,73 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
                 ))
             });
 
-        let assign = target
+        // e.g. a.b[i] = value
+        let assign_target = target
             .clone()
+            .then(
+                just(Token::Dot)
+                    .ignore_then(select!(Token::Identifier(i) => i))
+                    .map(|attr| Postfix::Attr(attr, false))
+                    .or(e
+                        .clone()
+                        .delimited_by(
+                            just(Token::StartBracket(Bracket::Bracket)),
+                            just(Token::EndBracket(Bracket::Bracket)),
+                        )
+                        .map(|index| Postfix::Index(index, false)))
+                    .map_with_span(|link, span: Span| (link, span))
+                    .repeated(),
+            )
+            .validate(|(subject, links), _, emit| {
+                if !links.is_empty() && matches!(subject.node(), Target::Array(_)) {
+                    emit(Error::custom(
+                        subject.span(),
+                        "destructuring targets can not be indexed or have attributes",
+                    ));
+                }
+
+                (subject, links)
+            })
+            .foldl(|subject, (link, span)| {
+                let span = subject.span().merge(span);
+
+                Spanned::new(
+                    match link {
+                        Postfix::Attr(attr, _) => Target::Attr(subject, attr),
+                        Postfix::Index(index, _) => Target::Index(subject, index),
+                        _ => unreachable!(),
+                    },
+                    span,
+                )
+            });
+
+        let compound_assign = assign_target
+            .clone()
+            .then(select!(Token::CompoundAssign(op) => op).map_with_span(Spanned::new))
+            .validate(|(target, operator), _, emit| {
+                if matches!(target.node(), Target::Array(_)) {
+                    emit(Error::custom(
+                        target.span(),
+                        format!("cannot destructure in a `{}=` assignment", operator.node()),
+                    ));
+                }
+
+                (target, operator)
+            })
+            .then(e.clone())
+            .then_ignore(just::<_, Token, _>(Token::Semicolon))
+            .map_with_span(|((target, operator), value), span| {
+                Spanned::new(
+                    Node::CompoundAssign {
+                        target,
+                        operator,
+                        value,
+                    },
+                    span,
+                )
+            });
+
+        let assign = assign_target
             .then_ignore(just::<_, Token, _>(Token::Assign))
             .repeated()
             .at_least(1)
@@ -825,67 +2278,365 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
                 )
             });
 
-        let param = target
-            .clone()
+        let params = params(e.clone());
+
+        let return_ty = just(Token::Arrow)
+            .ignore_then(ty.clone())
+            .or_not()
+            .map_with_span(|t, span| t.unwrap_or_else(|| Spanned::new(TypeExpr::Auto, span)));
+
+        // e.g. throws IOError, StopIteration
+        let throws = just(Token::Keyword(Keyword::Throws))
+            .ignore_then(
+                ty.clone()
+                    .separated_by(just(Token::Comma))
+                    .allow_trailing()
+                    .at_least(1),
+            )
+            .or_not()
+            .map(Option::unwrap_or_default);
+
+        // e.g. <T, I: Iterator<T>>
+        let generic_param = select!(Token::Identifier(i) => i)
+            .then(just(Token::Colon).ignore_then(owing_ty).or_not())
+            .map_with_span(|(name, bound), span| {
+                let owing = matches!(bound, Some((_, true)));
+                let param = GenericParam {
+                    name,
+                    bounds: bound.into_iter().map(|(bound, _)| bound).collect(),
+                    ops: Vec::new(),
+                };
+
+                (Spanned::new(param, span), owing)
+            })
+            .boxed();
+        let generics = just(Token::Operator(Operator::Lt))
+            .ignore_then(
+                generic_param
+                    .clone()
+                    .try_map(|(param, owing), span| match owing {
+                        false => Ok(param),
+                        true => Err(Error::custom(span, "expected `,` or `>`")),
+                    })
+                    .then_ignore(just(Token::Comma))
+                    .repeated(),
+            )
+            .then(generic_param.or_not().then_with(|last| {
+                match last {
+                    // The `>>` ending the bound of the last parameter already closed the list
+                    Some((last, true)) => consumed(Some(last)).boxed(),
+                    last => just(Token::Operator(Operator::Gt))
+                        .map(move |_| last.clone().map(|(param, _)| param))
+                        .boxed(),
+                }
+            }))
+            .map(|(mut params, last)| {
+                params.extend(last);
+                params
+            })
+            .or_not()
+            .map(Option::unwrap_or_default);
+
+        // e.g. where T: Iterator<int>, U: int | float, V @ add: (V) -> V
+        let where_clause = just(Token::Keyword(Keyword::Where))
+            .ignore_then(
+                select!(Token::Identifier(i) => i)
+                    .map_with_span(Spanned::new)
+                    .then(
+                        just(Token::At)
+                            .ignore_then(select!(Token::Identifier(i) => i))
+                            .or_not(),
+                    )
+                    .then_ignore(just(Token::Colon))
+                    .then(ty.clone())
+                    .separated_by(just(Token::Comma))
+                    .allow_trailing(),
+            )
+            .or_not()
+            .map(Option::unwrap_or_default);
+
+        let func = just::<_, Token, _>(Token::Keyword(Keyword::Func))
+            .ignore_then(select! {
+                Token::Identifier(i) => i,
+            })
+            .then(generics.clone())
+            .then(params.clone())
+            .then(return_ty.clone())
+            .then(throws.clone())
+            .then(where_clause.clone())
+            .then(body.clone().delimited_by(
+                just(Token::StartBracket(Bracket::Brace)),
+                just(Token::EndBracket(Bracket::Brace)),
+            ))
+            .validate(
+                |((((((name, generics), params), return_ty), throws), clauses), body),
+                 span,
+                 emit| {
+                    let Body(body, return_last) = body.into_node();
+
+                    Spanned::new(
+                        Node::Func {
+                            name,
+                            generics: bound_generics(generics, clauses, emit),
+                            params,
+                            body,
+                            return_last,
+                            return_ty,
+                            throws,
+                        },
+                        span,
+                    )
+                },
+            );
+
+        // e.g. private static
+        let modifiers = just(Token::Keyword(Keyword::Private))
+            .or_not()
+            .map(|p| p.is_some())
             .then(
-                just(Token::Colon)
-                    .ignore_then(ty.clone())
+                just(Token::Keyword(Keyword::Static))
                     .or_not()
-                    .map_with_span(|t, span| {
-                        t.unwrap_or_else(|| Spanned::new(TypeExpr::Auto, span))
-                    }),
+                    .map(|s| s.is_some()),
+            );
+
+        let field = modifiers
+            .clone()
+            .then(just(Token::Keyword(Keyword::Const)).or_not())
+            .then(select!(Token::Identifier(i) => i))
+            .then_ignore(just(Token::Colon))
+            .then(ty.clone())
+            .then(just(Token::Assign).ignore_then(e.clone()).or_not())
+            .then_ignore(just(Token::Semicolon))
+            .map_with_span(
+                |(((((private, r#static), r#const), name), ty), default), span| {
+                    Spanned::new(
+                        Field {
+                            name,
+                            ty,
+                            default,
+                            private,
+                            r#static,
+                            r#const: r#const.is_some(),
+                        },
+                        span,
+                    )
+                },
+            );
+
+        let method = modifiers
+            .then(
+                just(Token::Keyword(Keyword::Op))
+                    .to(true)
+                    .or(just(Token::Keyword(Keyword::Func)).to(false))
+                    .or_not(),
             )
+            .then(select!(Token::Identifier(i) => i))
+            .then(generics.clone())
+            .then(params)
+            .then(return_ty)
+            .then(throws)
+            .then(where_clause.clone())
             .then(
-                just::<_, Token, _>(Token::Assign)
-                    .ignore_then(e.clone())
+                body.clone()
+                    .delimited_by(
+                        just(Token::StartBracket(Bracket::Brace)),
+                        just(Token::EndBracket(Bracket::Brace)),
+                    )
+                    .map(Some)
+                    .or(just(Token::Semicolon).to(None)),
+            )
+            .validate(
+                |(
+                    (((((((modifiers, op), name), generics), params), return_ty), throws), clauses),
+                    body,
+                ),
+                 span,
+                 emit| {
+                    let (private, r#static) = modifiers;
+                    let (body, return_last) = match body.map(Spanned::into_node) {
+                        Some(Body(body, return_last)) => (Some(body), return_last),
+                        None => (None, false),
+                    };
+
+                    // Clauses for any other type parameter constrain the enclosing class or trait
+                    let (clauses, outer) = clauses.into_iter().partition::<Vec<_>, _>(
+                        |((name, _), _): &WhereClause| {
+                            generics.iter().any(|g| &g.node().name == name.node())
+                        },
+                    );
+                    let mut constraints: Vec<SpannedGenericParam> = Vec::new();
+                    for ((name, _), _) in &outer {
+                        if !constraints.iter().any(|g| &g.node().name == name.node()) {
+                            constraints.push(Spanned::new(
+                                GenericParam {
+                                    name: name.node().clone(),
+                                    bounds: Vec::new(),
+                                    ops: Vec::new(),
+                                },
+                                name.span(),
+                            ));
+                        }
+                    }
+
+                    Spanned::new(
+                        Method {
+                            name,
+                            generics: bound_generics(generics, clauses, emit),
+                            constraints: bound_generics(constraints, outer, emit),
+                            params,
+                            body,
+                            return_last,
+                            return_ty,
+                            throws,
+                            private,
+                            r#static,
+                            op: op.unwrap_or(false),
+                        },
+                        span,
+                    )
+                },
+            );
+
+        // e.g. cast int { $inner }, cast from int(value) { $(value) }
+        let cast = just(Token::Identifier("cast".to_string()))
+            .ignore_then(just(Token::Keyword(Keyword::From)).or_not())
+            .then(ty.clone())
+            .then(
+                select!(Token::Identifier(i) => i)
+                    .delimited_by(
+                        just(Token::StartBracket(Bracket::Paren)),
+                        just(Token::EndBracket(Bracket::Paren)),
+                    )
                     .or_not(),
             )
-            .map_with_span(|((target, ty), default), span| {
+            .then(body.clone().delimited_by(
+                just(Token::StartBracket(Bracket::Brace)),
+                just(Token::EndBracket(Bracket::Brace)),
+            ))
+            .map_with_span(|(((from, ty), param), body), span| {
+                let Body(body, return_last) = body.into_node();
+
                 Spanned::new(
-                    Param {
-                        target,
+                    Cast {
                         ty,
-                        default,
+                        from: from.is_some(),
+                        param,
+                        body,
+                        return_last,
                     },
                     span,
                 )
             });
 
-        let func = just::<_, Token, _>(Token::Keyword(Keyword::Func))
-            .ignore_then(select! {
-                Token::Identifier(i) => i,
-            })
+        // e.g. default op iadd;
+        let default_op = just(Token::Identifier("default".to_string()))
+            .ignore_then(just(Token::Keyword(Keyword::Op)))
+            .ignore_then(select!(Token::Identifier(i) => i))
+            .then_ignore(just(Token::Semicolon))
+            .map_with_span(Spanned::new);
+
+        let class_method = method.clone().try_map(|m, span| match m.body {
+            Some(_) => Ok(m),
+            None => Err(Error::custom(span, "class methods must have a body")),
+        });
+
+        let members = |method: BoxedParser<'a, Token, SpannedMethod, Error>| {
+            choice((
+                method.map(Member::Method),
+                field.clone().map(Member::Field),
+                cast.clone().map(Member::Cast),
+                default_op.clone().map(Member::DefaultOp),
+            ))
+            .repeated()
+            .delimited_by(
+                just(Token::StartBracket(Bracket::Brace)),
+                just(Token::EndBracket(Bracket::Brace)),
+            )
+            .map(split_members)
+        };
+
+        let class = just::<_, Token, _>(Token::Keyword(Keyword::Class))
+            .ignore_then(select!(Token::Identifier(i) => i))
+            .then(generics.clone())
             .then(
-                param
+                ty.clone()
                     .separated_by(just::<_, Token, _>(Token::Comma))
                     .allow_trailing()
                     .delimited_by(
                         just(Token::StartBracket(Bracket::Paren)),
                         just(Token::EndBracket(Bracket::Paren)),
-                    ),
-            )
-            .then(
-                just(Token::Arrow)
-                    .ignore_then(ty)
-                    .or_not()
-                    .map_with_span(|t, span| {
-                        t.unwrap_or_else(|| Spanned::new(TypeExpr::Auto, span))
-                    }),
+                    )
+                    .or_not(),
             )
-            .then(body.clone().delimited_by(
+            .then(where_clause.clone())
+            .then(members(class_method.clone().boxed()))
+            .validate(
+                |((((name, generics), bases), clauses), members), span, emit| {
+                    let (fields, methods, casts, default_ops) = members;
+
+                    let names = generics
+                        .iter()
+                        .map(|g| g.node().name.as_str())
+                        .collect::<Vec<_>>();
+                    check_constraints(&methods, &names, emit);
+
+                    Spanned::new(
+                        Node::Class {
+                            name,
+                            generics: bound_generics(generics, clauses, emit),
+                            bases: bases.unwrap_or_default(),
+                            fields,
+                            methods,
+                            casts,
+                            default_ops,
+                        },
+                        span,
+                    )
+                },
+            );
+
+        let r#trait = just::<_, Token, _>(Token::Keyword(Keyword::Trait))
+            .ignore_then(select!(Token::Identifier(i) => i))
+            .then(generics)
+            .then(where_clause)
+            .then(members(method.boxed()))
+            .validate(|(((name, generics), clauses), members), span, emit| {
+                let (fields, methods, casts, default_ops) = members;
+
+                let names = generics
+                    .iter()
+                    .map(|g| g.node().name.as_str())
+                    .collect::<Vec<_>>();
+                check_constraints(&methods, &names, emit);
+
+                Spanned::new(
+                    Node::Trait {
+                        name,
+                        generics: bound_generics(generics, clauses, emit),
+                        fields,
+                        methods,
+                        casts,
+                        default_ops,
+                    },
+                    span,
+                )
+            });
+
+        // e.g. extend Joinable<string, string> for Iterator<string> { ... }
+        let extend = just(Token::Identifier("extend".to_string()))
+            .ignore_then(ty.clone())
+            .then_ignore(just(Token::Keyword(Keyword::For)))
+            .then(ty.clone())
+            .then(class_method.repeated().delimited_by(
                 just(Token::StartBracket(Bracket::Brace)),
                 just(Token::EndBracket(Bracket::Brace)),
             ))
-            .map_with_span(|(((name, params), return_ty), body), span| {
-                let Body(body, return_last) = body.into_node();
-
+            .map_with_span(|((r#trait, target), methods), span| {
                 Spanned::new(
-                    Node::Func {
-                        name,
-                        params,
-                        body,
-                        return_last,
-                        return_ty,
+                    Node::Extend {
+                        r#trait,
+                        target,
+                        methods,
                     },
                     span,
                 )
@@ -896,38 +2647,77 @@ pub fn get_body_parser<'a>() -> RecursiveParser<'a, SpannedBody> {
             .then_ignore(just::<_, Token, _>(Token::Semicolon))
             .map_with_span(|e, span| Spanned::new(Node::Return(e), span));
 
+        let throw = just::<_, Token, _>(Token::Keyword(Keyword::Throw))
+            .ignore_then(e.clone())
+            .then_ignore(just::<_, Token, _>(Token::Semicolon))
+            .map_with_span(|e, span| Spanned::new(Node::Throw(e), span));
+
+        let loop_label = just::<_, Token, _>(Token::At)
+            .ignore_then(select!(Token::Identifier(s) => s))
+            .or_not();
+
+        let r#break = just::<_, Token, _>(Token::Keyword(Keyword::Break))
+            .ignore_then(loop_label.clone())
+            .then(e.clone().or_not())
+            .then_ignore(just::<_, Token, _>(Token::Semicolon))
+            .map_with_span(|(label, value), span| Spanned::new(Node::Break { label, value }, span));
+
+        let r#continue = just::<_, Token, _>(Token::Keyword(Keyword::Continue))
+            .ignore_then(loop_label)
+            .then_ignore(just::<_, Token, _>(Token::Semicolon))
+            .map_with_span(|label, span| Spanned::new(Node::Continue(label), span));
+
         let expr = e
             .clone()
             .then_ignore(just::<_, Token, _>(Token::Semicolon))
             .or(e
                 .clone()
                 .try_map(|e, span| match e.node() {
-                    Expr::If { .. } | Expr::While { .. } => Ok(e),
+                    Expr::If { .. }
+                    | Expr::While { .. }
+                    | Expr::For { .. }
+                    | Expr::Match { .. }
+                    | Expr::Try { .. }
+                    | Expr::With { .. } => Ok(e),
                     _ => Err(Error::custom(span, "missing semicolon")),
                 })
                 .then_ignore(none_of(Token::EndBracket(Bracket::Brace)).rewind()))
             .map_with_span(|e, span| Spanned::new(Node::Expr(e), span));
 
-        choice((func, declare, assign, r#return, require, expr))
-            .repeated()
-            .then(
-                e.clone()
-                    .or_not()
-                    .map_with_span(|o, span| o.map(|o| Spanned::new(Node::Expr(o), span))),
+        choice((
+            func,
+            class,
+            r#trait,
+            extend,
+            declare,
+            assign,
+            compound_assign,
+            r#return,
+            throw,
+            r#break,
+            r#continue,
+            require,
+            expr,
+        ))
+        .repeated()
+        .then(
+            e.clone()
+                .or_not()
+                .map_with_span(|o, span| o.map(|o| Spanned::new(Node::Expr(o), span))),
+        )
+        .recover_with(skip_then_retry_until([]))
+        .map_with_span(|(mut nodes, last), span| {
+            Spanned::new(
+                {
+                    let return_last = last.is_some();
+                    if let Some(last) = last {
+                        nodes.push(last);
+                    }
+                    Body(nodes, return_last)
+                },
+                span,
             )
-            .recover_with(skip_then_retry_until([]))
-            .map_with_span(|(mut nodes, last), span| {
-                Spanned::new(
-                    {
-                        let return_last = last.is_some();
-                        if let Some(last) = last {
-                            nodes.push(last);
-                        }
-                        Body(nodes, return_last)
-                    },
-                    span,
-                )
-            })
+        })
     })
 }
 
@@ -4,7 +4,11 @@ pub mod ast;
 pub mod error;
 pub mod token;
 
-pub use crate::ast::{Body, Expr, Node, Param, ParseInterface, Target, TypeExpr};
+pub use crate::ast::{
+    bind_args, Align, ArgumentError, ArrayLength, Body, Cast, CatchClause, Expr, Field, FormatSpec,
+    GenericParam, InterpolatedPart, MatchArm, Method, Node, Param, ParamKind, ParamSpec,
+    ParseInterface, Pattern, RequireItem, RequireItems, RequirePath, Target, TypeExpr,
+};
 pub use crate::error::*;
 pub use crate::token::{get_lexer as tokenizer, Operator, Token};
 pub use chumsky::Parser as ChumskyParser;
@@ -1,6 +1,8 @@
+use super::ast::{Align, FormatSpec};
 use super::{Error, Span};
 
 use chumsky::prelude::*;
+use chumsky::Stream;
 
 use std::{fmt::Display, hash::Hash};
 
@@ -11,6 +13,7 @@ pub enum Operator {
     Sub,
     Mul,
     Div,
+    TrueDiv, // Divides as floats even if both operands are integers, unlike Div which floors them
     Mod,
     Pow,
     // Logical
@@ -49,6 +52,7 @@ impl Operator {
                 | Self::Sub
                 | Self::Mul
                 | Self::Div
+                | Self::TrueDiv
                 | Self::Mod
                 | Self::Pow
                 | Self::Eq
@@ -77,6 +81,7 @@ impl Display for Operator {
             Self::Sub => "-",
             Self::Mul => "*",
             Self::Div => "/",
+            Self::TrueDiv => "/.",
             Self::Mod => "%",
             Self::Pow => "**",
             Self::Eq => "==",
@@ -145,27 +150,37 @@ impl Display for Literal {
 pub enum Keyword {
     Func,
     Class,
+    Trait,
+    Op,
     // Modules
     Require,
     Export,
+    From,
+    As,
     // Variables
     Let,
     Const,
     Mut,
     // Attributes
     Private,
+    Static,
     // Control flow
     If,
     Else,
     Match,
     For,
     In,
+    Is,
     While,
     Break,
     Continue,
     Return,
     With,
+    Throw,
     Throws,
+    Try,
+    Catch,
+    Finally,
     Where,
 }
 
@@ -174,23 +189,33 @@ impl Display for Keyword {
         f.write_str(match self {
             Self::Func => "func",
             Self::Class => "class",
+            Self::Trait => "trait",
+            Self::Op => "op",
             Self::Require => "require",
             Self::Export => "export",
+            Self::From => "from",
+            Self::As => "as",
             Self::Let => "let",
             Self::Const => "const",
             Self::Mut => "mut",
             Self::Private => "private",
+            Self::Static => "static",
             Self::If => "if",
             Self::Else => "else",
             Self::Match => "match",
             Self::For => "for",
             Self::In => "in",
+            Self::Is => "is",
             Self::While => "while",
             Self::Break => "break",
             Self::Continue => "continue",
             Self::Return => "return",
             Self::With => "with",
+            Self::Throw => "throw",
             Self::Throws => "throws",
+            Self::Try => "try",
+            Self::Catch => "catch",
+            Self::Finally => "finally",
             Self::Where => "where",
         })
     }
@@ -203,6 +228,7 @@ impl Keyword {
             self,
             Self::Func
                 | Self::Class
+                | Self::Trait
                 | Self::Let
                 | Self::Const
                 | Self::Mut
@@ -210,11 +236,16 @@ impl Keyword {
                 | Self::Else
                 | Self::For
                 | Self::In
+                | Self::Is
                 | Self::While
                 | Self::Break
                 | Self::Continue
                 | Self::Return
                 | Self::With
+                | Self::Throw
+                | Self::Try
+                | Self::Catch
+                | Self::Finally
         )
     }
 }
@@ -237,12 +268,17 @@ pub enum Token {
     EndBracket(Bracket),
     Comma,
     Dot,
-    Cast, // ::
+    Ellipsis, // ...
+    Cast,     // ::
     Question,
+    Dollar, // $
+    At,     // @
     Semicolon,
-    Assign, // =
-    Colon,  // :
-    Arrow,  // ->
+    Assign,                   // =
+    CompoundAssign(Operator), // e.g. +=
+    Colon,                    // :
+    Arrow,                    // ->
+    FatArrow,                 // =>
 }
 
 impl Display for Token {
@@ -279,12 +315,20 @@ impl Display for Token {
             },
             Self::Comma => ",",
             Self::Dot => ".",
+            Self::Ellipsis => "...",
             Self::Cast => "::",
             Self::Question => "?",
+            Self::Dollar => "$",
+            Self::At => "@",
             Self::Semicolon => ";",
             Self::Assign => "=",
+            Self::CompoundAssign(o) => {
+                s = format!("{}=", o);
+                s.as_str()
+            }
             Self::Colon => ":",
             Self::Arrow => "->",
+            Self::FatArrow => "=>",
         })
     }
 }
@@ -309,27 +353,8 @@ macro_rules! escape_hex {
     }};
 }
 
-#[must_use]
-#[allow(clippy::too_many_lines)]
-#[allow(clippy::cast_sign_loss)] // text::int does not handle signed
-pub fn get_lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = Error> {
-    let integer = text::int::<_, Error>(10)
-        .from_str::<i128>()
-        // This is done to ensure that the interger won't overflow i128
-        .unwrapped()
-        .map(|int| Literal::Integer(int as u128))
-        .map(Token::Literal)
-        .labelled("integer literal");
-
-    let float = text::int::<_, Error>(10)
-        .chain::<char, _, _>(just('.').chain(filter(char::is_ascii_digit).repeated()))
-        .or(just('.').chain::<char, _, _>(text::digits(10)))
-        .collect::<String>()
-        .map(Literal::Float)
-        .map(Token::Literal)
-        .labelled("float literal");
-
-    let escape = just::<_, _, Error>('\\')
+fn escape() -> impl Parser<char, char, Error = Error> + Clone {
+    just::<_, _, Error>('\\')
         .ignore_then(
             just('\\')
                 .or(just('"'))
@@ -343,12 +368,113 @@ pub fn get_lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = Error> {
                 .or(escape_hex!('u', 4))
                 .or(escape_hex!('U', 8)),
         )
-        .labelled("escape sequence");
+        .labelled("escape sequence")
+}
+
+#[must_use]
+#[allow(clippy::too_many_lines)]
+#[allow(clippy::cast_sign_loss)] // Integer literals are parsed as i128 but are never negative
+pub fn get_lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = Error> {
+    // Digits may be separated by underscores, e.g. 1_000_000
+    let decimal = || {
+        filter(char::is_ascii_digit)
+            .chain(filter(|c: &char| c.is_ascii_digit() || *c == '_').repeated())
+    };
+
+    let prefixed = |prefix: &'static str, radix: u32| {
+        just::<_, _, Error>(prefix)
+            .ignore_then(
+                filter(|c: &char| c.is_ascii_alphanumeric() || *c == '_')
+                    .repeated()
+                    .collect::<String>(),
+            )
+            .map(move |digits| (digits, radix))
+    };
+
+    let integer = choice((prefixed("0x", 16), prefixed("0o", 8), prefixed("0b", 2)))
+        .or(decimal().collect::<String>().map(|digits| (digits, 10)))
+        .validate(|(digits, radix), span, emit| {
+            let digits = digits.replace('_', "");
+
+            if let Some(c) = digits.chars().find(|c| !c.is_digit(radix)) {
+                emit(Error::custom(
+                    span,
+                    format!("invalid digit '{}' in base {} integer literal", c, radix),
+                ));
+                return 0;
+            }
+
+            // Negative literals are parsed as unary minus, so the literal must fit in an i128
+            i128::from_str_radix(&digits, radix).map_or_else(
+                |_| {
+                    emit(Error::custom(
+                        span,
+                        if digits.is_empty() {
+                            "expected digits after the integer literal prefix"
+                        } else {
+                            "integer literal is too large, the maximum is 2^127 - 1"
+                        },
+                    ));
+                    0
+                },
+                |int| int as u128,
+            )
+        })
+        .map(Literal::Integer)
+        .map(Token::Literal)
+        .labelled("integer literal");
+
+    let exponent = one_of("eE")
+        .chain(one_of("+-").or_not())
+        .chain::<char, _, _>(decimal());
+
+    let float = decimal()
+        .chain::<char, _, _>(
+            // Make sure 0..1 is lexed as a range rather than the float 0. followed by .1
+            just('.')
+                .then_ignore(just('.').not().rewind())
+                .chain(filter(|c: &char| c.is_ascii_digit() || *c == '_').repeated())
+                .chain::<char, _, _>(exponent.clone().or_not().map(Option::unwrap_or_default)),
+        )
+        .or(decimal().chain::<char, _, _>(exponent.clone()))
+        .or(just('.')
+            .chain::<char, _, _>(decimal())
+            .chain::<char, _, _>(exponent.or_not().map(Option::unwrap_or_default)))
+        .collect::<String>()
+        .map(|f| Literal::Float(f.replace('_', "")))
+        .map(Token::Literal)
+        .labelled("float literal");
+
+    let escape = escape();
+
+    // Escaped characters are flagged so that they never count as indentation or line breaks
+    let multi_line_string = just::<_, _, Error>("\"\"\"")
+        .ignore_then(
+            escape
+                .clone()
+                .map(|c| (c, true))
+                .or(just("\"\"\"").or(just("\\")).not().map(|c| (c, false)))
+                .repeated(),
+        )
+        .then_ignore(just("\"\"\""))
+        .map(|chars| Token::Literal(Literal::String(StringLiteral::String(dedent(&chars)))))
+        .labelled("multi-line string literal");
+
+    // r"...", or fenced with any number of #s to allow quotes inside, e.g. r#"say "hi""#
+    let raw_string = just::<_, _, Error>('r')
+        .ignore_then(just('#').repeated())
+        .then_ignore(just('"'))
+        .then_with(|fence: Vec<char>| {
+            take_until(just('"').chain(just('#').repeated().exactly(fence.len())))
+                .map(|(s, _)| s.into_iter().collect::<String>())
+        })
+        .map(|s| Token::Literal(Literal::String(StringLiteral::RawString(s))))
+        .labelled("raw string literal");
 
     let string = just::<_, _, Error>('"')
         .ignore_then(
             filter(|c: &char| *c != '\\' && *c != '"')
-                .or(escape)
+                .or(escape.clone())
                 .repeated(),
         )
         .then_ignore(just::<_, char, _>('"'))
@@ -363,26 +489,42 @@ pub fn get_lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = Error> {
         .map(|s| Token::Literal(Literal::String(StringLiteral::String(s))))
         .labelled("string literal");
 
+    // Escapes and embedded expressions are kept as-is, see `split_interpolated`
+    let interpolated = just('$')
+        .ignore_then(raw_quoted('"').or(raw_quoted('\'')))
+        .map(|s| Token::Literal(Literal::String(StringLiteral::InterpolatedString(s))))
+        .labelled("interpolated string literal");
+
     let ident_or_keyword = text::ident().map(|s: String| match s.as_str() {
         "func" => Token::Keyword(Keyword::Func),
         "class" => Token::Keyword(Keyword::Class),
+        "trait" => Token::Keyword(Keyword::Trait),
+        "op" => Token::Keyword(Keyword::Op),
         "require" => Token::Keyword(Keyword::Require),
         "export" => Token::Keyword(Keyword::Export),
+        "from" => Token::Keyword(Keyword::From),
+        "as" => Token::Keyword(Keyword::As),
         "let" => Token::Keyword(Keyword::Let),
         "const" => Token::Keyword(Keyword::Const),
         "mut" => Token::Keyword(Keyword::Mut),
         "private" => Token::Keyword(Keyword::Private),
+        "static" => Token::Keyword(Keyword::Static),
         "if" => Token::Keyword(Keyword::If),
         "else" => Token::Keyword(Keyword::Else),
         "match" => Token::Keyword(Keyword::Match),
         "for" => Token::Keyword(Keyword::For),
         "in" => Token::Keyword(Keyword::In),
+        "is" => Token::Keyword(Keyword::Is),
         "while" => Token::Keyword(Keyword::While),
         "break" => Token::Keyword(Keyword::Break),
         "continue" => Token::Keyword(Keyword::Continue),
         "return" => Token::Keyword(Keyword::Return),
         "with" => Token::Keyword(Keyword::With),
+        "throw" => Token::Keyword(Keyword::Throw),
         "throws" => Token::Keyword(Keyword::Throws),
+        "try" => Token::Keyword(Keyword::Try),
+        "catch" => Token::Keyword(Keyword::Catch),
+        "finally" => Token::Keyword(Keyword::Finally),
         "where" => Token::Keyword(Keyword::Where),
         _ => Token::Identifier(s),
     });
@@ -395,23 +537,48 @@ pub fn get_lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = Error> {
         .then(take_until(just("*/")))
         .ignored();
 
-    let comment = single_line.or(multi_line).or_not();
+    // Any number of comments may sit between two tokens, e.g. a block of `///` doc comments
+    let comments = single_line.or(multi_line).padded().repeated();
 
     let right_shift = just(">>").then_ignore(none_of(")<>]},;").padded().rewind());
 
+    // These must be tried before the operators they start with, e.g. `**=` before `**` and `*=`
+    let compound_assign = choice::<_, Error>((
+        just("**=").to(Operator::Pow),
+        just("<<=").to(Operator::BitLShift),
+        just(">>=").to(Operator::BitRShift),
+        just("+=").to(Operator::Add),
+        just("-=").to(Operator::Sub),
+        just("*=").to(Operator::Mul),
+        just("/=").to(Operator::Div),
+        just("/.=").to(Operator::TrueDiv),
+        just("%=").to(Operator::Mod),
+        just("|=").to(Operator::BitOr),
+        just("^=").to(Operator::BitXor),
+        just("&=").to(Operator::BitAnd),
+    ))
+    .map(Token::CompoundAssign);
+
     let symbol = choice::<_, Error>((
         just(',').to(Token::Comma),
         just(';').to(Token::Semicolon),
         just('?').to(Token::Question),
+        just('$').to(Token::Dollar),
+        just('@').to(Token::At),
         just("::").to(Token::Cast),
         just(':').to(Token::Colon),
         just("->").to(Token::Arrow),
+        just("...").to(Token::Ellipsis),
         just("..").map(|_| Token::Operator(Operator::Range)),
         just('.').to(Token::Dot),
         just('+').map(|_| Token::Operator(Operator::Add)),
         just('-').map(|_| Token::Operator(Operator::Sub)),
         just("**").map(|_| Token::Operator(Operator::Pow)),
         just('*').map(|_| Token::Operator(Operator::Mul)),
+        // Not followed by a digit, so that `1/.5` still divides by the float .5
+        just("/.")
+            .then_ignore(filter(|c: &char| !c.is_ascii_digit()).rewind())
+            .to(Token::Operator(Operator::TrueDiv)),
         just('/').map(|_| Token::Operator(Operator::Div)),
         just('%').map(|_| Token::Operator(Operator::Mod)),
     ))
@@ -421,7 +588,8 @@ pub fn get_lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = Error> {
         just("==").map(|_| Token::Operator(Operator::Eq)),
         just("!=").map(|_| Token::Operator(Operator::Ne)),
         just('!').map(|_| Token::Operator(Operator::Not)), // Conflicts with !=
-        just('=').to(Token::Assign),                       // Conflicts with ==
+        just("=>").to(Token::FatArrow),
+        just('=').to(Token::Assign), // Conflicts with == and =>
         just("<=").map(|_| Token::Operator(Operator::Le)),
         just(">=").map(|_| Token::Operator(Operator::Ge)),
         just("<<").to(Token::Operator(Operator::BitLShift)),
@@ -445,16 +613,314 @@ pub fn get_lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = Error> {
         just('}').map(|_| Token::EndBracket(Bracket::Brace)),
     ));
 
-    choice::<_, Error>((string, float, symbol, brackets, ident_or_keyword, integer))
-        .or(any().map(Token::Invalid).validate(|token, span, emit| {
-            emit(Error::unexpected_token(span, &token));
-            token
-        }))
-        .map_with_span(move |token, span| (token, span))
-        .padded()
-        .recover_with(skip_then_retry_until([]))
-        .padded_by(comment.padded())
+    choice::<_, Error>((
+        interpolated,
+        multi_line_string,
+        string,
+        raw_string,
+        float,
+        compound_assign,
+        symbol,
+        brackets,
+        ident_or_keyword,
+        integer,
+    ))
+    .or(any().map(Token::Invalid).validate(|token, span, emit| {
+        emit(Error::unexpected_token(span, &token));
+        token
+    }))
+    .map_with_span(move |token, span| (token, span))
+    .padded()
+    .recover_with(skip_then_retry_until([]))
+    .padded_by(comments)
+    .repeated()
+    .padded()
+    .then_ignore(end())
+}
+
+/// Builds the contents of a multi-line string from its characters, each flagged with whether
+/// it was escaped.
+///
+/// The line break after the opening quotes and the whitespace-only line before the closing quotes
+/// are removed, blank lines are emptied, then the indentation common to all other lines is stripped.
+fn dedent(chars: &[(char, bool)]) -> String {
+    let is_space = |(c, escaped): &(char, bool)| !escaped && (*c == ' ' || *c == '\t');
+    let is_blank = |line: &&[(char, bool)]| line.iter().all(is_space);
+
+    let mut lines = chars
+        .split(|&(c, escaped)| !escaped && c == '\n')
+        .map(|line| match line.last() {
+            Some(('\r', false)) => &line[..line.len() - 1],
+            _ => line,
+        })
+        .collect::<Vec<_>>();
+
+    if lines.len() > 1 && is_blank(&lines[0]) {
+        lines.remove(0);
+    }
+    if lines.len() > 1 && is_blank(&lines[lines.len() - 1]) {
+        lines.pop();
+    }
+
+    let indent = lines
+        .iter()
+        .filter(|line| !is_blank(line))
+        .map(|line| line.iter().take_while(|c| is_space(c)).count())
+        .min()
+        .unwrap_or(0);
+
+    lines
+        .into_iter()
+        .map(|line| {
+            if is_blank(&line) {
+                return String::new();
+            }
+
+            line.iter().skip(indent).map(|(c, _)| c).collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The contents of a string delimited by `quote`, without processing escape sequences.
+fn raw_quoted(quote: char) -> impl Parser<char, String, Error = Error> + Clone {
+    just('\\')
+        .chain(any())
+        .or(filter(move |c: &char| *c != '\\' && *c != quote).map(|c| vec![c]))
         .repeated()
-        .padded()
-        .then_ignore(end())
+        .flatten()
+        .collect()
+        .delimited_by(just(quote), just(quote))
+}
+
+/// A segment of an interpolated string literal.
+#[derive(Debug)]
+pub(crate) enum Segment {
+    Literal(String),
+    Value {
+        tokens: Vec<(Token, Span)>,
+        spec: FormatSpec,
+        span: Span,
+    },
+}
+
+/// Splits the raw contents of an interpolated string literal spanning `span` into literal
+/// segments, with escapes processed, and the tokens of its embedded expressions.
+///
+/// An embedded expression may contain strings, but only ones using the other quote character.
+pub(crate) fn split_interpolated(raw: &str, span: &Span) -> Result<Vec<Segment>, Vec<Error>> {
+    let chars = raw.chars().collect::<Vec<_>>();
+    let src = span.src();
+    // Skip the leading `$"`
+    let offset = span.start() + 2;
+    let span_of =
+        |start: usize, end: usize| Span::from_range(src.clone(), offset + start..offset + end);
+
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut errors = Vec::new();
+
+    let push_literal = |from: usize, to: usize, literal: &mut String, errors: &mut Vec<Error>| {
+        if from == to {
+            return;
+        }
+
+        match filter(|c: &char| *c != '\\')
+            .or(escape())
+            .repeated()
+            .collect::<String>()
+            .then_ignore(end())
+            .parse(Stream::<_, Span, _>::from_iter(
+                span_of(to, to + 1),
+                (from..to).map(|i| (chars[i], span_of(i, i + 1))),
+            )) {
+            Ok(s) => literal.push_str(&s),
+            Err(e) => errors.extend(e),
+        }
+    };
+
+    let (mut start, mut i) = (0, 0);
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            c @ ('{' | '}') if chars.get(i + 1) == Some(&c) => {
+                // Keep one of the two braces
+                push_literal(start, i + 1, &mut literal, &mut errors);
+                i += 2;
+                start = i;
+            }
+            '}' => {
+                push_literal(start, i, &mut literal, &mut errors);
+                errors.push(Error::custom(
+                    span_of(i, i + 1),
+                    "unmatched '}' in interpolated string, use '}}' for a literal brace",
+                ));
+                i += 1;
+                start = i;
+            }
+            '{' => {
+                push_literal(start, i, &mut literal, &mut errors);
+
+                let (end, colon) = match find_closing_brace(&chars, i + 1) {
+                    Some(found) => found,
+                    None => {
+                        errors.push(Error::custom(
+                            span_of(i, chars.len()),
+                            "unclosed '{' in interpolated string",
+                        ));
+                        start = chars.len();
+                        break;
+                    }
+                };
+                let expr_end = colon.unwrap_or(end);
+
+                let spec = colon.map_or_else(
+                    || Ok(FormatSpec::default()),
+                    |colon| {
+                        let spec = &chars[colon + 1..end];
+
+                        parse_format_spec(spec).ok_or_else(|| {
+                            Error::custom(
+                                span_of(colon + 1, end),
+                                format!(
+                                    "invalid format specifier '{}', expected [[fill]align][width][.precision][?]",
+                                    spec.iter().collect::<String>()
+                                ),
+                            )
+                        })
+                    },
+                );
+                let tokens = get_lexer().parse(Stream::<_, Span, _>::from_iter(
+                    span_of(expr_end, expr_end + 1),
+                    (i + 1..expr_end).map(|j| (chars[j], span_of(j, j + 1))),
+                ));
+
+                match (tokens, spec) {
+                    (Ok(tokens), _) if tokens.is_empty() => errors.push(Error::custom(
+                        span_of(i, end + 1),
+                        "expected an expression in interpolated string",
+                    )),
+                    (Ok(tokens), Ok(spec)) => {
+                        if !literal.is_empty() {
+                            segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                        }
+
+                        segments.push(Segment::Value {
+                            tokens,
+                            spec,
+                            span: span_of(i + 1, expr_end),
+                        });
+                    }
+                    (tokens, spec) => {
+                        errors.extend(tokens.err().into_iter().flatten());
+                        errors.extend(spec.err());
+                    }
+                }
+
+                i = end + 1;
+                start = i;
+            }
+            _ => i += 1,
+        }
+    }
+
+    push_literal(start, chars.len(), &mut literal, &mut errors);
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+
+    if errors.is_empty() {
+        Ok(segments)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Finds the brace closing an embedded expression starting at `start`, along with the
+/// colon separating the expression from its format specifier, if any.
+fn find_closing_brace(chars: &[char], start: usize) -> Option<(usize, Option<usize>)> {
+    let mut depth = 0_usize;
+    let mut quote = None;
+    let mut colon = None;
+
+    let mut i = start;
+    while i < chars.len() {
+        let c = chars[i];
+
+        match quote {
+            Some(_) if c == '\\' => i += 1,
+            Some(q) if c == q => quote = None,
+            Some(_) => (),
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '(' | '[' | '{' => depth += 1,
+                '}' if depth == 0 => return Some((i, colon)),
+                ')' | ']' | '}' => depth = depth.saturating_sub(1),
+                // `::` is the cast operator
+                ':' if depth == 0 && colon.is_none() => {
+                    if chars.get(i + 1) == Some(&':') {
+                        i += 1;
+                    } else {
+                        colon = Some(i);
+                    }
+                }
+                _ => (),
+            },
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+/// Parses a format specifier of the form `[[fill]align][width][.precision][?]`.
+fn parse_format_spec(chars: &[char]) -> Option<FormatSpec> {
+    let align = |c: &char| match c {
+        '<' => Some(Align::Left),
+        '^' => Some(Align::Center),
+        '>' => Some(Align::Right),
+        _ => None,
+    };
+    let number = |chars: &[char]| {
+        let digits = chars.iter().take_while(|c| c.is_ascii_digit()).count();
+        let value = chars[..digits].iter().collect::<String>().parse::<usize>();
+
+        (digits, value.ok())
+    };
+
+    let mut spec = FormatSpec::default();
+    let mut i = 0;
+
+    if let Some(a) = chars.get(1).and_then(align) {
+        spec.fill = chars[0];
+        spec.align = Some(a);
+        i = 2;
+    } else if let Some(a) = chars.first().and_then(align) {
+        spec.align = Some(a);
+        i = 1;
+    }
+
+    let (digits, width) = number(&chars[i..]);
+    if digits > 0 {
+        spec.width = Some(width?);
+        i += digits;
+    }
+
+    if chars.get(i) == Some(&'.') {
+        let (digits, precision) = number(&chars[i + 1..]);
+        if digits == 0 {
+            return None;
+        }
+
+        spec.precision = Some(precision?);
+        i += digits + 1;
+    }
+
+    if chars.get(i) == Some(&'?') {
+        spec.repr = true;
+        i += 1;
+    }
+
+    (i == chars.len()).then_some(spec)
 }
@@ -5,7 +5,12 @@
 mod interner;
 
 use std::collections::HashMap;
-use terbium_bytecode::{Addr, AddrRepr, EqComparableFloat, Instruction, Program};
+use std::io::Write;
+use terbium_bytecode::{
+    Addr, AddrRepr, EqComparableFloat, Instruction, Program, TypeTag, BUILTIN_CLASSES,
+    BUILTIN_FUNCS,
+};
+use terbium_grammar::{bind_args, Align, FormatSpec, ParamKind, ParamSpec};
 
 pub use interner::Interner;
 use interner::StringId;
@@ -13,6 +18,43 @@ use interner::StringId;
 /// The integer type of the location of a `TerbiumObject`.
 pub type ObjectRef = usize;
 
+/// The integer type of the location of an array's elements in a `Context`.
+pub type ArrayId = usize;
+
+/// The integer type of the location of an iterator's state in a `Context`.
+pub type IteratorId = usize;
+
+/// The integer type of the location of a module's exports in a `Context`.
+pub type ModuleId = usize;
+
+/// The integer type of the location of a function in a `Context`.
+pub type FuncId = usize;
+
+/// The integer type of the location of a cell holding a captured variable in a `Context`.
+pub type CellId = usize;
+
+/// The integer type of the location of a class in a `Context`.
+pub type ClassId = usize;
+
+/// The integer type of the location of an instance of a class in a `Context`.
+pub type InstanceId = usize;
+
+/// The positional arguments of a call followed by its keyword arguments, each a name and a value.
+type Arguments = (Vec<ObjectRef>, Vec<(ObjectRef, ObjectRef)>);
+
+/// The builtin `Error` class, which every object that is thrown must be an instance of.
+/// Builtin classes are stored in the order of `BUILTIN_CLASSES`, before any other class.
+pub const ERROR_CLASS: ClassId = 0;
+pub const NULL_ERROR_CLASS: ClassId = 1;
+pub const INDEX_ERROR_CLASS: ClassId = 2;
+pub const ZERO_DIVISION_ERROR_CLASS: ClassId = 3;
+pub const VALUE_ERROR_CLASS: ClassId = 4;
+pub const TYPE_ERROR_CLASS: ClassId = 5;
+/// Thrown by the `op next` of an iterator once it has no more items, which ends a for loop.
+pub const STOP_ITERATION_CLASS: ClassId = 6;
+pub const ATTRIBUTE_ERROR_CLASS: ClassId = 7;
+pub const SIGNATURE_ERROR_CLASS: ClassId = 8;
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 /// The internal Terbium object model. These are created during the interpreter runtime.
 pub enum TerbiumObject {
@@ -21,6 +63,90 @@ pub enum TerbiumObject {
     Float(EqComparableFloat),
     String(StringId),
     Bool(bool),
+    Array(ArrayId),
+    /// An integer range from field 0 (inclusive) to field 1 (exclusive).
+    Range(i128, i128),
+    Iterator(IteratorId),
+    Module(ModuleId),
+    Func(FuncId),
+    /// A function bound to the object in field 1, which is passed as its first argument.
+    Method(FuncId, ObjectRef),
+    Class(ClassId),
+    Instance(InstanceId),
+    /// An intrinsic function, by its index in `BUILTIN_FUNCS`.
+    Builtin(usize),
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+/// The state of an iterator over a builtin iterable.
+pub enum NativeIterator {
+    Array { array: ArrayId, index: usize },
+    Range { current: i128, end: i128 },
+}
+
+#[derive(Clone, Debug)]
+/// A function created at runtime, along with the variables it captured.
+pub struct Function {
+    /// The address of the body of the function, which stores its parameters before anything else.
+    pub addr: AddrRepr,
+    pub params: Vec<ParamSpec>,
+    /// The default value of each parameter, evaluated when the function was created.
+    pub defaults: Vec<Option<ObjectRef>>,
+    /// The captured variables keyed by identifier, stored into the scope of every call.
+    pub captures: Vec<(usize, ScopeEntry)>,
+}
+
+#[derive(Clone, Debug)]
+/// A class created at runtime.
+pub struct Class {
+    pub name: String,
+    pub bases: Vec<ClassId>,
+    /// The methods declared by the class, keyed by identifier.
+    pub methods: HashMap<usize, FuncId>,
+    /// The operator overloads declared by the class, keyed by name, e.g. `enter`.
+    pub ops: HashMap<String, FuncId>,
+}
+
+#[derive(Clone, Debug)]
+/// An instance of a class, along with the arguments it was constructed with.
+///
+/// The first argument of an error is its message.
+// TODO: declared fields, once classes can declare them
+pub struct Instance {
+    pub class: ClassId,
+    pub args: Vec<ObjectRef>,
+    /// The attributes assigned to the instance, keyed by identifier.
+    pub fields: HashMap<usize, ObjectRef>,
+    /// The address of the instruction which first threw the instance, if it was ever thrown.
+    pub raised_at: Option<AddrRepr>,
+}
+
+/// An exception handler installed by `EnterTry`, which stays active until the scope it
+/// entered is left.
+#[derive(Copy, Clone, Debug)]
+struct ActiveHandler {
+    /// The address errors caught by this handler are sent to.
+    target: AddrRepr,
+    /// The amount of frames, scopes and objects on the stack before the handler was installed.
+    frame_base: usize,
+    scope_base: usize,
+    stack_base: usize,
+    /// Whether the handler guards the `op next` called by a for loop, in which case it only
+    /// catches `StopIteration` and sends `false` to the loop instead of the error.
+    stop_iteration: bool,
+}
+
+/// A call to a function, which is left once it returns.
+#[derive(Copy, Clone, Debug)]
+struct Frame {
+    /// The address of the instruction that made the call.
+    ret: AddrRepr,
+    /// The amount of scopes and objects on the stack before the call.
+    scope_base: usize,
+    stack_base: usize,
+    /// Whether the call is the `op next` of an iterator advanced by a for loop, in which case
+    /// its value is followed by `true` once it returns.
+    iter_next: bool,
 }
 
 #[derive(Debug)]
@@ -82,6 +208,15 @@ impl<const STACK_SIZE: usize> Stack<STACK_SIZE> {
         self.inner[self.ptr]
     }
 
+    /// Retrieves the last object in the stack without popping it.
+    ///
+    /// # Panics
+    /// - The stack is empty
+    #[must_use]
+    pub fn peek(&self) -> ObjectRef {
+        self.inner[self.ptr.checked_sub(1).expect("stack ptr at 0")]
+    }
+
     /// Retrieves a reference to the next free slot.
     #[must_use]
     pub const fn next_free(&self) -> &ObjectRef {
@@ -142,14 +277,25 @@ impl Default for ObjectStore {
     }
 }
 
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+/// Where the object referenced by a `ScopeEntry` is kept.
+pub enum Binding {
+    /// The entry references the object directly.
+    Local(ObjectRef),
+    /// The entry was captured by a closure, so it references the object through the cell
+    /// with this id. Every entry capturing the same variable shares this cell, which
+    /// outlives the scope the variable was declared in.
+    Cell(CellId),
+}
+
+#[derive(Copy, Clone, Debug)]
 /// Represents an entry in a scope.
 ///
 /// Apart from which object this entry references,
 /// it also stores metadata such was whether or not this
 /// entry is a constant or if it is mutable.
 pub struct ScopeEntry {
-    pub loc: ObjectRef,
+    pub binding: Binding,
     r#mut: bool,
     r#const: bool,
 }
@@ -179,12 +325,6 @@ impl ScopeEntry {
     }
 }
 
-impl From<ScopeEntry> for ObjectRef {
-    fn from(s: ScopeEntry) -> Self {
-        s.loc
-    }
-}
-
 #[derive(Debug)]
 /// Represents a scope of identifiers.
 pub struct Scope {
@@ -212,6 +352,15 @@ pub struct Context<const STACK_SIZE: usize = 512> {
     pub store: ObjectStore,
     pub(crate) stack: Stack<STACK_SIZE>,
     scopes: Vec<Scope>,
+    arrays: Vec<Vec<ObjectRef>>,
+    iterators: Vec<NativeIterator>,
+    modules: Vec<HashMap<usize, ObjectRef>>,
+    funcs: Vec<Function>,
+    cells: Vec<ObjectRef>,
+    classes: Vec<Class>,
+    instances: Vec<Instance>,
+    frames: Vec<Frame>,
+    handlers: Vec<ActiveHandler>,
     integer_lookup: HashMap<i128, ObjectRef>,
     bool_lookup: [ObjectRef; 2],
 }
@@ -219,13 +368,59 @@ pub struct Context<const STACK_SIZE: usize = 512> {
 impl<const STACK_SIZE: usize> Context<STACK_SIZE> {
     #[must_use]
     pub fn new() -> Self {
-        Self {
+        let mut ctx = Self {
             store: ObjectStore::default(),
             stack: Stack::new(),
             scopes: vec![Scope::default()],
+            arrays: Vec::new(),
+            iterators: Vec::new(),
+            modules: Vec::new(),
+            funcs: Vec::new(),
+            cells: Vec::new(),
+            classes: Vec::new(),
+            instances: Vec::new(),
+            frames: Vec::new(),
+            handlers: Vec::new(),
             integer_lookup: HashMap::new(),
             bool_lookup: [0, 0],
+        };
+
+        // The key of each builtin class is its index, and every builtin class other than
+        // Error itself derives from Error
+        for (key, name) in BUILTIN_CLASSES.into_iter().enumerate() {
+            let class = ctx.store_class(Class {
+                name: name.to_string(),
+                bases: if key == ERROR_CLASS {
+                    Vec::new()
+                } else {
+                    vec![ERROR_CLASS]
+                },
+                methods: HashMap::new(),
+                ops: HashMap::new(),
+            });
+
+            ctx.store_var(
+                key,
+                ScopeEntry {
+                    binding: Binding::Local(class),
+                    r#mut: false,
+                    r#const: true,
+                },
+            );
+        }
+        for index in 0..BUILTIN_FUNCS.len() {
+            let func = ctx.store_auto(TerbiumObject::Builtin(index));
+
+            ctx.store_var(
+                BUILTIN_CLASSES.len() + index,
+                ScopeEntry {
+                    binding: Binding::Local(func),
+                    r#mut: false,
+                    r#const: true,
+                },
+            );
         }
+        ctx
     }
 
     /// Pushes an object by location to the stack.
@@ -238,6 +433,11 @@ impl<const STACK_SIZE: usize> Context<STACK_SIZE> {
         self.stack.pop()
     }
 
+    /// Returns the location of the last object on the stack without popping it.
+    pub fn peek_ref(&self) -> ObjectRef {
+        self.stack.peek()
+    }
+
     /// Return `true` if the stack is empty
     pub fn stack_is_empty(&self) -> bool {
         self.stack.ptr == 0
@@ -314,6 +514,143 @@ impl<const STACK_SIZE: usize> Context<STACK_SIZE> {
         }
     }
 
+    /// Stores an array of the given elements and returns its location.
+    pub fn store_array(&mut self, elements: Vec<ObjectRef>) -> ObjectRef {
+        self.arrays.push(elements);
+
+        self.store_auto(TerbiumObject::Array(self.arrays.len() - 1))
+    }
+
+    #[must_use]
+    /// Returns the elements of the array with the given id.
+    pub fn array(&self, id: ArrayId) -> &[ObjectRef] {
+        &self.arrays[id]
+    }
+
+    /// Returns the elements of the array with the given id, which can be modified in place.
+    pub fn array_mut(&mut self, id: ArrayId) -> &mut Vec<ObjectRef> {
+        &mut self.arrays[id]
+    }
+
+    /// Stores an iterator with the given state and returns its location.
+    pub fn store_iterator(&mut self, iterator: NativeIterator) -> ObjectRef {
+        self.iterators.push(iterator);
+
+        self.store_auto(TerbiumObject::Iterator(self.iterators.len() - 1))
+    }
+
+    /// Advances the iterator with the given id, returning the location of the next item
+    /// or `None` if it is exhausted.
+    pub fn iter_next(&mut self, id: IteratorId) -> Option<ObjectRef> {
+        match self.iterators[id] {
+            NativeIterator::Array { array, index } => {
+                let item = self.arrays[array].get(index).copied()?;
+                self.iterators[id] = NativeIterator::Array {
+                    array,
+                    index: index + 1,
+                };
+
+                Some(item)
+            }
+            NativeIterator::Range { current, end } => {
+                if current >= end {
+                    return None;
+                }
+                self.iterators[id] = NativeIterator::Range {
+                    current: current + 1,
+                    end,
+                };
+
+                Some(self.load_int(current))
+            }
+        }
+    }
+
+    /// Stores a module with the given exports and returns its location.
+    pub fn store_module(&mut self, exports: HashMap<usize, ObjectRef>) -> ObjectRef {
+        self.modules.push(exports);
+
+        self.store_auto(TerbiumObject::Module(self.modules.len() - 1))
+    }
+
+    #[must_use]
+    /// Returns the exports of the module with the given id, keyed by identifier.
+    pub fn module(&self, id: ModuleId) -> &HashMap<usize, ObjectRef> {
+        &self.modules[id]
+    }
+
+    /// Stores the function and returns its location.
+    pub fn store_func(&mut self, func: Function) -> ObjectRef {
+        self.funcs.push(func);
+
+        self.store_auto(TerbiumObject::Func(self.funcs.len() - 1))
+    }
+
+    #[must_use]
+    /// Returns the function with the given id.
+    pub fn func(&self, id: FuncId) -> &Function {
+        &self.funcs[id]
+    }
+
+    /// Returns a mutable reference to the function with the given id.
+    pub fn func_mut(&mut self, id: FuncId) -> &mut Function {
+        &mut self.funcs[id]
+    }
+
+    /// Stores the class and returns its location.
+    pub fn store_class(&mut self, class: Class) -> ObjectRef {
+        self.classes.push(class);
+
+        self.store_auto(TerbiumObject::Class(self.classes.len() - 1))
+    }
+
+    #[must_use]
+    /// Returns the class with the given id.
+    pub fn class(&self, id: ClassId) -> &Class {
+        &self.classes[id]
+    }
+
+    /// Returns a mutable reference to the class with the given id.
+    pub fn class_mut(&mut self, id: ClassId) -> &mut Class {
+        &mut self.classes[id]
+    }
+
+    /// Finds a member of the class using `get`, looking through its bases in order
+    /// if the class does not declare it itself.
+    pub fn find_member<T>(&self, class: ClassId, get: &impl Fn(&Class) -> Option<T>) -> Option<T> {
+        let class = &self.classes[class];
+
+        get(class).or_else(|| class.bases.iter().find_map(|&c| self.find_member(c, get)))
+    }
+
+    #[must_use]
+    /// Whether the class is `base` or derives from it, directly or not.
+    pub fn is_subclass(&self, class: ClassId, base: ClassId) -> bool {
+        class == base
+            || self.classes[class]
+                .bases
+                .iter()
+                .any(|&c| self.is_subclass(c, base))
+    }
+
+    /// Stores the instance and returns its location.
+    pub fn store_instance(&mut self, instance: Instance) -> ObjectRef {
+        self.instances.push(instance);
+
+        self.store_auto(TerbiumObject::Instance(self.instances.len() - 1))
+    }
+
+    #[must_use]
+    /// Returns the instance with the given id.
+    pub fn instance(&self, id: InstanceId) -> &Instance {
+        &self.instances[id]
+    }
+
+    /// Returns a mutable reference to the instance with the given id.
+    pub fn instance_mut(&mut self, id: InstanceId) -> &mut Instance {
+        &mut self.instances[id]
+    }
+
     #[must_use]
     /// Returns a reference to the current local scope.
     pub fn locals(&self) -> &Scope {
@@ -342,28 +679,85 @@ impl<const STACK_SIZE: usize> Context<STACK_SIZE> {
             unimplemented!();
         }
 
-        entry.loc = value;
+        match entry.binding {
+            Binding::Local(_) => entry.binding = Binding::Local(value),
+            Binding::Cell(cell) => self.cells[cell] = value,
+        }
     }
 
-    #[must_use]
-    pub fn lookup_var(&self, key: usize) -> Option<&ScopeEntry> {
-        for scope in self.scopes.iter().rev() {
-            if let Some(entry) = scope.locals.get(&key) {
-                return Some(entry);
+    /// Sets the variable declared in the current scope with the given `key` to the value,
+    /// writing through its cell if closures have captured it already.
+    pub fn init_var(&mut self, key: usize, value: ObjectRef) {
+        match self.locals_mut().locals.get_mut(&key) {
+            Some(ScopeEntry {
+                binding: Binding::Cell(cell),
+                ..
+            }) => {
+                let cell = *cell;
+                self.cells[cell] = value;
             }
+            _ => self.store_var(
+                key,
+                ScopeEntry {
+                    binding: Binding::Local(value),
+                    r#mut: false,
+                    r#const: false,
+                },
+            ),
         }
+    }
 
-        None
+    #[must_use]
+    /// Returns the location of the object the variable with the given `key` references.
+    pub fn load_var(&self, key: usize) -> Option<ObjectRef> {
+        Some(match self.lookup_var(key)?.binding {
+            Binding::Local(loc) => loc,
+            Binding::Cell(cell) => self.cells[cell],
+        })
     }
 
-    pub fn lookup_var_mut(&mut self, key: usize) -> Option<&mut ScopeEntry> {
-        for scope in self.scopes.iter_mut().rev() {
-            if let Some(entry) = scope.locals.get_mut(&key) {
-                return Some(entry);
-            }
+    /// Moves the variable with the given `key` into a cell if it is not in one already,
+    /// returning an entry which references that same cell.
+    pub fn capture_var(&mut self, key: usize) -> Option<ScopeEntry> {
+        let cell = self.cells.len();
+        let entry = self.lookup_var_mut(key)?;
+
+        if let Binding::Local(loc) = entry.binding {
+            entry.binding = Binding::Cell(cell);
+            self.cells.push(loc);
         }
 
-        None
+        self.lookup_var(key).copied()
+    }
+
+    /// Returns the index of the innermost scope visible from the current frame which
+    /// contains `key`. A function only sees its own scopes, followed by the global scope.
+    fn scope_of(&self, key: usize) -> Option<usize> {
+        let base = self.frames.last().map_or(0, |f| f.scope_base);
+
+        (base..self.scopes.len())
+            .rev()
+            .chain((base > 0).then_some(0))
+            .find(|&i| self.scopes[i].locals.contains_key(&key))
+    }
+
+    #[must_use]
+    pub fn lookup_var(&self, key: usize) -> Option<&ScopeEntry> {
+        let scope = self.scope_of(key)?;
+
+        self.scopes[scope].locals.get(&key)
+    }
+
+    pub fn lookup_var_mut(&mut self, key: usize) -> Option<&mut ScopeEntry> {
+        let scope = self.scope_of(key)?;
+
+        self.scopes[scope].locals.get_mut(&key)
+    }
+
+    /// The amount of scopes currently entered, including the global scope.
+    #[must_use]
+    pub fn scope_depth(&self) -> usize {
+        self.scopes.len()
     }
 
     pub fn enter_scope(&mut self) {
@@ -372,6 +766,116 @@ impl<const STACK_SIZE: usize> Context<STACK_SIZE> {
 
     pub fn exit_scope(&mut self) {
         self.scopes.pop();
+        self.drop_handlers();
+    }
+
+    /// Uninstalls every handler whose scope was left.
+    fn drop_handlers(&mut self) {
+        while matches!(self.handlers.last(), Some(h) if h.scope_base >= self.scopes.len()) {
+            self.handlers.pop();
+        }
+    }
+
+    /// Enters a new scope guarded by a handler sending caught errors to `target`.
+    pub fn enter_try(&mut self, target: AddrRepr) {
+        self.handlers.push(ActiveHandler {
+            target,
+            frame_base: self.frames.len(),
+            scope_base: self.scopes.len(),
+            stack_base: self.stack.ptr,
+            stop_iteration: false,
+        });
+        self.enter_scope();
+    }
+
+    /// Marks the frame just entered as the `op next` of an iterator advanced by a for loop.
+    /// Once it returns, its value is followed by `true`, while a `StopIteration` thrown from it
+    /// leaves `false` instead and resumes after the call.
+    pub fn catch_iter_next(&mut self) {
+        let frame_base = self.frames.len() - 1;
+        let frame = &mut self.frames[frame_base];
+        frame.iter_next = true;
+
+        let handler = ActiveHandler {
+            target: frame.ret + 1,
+            frame_base,
+            scope_base: frame.scope_base,
+            stack_base: frame.stack_base,
+            stop_iteration: true,
+        };
+        self.handlers.push(handler);
+    }
+
+    /// Whether the object is an instance of `StopIteration`.
+    fn is_stop_iteration(&self, o: ObjectRef) -> bool {
+        matches!(
+            self.store.resolve(o),
+            TerbiumObject::Instance(id)
+                if self.is_subclass(self.instances[*id].class, STOP_ITERATION_CLASS)
+        )
+    }
+
+    /// Unwinds the frames, scopes and stack to where the innermost handler was installed,
+    /// then pushes the error. Returns the address the handler sends errors to, or `None`
+    /// if no handler is installed.
+    pub fn unwind(&mut self, error: ObjectRef) -> Option<AddrRepr> {
+        let stop_iteration = self.is_stop_iteration(error);
+        let handler = loop {
+            let handler = self.handlers.pop()?;
+
+            if !handler.stop_iteration || stop_iteration {
+                break handler;
+            }
+        };
+
+        self.frames.truncate(handler.frame_base);
+        self.scopes.truncate(handler.scope_base);
+        while self.stack.ptr > handler.stack_base {
+            self.stack.pop();
+        }
+
+        if handler.stop_iteration {
+            let o = self.load_bool(false);
+            self.push(o);
+        } else {
+            self.push(error);
+        }
+        Some(handler.target)
+    }
+
+    /// Enters a new frame for a call made by the instruction at `ret`,
+    /// along with the first scope of the called function.
+    pub fn enter_frame(&mut self, ret: AddrRepr) {
+        self.frames.push(Frame {
+            ret,
+            scope_base: self.scopes.len(),
+            stack_base: self.stack.ptr,
+            iter_next: false,
+        });
+        self.enter_scope();
+    }
+
+    /// Leaves the current frame, discarding its scopes and whatever it left on the stack,
+    /// then pushes its return value. Returns the address of the instruction that made the
+    /// call, or `None` if no function is being called.
+    pub fn exit_frame(&mut self, value: ObjectRef) -> Option<AddrRepr> {
+        let frame = self.frames.pop();
+
+        if let Some(frame) = frame {
+            self.scopes.truncate(frame.scope_base);
+            self.drop_handlers();
+
+            while self.stack.ptr > frame.stack_base {
+                self.stack.pop();
+            }
+        }
+
+        self.push(value);
+        if frame.is_some_and(|f| f.iter_next) {
+            let o = self.load_bool(true);
+            self.push(o);
+        }
+        frame.map(|f| f.ret)
     }
 }
 
@@ -474,6 +978,31 @@ impl<const STACK_SIZE: usize> Interpreter<STACK_SIZE> {
             TerbiumObject::Float(EqComparableFloat(f)) => *f != 0_f64,
             TerbiumObject::String(s) => !self.string_interner.lookup(*s).is_empty(),
             TerbiumObject::Null => false,
+            TerbiumObject::Array(a) => !self.ctx.array(*a).is_empty(),
+            TerbiumObject::Range(start, end) => start < end,
+            TerbiumObject::Iterator(_)
+            | TerbiumObject::Module(_)
+            | TerbiumObject::Func(_)
+            | TerbiumObject::Method(..)
+            | TerbiumObject::Class(_)
+            | TerbiumObject::Instance(_)
+            | TerbiumObject::Builtin(_) => true,
+        }
+    }
+
+    #[must_use]
+    /// Compares two arrays element by element.
+    pub fn arrays_eq(&self, lhs: ArrayId, rhs: ArrayId) -> bool {
+        let (lhs, rhs) = (self.ctx.array(lhs), self.ctx.array(rhs));
+
+        lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(a, b)| self.items_eq(*a, *b))
+    }
+
+    /// Compares two items of an array, comparing arrays element by element.
+    fn items_eq(&self, lhs: ObjectRef, rhs: ObjectRef) -> bool {
+        match (self.ctx.store.resolve(lhs), self.ctx.store.resolve(rhs)) {
+            (TerbiumObject::Array(a), TerbiumObject::Array(b)) => self.arrays_eq(*a, *b),
+            (a, b) => a == b,
         }
     }
 
@@ -502,7 +1031,6 @@ impl<const STACK_SIZE: usize> Interpreter<STACK_SIZE> {
     /// to prevent this behavior from being executed.
     pub fn run_bytecode(&mut self, code: &Program) {
         let mut pos: AddrRepr = 0;
-        let mut jump_history: Vec<AddrRepr> = Vec::new();
         let instructions = code.inner().collect::<Vec<_>>();
 
         loop {
@@ -524,17 +1052,32 @@ impl<const STACK_SIZE: usize> Interpreter<STACK_SIZE> {
                     push!(self.ctx, store_auto!(self.ctx, TerbiumObject::Float(f)));
                 }
                 Instruction::LoadBool(b) => push!(self.ctx, load_bool!(self.ctx, b)),
+                Instruction::LoadNull => self.ctx.push(0), // 0 is null
                 Instruction::UnOpPos => match self.ctx.pop_detailed() {
                     (o, TerbiumObject::Integer(_) | TerbiumObject::Float(_)) => self.ctx.push(o),
-                    _ => todo!(),
+                    (_, &o) => {
+                        let message =
+                            format!("unsupported operand for +: {}", self.get_object_repr(&o));
+                        let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                        pos = self.throw(code, error, pos);
+                        continue;
+                    }
                 },
-                Instruction::UnOpNeg => match self.ctx.pop() {
-                    TerbiumObject::Integer(i) => push!(self.ctx, load_int!(self.ctx, -*i)),
+                Instruction::UnOpNeg => match *self.ctx.pop() {
+                    TerbiumObject::Integer(i) => push!(self.ctx, load_int!(self.ctx, -i)),
                     TerbiumObject::Float(f) => push!(
                         self.ctx,
                         store_auto!(self.ctx, TerbiumObject::Float((-f.0).into()))
                     ),
-                    _ => todo!(),
+                    o => {
+                        let message =
+                            format!("unsupported operand for -: {}", self.get_object_repr(&o));
+                        let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                        pos = self.throw(code, error, pos);
+                        continue;
+                    }
                 },
                 Instruction::BinOpAdd => pat_num_ops!(
                     self.ctx, lhs, rhs;
@@ -552,8 +1095,12 @@ impl<const STACK_SIZE: usize> Interpreter<STACK_SIZE> {
 
                         self.ctx.push(loc);
                     },
-                    _ => {
-                        // TODO: Call op function, raise error if not found
+                    (rhs, lhs) => {
+                        let message = self.unsupported_operands("+", lhs, rhs);
+                        let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                        pos = self.throw(code, error, pos);
+                        continue;
                     }
                 ),
                 Instruction::BinOpSub => pat_num_ops!(
@@ -562,16 +1109,20 @@ impl<const STACK_SIZE: usize> Interpreter<STACK_SIZE> {
                     store_auto!(self.ctx, TerbiumObject::Float((lhs.0 - rhs.0).into())),
                     store_auto!(self.ctx, TerbiumObject::Float((*lhs as f64 - rhs.0).into())),
                     store_auto!(self.ctx, TerbiumObject::Float((lhs.0 - *rhs as f64).into()));
-                    _ => {
-                        // TODO
+                    (rhs, lhs) => {
+                        let message = self.unsupported_operands("-", lhs, rhs);
+                        let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                        pos = self.throw(code, error, pos);
+                        continue;
                     }
                 ),
                 Instruction::BinOpMul => pat_num_ops!(
                     self.ctx, lhs, rhs;
                     load_int!(self.ctx, *lhs * *rhs),
                     store_auto!(self.ctx, TerbiumObject::Float((lhs.0 * rhs.0).into())),
-                    store_auto!(self.ctx, TerbiumObject::Float((*lhs as f64 - rhs.0).into())),
-                    store_auto!(self.ctx, TerbiumObject::Float((lhs.0 - *rhs as f64).into()));
+                    store_auto!(self.ctx, TerbiumObject::Float((*lhs as f64 * rhs.0).into())),
+                    store_auto!(self.ctx, TerbiumObject::Float((lhs.0 * *rhs as f64).into()));
                     (TerbiumObject::Integer(rhs), TerbiumObject::String(lhs)) => {
                         let loc = store_auto!(self.ctx, TerbiumObject::String(
                             self.string_interner.intern(
@@ -581,10 +1132,188 @@ impl<const STACK_SIZE: usize> Interpreter<STACK_SIZE> {
                         ));
                         self.ctx.push(loc);
                     },
-                    _ => {
-                        // TODO
+                    (rhs, lhs) => {
+                        let message = self.unsupported_operands("*", lhs, rhs);
+                        let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                        pos = self.throw(code, error, pos);
+                        continue;
+                    }
+                ),
+                Instruction::BinOpDiv => pat_num_ops!(
+                    self.ctx, lhs, rhs;
+                    {
+                        if *rhs == 0 {
+                            let error = self.make_error(ZERO_DIVISION_ERROR_CLASS, "division by zero");
+
+                            pos = self.throw(code, error, pos);
+                            continue;
+                        }
+                        load_int!(self.ctx, floor_div(*lhs, *rhs))
+                    },
+                    store_auto!(self.ctx, TerbiumObject::Float((lhs.0 / rhs.0).into())),
+                    store_auto!(self.ctx, TerbiumObject::Float((*lhs as f64 / rhs.0).into())),
+                    store_auto!(self.ctx, TerbiumObject::Float((lhs.0 / *rhs as f64).into()));
+                    (rhs, lhs) => {
+                        let message = self.unsupported_operands("/", lhs, rhs);
+                        let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                        pos = self.throw(code, error, pos);
+                        continue;
+                    }
+                ),
+                Instruction::BinOpTrueDiv => pat_num_ops!(
+                    self.ctx, lhs, rhs;
+                    store_auto!(self.ctx, TerbiumObject::Float((*lhs as f64 / *rhs as f64).into())),
+                    store_auto!(self.ctx, TerbiumObject::Float((lhs.0 / rhs.0).into())),
+                    store_auto!(self.ctx, TerbiumObject::Float((*lhs as f64 / rhs.0).into())),
+                    store_auto!(self.ctx, TerbiumObject::Float((lhs.0 / *rhs as f64).into()));
+                    (rhs, lhs) => {
+                        let message = self.unsupported_operands("/.", lhs, rhs);
+                        let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                        pos = self.throw(code, error, pos);
+                        continue;
+                    }
+                ),
+                Instruction::BinOpMod => pat_num_ops!(
+                    self.ctx, lhs, rhs;
+                    {
+                        if *rhs == 0 {
+                            let error = self.make_error(ZERO_DIVISION_ERROR_CLASS, "modulo by zero");
+
+                            pos = self.throw(code, error, pos);
+                            continue;
+                        }
+                        load_int!(self.ctx, floor_mod(*lhs, *rhs))
+                    },
+                    store_auto!(self.ctx, TerbiumObject::Float(float_mod(lhs.0, rhs.0).into())),
+                    store_auto!(self.ctx, TerbiumObject::Float(float_mod(*lhs as f64, rhs.0).into())),
+                    store_auto!(self.ctx, TerbiumObject::Float(float_mod(lhs.0, *rhs as f64).into()));
+                    (rhs, lhs) => {
+                        let message = self.unsupported_operands("%", lhs, rhs);
+                        let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                        pos = self.throw(code, error, pos);
+                        continue;
+                    }
+                ),
+                Instruction::BinOpPow => pat_num_ops!(
+                    self.ctx, lhs, rhs;
+                    {
+                        if *lhs == 0 && *rhs < 0 {
+                            let error = self.make_error(
+                                ZERO_DIVISION_ERROR_CLASS,
+                                "zero raised to a negative power",
+                            );
+
+                            pos = self.throw(code, error, pos);
+                            continue;
+                        }
+                        match int_pow(*lhs, *rhs) {
+                            Some(pow) => load_int!(self.ctx, pow),
+                            None => {
+                                let message = format!("{} ** {} overflows an int", lhs, rhs);
+                                let error = self.make_error(VALUE_ERROR_CLASS, &message);
+
+                                pos = self.throw(code, error, pos);
+                                continue;
+                            }
+                        }
+                    },
+                    store_auto!(self.ctx, TerbiumObject::Float(lhs.0.powf(rhs.0).into())),
+                    store_auto!(self.ctx, TerbiumObject::Float((*lhs as f64).powf(rhs.0).into())),
+                    store_auto!(self.ctx, TerbiumObject::Float(lhs.0.powf(*rhs as f64).into()));
+                    (rhs, lhs) => {
+                        let message = self.unsupported_operands("**", lhs, rhs);
+                        let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                        pos = self.throw(code, error, pos);
+                        continue;
                     }
                 ),
+                instr @ (Instruction::BinOpBitOr
+                | Instruction::BinOpBitXor
+                | Instruction::BinOpBitAnd
+                | Instruction::BinOpLShift
+                | Instruction::BinOpRShift) => {
+                    let rhs = self.ctx.pop_ref();
+                    let lhs = self.ctx.pop_ref();
+
+                    let result = match (self.ctx.store.resolve(lhs), self.ctx.store.resolve(rhs)) {
+                        (TerbiumObject::Integer(lhs), TerbiumObject::Integer(rhs)) => {
+                            let (lhs, rhs) = (*lhs, *rhs);
+
+                            if rhs < 0
+                                && matches!(
+                                    instr,
+                                    Instruction::BinOpLShift | Instruction::BinOpRShift
+                                )
+                            {
+                                let message = format!("negative shift count {}", rhs);
+                                let error = self.make_error(VALUE_ERROR_CLASS, &message);
+
+                                pos = self.throw(code, error, pos);
+                                continue;
+                            }
+                            load_int!(
+                                self.ctx,
+                                match instr {
+                                    Instruction::BinOpBitOr => lhs | rhs,
+                                    Instruction::BinOpBitXor => lhs ^ rhs,
+                                    Instruction::BinOpBitAnd => lhs & rhs,
+                                    _ => shift(lhs, rhs, instr == Instruction::BinOpLShift),
+                                }
+                            )
+                        }
+                        (TerbiumObject::Bool(lhs), TerbiumObject::Bool(rhs))
+                            if !matches!(
+                                instr,
+                                Instruction::BinOpLShift | Instruction::BinOpRShift
+                            ) =>
+                        {
+                            let (lhs, rhs) = (*lhs, *rhs);
+
+                            load_bool!(
+                                self.ctx,
+                                match instr {
+                                    Instruction::BinOpBitOr => lhs | rhs,
+                                    Instruction::BinOpBitXor => lhs ^ rhs,
+                                    _ => lhs & rhs,
+                                }
+                            )
+                        }
+                        (lhs, rhs) => {
+                            let message = self.unsupported_operands(
+                                match instr {
+                                    Instruction::BinOpBitOr => "|",
+                                    Instruction::BinOpBitXor => "^",
+                                    Instruction::BinOpBitAnd => "&",
+                                    Instruction::BinOpLShift => "<<",
+                                    _ => ">>",
+                                },
+                                lhs,
+                                rhs,
+                            );
+                            let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                            pos = self.throw(code, error, pos);
+                            continue;
+                        }
+                    };
+                    self.ctx.push(result);
+                }
+                Instruction::UnOpBitNot => match *self.ctx.pop() {
+                    TerbiumObject::Integer(i) => push!(self.ctx, load_int!(self.ctx, !i)),
+                    o => {
+                        let message =
+                            format!("unsupported operand for ~: {}", self.get_object_repr(&o));
+                        let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                        pos = self.throw(code, error, pos);
+                        continue;
+                    }
+                },
                 #[allow(unused_parens)]
                 Instruction::OpEq => pat_num_ops!(
                     self.ctx, lhs, rhs;
@@ -609,8 +1338,13 @@ impl<const STACK_SIZE: usize> Interpreter<STACK_SIZE> {
                     ((TerbiumObject::Null, _) | (_, TerbiumObject::Null)) => {
                         push!(self.ctx, load_bool!(self.ctx, false));
                     },
+                    (TerbiumObject::Array(rhs), TerbiumObject::Array(lhs)) => {
+                        let (lhs, rhs) = (*lhs, *rhs);
+                        push!(self.ctx, load_bool!(self.ctx, self.arrays_eq(lhs, rhs)));
+                    },
                     _ => {
-                        // TODO
+                        // TODO: Call op function, objects of differing types are never equal otherwise
+                        push!(self.ctx, load_bool!(self.ctx, false));
                     }
                 ),
                 #[allow(unused_parens)]
@@ -637,38 +1371,151 @@ impl<const STACK_SIZE: usize> Interpreter<STACK_SIZE> {
                     ((TerbiumObject::Null, _) | (_, TerbiumObject::Null)) => {
                         push!(self.ctx, load_bool!(self.ctx, true));
                     },
+                    (TerbiumObject::Array(rhs), TerbiumObject::Array(lhs)) => {
+                        let (lhs, rhs) = (*lhs, *rhs);
+                        push!(self.ctx, load_bool!(self.ctx, !self.arrays_eq(lhs, rhs)));
+                    },
                     _ => {
-                        // TODO
+                        // TODO: Call op function, objects of differing types are never equal otherwise
+                        push!(self.ctx, load_bool!(self.ctx, true));
                     }
                 ),
-                Instruction::OpLogicalNot => {
-                    let subject = self.ctx.pop_ref();
-                    let subject = self.ctx.store.resolve(subject);
+                Instruction::OpLt => pat_num_ops!(
+                    self.ctx, lhs, rhs;
+                    load_bool!(self.ctx, *lhs < *rhs),
+                    load_bool!(self.ctx, lhs.0 < rhs.0),
+                    load_bool!(self.ctx, (*lhs as f64) < rhs.0),
+                    load_bool!(self.ctx, lhs.0 < *rhs as f64);
+                    (TerbiumObject::String(rhs), TerbiumObject::String(lhs)) => {
+                        let b = load_bool!(self.ctx,
+                            self.string_interner.lookup(*lhs)
+                            < self.string_interner.lookup(*rhs)
+                        );
+                        self.ctx.push(b);
+                    },
+                    (rhs, lhs) => {
+                        let message = self.unsupported_operands("<", lhs, rhs);
+                        let error = self.make_error(TYPE_ERROR_CLASS, &message);
 
-                    match subject {
-                        TerbiumObject::Bool(b) => push!(self.ctx, load_bool!(self.ctx, !*b)),
-                        // TODO: support custom not operation
-                        o => push!(self.ctx, load_bool!(self.ctx, !self.is_truthy(o))),
-                    }
-                }
-                Instruction::Pop => {
-                    self.ctx.pop_ref();
-                }
-                Instruction::Jump(addr) => match addr {
-                    Addr::Absolute(a) => {
-                        jump_history.push(pos);
-                        pos = a;
+                        pos = self.throw(code, error, pos);
                         continue;
                     }
-                    _ => panic!("attempted to run unresolved bytecode"),
-                },
-                Instruction::JumpIf(addr) => match addr {
-                    Addr::Absolute(a) => {
-                        let popped = self.ctx.pop_ref();
-                        let popped = self.ctx.store.resolve(popped);
+                ),
+                Instruction::OpLe => pat_num_ops!(
+                    self.ctx, lhs, rhs;
+                    load_bool!(self.ctx, *lhs <= *rhs),
+                    load_bool!(self.ctx, lhs.0 <= rhs.0),
+                    load_bool!(self.ctx, (*lhs as f64) <= rhs.0),
+                    load_bool!(self.ctx, lhs.0 <= *rhs as f64);
+                    (TerbiumObject::String(rhs), TerbiumObject::String(lhs)) => {
+                        let b = load_bool!(self.ctx,
+                            self.string_interner.lookup(*lhs)
+                            <= self.string_interner.lookup(*rhs)
+                        );
+                        self.ctx.push(b);
+                    },
+                    (rhs, lhs) => {
+                        let message = self.unsupported_operands("<=", lhs, rhs);
+                        let error = self.make_error(TYPE_ERROR_CLASS, &message);
 
-                        if self.is_truthy(popped) {
-                            jump_history.push(pos);
+                        pos = self.throw(code, error, pos);
+                        continue;
+                    }
+                ),
+                Instruction::OpGt => pat_num_ops!(
+                    self.ctx, lhs, rhs;
+                    load_bool!(self.ctx, *lhs > *rhs),
+                    load_bool!(self.ctx, lhs.0 > rhs.0),
+                    load_bool!(self.ctx, (*lhs as f64) > rhs.0),
+                    load_bool!(self.ctx, lhs.0 > *rhs as f64);
+                    (TerbiumObject::String(rhs), TerbiumObject::String(lhs)) => {
+                        let b = load_bool!(self.ctx,
+                            self.string_interner.lookup(*lhs)
+                            > self.string_interner.lookup(*rhs)
+                        );
+                        self.ctx.push(b);
+                    },
+                    (rhs, lhs) => {
+                        let message = self.unsupported_operands(">", lhs, rhs);
+                        let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                        pos = self.throw(code, error, pos);
+                        continue;
+                    }
+                ),
+                Instruction::OpGe => pat_num_ops!(
+                    self.ctx, lhs, rhs;
+                    load_bool!(self.ctx, *lhs >= *rhs),
+                    load_bool!(self.ctx, lhs.0 >= rhs.0),
+                    load_bool!(self.ctx, (*lhs as f64) >= rhs.0),
+                    load_bool!(self.ctx, lhs.0 >= *rhs as f64);
+                    (TerbiumObject::String(rhs), TerbiumObject::String(lhs)) => {
+                        let b = load_bool!(self.ctx,
+                            self.string_interner.lookup(*lhs)
+                            >= self.string_interner.lookup(*rhs)
+                        );
+                        self.ctx.push(b);
+                    },
+                    (rhs, lhs) => {
+                        let message = self.unsupported_operands(">=", lhs, rhs);
+                        let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                        pos = self.throw(code, error, pos);
+                        continue;
+                    }
+                ),
+                Instruction::OpLogicalNot => {
+                    let subject = self.ctx.pop_ref();
+                    let subject = self.ctx.store.resolve(subject);
+
+                    match subject {
+                        TerbiumObject::Bool(b) => push!(self.ctx, load_bool!(self.ctx, !*b)),
+                        // TODO: support custom not operation
+                        o => push!(self.ctx, load_bool!(self.ctx, !self.is_truthy(o))),
+                    }
+                }
+                Instruction::OpLogicalOr | Instruction::OpLogicalAnd => {
+                    let rhs = self.ctx.pop_ref();
+                    let lhs = self.ctx.pop_ref();
+                    let (lhs, rhs) = (
+                        self.is_truthy(self.ctx.store.resolve(lhs)),
+                        self.is_truthy(self.ctx.store.resolve(rhs)),
+                    );
+
+                    let result = if *instr == Instruction::OpLogicalOr {
+                        lhs || rhs
+                    } else {
+                        lhs && rhs
+                    };
+                    push!(self.ctx, load_bool!(self.ctx, result));
+                }
+                Instruction::Pop => {
+                    self.ctx.pop_ref();
+                }
+                Instruction::Jump(addr) => match addr {
+                    Addr::Absolute(a) => {
+                        pos = a;
+                        continue;
+                    }
+                    _ => panic!("attempted to run unresolved bytecode"),
+                },
+                Instruction::JumpIf(addr) => match addr {
+                    Addr::Absolute(a) => {
+                        let popped = self.ctx.pop_ref();
+                        let popped = self.ctx.store.resolve(popped);
+
+                        if self.is_truthy(popped) {
+                            pos = a;
+                            continue;
+                        }
+                    }
+                    _ => panic!("attempted to run unresolved bytecode"),
+                },
+                Instruction::JumpIfNotNull(addr) => match addr {
+                    Addr::Absolute(a) => {
+                        let top = self.ctx.store.resolve(self.ctx.peek_ref());
+
+                        if !matches!(top, TerbiumObject::Null) {
                             pos = a;
                             continue;
                         }
@@ -677,7 +1524,6 @@ impl<const STACK_SIZE: usize> Interpreter<STACK_SIZE> {
                 },
                 Instruction::JumpIfElse(then, fb) => match (then, fb) {
                     (Addr::Absolute(then), Addr::Absolute(fb)) => {
-                        jump_history.push(pos);
                         let popped = self.ctx.pop_ref();
                         let popped = self.ctx.store.resolve(popped);
 
@@ -686,16 +1532,14 @@ impl<const STACK_SIZE: usize> Interpreter<STACK_SIZE> {
                     }
                     _ => panic!("attempted to run unresolved bytecode"),
                 },
-                Instruction::Ret => {
-                    if let Some(back) = jump_history.pop() {
-                        pos = back;
-                    } else {
-                        break;
-                    }
-                }
-                Instruction::RetNull => {
-                    self.ctx.push(0); // 0 is null
-                    if let Some(back) = jump_history.pop() {
+                // Jumps are plain gotos; only calls record where to return to.
+                ref instr @ (Instruction::Ret | Instruction::RetNull) => {
+                    let value = match instr {
+                        Instruction::Ret => self.ctx.pop_ref(),
+                        _ => 0, // 0 is null
+                    };
+
+                    if let Some(back) = self.ctx.exit_frame(value) {
                         pos = back;
                     } else {
                         break;
@@ -709,8 +1553,8 @@ impl<const STACK_SIZE: usize> Interpreter<STACK_SIZE> {
                 Instruction::LoadVar(key) => {
                     push!(
                         self.ctx,
-                        match self.ctx.lookup_var(key) {
-                            Some(ScopeEntry { loc, .. }) => *loc,
+                        match self.ctx.load_var(key) {
+                            Some(loc) => loc,
                             None => {
                                 todo!(); // TODO error if variable is not found
                             }
@@ -725,7 +1569,7 @@ impl<const STACK_SIZE: usize> Interpreter<STACK_SIZE> {
                     self.ctx.store_var(
                         key,
                         ScopeEntry {
-                            loc,
+                            binding: Binding::Local(loc),
                             r#mut: matches!(instr, Instruction::StoreMutVar(_)),
                             r#const: matches!(instr, Instruction::StoreConstVar(_)),
                         },
@@ -736,6 +1580,681 @@ impl<const STACK_SIZE: usize> Interpreter<STACK_SIZE> {
 
                     self.ctx.assign_var(key, loc);
                 }
+                Instruction::InitVar(key) => {
+                    let loc = self.ctx.pop_ref();
+
+                    self.ctx.init_var(key, loc);
+                }
+                Instruction::MakeArray(len) => {
+                    let mut elements = (0..len).map(|_| self.ctx.pop_ref()).collect::<Vec<_>>();
+                    elements.reverse();
+
+                    push!(self.ctx, self.ctx.store_array(elements));
+                }
+                Instruction::Index => {
+                    let index = self.ctx.pop_ref();
+                    let subject = self.ctx.pop_ref();
+
+                    if let TerbiumObject::Instance(_) = self.ctx.store.resolve(subject) {
+                        match self.call_op(subject, "index", vec![index], pos) {
+                            Ok(Some(addr)) => {
+                                pos = addr;
+                                continue;
+                            }
+                            Ok(None) => (),
+                            Err(error) => {
+                                pos = self.throw(code, error, pos);
+                                continue;
+                            }
+                        }
+                    } else {
+                        match self.get_item(subject, index) {
+                            Ok(item) => self.ctx.push(item),
+                            Err(error) => {
+                                pos = self.throw(code, error, pos);
+                                continue;
+                            }
+                        }
+                    }
+                }
+                Instruction::Slice => {
+                    let end = self.ctx.pop_ref();
+                    let start = self.ctx.pop_ref();
+                    let subject = self.ctx.pop_ref();
+
+                    if let TerbiumObject::Instance(_) = self.ctx.store.resolve(subject) {
+                        match self.call_op(subject, "slice", vec![start, end], pos) {
+                            Ok(Some(addr)) => {
+                                pos = addr;
+                                continue;
+                            }
+                            Ok(None) => (),
+                            Err(error) => {
+                                pos = self.throw(code, error, pos);
+                                continue;
+                            }
+                        }
+                    } else {
+                        let [start, end] =
+                            [start, end].map(|bound| match self.ctx.store.resolve(bound) {
+                                TerbiumObject::Null => Ok(None),
+                                TerbiumObject::Integer(i) => Ok(Some(*i)),
+                                _ => Err(bound),
+                            });
+
+                        let slice = match (start, end) {
+                            (Ok(start), Ok(end)) => self.slice(subject, start, end),
+                            (Err(bound), _) | (_, Err(bound)) => {
+                                let message = format!(
+                                    "slice bounds must be integers, not {}",
+                                    self.get_object_repr(self.ctx.store.resolve(bound))
+                                );
+                                Err(self.make_error(TYPE_ERROR_CLASS, &message))
+                            }
+                        };
+                        match slice {
+                            Ok(slice) => self.ctx.push(slice),
+                            Err(error) => {
+                                pos = self.throw(code, error, pos);
+                                continue;
+                            }
+                        }
+                    }
+                }
+                Instruction::StoreIndex => {
+                    let index = self.ctx.pop_ref();
+                    let subject = self.ctx.pop_ref();
+                    let value = self.ctx.pop_ref();
+
+                    match (
+                        *self.ctx.store.resolve(subject),
+                        self.ctx.store.resolve(index),
+                    ) {
+                        (TerbiumObject::Instance(_), _) => {
+                            match self.call_op(subject, "set_index", vec![index, value], pos) {
+                                Ok(Some(addr)) => {
+                                    pos = addr;
+                                    continue;
+                                }
+                                Ok(None) => (),
+                                Err(error) => {
+                                    pos = self.throw(code, error, pos);
+                                    continue;
+                                }
+                            }
+                        }
+                        (TerbiumObject::Array(a), TerbiumObject::Integer(i)) => {
+                            let len = self.ctx.array(a).len();
+
+                            match resolve_index(len, *i) {
+                                Some(i) => {
+                                    self.ctx.array_mut(a)[i] = value;
+                                    self.ctx.push(0); // 0 is null
+                                }
+                                None => {
+                                    let error = self.index_error(*i, len);
+
+                                    pos = self.throw(code, error, pos);
+                                    continue;
+                                }
+                            }
+                        }
+                        (o, _) => {
+                            let message = format!(
+                                "items of {} can not be assigned to",
+                                self.get_object_repr(&o)
+                            );
+                            let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                            pos = self.throw(code, error, pos);
+                            continue;
+                        }
+                    }
+                }
+                Instruction::BinOpRange => {
+                    let end = self.ctx.pop_ref();
+                    let start = self.ctx.pop_ref();
+
+                    match (self.ctx.store.resolve(start), self.ctx.store.resolve(end)) {
+                        (TerbiumObject::Integer(start), TerbiumObject::Integer(end)) => {
+                            let range = TerbiumObject::Range(*start, *end);
+                            push!(self.ctx, store_auto!(self.ctx, range));
+                        }
+                        (start, end) => {
+                            let message = self.unsupported_operands("..", start, end);
+                            let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                            pos = self.throw(code, error, pos);
+                            continue;
+                        }
+                    }
+                }
+                Instruction::GetIter => {
+                    let subject = self.ctx.pop_ref();
+
+                    let iterator = match self.ctx.store.resolve(subject) {
+                        TerbiumObject::Array(array) => NativeIterator::Array {
+                            array: *array,
+                            index: 0,
+                        },
+                        TerbiumObject::Range(start, end) => NativeIterator::Range {
+                            current: *start,
+                            end: *end,
+                        },
+                        TerbiumObject::Iterator(_) => {
+                            self.ctx.push(subject);
+                            pos += 1;
+                            continue;
+                        }
+                        // An instance defining `op next` is its own iterator
+                        TerbiumObject::Instance(_) if self.find_op(subject, "next").is_some() => {
+                            self.ctx.push(subject);
+                            pos += 1;
+                            continue;
+                        }
+                        TerbiumObject::Instance(_) if self.find_op(subject, "iter").is_some() => {
+                            // What `op iter` returns goes through `GetIter` again, so that it
+                            // may return any iterable
+                            match self.call_op(subject, "iter", Vec::new(), pos - 1) {
+                                Ok(Some(addr)) => {
+                                    pos = addr;
+                                    continue;
+                                }
+                                Ok(None) => (),
+                                Err(error) => {
+                                    pos = self.throw(code, error, pos);
+                                    continue;
+                                }
+                            }
+                            pos -= 1;
+                            continue;
+                        }
+                        o => {
+                            let message = format!("{} is not iterable", self.get_object_repr(o));
+                            let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                            pos = self.throw(code, error, pos);
+                            continue;
+                        }
+                    };
+
+                    push!(self.ctx, self.ctx.store_iterator(iterator));
+                }
+                Instruction::IterNext => {
+                    let subject = self.ctx.pop_ref();
+
+                    match *self.ctx.store.resolve(subject) {
+                        TerbiumObject::Iterator(id) => {
+                            if let Some(item) = self.ctx.iter_next(id) {
+                                self.ctx.push(item);
+                                push!(self.ctx, load_bool!(self.ctx, true));
+                            } else {
+                                push!(self.ctx, load_bool!(self.ctx, false));
+                            }
+                        }
+                        // Only instances defining `op next` pass through `GetIter`
+                        _ => match self.call_op(subject, "next", Vec::new(), pos) {
+                            Ok(Some(addr)) => {
+                                self.ctx.catch_iter_next();
+                                pos = addr;
+                                continue;
+                            }
+                            Ok(None) => (),
+                            Err(error) => {
+                                pos = self.throw(code, error, pos);
+                                continue;
+                            }
+                        },
+                    }
+                }
+                Instruction::MatchArray(len) => {
+                    let subject = self.ctx.pop_ref();
+                    let matches = match self.ctx.store.resolve(subject) {
+                        TerbiumObject::Array(a) => self.ctx.array(*a).len() == len,
+                        _ => false,
+                    };
+
+                    push!(self.ctx, load_bool!(self.ctx, matches));
+                }
+                Instruction::MakeModule(len) => {
+                    let mut exports = HashMap::with_capacity(len);
+
+                    for _ in 0..len {
+                        let value = self.ctx.pop_ref();
+                        let key = self.ctx.pop_ref();
+
+                        match *self.ctx.store.resolve(key) {
+                            #[allow(clippy::cast_sign_loss)] // Keys are identifiers, never negative
+                            TerbiumObject::Integer(key) => exports.insert(key as usize, value),
+                            _ => unreachable!("module export keys are always integers"),
+                        };
+                    }
+
+                    push!(self.ctx, self.ctx.store_module(exports));
+                }
+                Instruction::LoadAttr(key) => {
+                    let subject = self.ctx.pop_ref();
+
+                    let attr = match *self.ctx.store.resolve(subject) {
+                        TerbiumObject::Module(id) => self.ctx.module(id).get(&key).copied(),
+                        TerbiumObject::Instance(id) => {
+                            let instance = self.ctx.instance(id);
+
+                            match instance.fields.get(&key) {
+                                Some(&field) => Some(field),
+                                None => self
+                                    .ctx
+                                    .find_member(instance.class, &|c| c.methods.get(&key).copied())
+                                    .map(|method| {
+                                        store_auto!(
+                                            self.ctx,
+                                            TerbiumObject::Method(method, subject)
+                                        )
+                                    }),
+                            }
+                        }
+                        _ => None,
+                    };
+
+                    match attr {
+                        Some(attr) => self.ctx.push(attr),
+                        None => {
+                            let name = instructions[pos as usize]
+                                .name()
+                                .clone()
+                                .unwrap_or_else(|| format!("#{}", key));
+                            let message = format!(
+                                "{} has no attribute {:?}",
+                                self.get_object_repr(self.ctx.store.resolve(subject)),
+                                name,
+                            );
+                            let error = self.make_error(ATTRIBUTE_ERROR_CLASS, &message);
+
+                            pos = self.throw(code, error, pos);
+                            continue;
+                        }
+                    }
+                }
+                Instruction::LoadOp(name) => {
+                    let subject = self.ctx.pop_ref();
+
+                    match self.load_op(subject, &name) {
+                        Ok(op) => self.ctx.push(op),
+                        Err(error) => {
+                            pos = self.throw(code, error, pos);
+                            continue;
+                        }
+                    }
+                }
+                Instruction::StoreAttr(key) => {
+                    let subject = self.ctx.pop_ref();
+                    let value = self.ctx.pop_ref();
+
+                    match *self.ctx.store.resolve(subject) {
+                        TerbiumObject::Instance(id) => {
+                            self.ctx.instance_mut(id).fields.insert(key, value);
+                        }
+                        ref o => {
+                            let message = format!(
+                                "attributes of {} can not be assigned to",
+                                self.get_object_repr(o)
+                            );
+                            let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                            pos = self.throw(code, error, pos);
+                            continue;
+                        }
+                    }
+                }
+                Instruction::BuildString(len) => {
+                    let mut parts = Vec::with_capacity(len);
+                    for _ in 0..len {
+                        let o = self.ctx.pop_ref();
+                        parts.push(self.get_object_str(self.ctx.store.resolve(o)));
+                    }
+                    parts.reverse();
+
+                    push!(
+                        self.ctx,
+                        store_auto!(
+                            self.ctx,
+                            TerbiumObject::String(self.string_interner.intern(&parts.concat()))
+                        )
+                    );
+                }
+                Instruction::FormatValue(spec) => {
+                    let value = *self.ctx.pop();
+                    let s = self.format_object(&value, &spec);
+
+                    push!(
+                        self.ctx,
+                        store_auto!(
+                            self.ctx,
+                            TerbiumObject::String(self.string_interner.intern(&s))
+                        )
+                    );
+                }
+                Instruction::UnpackModule => {
+                    let subject = self.ctx.pop_ref();
+
+                    match *self.ctx.store.resolve(subject) {
+                        TerbiumObject::Module(id) => {
+                            let exports = self.ctx.module(id).clone();
+
+                            for (key, loc) in exports {
+                                self.ctx.store_var(
+                                    key,
+                                    ScopeEntry {
+                                        binding: Binding::Local(loc),
+                                        r#mut: false,
+                                        r#const: false,
+                                    },
+                                );
+                            }
+                        }
+                        o => {
+                            let message = format!("{} is not a module", self.get_object_repr(&o));
+                            let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                            pos = self.throw(code, error, pos);
+                            continue;
+                        }
+                    }
+                }
+                Instruction::MakeFunc(addr) => match addr {
+                    Addr::Absolute(addr) => push!(
+                        self.ctx,
+                        self.ctx.store_func(Function {
+                            addr,
+                            params: Vec::new(),
+                            defaults: Vec::new(),
+                            captures: Vec::new(),
+                        })
+                    ),
+                    _ => panic!("attempted to run unresolved bytecode"),
+                },
+                Instruction::MakeParam(spec) => {
+                    let default = spec.default.then(|| self.ctx.pop_ref());
+                    let subject = self.ctx.pop_ref();
+
+                    match *self.ctx.store.resolve(subject) {
+                        TerbiumObject::Func(id) => {
+                            let func = self.ctx.func_mut(id);
+                            func.params.push(spec);
+                            func.defaults.push(default);
+                        }
+                        _ => unreachable!("only functions have parameters"),
+                    }
+
+                    self.ctx.push(subject);
+                }
+                Instruction::CaptureVar(key) => {
+                    let subject = self.ctx.pop_ref();
+
+                    match *self.ctx.store.resolve(subject) {
+                        TerbiumObject::Func(id) => {
+                            // Names which are not declared yet are looked up when called instead
+                            if let Some(entry) = self.ctx.capture_var(key) {
+                                self.ctx.func_mut(id).captures.push((key, entry));
+                            }
+                        }
+                        _ => unreachable!("only functions capture variables"),
+                    }
+
+                    self.ctx.push(subject);
+                }
+                Instruction::CallFunc(argc, kwargc) => {
+                    let mut kwargs = (0..kwargc)
+                        .map(|_| {
+                            let value = self.ctx.pop_ref();
+                            (self.ctx.pop_ref(), value)
+                        })
+                        .collect::<Vec<_>>();
+                    kwargs.reverse();
+                    let mut args = (0..argc).map(|_| self.ctx.pop_ref()).collect::<Vec<_>>();
+                    args.reverse();
+                    let subject = self.ctx.pop_ref();
+
+                    match self.call(subject, args, kwargs, pos) {
+                        Ok(Some(addr)) => {
+                            pos = addr;
+                            continue;
+                        }
+                        Ok(None) => (),
+                        Err(error) => {
+                            pos = self.throw(code, error, pos);
+                            continue;
+                        }
+                    }
+                }
+                Instruction::CallFuncUnpacked(argc, kwargc) => {
+                    let unpacked = self.pop_unpacked_args(argc, kwargc);
+                    let subject = self.ctx.pop_ref();
+                    let result =
+                        unpacked.and_then(|(args, kwargs)| self.call(subject, args, kwargs, pos));
+
+                    match result {
+                        Ok(Some(addr)) => {
+                            pos = addr;
+                            continue;
+                        }
+                        Ok(None) => (),
+                        Err(error) => {
+                            pos = self.throw(code, error, pos);
+                            continue;
+                        }
+                    }
+                }
+                Instruction::MakeClass(name, len) => {
+                    let mut bases = (0..len)
+                        .map(|_| match self.ctx.pop_cloned() {
+                            TerbiumObject::Class(id) => id,
+                            // TODO: raise an error instead once there is a way to report it
+                            o => panic!(
+                                "{} can only derive from classes, not {}",
+                                name,
+                                self.get_object_repr(&o)
+                            ),
+                        })
+                        .collect::<Vec<_>>();
+                    bases.reverse();
+
+                    push!(
+                        self.ctx,
+                        self.ctx.store_class(Class {
+                            name,
+                            bases,
+                            methods: HashMap::new(),
+                            ops: HashMap::new(),
+                        })
+                    );
+                }
+                ref instr @ (Instruction::MakeMethod(_) | Instruction::MakeOp(_)) => {
+                    let func = match self.ctx.pop_cloned() {
+                        TerbiumObject::Func(id) => id,
+                        _ => unreachable!("methods are always functions"),
+                    };
+                    let subject = self.ctx.pop_ref();
+
+                    match *self.ctx.store.resolve(subject) {
+                        TerbiumObject::Class(id) => {
+                            let class = self.ctx.class_mut(id);
+
+                            match instr {
+                                Instruction::MakeMethod(key) => class.methods.insert(*key, func),
+                                Instruction::MakeOp(name) => class.ops.insert(name.clone(), func),
+                                _ => unreachable!(),
+                            };
+                        }
+                        _ => unreachable!("only classes have methods"),
+                    }
+
+                    self.ctx.push(subject);
+                }
+                Instruction::IsInstance => {
+                    let class = self.ctx.pop_ref();
+                    let subject = self.ctx.pop_ref();
+
+                    let is_instance = match (
+                        self.ctx.store.resolve(subject),
+                        self.ctx.store.resolve(class),
+                    ) {
+                        (TerbiumObject::Instance(id), TerbiumObject::Class(class)) => {
+                            self.ctx.is_subclass(self.ctx.instance(*id).class, *class)
+                        }
+                        (_, TerbiumObject::Class(_)) => false,
+                        (_, o) => {
+                            let message =
+                                format!("expected a class, got {}", self.get_object_repr(o));
+                            let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                            pos = self.throw(code, error, pos);
+                            continue;
+                        }
+                    };
+
+                    push!(self.ctx, load_bool!(self.ctx, is_instance));
+                }
+                Instruction::IsType(tag) => {
+                    let subject = self.ctx.pop_ref();
+                    let is_type = matches!(
+                        (tag, self.ctx.store.resolve(subject)),
+                        (TypeTag::Int, TerbiumObject::Integer(_))
+                            | (TypeTag::Float, TerbiumObject::Float(_))
+                            | (TypeTag::String, TerbiumObject::String(_))
+                            | (TypeTag::Bool, TerbiumObject::Bool(_))
+                            | (TypeTag::Null, TerbiumObject::Null)
+                            | (TypeTag::Array, TerbiumObject::Array(_))
+                    );
+
+                    push!(self.ctx, load_bool!(self.ctx, is_type));
+                }
+                Instruction::Contains => {
+                    let container = self.ctx.pop_ref();
+                    let item = self.ctx.pop_ref();
+
+                    if let TerbiumObject::Instance(_) = self.ctx.store.resolve(container) {
+                        match self.call_op(container, "contains", vec![item], pos) {
+                            Ok(Some(addr)) => {
+                                pos = addr;
+                                continue;
+                            }
+                            Ok(None) => (),
+                            Err(error) => {
+                                pos = self.throw(code, error, pos);
+                                continue;
+                            }
+                        }
+                    } else {
+                        let contains = match *self.ctx.store.resolve(container) {
+                            TerbiumObject::Array(a) => Ok(self
+                                .ctx
+                                .array(a)
+                                .iter()
+                                .any(|element| self.items_eq(*element, item))),
+                            TerbiumObject::String(s) => match *self.ctx.store.resolve(item) {
+                                TerbiumObject::String(sub) => {
+                                    Ok(self.string_lookup(s).contains(self.string_lookup(sub)))
+                                }
+                                ref o => Err(format!(
+                                    "only a string can be in a string, got {}",
+                                    self.get_object_repr(o)
+                                )),
+                            },
+                            TerbiumObject::Range(start, end) => Ok(matches!(
+                                *self.ctx.store.resolve(item),
+                                TerbiumObject::Integer(i) if (start..end).contains(&i)
+                            )),
+                            ref o => {
+                                Err(format!("{} does not support `in`", self.get_object_repr(o)))
+                            }
+                        };
+
+                        match contains {
+                            Ok(contains) => push!(self.ctx, load_bool!(self.ctx, contains)),
+                            Err(message) => {
+                                let error = self.make_error(TYPE_ERROR_CLASS, &message);
+
+                                pos = self.throw(code, error, pos);
+                                continue;
+                            }
+                        }
+                    }
+                }
+                Instruction::Cast(tag) => {
+                    let subject = self.ctx.pop_ref();
+                    let defines_cast = match *self.ctx.store.resolve(subject) {
+                        TerbiumObject::Instance(id) => {
+                            let class = self.ctx.instance(id).class;
+
+                            self.ctx
+                                .find_member(class, &|c| c.ops.get("cast").copied())
+                                .is_some()
+                        }
+                        _ => false,
+                    };
+
+                    if defines_cast {
+                        let name = self.string_interner.intern(tag.name());
+                        let name = store_auto!(self.ctx, TerbiumObject::String(name));
+
+                        match self.call_op(subject, "cast", vec![name], pos) {
+                            Ok(Some(addr)) => {
+                                pos = addr;
+                                continue;
+                            }
+                            Ok(None) => (),
+                            Err(error) => {
+                                pos = self.throw(code, error, pos);
+                                continue;
+                            }
+                        }
+                    } else {
+                        match self.cast(subject, tag) {
+                            Ok(o) => self.ctx.push(o),
+                            Err(error) => {
+                                pos = self.throw(code, error, pos);
+                                continue;
+                            }
+                        }
+                    }
+                }
+                Instruction::EnterTry(handler) => match code.handler(handler).target {
+                    Addr::Absolute(target) => self.ctx.enter_try(target),
+                    _ => panic!("attempted to run unresolved bytecode"),
+                },
+                Instruction::Throw => {
+                    let mut error = self.ctx.pop_ref();
+
+                    // Throwing a class throws a new instance of it
+                    if let TerbiumObject::Class(class) = *self.ctx.store.resolve(error) {
+                        error = self.ctx.store_instance(Instance {
+                            class,
+                            args: Vec::new(),
+                            fields: HashMap::new(),
+                            raised_at: None,
+                        });
+                    }
+
+                    match self.ctx.store.resolve(error) {
+                        TerbiumObject::Instance(id)
+                            if self
+                                .ctx
+                                .is_subclass(self.ctx.instance(*id).class, ERROR_CLASS) => {}
+                        o => panic!("only errors can be thrown, not {}", self.get_object_repr(o)),
+                    }
+
+                    pos = self.throw(code, error, pos);
+                    continue;
+                }
+                Instruction::AssertNonNull => {
+                    if let TerbiumObject::Null = self.ctx.store.resolve(self.ctx.peek_ref()) {
+                        let error = self.make_error(NULL_ERROR_CLASS, "unexpected null");
+
+                        pos = self.throw(code, error, pos);
+                        continue;
+                    }
+                }
                 _ => todo!(),
             }
 
@@ -743,6 +2262,566 @@ impl<const STACK_SIZE: usize> Interpreter<STACK_SIZE> {
         }
     }
 
+    /// Pops the arrays of a `CallFuncUnpacked` and concatenates them into positional and keyword
+    /// arguments, or returns the error to throw if one of them can not be unpacked.
+    fn pop_unpacked_args(&mut self, argc: usize, kwargc: usize) -> Result<Arguments, ObjectRef> {
+        let mut kw_arrays = (0..kwargc).map(|_| self.ctx.pop_ref()).collect::<Vec<_>>();
+        kw_arrays.reverse();
+        let mut arrays = (0..argc).map(|_| self.ctx.pop_ref()).collect::<Vec<_>>();
+        arrays.reverse();
+
+        let mut args = Vec::new();
+        for array in arrays {
+            match *self.ctx.store.resolve(array) {
+                TerbiumObject::Array(a) => args.extend_from_slice(self.ctx.array(a)),
+                o => {
+                    let message = format!(
+                        "{} can not be unpacked as arguments",
+                        self.get_object_repr(&o)
+                    );
+
+                    return Err(self.make_error(TYPE_ERROR_CLASS, &message));
+                }
+            }
+        }
+
+        let mut kwargs = Vec::new();
+        for array in kw_arrays {
+            let pairs = match *self.ctx.store.resolve(array) {
+                TerbiumObject::Array(a) => self.ctx.array(a).to_vec(),
+                o => {
+                    let message = format!(
+                        "{} can not be unpacked as keyword arguments",
+                        self.get_object_repr(&o)
+                    );
+
+                    return Err(self.make_error(TYPE_ERROR_CLASS, &message));
+                }
+            };
+
+            for pair in pairs {
+                // Each keyword argument is a `[name, value]` array, as bound to `**kwargs`
+                let entry = match *self.ctx.store.resolve(pair) {
+                    TerbiumObject::Array(p) => match *self.ctx.array(p) {
+                        [name, value]
+                            if matches!(self.ctx.store.resolve(name), TerbiumObject::String(_)) =>
+                        {
+                            Some((name, value))
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                };
+
+                match entry {
+                    Some(entry) => kwargs.push(entry),
+                    None => {
+                        let o = *self.ctx.store.resolve(pair);
+                        let message =
+                            format!("{} is not a keyword argument", self.get_object_repr(&o));
+
+                        return Err(self.make_error(TYPE_ERROR_CLASS, &message));
+                    }
+                }
+            }
+        }
+
+        Ok((args, kwargs))
+    }
+
+    /// Calls the function, method or class `subject` with the given arguments. If a frame is
+    /// entered for the call, returns the address to jump to, and the value of the call is
+    /// pushed once the frame returns to `ret`. Otherwise, the value is pushed right away.
+    fn call(
+        &mut self,
+        subject: ObjectRef,
+        mut args: Vec<ObjectRef>,
+        kwargs: Vec<(ObjectRef, ObjectRef)>,
+        ret: AddrRepr,
+    ) -> Result<Option<AddrRepr>, ObjectRef> {
+        // A method is passed the object it is bound to before any other argument
+        let mut argc = args.len();
+        if let TerbiumObject::Method(_, bound) = *self.ctx.store.resolve(subject) {
+            args.insert(0, bound);
+            argc += 1;
+        }
+
+        match *self.ctx.store.resolve(subject) {
+            TerbiumObject::Func(id) | TerbiumObject::Method(id, _) => {
+                let Function {
+                    addr,
+                    params,
+                    defaults,
+                    captures,
+                } = self.ctx.func(id).clone();
+
+                let names = kwargs
+                    .iter()
+                    .map(|(name, _)| match self.ctx.store.resolve(*name) {
+                        TerbiumObject::String(s_id) => self.string_lookup(*s_id),
+                        _ => unreachable!("keyword argument names are always strings"),
+                    })
+                    .collect::<Vec<_>>();
+
+                let bound = match bind_args(&params, argc, &names) {
+                    Ok(bound) => bound,
+                    Err(e) => return Err(self.make_error(TYPE_ERROR_CLASS, &e.to_string())),
+                };
+
+                // Keyword arguments are indexed after every positional argument
+                args.extend(kwargs.iter().map(|(_, value)| *value));
+
+                let values = params
+                    .iter()
+                    .zip(defaults)
+                    .zip(bound)
+                    .map(|((param, default), indices)| match param.kind {
+                        // 0 is null, which omitted optional parameters are bound to
+                        ParamKind::Normal => indices
+                            .first()
+                            .map_or_else(|| default.unwrap_or(0), |&i| args[i]),
+                        ParamKind::Variadic => self
+                            .ctx
+                            .store_array(indices.into_iter().map(|i| args[i]).collect()),
+                        ParamKind::KeywordVariadic => {
+                            let pairs = indices
+                                .into_iter()
+                                .map(|i| {
+                                    let (name, value) = kwargs[i - argc];
+                                    self.ctx.store_array(vec![name, value])
+                                })
+                                .collect();
+
+                            self.ctx.store_array(pairs)
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                self.ctx.enter_frame(ret);
+                for (key, entry) in captures {
+                    self.ctx.store_var(key, entry);
+                }
+                // The body of the function stores its own parameters
+                for value in values {
+                    self.ctx.push(value);
+                }
+
+                Ok(Some(addr))
+            }
+            // TODO: call the constructor once classes can declare one
+            TerbiumObject::Class(class) => {
+                if !kwargs.is_empty() {
+                    let message = format!(
+                        "class {} takes no keyword arguments",
+                        self.ctx.class(class).name
+                    );
+
+                    return Err(self.make_error(TYPE_ERROR_CLASS, &message));
+                }
+
+                push!(
+                    self.ctx,
+                    self.ctx.store_instance(Instance {
+                        class,
+                        args,
+                        fields: HashMap::new(),
+                        raised_at: None,
+                    })
+                );
+                Ok(None)
+            }
+            TerbiumObject::Builtin(index) => self.call_builtin(index, args, kwargs, ret),
+            ref o => {
+                let message = format!("{} is not callable", self.get_object_repr(o));
+
+                Err(self.make_error(TYPE_ERROR_CLASS, &message))
+            }
+        }
+    }
+
+    /// Calls the intrinsic function at the index of `BUILTIN_FUNCS`, see [`Self::call`].
+    ///
+    /// Operator overloads are only looked up on instances, but every object supports `repr` and
+    /// `str`, where the representation of a string is single-quoted.
+    fn call_builtin(
+        &mut self,
+        index: usize,
+        mut args: Vec<ObjectRef>,
+        kwargs: Vec<(ObjectRef, ObjectRef)>,
+        ret: AddrRepr,
+    ) -> Result<Option<AddrRepr>, ObjectRef> {
+        let name = BUILTIN_FUNCS[index];
+        let (arity, variadic) = match name {
+            "__trb_internal_call_op" => (2, true),
+            "__trb_internal_has_op" => (2, false),
+            _ => (1, false),
+        };
+        if args.len() < arity || !variadic && (args.len() > arity || !kwargs.is_empty()) {
+            let message = format!("{} takes {} arguments", name, arity);
+
+            return Err(self.make_error(TYPE_ERROR_CLASS, &message));
+        }
+        let rest = args.split_off(arity);
+        let subject = args[0];
+
+        let result = match name {
+            "__trb_internal_call_op" | "__trb_internal_has_op" => {
+                let op = match *self.ctx.store.resolve(args[1]) {
+                    TerbiumObject::String(id) => self.string_lookup(id).to_string(),
+                    ref o => {
+                        let message = format!(
+                            "the name of an operator must be a string, not {}",
+                            self.get_object_repr(o)
+                        );
+
+                        return Err(self.make_error(TYPE_ERROR_CLASS, &message));
+                    }
+                };
+                let found = self.find_op(subject, &op);
+
+                if name == "__trb_internal_has_op" {
+                    let defined = found.is_some() || op == "repr" || op == "str";
+
+                    load_bool!(self.ctx, defined)
+                } else if let Some(op) = found {
+                    let method = store_auto!(self.ctx, TerbiumObject::Method(op, subject));
+
+                    return self.call(method, rest, kwargs, ret);
+                } else if !rest.is_empty() || !kwargs.is_empty() {
+                    return Err(self.make_error(
+                        TYPE_ERROR_CLASS,
+                        &format!("builtin `op {}` takes no arguments", op),
+                    ));
+                } else {
+                    let s = match (op.as_str(), self.ctx.store.resolve(subject)) {
+                        ("repr", TerbiumObject::String(id)) => {
+                            format!("'{}'", self.string_lookup(*id).replace('\'', "\\'"))
+                        }
+                        ("repr", o) => self.get_object_repr(o),
+                        ("str", o) => self.get_object_str(o),
+                        (_, o) => {
+                            let message =
+                                format!("{} does not define `op {}`", self.get_object_repr(o), op);
+
+                            return Err(self.make_error(TYPE_ERROR_CLASS, &message));
+                        }
+                    };
+                    let s = self.string_interner.intern(&s);
+
+                    store_auto!(self.ctx, TerbiumObject::String(s))
+                }
+            }
+            "__trb_internal_print" => {
+                let s = self.get_object_str(self.ctx.store.resolve(subject));
+                let mut stdout = std::io::stdout();
+
+                // Output that can not be written has nowhere else to be reported
+                let _ = write!(stdout, "{}", s).and_then(|_| stdout.flush());
+                0 // 0 is null
+            }
+            "__trb_internal_floor_num" => match *self.ctx.store.resolve(subject) {
+                TerbiumObject::Integer(i) => load_int!(self.ctx, i),
+                TerbiumObject::Float(EqComparableFloat(f)) => {
+                    load_int!(self.ctx, f.floor() as i128)
+                }
+                ref o => {
+                    let message = format!("{} is not a number", self.get_object_repr(o));
+
+                    return Err(self.make_error(TYPE_ERROR_CLASS, &message));
+                }
+            },
+            // TODO: functions do not keep their names at runtime yet
+            "__trb_internal_name_of" => match *self.ctx.store.resolve(subject) {
+                TerbiumObject::Class(id) => {
+                    let s = self.string_interner.intern(&self.ctx.class(id).name);
+
+                    store_auto!(self.ctx, TerbiumObject::String(s))
+                }
+                _ => 0,
+            },
+            // TODO: builtin types are not first-class objects yet
+            "__trb_internal_type_of" => match *self.ctx.store.resolve(subject) {
+                TerbiumObject::Instance(id) => {
+                    let class = self.ctx.instance(id).class;
+
+                    store_auto!(self.ctx, TerbiumObject::Class(class))
+                }
+                ref o => {
+                    let message = format!(
+                        "the type of {} is not a first-class object",
+                        self.get_object_repr(o)
+                    );
+
+                    return Err(self.make_error(TYPE_ERROR_CLASS, &message));
+                }
+            },
+            _ => unreachable!("{} is not an intrinsic function", name),
+        };
+
+        push!(self.ctx, result);
+        Ok(None)
+    }
+
+    /// Returns the function of the named operator overload of the object, if it defines one.
+    fn find_op(&self, subject: ObjectRef, name: &str) -> Option<FuncId> {
+        match *self.ctx.store.resolve(subject) {
+            TerbiumObject::Instance(id) => {
+                let class = self.ctx.instance(id).class;
+
+                self.ctx.find_member(class, &|c| c.ops.get(name).copied())
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the named operator overload of the object, bound to it.
+    ///
+    /// # Panics
+    /// - The object does not define the operator
+    fn load_op(&mut self, subject: ObjectRef, name: &str) -> Result<ObjectRef, ObjectRef> {
+        match self.find_op(subject, name) {
+            Some(op) => Ok(store_auto!(self.ctx, TerbiumObject::Method(op, subject))),
+            None => {
+                let message = format!(
+                    "{} does not define `op {}`",
+                    self.get_object_repr(self.ctx.store.resolve(subject)),
+                    name
+                );
+
+                Err(self.make_error(TYPE_ERROR_CLASS, &message))
+            }
+        }
+    }
+
+    /// Calls the named operator overload of the object with the arguments, see [`Self::call`].
+    fn call_op(
+        &mut self,
+        subject: ObjectRef,
+        name: &str,
+        args: Vec<ObjectRef>,
+        ret: AddrRepr,
+    ) -> Result<Option<AddrRepr>, ObjectRef> {
+        let op = self.load_op(subject, name)?;
+
+        self.call(op, args, Vec::new(), ret)
+    }
+
+    /// Converts the object to the builtin type, or returns the error to throw if it can not be.
+    ///
+    /// Floats are truncated towards zero when cast to an int, strings are parsed when cast to a
+    /// number, and every object is converted to a bool by its truthiness.
+    fn cast(&mut self, subject: ObjectRef, tag: TypeTag) -> Result<ObjectRef, ObjectRef> {
+        let o = *self.ctx.store.resolve(subject);
+
+        Ok(match (tag, o) {
+            (TypeTag::Int, TerbiumObject::Integer(_))
+            | (TypeTag::Float, TerbiumObject::Float(_))
+            | (TypeTag::String, TerbiumObject::String(_))
+            | (TypeTag::Bool, TerbiumObject::Bool(_)) => subject,
+            (TypeTag::Int, TerbiumObject::Float(f)) if f.0.is_finite() => {
+                load_int!(self.ctx, f.0.trunc() as i128)
+            }
+            (TypeTag::Int, TerbiumObject::Bool(b)) => load_int!(self.ctx, i128::from(b)),
+            (TypeTag::Int, TerbiumObject::String(s)) => match self.string_lookup(s).parse() {
+                Ok(i) => load_int!(self.ctx, i),
+                Err(_) => return Err(self.cast_error(VALUE_ERROR_CLASS, &o, tag)),
+            },
+            (TypeTag::Float, TerbiumObject::Integer(i)) => {
+                store_auto!(self.ctx, TerbiumObject::Float((i as f64).into()))
+            }
+            (TypeTag::Float, TerbiumObject::Bool(b)) => {
+                store_auto!(
+                    self.ctx,
+                    TerbiumObject::Float(f64::from(u8::from(b)).into())
+                )
+            }
+            (TypeTag::Float, TerbiumObject::String(s)) => match self.string_lookup(s).parse() {
+                Ok(f) => store_auto!(self.ctx, TerbiumObject::Float(EqComparableFloat(f))),
+                Err(_) => return Err(self.cast_error(VALUE_ERROR_CLASS, &o, tag)),
+            },
+            (TypeTag::String, o) => {
+                let s = self.string_interner.intern(&self.get_object_str(&o));
+
+                store_auto!(self.ctx, TerbiumObject::String(s))
+            }
+            (TypeTag::Bool, o) => load_bool!(self.ctx, self.is_truthy(&o)),
+            // Infinite and NaN floats have no integer value
+            (TypeTag::Int, TerbiumObject::Float(_)) => {
+                return Err(self.cast_error(VALUE_ERROR_CLASS, &o, tag))
+            }
+            _ => return Err(self.cast_error(TYPE_ERROR_CLASS, &o, tag)),
+        })
+    }
+
+    fn cast_error(&mut self, class: ClassId, o: &TerbiumObject, tag: TypeTag) -> ObjectRef {
+        let message = format!("cannot cast {} to {}", self.get_object_repr(o), tag.name());
+
+        self.make_error(class, &message)
+    }
+
+    /// Returns the item of a builtin object at the index, or the error to throw if the index is
+    /// out of range or the object can not be indexed by it. Indexing by a range slices the object.
+    fn get_item(&mut self, subject: ObjectRef, index: ObjectRef) -> Result<ObjectRef, ObjectRef> {
+        match (
+            *self.ctx.store.resolve(subject),
+            *self.ctx.store.resolve(index),
+        ) {
+            (TerbiumObject::Array(a), TerbiumObject::Integer(i)) => {
+                let elements = self.ctx.array(a);
+
+                match resolve_index(elements.len(), i) {
+                    Some(i) => Ok(elements[i]),
+                    None => Err(self.index_error(i, elements.len())),
+                }
+            }
+            (TerbiumObject::String(s), TerbiumObject::Integer(i)) => {
+                let chars = self.string_lookup(s).chars().collect::<Vec<_>>();
+
+                match resolve_index(chars.len(), i) {
+                    Some(i) => {
+                        let c = self.string_interner.intern(&chars[i].to_string());
+                        Ok(store_auto!(self.ctx, TerbiumObject::String(c)))
+                    }
+                    None => Err(self.index_error(i, chars.len())),
+                }
+            }
+            (
+                TerbiumObject::Array(_) | TerbiumObject::String(_),
+                TerbiumObject::Range(start, end),
+            ) => self.slice(subject, Some(start), Some(end)),
+            (o, i) => {
+                let message = format!(
+                    "{} can not be indexed by {}",
+                    self.get_object_repr(&o),
+                    self.get_object_repr(&i)
+                );
+                Err(self.make_error(TYPE_ERROR_CLASS, &message))
+            }
+        }
+    }
+
+    /// Returns the slice of a builtin object between the bounds, or the `TypeError` to throw if
+    /// the object can not be sliced. Omitted bounds default to the start or end of the object.
+    fn slice(
+        &mut self,
+        subject: ObjectRef,
+        start: Option<i128>,
+        end: Option<i128>,
+    ) -> Result<ObjectRef, ObjectRef> {
+        match *self.ctx.store.resolve(subject) {
+            TerbiumObject::Array(a) => {
+                let elements = self.ctx.array(a);
+                let range = slice_range(elements.len(), start, end);
+
+                Ok(self.ctx.store_array(elements[range].to_vec()))
+            }
+            TerbiumObject::String(s) => {
+                let chars = self.string_lookup(s).chars().collect::<Vec<_>>();
+                let range = slice_range(chars.len(), start, end);
+
+                let s = self
+                    .string_interner
+                    .intern(&chars[range].iter().collect::<String>());
+                Ok(store_auto!(self.ctx, TerbiumObject::String(s)))
+            }
+            ref o => {
+                let message = format!("{} can not be sliced", self.get_object_repr(o));
+                Err(self.make_error(TYPE_ERROR_CLASS, &message))
+            }
+        }
+    }
+
+    /// Creates an instance of the builtin error class with the given message.
+    fn make_error(&mut self, class: ClassId, message: &str) -> ObjectRef {
+        let message = self.string_interner.intern(message);
+        let message = store_auto!(self.ctx, TerbiumObject::String(message));
+
+        self.ctx.store_instance(Instance {
+            class,
+            args: vec![message],
+            fields: HashMap::new(),
+            raised_at: None,
+        })
+    }
+
+    /// The message of the `TypeError` thrown when neither operand supports the operator.
+    fn unsupported_operands(&self, op: &str, lhs: &TerbiumObject, rhs: &TerbiumObject) -> String {
+        format!(
+            "unsupported operands for {}: {} and {}",
+            op,
+            self.get_object_repr(lhs),
+            self.get_object_repr(rhs),
+        )
+    }
+
+    fn index_error(&mut self, index: i128, len: usize) -> ObjectRef {
+        self.make_error(
+            INDEX_ERROR_CLASS,
+            &format!("index {} is out of range for length {}", index, len),
+        )
+    }
+
+    /// Unwinds to the innermost handler and returns the address it sends the error to.
+    /// `pos` is the address of the instruction throwing the error, which is where an uncaught
+    /// error is reported to come from unless it was thrown before, e.g. by a rethrow.
+    ///
+    /// # Panics
+    /// - No handler is installed
+    fn throw(&mut self, code: &Program, error: ObjectRef, pos: AddrRepr) -> AddrRepr {
+        let raised_at = match *self.ctx.store.resolve(error) {
+            TerbiumObject::Instance(id) => *self.ctx.instance_mut(id).raised_at.get_or_insert(pos),
+            _ => pos,
+        };
+
+        self.ctx.unwind(error).unwrap_or_else(|| {
+            let error = self.get_object_repr(self.ctx.store.resolve(error));
+
+            match code.location(raised_at) {
+                Some(location) => panic!("uncaught {} at {}", error, location),
+                None => panic!("uncaught {}", error),
+            }
+        })
+    }
+
+    /// Returns the string the object is converted to when interpolated into a string,
+    /// which is its representation for anything other than strings.
+    #[must_use]
+    pub fn get_object_str(&self, o: &TerbiumObject) -> String {
+        match o {
+            TerbiumObject::String(s_id) => self.string_lookup(*s_id).to_string(),
+            o => self.get_object_repr(o),
+        }
+    }
+
+    #[must_use]
+    pub fn format_object(&self, o: &TerbiumObject, spec: &FormatSpec) -> String {
+        let s = match (o, spec.precision) {
+            (TerbiumObject::Float(f), Some(precision)) => format!("{:.*}", precision, f.0),
+            _ if spec.repr => self.get_object_repr(o),
+            (TerbiumObject::String(s_id), Some(precision)) => {
+                self.string_lookup(*s_id).chars().take(precision).collect()
+            }
+            _ => self.get_object_str(o),
+        };
+
+        let pad = match spec.width {
+            Some(width) => width.saturating_sub(s.chars().count()),
+            None => return s,
+        };
+        let align = spec.align.unwrap_or(match o {
+            TerbiumObject::Integer(_) | TerbiumObject::Float(_) => Align::Right,
+            _ => Align::Left,
+        });
+        let (left, right) = match align {
+            Align::Left => (0, pad),
+            Align::Center => (pad / 2, pad - pad / 2),
+            Align::Right => (pad, 0),
+        };
+        let fill = spec.fill.to_string();
+
+        fill.repeat(left) + &s + &fill.repeat(right)
+    }
+
     #[must_use]
     pub fn get_object_repr(&self, o: &TerbiumObject) -> String {
         match o {
@@ -751,10 +2830,124 @@ impl<const STACK_SIZE: usize> Interpreter<STACK_SIZE> {
             TerbiumObject::String(s_id) => format!("{:?}", self.string_lookup(*s_id)),
             TerbiumObject::Bool(b) => b.to_string(),
             TerbiumObject::Null => "null".to_string(),
+            TerbiumObject::Array(a) => format!(
+                "[{}]",
+                self.ctx
+                    .array(*a)
+                    .iter()
+                    .map(|o| self.get_object_repr(self.ctx.store.resolve(*o)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            TerbiumObject::Range(start, end) => format!("{}..{}", start, end),
+            TerbiumObject::Iterator(_) => "<iterator>".to_string(),
+            TerbiumObject::Module(_) => "<module>".to_string(),
+            TerbiumObject::Func(_) | TerbiumObject::Builtin(_) => "<function>".to_string(),
+            TerbiumObject::Method(_, subject) => format!(
+                "<method of {}>",
+                self.get_object_repr(self.ctx.store.resolve(*subject))
+            ),
+            TerbiumObject::Class(id) => format!("<class {}>", self.ctx.class(*id).name),
+            TerbiumObject::Instance(id) => {
+                let Instance { class, args, .. } = self.ctx.instance(*id);
+
+                format!(
+                    "{}({})",
+                    self.ctx.class(*class).name,
+                    args.iter()
+                        .map(|o| self.get_object_repr(self.ctx.store.resolve(*o)))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }
         }
     }
 }
 
+/// Divides two integers, rounding towards negative infinity, e.g. `-5 / 2` is `-3`.
+fn floor_div(lhs: i128, rhs: i128) -> i128 {
+    let quotient = lhs / rhs;
+
+    if lhs % rhs != 0 && (lhs < 0) != (rhs < 0) {
+        quotient - 1
+    } else {
+        quotient
+    }
+}
+
+/// The remainder of [`floor_div`], which takes the sign of the divisor, e.g. `-5 % 2` is `1`.
+fn floor_mod(lhs: i128, rhs: i128) -> i128 {
+    let remainder = lhs % rhs;
+
+    if remainder != 0 && (remainder < 0) != (rhs < 0) {
+        remainder + rhs
+    } else {
+        remainder
+    }
+}
+
+/// Like [`floor_mod`], but for floats.
+fn float_mod(lhs: f64, rhs: f64) -> f64 {
+    let remainder = lhs % rhs;
+
+    if remainder != 0.0 && (remainder < 0.0) != (rhs < 0.0) {
+        remainder + rhs
+    } else {
+        remainder
+    }
+}
+
+/// Raises an integer to an integer power, returning `None` if the result overflows. Results of
+/// negative exponents are truncated towards zero, e.g. `2 ** -1` is `0`. The base must not be
+/// zero if the exponent is negative.
+fn int_pow(base: i128, exponent: i128) -> Option<i128> {
+    match base {
+        1 => Some(1),
+        -1 => Some(if exponent % 2 == 0 { 1 } else { -1 }),
+        0 if exponent > 0 => Some(0),
+        _ if exponent < 0 => Some(0),
+        // Any other base overflows long before the exponent exceeds a u32
+        _ => base.checked_pow(u32::try_from(exponent).ok()?),
+    }
+}
+
+/// Shifts the bits of an integer, where right shifts are arithmetic. Bits shifted past the
+/// width of the integer are discarded. The shift count must not be negative.
+fn shift(lhs: i128, rhs: i128, left: bool) -> i128 {
+    let rhs = u32::try_from(rhs).unwrap_or(u32::MAX);
+
+    if left {
+        lhs.checked_shl(rhs).unwrap_or(0)
+    } else {
+        lhs >> rhs.min(i128::BITS - 1)
+    }
+}
+
+/// Resolves an index into a sequence of `len` items, where negative indices count back from
+/// the end. Returns `None` if the index is out of range.
+fn resolve_index(len: usize, index: i128) -> Option<usize> {
+    let index = if index < 0 {
+        index + len as i128
+    } else {
+        index
+    };
+
+    usize::try_from(index).ok().filter(|&i| i < len)
+}
+
+/// Resolves the bounds of a slice of a sequence of `len` items like [`resolve_index`], except
+/// that bounds out of range are clamped to the sequence instead.
+fn slice_range(len: usize, start: Option<i128>, end: Option<i128>) -> std::ops::Range<usize> {
+    let bound = |i: i128| {
+        let i = if i < 0 { i + len as i128 } else { i };
+
+        i.clamp(0, len as i128) as usize
+    };
+    let start = start.map_or(0, bound);
+
+    start..end.map_or(len, bound).max(start)
+}
+
 impl Default for Interpreter {
     fn default() -> Self {
         Self::new()
@@ -1,8 +1,15 @@
-use terbium::bytecode::Interpreter as Transformer;
-use terbium::grammar::{Body, ParseInterface, Source};
+// Each test crate only uses some of these helpers
+#![allow(dead_code)]
+
+use terbium::analyzer::{
+    run_analysis, visit_expr, AnalyzerKind, AnalyzerMessage, AnalyzerMessageKind, AnalyzerSet,
+    Context, Type,
+};
+use terbium::bytecode::{Interpreter as Transformer, Program};
+use terbium::grammar::{Body, Expr, ParseInterface, Source, Span, Spanned, Token};
 use terbium::interpreter::{DefaultInterpreter, TerbiumObject};
 
-pub fn interpret(code: &str) -> TerbiumObject {
+pub fn compile(code: &str) -> Program {
     let body = Body::from_string(Source::default(), code.to_string()).unwrap_or_else(|e| {
         panic!("tokenization error: {:?}", e);
     });
@@ -11,9 +18,64 @@ pub fn interpret(code: &str) -> TerbiumObject {
 
     let mut program = transformer.program();
     program.resolve();
+    program
+}
 
+pub fn execute(program: &Program) -> DefaultInterpreter {
     let mut interpreter = DefaultInterpreter::default();
-    interpreter.run_bytecode(&program);
+    interpreter.run_bytecode(program);
+
+    // Every call frame leaves its scopes, however it returns
+    assert_eq!(interpreter.ctx.scope_depth(), 1);
+    interpreter
+}
+
+pub fn interpret(code: &str) -> TerbiumObject {
+    execute(&compile(code)).ctx.pop().clone()
+}
+
+pub fn interpret_string(code: &str) -> String {
+    pop_string(&mut execute(&compile(code)))
+}
+
+pub fn pop_string(interpreter: &mut DefaultInterpreter) -> String {
+    match *interpreter.ctx.pop() {
+        TerbiumObject::String(id) => interpreter.string_lookup(id).to_string(),
+        ref o => panic!("expected a string, got {:?}", o),
+    }
+}
+
+fn context(code: &str) -> Context {
+    let tokens = Vec::<(Token, Span)>::from_string(Source::default(), code.to_string())
+        .unwrap_or_else(|e| panic!("tokenization error: {:?}", e));
+
+    Context::from_tokens(vec![(Source::default(), code.to_string())], tokens)
+}
+
+pub fn analyze(code: &str) -> Vec<AnalyzerMessage> {
+    run_analysis(&AnalyzerSet::default(), context(code)).unwrap()
+}
+
+/// Infers the type of the expression, along with the messages reported while doing so.
+pub fn infer(code: &str) -> (Type, Vec<AnalyzerMessage>) {
+    let expr = Expr::from_string(Source::default(), code.to_string())
+        .unwrap_or_else(|e| panic!("parse error: {:?}", e));
+    let span = Span::from_range(Source::default(), 0..code.len());
+    let mut messages = Vec::new();
+
+    let ty = visit_expr(
+        &AnalyzerSet::default(),
+        &mut context(code),
+        &mut messages,
+        Spanned::new(expr, span),
+    )
+    .unwrap();
+    (ty, messages)
+}
 
-    interpreter.ctx.pop().clone()
+pub fn count(messages: &[AnalyzerMessage], kind: AnalyzerKind) -> usize {
+    messages
+        .iter()
+        .filter(|m| m.kind == AnalyzerMessageKind::Alert(kind))
+        .count()
 }
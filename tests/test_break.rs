@@ -0,0 +1,80 @@
+use terbium::analyzer::AnalyzerKind;
+use terbium::interpreter::TerbiumObject;
+
+mod interpreter;
+use interpreter::{analyze, count, interpret};
+
+#[test]
+fn test_break_value() {
+    let res = interpret(
+        r#"
+        let mut i = 0;
+        let found = while true {
+            i = i + 1;
+            if i * i > 50 {
+                break i;
+            }
+        };
+        found
+    "#,
+    );
+
+    assert_eq!(res, TerbiumObject::Integer(8));
+}
+
+#[test]
+fn test_labelled_loops() {
+    let res = interpret(
+        r#"
+        let mut total = 0;
+        outer@ for i in 0..10 {
+            for j in 0..10 {
+                if j > i {
+                    continue@outer;
+                }
+                if i == 5 {
+                    break@outer;
+                }
+                total = total + 1;
+            }
+        }
+        total
+    "#,
+    );
+
+    assert_eq!(res, TerbiumObject::Integer(15));
+}
+
+#[test]
+fn test_loop_control_outside_loop() {
+    let messages = analyze(
+        r#"
+        break;
+        while true {
+            continue@missing;
+        }
+    "#,
+    );
+
+    assert_eq!(count(&messages, AnalyzerKind::LoopControlOutsideLoop), 2);
+
+    // Lowering them anyway throws once they are reached
+    let res = interpret(
+        r#"
+        let a = try { break; 0 } catch Error { 1 };
+        let b = try { while true { continue@missing; } 0 } catch Error { 2 };
+        a + b
+    "#,
+    );
+
+    assert_eq!(res, TerbiumObject::Integer(3));
+}
+
+#[test]
+fn test_while_break_type() {
+    let messages = analyze("let x: int = while true { break 1; };");
+    assert_eq!(count(&messages, AnalyzerKind::IncompatibleTypes), 0);
+
+    let messages = analyze("let x: string = while true { break 1; };");
+    assert_eq!(count(&messages, AnalyzerKind::IncompatibleTypes), 1);
+}
@@ -0,0 +1,298 @@
+use terbium::bytecode::{
+    Addr, DecodeError, Instruction, Program, RichInstruction, FORMAT_VERSION, MAGIC,
+};
+use terbium::grammar::Source;
+
+mod interpreter;
+use interpreter::{compile, execute, pop_string};
+
+fn compile_with_source(code: &str) -> Program {
+    let mut program = compile(code);
+    program.add_source(Source::default(), code.to_string());
+    program
+}
+
+fn dis(program: &Program) -> String {
+    let mut out = Vec::new();
+    program.dis(&mut out).unwrap();
+
+    String::from_utf8(out).unwrap()
+}
+
+fn instructions(program: &Program) -> Vec<RichInstruction> {
+    program.inner().cloned().collect()
+}
+
+/// The bytes of a program with no debug info, constants or handlers, followed by its code.
+fn raw(code: &[u8]) -> Vec<u8> {
+    [
+        &MAGIC,
+        FORMAT_VERSION.to_le_bytes().as_slice(),
+        &[0, 0, 0],
+        code,
+    ]
+    .concat()
+}
+
+const CODE: &str = r#"
+    class Shape {
+        op cast(ty) { "shape" }
+    }
+
+    func area(width, height = 2.5, *rest, scale = 1.0) {
+        width * height * scale
+    }
+
+    let big = 18446744073709551616;
+    let half = area(2, scale = 0.5);
+    let safe = try { [1, 2][5] } catch IndexError as e { e };
+
+    $"{area(4):>8.2} {half} {big} {Shape()::string} {safe}"
+"#;
+
+#[test]
+fn test_round_trip() {
+    let program = compile(CODE);
+    let decoded = Program::from_bytes(&program.bytes()).unwrap();
+
+    assert_eq!(instructions(&decoded), instructions(&program));
+    assert_eq!(decoded.handler(0), program.handler(0));
+    assert_eq!(
+        pop_string(&mut execute(&decoded)),
+        pop_string(&mut execute(&program))
+    );
+    assert_eq!(
+        pop_string(&mut execute(&decoded)),
+        r#"   10.00 2.5 18446744073709551616 shape IndexError("index 5 is out of range for length 2")"#
+    );
+}
+
+#[test]
+fn test_constant_pool() {
+    let long = "a string long enough to notice if it were stored twice".to_string();
+    let program = Program::from_iter([
+        Instruction::LoadString(long.clone()).into(),
+        Instruction::LoadString(long.clone()).into(),
+        Instruction::LoadInt(u128::MAX).into(),
+        Instruction::LoadInt(u128::MAX).into(),
+        Instruction::LoadFloat(0.1.into()).into(),
+        Instruction::LoadFloat(0.1.into()).into(),
+        Instruction::LoadInt(1).into(),
+        Instruction::Halt.into(),
+    ]);
+    let bytes = program.bytes();
+
+    let count = |needle: &[u8]| bytes.windows(needle.len()).filter(|w| *w == needle).count();
+    assert_eq!(count(long.as_bytes()), 1);
+    assert_eq!(count(&u128::MAX.to_le_bytes()), 1);
+    assert_eq!(count(&0.1_f64.to_le_bytes()), 1);
+
+    // Small integers are inline, so the code only takes a few bytes per instruction
+    assert!(bytes.len() < long.len() + 16 + 8 + 48);
+    assert_eq!(
+        instructions(&Program::from_bytes(&bytes).unwrap()),
+        instructions(&program)
+    );
+}
+
+#[test]
+fn test_header() {
+    let bytes = compile("1 + 1;").bytes();
+    assert!(bytes.starts_with(&MAGIC));
+
+    let mut foreign = bytes.clone();
+    foreign[0] = b'X';
+    assert_eq!(
+        Program::from_bytes(&foreign).unwrap_err(),
+        DecodeError::InvalidMagic
+    );
+    assert_eq!(
+        Program::from_bytes(&[]).unwrap_err(),
+        DecodeError::InvalidMagic
+    );
+
+    let mut future = bytes;
+    future[MAGIC.len()..MAGIC.len() + 2].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+    assert_eq!(
+        Program::from_bytes(&future).unwrap_err(),
+        DecodeError::UnsupportedVersion(FORMAT_VERSION + 1)
+    );
+}
+
+#[test]
+fn test_truncated() {
+    let bytes = compile(CODE).bytes();
+
+    for len in MAGIC.len()..bytes.len() {
+        assert!(
+            matches!(
+                Program::from_bytes(&bytes[..len]),
+                Err(DecodeError::UnexpectedEof(_))
+            ),
+            "truncated to {} bytes",
+            len,
+        );
+    }
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        Program::from_bytes(&trailing).unwrap_err(),
+        DecodeError::TrailingBytes(bytes.len())
+    );
+}
+
+#[test]
+fn test_malformed() {
+    let halt = Instruction::Halt.to_instr_id();
+    let mut unknown_flags = raw(&[1, halt]);
+    unknown_flags[6] = 0b10;
+
+    // A debug section whose span table refers to the second instruction of one
+    let mut debug_info = raw(&[1, halt, 0, 1, 1]);
+    debug_info[6] = 0b1;
+
+    // The code starts at offset 9, with its length
+    let cases = [
+        (
+            raw(&[1, 0xff]),
+            DecodeError::InvalidOpcode {
+                opcode: 0xff,
+                offset: 10,
+            },
+        ),
+        (
+            raw(&[1, Instruction::LoadString(String::new()).to_instr_id(), 0]),
+            DecodeError::InvalidConstant {
+                index: 0,
+                offset: 11,
+            },
+        ),
+        (
+            raw(&[1, Instruction::Jump(Addr::Absolute(0)).to_instr_id(), 1]),
+            DecodeError::InvalidOperand {
+                reason: "address past the end of the code",
+                offset: 11,
+            },
+        ),
+        (
+            raw(&[1, Instruction::EnterTry(0).to_instr_id(), 0]),
+            DecodeError::InvalidOperand {
+                reason: "unknown handler",
+                offset: 11,
+            },
+        ),
+        (
+            unknown_flags,
+            DecodeError::InvalidOperand {
+                reason: "unknown flags",
+                offset: 6,
+            },
+        ),
+        (
+            debug_info,
+            DecodeError::InvalidOperand {
+                reason: "address past the end of the code",
+                offset: 13,
+            },
+        ),
+        (raw(&[0xff; 20]), DecodeError::VarintOverflow(9)),
+    ];
+
+    for (bytes, error) in cases {
+        assert_eq!(Program::from_bytes(&bytes).unwrap_err(), error);
+    }
+
+    assert_eq!(
+        DecodeError::InvalidOpcode {
+            opcode: 0xff,
+            offset: 10
+        }
+        .to_string(),
+        "invalid opcode 0xff at offset 10"
+    );
+}
+
+const LOCATED: &str = r#"let mut xs = [1, 2];
+
+func get(i) {
+    try {
+        xs[i]
+    } finally {
+        xs = [];
+    }
+}
+
+get(0);
+get(5);
+"#;
+
+#[test]
+fn test_debug_info() {
+    let program = compile_with_source(LOCATED);
+    let decoded = Program::from_bytes(&program.bytes()).unwrap();
+
+    assert_eq!(instructions(&decoded), instructions(&program));
+    for addr in 0..instructions(&program).len() {
+        assert_eq!(decoded.location(addr), program.location(addr));
+    }
+
+    let index = instructions(&program)
+        .iter()
+        .position(|instr| instr.instr() == &Instruction::Index)
+        .unwrap();
+    let location = decoded.location(index).unwrap();
+    assert_eq!((location.line, location.column), (5, 9));
+    assert_eq!(location.to_string(), "<unknown>:5:9");
+
+    // Without the source there is nothing to resolve spans with
+    assert_eq!(compile(LOCATED).location(index), None);
+}
+
+#[test]
+fn test_strip_debug_info() {
+    let mut program = compile_with_source(LOCATED);
+    let bytes = program.bytes();
+
+    program.strip_debug_info();
+    let stripped = Program::from_bytes(&program.bytes()).unwrap();
+
+    assert!(program.bytes().len() < bytes.len());
+    assert_eq!(stripped.sources().count(), 0);
+    assert!(stripped
+        .inner()
+        .all(|instr| instr.span().is_none() && instr.name().is_none()));
+}
+
+#[test]
+fn test_dis_source_lines() {
+    let program = compile_with_source("let x = 40;\n\nx + 2;\n");
+    assert_eq!(
+        dis(&program),
+        "\
+# <unknown>:1: let x = 40;
+0 | load_int 40
+1 | store_var 15 (x)
+# <unknown>:3: x + 2;
+2 | load_var 15 (x)
+3 | load_int 2
+4 | bin_add
+5 | pop
+6 | load_null
+7 | halt
+"
+    );
+
+    // The text of a source is not serialized, and there is no file to read it from
+    let decoded = Program::from_bytes(&program.bytes()).unwrap();
+    assert!(dis(&decoded).starts_with("# <unknown>:1\n0 | load_int 40\n"));
+}
+
+#[test]
+#[should_panic(
+    expected = "uncaught IndexError(\"index 5 is out of range for length 0\") at <unknown>:5:9"
+)]
+fn test_uncaught_error_location() {
+    let program = compile_with_source(LOCATED);
+    execute(&Program::from_bytes(&program.bytes()).unwrap());
+}
@@ -0,0 +1,96 @@
+use terbium::analyzer::AnalyzerKind;
+
+mod interpreter;
+use interpreter::{analyze, count, interpret_string};
+
+#[test]
+fn test_builtin_casts() {
+    let res = interpret_string(
+        r#"
+        let int = [1.9::int, -1.9::int, true::int, "-42"::int];
+        let float = [3::float + 0.5, false::float, "2.5"::float];
+        let string = [12::string, 1.5::string, true::string, [1, "a"]::string];
+        let bool = [0::bool, 0.1::bool, ""::bool, "a"::bool, []::bool];
+        let seven = "7";
+
+        $"{int} {float} {string} {bool} {seven::int::float / 2}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        r#"[1, -1, 1, -42] [3.5, 0, 2.5] ["12", "1.5", "true", "[1, \"a\"]"] [false, true, false, true, false] 3.5"#
+    );
+}
+
+#[test]
+fn test_cast_errors() {
+    let res = interpret_string(
+        r#"
+        let parse = s => try { s::int } catch ValueError as e { e };
+        let infinite = 1.0 / 0;
+
+        let a = parse("12a");
+        let b = parse("");
+        let c = try { infinite::int } catch ValueError as e { e };
+        let d = try { [1]::float } catch TypeError as e { e };
+        let e = try { 1::Celsius } catch TypeError as e { e };
+
+        $"{a} {b} {c} {d} {e}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        concat!(
+            r#"ValueError("cannot cast \"12a\" to int") ValueError("cannot cast \"\" to int") "#,
+            r#"ValueError("cannot cast inf to int") TypeError("cannot cast [1] to float") "#,
+            r#"TypeError("only int, float, string and bool can be cast to")"#,
+        )
+    );
+}
+
+#[test]
+fn test_op_cast() {
+    let res = interpret_string(
+        r#"
+        class Warm {
+            op cast(ty) {
+                if ty == "string" { "21.5°C" } else if ty == "int" { 21 } else { 21.5 }
+            }
+        }
+
+        class Plain {}
+
+        let t = Warm();
+        let plain = Plain();
+
+        $"{t::string} {t::int} {t::float} {plain::string} {plain::bool}"
+    "#,
+    );
+
+    assert_eq!(res, "21.5°C 21 21.5 Plain() true");
+}
+
+#[test]
+#[should_panic(expected = "uncaught TypeError(\"cannot cast Plain() to int\")")]
+fn test_uncaught_cast_error() {
+    interpret_string("class Plain {} Plain()::int;");
+}
+
+#[test]
+fn test_cast_analysis() {
+    let code = r#"
+        let valid = [1.5::int, "1"::float, [1]::string, [1]::bool];
+        let array = [1]::int;
+        let range = (0..1)::float;
+        let callback = (x => x)::int;
+
+        class Celsius {}
+        let custom = 1::Celsius;
+    "#;
+
+    let messages = analyze(code);
+
+    assert_eq!(count(&messages, AnalyzerKind::InvalidCasts), 4);
+}
@@ -0,0 +1,167 @@
+use terbium::analyzer::AnalyzerKind;
+use terbium::grammar::{
+    ArrayLength, Body, Expr, Node, ParseInterface, Source, Spanned, Target, TypeExpr,
+};
+
+mod interpreter;
+use interpreter::{analyze, count};
+
+#[test]
+fn test_class() {
+    let Body(body, _) = Body::from_string(
+        Source::default(),
+        r#"
+        class Counter(Base) {
+            private count: int = 0;
+            const step: int;
+
+            op constructor(step: int) -> null {
+                $count = 0;
+            }
+
+            func increment() -> $ {
+                $count = $count + 1;
+                $
+            }
+
+            private get() -> int { $count }
+        }
+    "#
+        .to_string(),
+    )
+    .unwrap_or_else(|e| panic!("parse error: {:?}", e));
+
+    let (name, generics, bases, fields, methods) = match body.first().map(|node| node.node()) {
+        Some(Node::Class {
+            name,
+            generics,
+            bases,
+            fields,
+            methods,
+            ..
+        }) => (name, generics, bases, fields, methods),
+        other => panic!("expected class, found {:?}", other),
+    };
+
+    assert_eq!(name, "Counter");
+    assert!(generics.is_empty());
+    assert_eq!(bases.len(), 1);
+    assert_eq!(bases[0].node(), &TypeExpr::Ident("Base".to_string()));
+
+    assert_eq!(fields.len(), 2);
+    assert_eq!(fields[0].name, "count");
+    assert!(fields[0].private && !fields[0].r#const);
+    assert!(fields[0].default.is_some());
+    assert_eq!(fields[1].name, "step");
+    assert!(fields[1].r#const && fields[1].default.is_none());
+
+    assert_eq!(methods.len(), 3);
+    assert!(methods[0].op && methods[0].name == "constructor");
+    assert_eq!(methods[1].return_ty.node(), &TypeExpr::SelfRef);
+    assert!(methods[1].return_last);
+    assert!(methods[2].private && !methods[2].op);
+
+    match methods[0].body.as_ref().unwrap()[0].node() {
+        Node::Assign { targets, .. } => match targets[0].node() {
+            Target::Attr(subject, attr) => {
+                assert_eq!(subject.node(), &Target::SelfRef);
+                assert_eq!(attr, "count");
+            }
+            other => panic!("expected attribute target, found {:?}", other),
+        },
+        other => panic!("expected assignment, found {:?}", other),
+    }
+
+    match methods[2].body.as_ref().unwrap()[0].node() {
+        Node::Expr(e) => assert_eq!(
+            e.node(),
+            &Expr::Attr {
+                subject: Spanned::new(Expr::SelfRef, e.span()),
+                attr: "count".to_string(),
+                optional: false,
+            }
+        ),
+        other => panic!("expected expression, found {:?}", other),
+    }
+}
+
+#[test]
+fn test_class_members() {
+    let Body(body, _) = Body::from_string(
+        Source::default(),
+        r#"
+        class Word {
+            default op iadd;
+            bits: int[SIZE];
+
+            cast int { 1 }
+            cast from int(value) { Word() }
+        }
+
+        extend Joinable for Iterator<string> {
+            join(sep: string) -> string { sep }
+        }
+    "#
+        .to_string(),
+    )
+    .unwrap_or_else(|e| panic!("parse error: {:?}", e));
+
+    match body[0].node() {
+        Node::Class {
+            fields,
+            casts,
+            default_ops,
+            ..
+        } => {
+            assert_eq!(default_ops.len(), 1);
+            assert_eq!(default_ops[0].node(), "iadd");
+            assert!(matches!(
+                fields[0].ty.node(),
+                TypeExpr::Array(_, Some(ArrayLength::Const(name))) if name == "SIZE"
+            ));
+
+            assert_eq!(casts.len(), 2);
+            assert!(!casts[0].from && casts[0].param.is_none() && casts[0].return_last);
+            assert!(casts[1].from);
+            assert_eq!(casts[1].param.as_deref(), Some("value"));
+        }
+        other => panic!("expected class, found {:?}", other),
+    }
+
+    match body[1].node() {
+        Node::Extend {
+            r#trait,
+            target,
+            methods,
+        } => {
+            assert_eq!(r#trait.node(), &TypeExpr::Ident("Joinable".to_string()));
+            assert!(matches!(target.node(), TypeExpr::Generic(..)));
+            assert_eq!(methods[0].name, "join");
+        }
+        other => panic!("expected extend block, found {:?}", other),
+    }
+}
+
+#[test]
+fn test_class_analysis() {
+    let code = r#"
+        class Counter {
+            count: int = "zero";
+            step: int = 1;
+
+            increment() -> int {
+                $count + missing
+            }
+
+            get() -> $ { $ }
+
+            name() -> string { 1 }
+        }
+    "#;
+
+    let messages = analyze(code);
+
+    // The default of `count` and the return value of `name` do not match their declared types
+    assert_eq!(count(&messages, AnalyzerKind::IncompatibleTypes), 2);
+    assert_eq!(count(&messages, AnalyzerKind::UnresolvedIdentifiers), 1);
+}
@@ -1,7 +1,12 @@
 use inkwell::context::Context;
 use inkwell::passes::PassManager;
-use terbium_grammar::{Source, ParseInterface, Spanned, Span, Body};
+use inkwell::OptimizationLevel;
+use terbium_grammar::{Source, ParseInterface, Spanned, Span, Body, Expr};
 use terbium::Compiler;
+use terbium::interpreter::TerbiumObject;
+
+mod interpreter;
+use interpreter::interpret;
 
 #[test]
 fn test_compiler() {
@@ -34,3 +39,123 @@ fn test_compiler() {
 
     func.print_to_stderr();
 }
+
+
+fn compile(code: &str) -> i64 {
+    try_compile(code).unwrap()
+}
+
+fn try_compile(code: &str) -> Result<i64, &'static str> {
+    let expr = Expr::from_string(Source::default(), code.to_string()).unwrap();
+    let span = Span::from_range(Source::default(), 0..code.len());
+
+    let ctx = Context::create();
+    let module = ctx.create_module("tmp");
+    let builder = ctx.create_builder();
+
+    let fpm = PassManager::create(&module);
+    fpm.initialize();
+
+    Compiler::compile_expr(
+        &ctx,
+        &builder,
+        &fpm,
+        &module,
+        "expr",
+        &Spanned::new(expr, span),
+    )?;
+
+    let engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+
+    Ok(unsafe {
+        engine
+            .get_function::<unsafe extern "C" fn() -> i64>("expr")
+            .unwrap()
+            .call()
+    })
+}
+
+#[test]
+fn test_operator_parity() {
+    for code in [
+        "1 + 2 * 3 - 4",
+        "7 / 2",
+        "-7 / 2",
+        "7 / -2",
+        "-7 % 3",
+        "7 % -3",
+        "2 ** 10",
+        "2 ** -1",
+        "-1 ** 3",
+        "3 ** 13",
+        "-3 ** 5",
+        "-1 ** -3",
+        "1 ** -2",
+        "7 /. 2",
+        "-7 /. 2.0",
+        "1 << 4",
+        "-8 >> 1",
+        "6 & 3",
+        "6 | 3",
+        "6 ^ 3",
+        "~5",
+        "0.5 + 1",
+        "1.5 * 3",
+        "7.0 / 2",
+        "-7.5 % 2",
+        "2.0 ** 0.5",
+        "1 < 2.5",
+        "2 >= 2",
+        "3 != 3.0",
+        "!0",
+        "1 && 0",
+        "0 || 2.5",
+        "true & false",
+        "true | false",
+        "true ^ true",
+        "true == false",
+        "\"a\" + \"b\" == \"ab\"",
+        "\"ab\" * 2 != \"abab\"",
+        "\"a\" < \"b\"",
+    ] {
+        assert_parity(code);
+    }
+}
+
+#[test]
+fn test_invalid_constants() {
+    for code in ["1 / 0", "1 % 0", "0 ** -1", "(1.0 / 0)::int", "(0.0 / 0)::int"] {
+        assert!(try_compile(code).is_err(), "{} should not compile", code);
+    }
+}
+
+#[test]
+fn test_cast_parity() {
+    for code in [
+        "1.9::int",
+        "-1.9::int",
+        "true::int",
+        "\"42\"::int",
+        "3::float",
+        "false::float",
+        "\"2.5\"::float",
+        "0::bool",
+        "2.5::bool",
+        "\"\"::bool",
+        "1::string == \"1\"",
+        "1.5::string + \"x\" == \"1.5x\"",
+    ] {
+        assert_parity(code);
+    }
+}
+
+fn assert_parity(code: &str) {
+    let bits = compile(code);
+
+    match interpret(code) {
+        TerbiumObject::Integer(i) => assert_eq!(i128::from(bits), i, "{}", code),
+        TerbiumObject::Float(f) => assert_eq!(f64::from_bits(bits as u64), f.0, "{}", code),
+        TerbiumObject::Bool(b) => assert_eq!(bits, i64::from(b), "{}", code),
+        o => panic!("unexpected result of {}: {:?}", code, o),
+    }
+}
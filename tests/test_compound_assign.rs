@@ -0,0 +1,113 @@
+use terbium::analyzer::AnalyzerKind;
+use terbium::grammar::{Body, Node, Operator, ParseInterface, Source};
+
+mod interpreter;
+use interpreter::{analyze, count, interpret_string};
+
+#[test]
+fn test_compound_assign() {
+    let res = interpret_string(
+        r#"
+        class Counter {}
+
+        let mut x = 1;
+        x += 2;
+        x *= 5;
+        x -= 1;
+
+        let mut y = 7;
+        y /= 2;
+        y **= 3;
+        y %= 10;
+        y <<= 4;
+        y >>= 2;
+        y |= 3;
+        y &= 26;
+        y ^= 5;
+
+        let mut s = "a";
+        s += "b";
+
+        let mut log = "";
+        let counter = () => {
+            log += "c";
+            let c = Counter();
+            c.n = 1;
+            c
+        };
+        let items = [1, 2, 3];
+        let index = () => {
+            log += "i";
+            1
+        };
+
+        let c = counter();
+        c.n += 10;
+        items[index()] *= 7;
+        items[-1] -= 3;
+
+        $"{x} {y} {s} {c.n} {items} {log}"
+    "#,
+    );
+
+    assert_eq!(res, "14 31 ab 11 [1, 14, 0] ci");
+}
+
+#[test]
+fn test_compound_assign_parsing() {
+    let code = "a.b **= 2; c <<= 1; d >>= 1; e %= 3; f ^= g;";
+
+    match Body::from_string(Source::default(), code.to_string()) {
+        Ok(Body(body, _)) => {
+            let operators = body
+                .iter()
+                .map(|node| match node.node() {
+                    Node::CompoundAssign { operator, .. } => *operator.node(),
+                    other => panic!("unexpected node: {:?}", other),
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                operators,
+                vec![
+                    Operator::Pow,
+                    Operator::BitLShift,
+                    Operator::BitRShift,
+                    Operator::Mod,
+                    Operator::BitXor,
+                ]
+            );
+        }
+        Err(e) => panic!("parse error: {:?}", e),
+    }
+
+    for code in ["[a, b] += 1;", "a += b += 1;", "a + = 1;"] {
+        assert!(
+            Body::from_string(Source::default(), code.to_string()).is_err(),
+            "{} should not parse",
+            code,
+        );
+    }
+}
+
+#[test]
+fn test_compound_assign_analysis() {
+    let code = r#"
+        let a = 1;
+        let mut b = 2;
+        let mut c = 3;
+
+        a += 1;
+        b += 1;
+        c;
+    "#;
+
+    let messages = analyze(code);
+
+    assert_eq!(
+        count(&messages, AnalyzerKind::ReassignedImmutableVariables),
+        1
+    );
+    // Only c is never mutated
+    assert_eq!(count(&messages, AnalyzerKind::UnnecessaryMutVariables), 1);
+}
@@ -0,0 +1,103 @@
+use terbium::analyzer::AnalyzerKind;
+use terbium::grammar::{Body, Node, ParseInterface, Source, Target};
+
+mod interpreter;
+use interpreter::{analyze, count, interpret_string};
+
+#[test]
+fn test_destructuring() {
+    let res = interpret_string(
+        r#"
+        let [a, [b, c]] = [1, [2, 3]];
+        let [head, ...tail] = [4, 5, 6];
+        let [first, ...middle, last] = "abcd";
+        let [x, ...none, y] = [7, 8];
+
+        let mut p = 1;
+        let mut q = 2;
+        [p, q] = [q, p];
+
+        $"{a}{b}{c} {head} {tail} {first} {middle} {last} {x} {none} {y} {p}{q}"
+    "#,
+    );
+
+    assert_eq!(res, "123 4 [5, 6] a bc d 7 [] 8 21");
+}
+
+#[test]
+fn test_chained_targets() {
+    let res = interpret_string(
+        r#"
+        let mut calls = 0;
+        let next = () => {
+            calls += 1;
+            calls
+        };
+
+        let a = b = next();
+        let mut c = 0;
+        let items = [0, 0];
+        items[0] = items[1] = c = next();
+
+        $"{a} {b} {c} {items} {calls}"
+    "#,
+    );
+
+    assert_eq!(res, "1 1 2 [2, 2] 2");
+}
+
+#[test]
+fn test_destructure_parsing() {
+    match Body::from_string(Source::default(), "let [a, ...b] = c = d;".to_string()) {
+        Ok(Body(body, _)) => match body[0].node() {
+            Node::Declare { targets, .. } => {
+                assert_eq!(targets.len(), 2);
+
+                match targets[0].node() {
+                    Target::Array(items) => {
+                        assert!(matches!(items[1].node(), Target::Rest(_)));
+                    }
+                    other => panic!("unexpected target: {:?}", other),
+                }
+            }
+            other => panic!("unexpected node: {:?}", other),
+        },
+        Err(e) => panic!("parse error: {:?}", e),
+    }
+
+    for code in [
+        "let ...a = b;",
+        "let [...a, ...b] = c;",
+        "let [a, ...] = b;",
+        "let [] = a;",
+    ] {
+        assert!(
+            Body::from_string(Source::default(), code.to_string()).is_err(),
+            "{} should not parse",
+            code,
+        );
+    }
+}
+
+#[test]
+fn test_destructure_analysis() {
+    let code = r#"
+        let [a, b] = [1, 2, 3];
+        let [c, d, ...e] = [1, 2, 3];
+        let [f, ...g, h, i] = [1, 2];
+        let [j, [k, l]] = [1, [2, 3]];
+
+        let mut m = 0;
+        let mut n = 0;
+        [m, n] = [1];
+        m = n = 4;
+    "#;
+
+    let messages = analyze(code);
+
+    assert_eq!(
+        count(&messages, AnalyzerKind::DestructuringLengthMismatch),
+        3
+    );
+    assert_eq!(count(&messages, AnalyzerKind::UnnecessaryMutVariables), 0);
+}
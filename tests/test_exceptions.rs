@@ -0,0 +1,178 @@
+use terbium::analyzer::AnalyzerKind;
+use terbium::grammar::{Body, Node, ParseInterface, Source};
+
+mod interpreter;
+use interpreter::{analyze, count, interpret_string};
+
+#[test]
+fn test_try_catch() {
+    let res = interpret_string(
+        r#"
+        class IOError(Error) {}
+        class NotFound(IOError) {}
+        class Other(Error) {}
+
+        let open = path => { throw NotFound(path); };
+        let read = path => open(path);
+        let mut log = "";
+
+        let a = try {
+            read("a.txt")
+        } catch Other {
+            0
+        } catch IOError as e {
+            log = $"{log}caught {e};";
+            1
+        };
+        let b = try {
+            try { throw Other; } catch NotFound { 2 } finally { log = $"{log}finally;"; }
+        } catch Error as e {
+            log = $"{log}rethrown {e};";
+            3
+        };
+        let c = try { 4 } catch { 5 } finally { log = $"{log}done"; };
+
+        $"{a} {b} {c} {log}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        r#"1 3 4 caught NotFound("a.txt");finally;rethrown Other();done"#
+    );
+}
+
+#[test]
+fn test_catch_types() {
+    let res = interpret_string(
+        r#"
+        class A(Error) {}
+        class B(Error) {}
+
+        let catch_either = error => try { throw error; } catch A | B as e { $"caught {e}" };
+        let a = catch_either(A());
+        let b = catch_either(B("b"));
+        // Types other than classes can not be tested against
+        let c = try { try { throw A(); } catch ?A { 0 } } catch TypeError as e { e };
+
+        $"{a}; {b}; {c}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        r#"caught A(); caught B("b"); TypeError("only classes can be referred to at runtime")"#
+    );
+}
+
+#[test]
+fn test_finally_on_exit() {
+    let res = interpret_string(
+        r#"
+        let mut log = "";
+
+        let f = () => {
+            try { return 1; } finally { log = $"{log}returned;"; }
+        };
+        let a = f();
+
+        let b = for i in 0..5 {
+            try {
+                if i == 1 { continue; }
+                if i == 2 { break i * 10; }
+            } finally {
+                log = $"{log}{i};";
+            }
+        };
+
+        $"{a} {b} {log}"
+    "#,
+    );
+
+    assert_eq!(res, "1 20 returned;0;1;2;");
+}
+
+#[test]
+fn test_attribute_error() {
+    let res = interpret_string(
+        r#"
+        class Point {}
+
+        let p = Point();
+        p.x = 1;
+        let a = try { p.y } catch AttributeError as e { e };
+        let b = try { "text".len } catch AttributeError as e { e };
+
+        $"{p.x} {a} {b}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        r#"1 AttributeError("Point() has no attribute \"y\"") AttributeError("\"text\" has no attribute \"len\"")"#
+    );
+}
+
+#[test]
+#[should_panic(expected = "uncaught Other()")]
+fn test_uncaught() {
+    // Returning out of the try expression uninstalls its handler
+    interpret_string(
+        r#"
+        class Other(Error) {}
+
+        let f = () => { try { return 1; } catch { 0 } };
+        f();
+        throw Other;
+    "#,
+    );
+}
+
+#[test]
+fn test_exception_parsing() {
+    let code = "func todo(message: string) -> never\nthrows\n    A,\n    B,\n{ throw A(message); }";
+
+    match Body::from_string(Source::default(), code.to_string()) {
+        Ok(Body(body, _)) => match body[0].node() {
+            Node::Func { throws, .. } => assert_eq!(throws.len(), 2),
+            other => panic!("unexpected node: {:?}", other),
+        },
+        Err(e) => panic!("parse error: {:?}", e),
+    }
+
+    for code in [
+        "try { 1 };",
+        "throw;",
+        "try { 1 } catch E;",
+        "func f() throws {}",
+    ] {
+        assert!(
+            Body::from_string(Source::default(), code.to_string()).is_err(),
+            "{} should not parse",
+            code,
+        );
+    }
+}
+
+#[test]
+fn test_exception_analysis() {
+    let code = r#"
+        class IOError(Error) {}
+
+        func fail() -> never throws IOError, Missing {
+            throw IOError("failed");
+        }
+
+        let x = try { fail() } catch IOError | Missing { 1 } catch Error as e { e };
+    "#;
+
+    let messages = analyze(code);
+
+    // Missing is reported in both the throws clause and the catch clause
+    assert_eq!(count(&messages, AnalyzerKind::UnresolvedIdentifiers), 2);
+    // The type of `x` includes the caught type `e` is bound to
+    assert_eq!(count(&messages, AnalyzerKind::UninferableTypes), 0);
+
+    let messages = analyze("let io = 1; try { io } catch io.IOError { 0 } catch fs.IOError { 1 };");
+    assert_eq!(count(&messages, AnalyzerKind::UnresolvedIdentifiers), 1);
+}
@@ -0,0 +1,116 @@
+use terbium::interpreter::TerbiumObject;
+
+mod interpreter;
+use interpreter::interpret;
+
+#[test]
+fn test_for_range() {
+    let res = interpret(
+        r#"
+        let mut total = 0;
+        for i in 0..5 {
+            total = total + i;
+        }
+        total
+    "#,
+    );
+
+    assert_eq!(res, TerbiumObject::Integer(10));
+}
+
+#[test]
+fn test_for_iterator() {
+    let res = interpret(
+        r#"
+        let mut total = 0;
+        for [a, b] in [[1, 2], [3, 4]] {
+            total = total + a * b;
+        }
+
+        let range = 1..4;
+        for i in range {
+            total = total + i;
+        }
+        total
+    "#,
+    );
+
+    assert_eq!(res, TerbiumObject::Integer(20));
+}
+
+#[test]
+fn test_for_user_iterator() {
+    let res = interpret(
+        r#"
+        class Countdown {
+            op next() {
+                if $n == 0 {
+                    throw StopIteration();
+                }
+                $n = $n - 1;
+                $n + 1
+            }
+        }
+
+        class Countable {
+            op iter() {
+                let c = Countdown();
+                c.n = $n;
+                c
+            }
+        }
+
+        let mut total = 0;
+        let c = Countable();
+        c.n = 3;
+        for i in c {
+            total = total * 10 + i;
+        }
+
+        let d = Countdown();
+        d.n = 5;
+        for i in d {
+            if i == 3 { break; }
+            total = total * 10 + i;
+        }
+
+        let e = Countdown();
+        e.n = 1;
+        let caught = try { for i in e { throw StopIteration(); } 0 } catch StopIteration { 1 };
+        let not_iterable = try { for i in 1 {} 0 } catch TypeError { 1 };
+
+        total * 100 + caught * 10 + not_iterable
+    "#,
+    );
+
+    assert_eq!(res, TerbiumObject::Integer(3_215_411));
+}
+
+#[test]
+fn test_for_declared_stop_iteration() {
+    let res = interpret(
+        r#"
+        class StopIteration(Error) {}
+
+        class Once {
+            op next() {
+                if $done {
+                    throw StopIteration();
+                }
+                $done = true;
+                1
+            }
+        }
+
+        let o = Once();
+        o.done = false;
+        let mut total = 0;
+        for i in o {
+            total = total + i;
+        }
+        total
+    "#,
+    );
+
+    assert_eq!(res, TerbiumObject::Integer(1));
+}
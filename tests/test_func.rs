@@ -0,0 +1,98 @@
+mod interpreter;
+use interpreter::interpret_string;
+
+#[test]
+fn test_func_declarations() {
+    let res = interpret_string(
+        r#"
+        func add(a: int, b: int = 10, *, scale: int = 1) -> int {
+            (a + b) * scale
+        }
+
+        func nothing() {
+            add(1);
+        }
+
+        func outer() {
+            let base = 100;
+
+            func inner(x) { base + x }
+            inner(1)
+        }
+
+        let a = add(1, 2);
+        let b = add(1);
+        let c = add(b = 1, a = 2, scale = 3);
+        let d = nothing();
+        let e = outer();
+
+        $"{a} {b} {c} {d} {e}"
+    "#,
+    );
+
+    assert_eq!(res, "3 11 9 null 101");
+}
+
+#[test]
+fn test_recursion() {
+    let res = interpret_string(
+        r#"
+        func fib(n: int) -> int {
+            if n < 2 {
+                return n;
+            }
+            fib(n - 1) + fib(n - 2)
+        }
+
+        func count(n: int) -> int {
+            for i in 0..n {
+                if i == 1 { return count(n - 1) + 1; }
+            }
+            n
+        }
+
+        func is_even(n: int) -> bool {
+            if n == 0 { true } else { is_odd(n - 1) }
+        }
+
+        func is_odd(n: int) -> bool {
+            if n == 0 { false } else { is_even(n - 1) }
+        }
+
+        $"{fib(15)} {count(500)} {is_even(10)} {is_odd(10)}"
+    "#,
+    );
+
+    assert_eq!(res, "610 500 true false");
+}
+
+#[test]
+fn test_nested_recursion() {
+    let res = interpret_string(
+        r#"
+        func outer() {
+            func f(n) { if n == 0 { 0 } else { f(n - 1) + 1 } }
+            f(3)
+        }
+
+        func parity(n) {
+            func is_even(n) { if n == 0 { true } else { is_odd(n - 1) } }
+            func is_odd(n) { if n == 0 { false } else { is_even(n - 1) } }
+
+            $"{is_even(n)} {is_odd(n)}"
+        }
+
+        func deepest(n) {
+            func middle() {
+                func inner(m) { if m == 0 { n } else { inner(m - 1) } }
+                inner(n)
+            }
+            middle()
+        }
+
+        $"{outer()} {parity(7)} {deepest(4)}"
+    "#,
+    );
+
+    assert_eq!(res, "3 false true 4");
+}
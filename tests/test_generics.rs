@@ -0,0 +1,160 @@
+use terbium::analyzer::AnalyzerKind;
+use terbium::grammar::{Body, Node, ParseInterface, Source, TypeExpr};
+
+mod interpreter;
+use interpreter::{analyze, count};
+
+#[test]
+fn test_generics_parsing() {
+    let Body(body, _) = Body::from_string(
+        Source::default(),
+        r#"
+        func each<I: Iterator<T>, T>(items: I) -> T where T: Display, I: Sized {}
+        class Pair<A, B>(Iterator<A>) where B: A {}
+    "#
+        .to_string(),
+    )
+    .unwrap_or_else(|e| panic!("parse error: {:?}", e));
+
+    match body.first().map(|node| node.node()) {
+        Some(Node::Func { generics, .. }) => {
+            let bounds = generics
+                .iter()
+                .map(|g| (g.node().name.as_str(), g.node().bounds.len()))
+                .collect::<Vec<_>>();
+
+            assert_eq!(bounds, vec![("I", 2), ("T", 1)]);
+        }
+        other => panic!("expected function, found {:?}", other),
+    }
+
+    match body.get(1).map(|node| node.node()) {
+        Some(Node::Class { generics, .. }) => {
+            assert_eq!(generics.len(), 2);
+            assert_eq!(
+                generics[1].node().bounds[0].node(),
+                &TypeExpr::Ident("A".to_string())
+            );
+        }
+        other => panic!("expected class, found {:?}", other),
+    }
+
+    let errors = Body::from_string(
+        Source::default(),
+        "func f<T>(x: T) where U: int {}".to_string(),
+    )
+    .unwrap_err();
+    assert_eq!(errors.len(), 1);
+}
+
+/// Renders a type made of identifiers and generics back into source code.
+fn render(ty: &TypeExpr) -> String {
+    match ty {
+        TypeExpr::Ident(name) => name.clone(),
+        TypeExpr::Generic(subject, params) => format!(
+            "{}<{}>",
+            render(subject.node()),
+            params
+                .iter()
+                .map(|p| render(p.node()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        other => panic!("unexpected type {:?}", other),
+    }
+}
+
+#[test]
+fn test_generics_right_shift() {
+    // Each `>>` here lexes as a right shift, which closes two generic argument lists
+    let Body(body, _) = Body::from_string(
+        Source::default(),
+        r#"
+        func each<I: Iterator<T>>(items: I) {}
+        func nested<I: Iterator<Array<T>>>(items: I) {}
+        class Grid<T: Iterator<int>> where T: Array<Array<int>> {}
+        let grid: Array<Array<int>> = [];
+    "#
+        .to_string(),
+    )
+    .unwrap_or_else(|e| panic!("parse error: {:?}", e));
+
+    let bounds = body
+        .iter()
+        .filter_map(|node| match node.node() {
+            Node::Func { generics, .. } | Node::Class { generics, .. } => Some(
+                generics[0]
+                    .node()
+                    .bounds
+                    .iter()
+                    .map(|b| render(b.node()))
+                    .collect::<Vec<_>>(),
+            ),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        bounds,
+        vec![
+            vec!["Iterator<T>"],
+            vec!["Iterator<Array<T>>"],
+            vec!["Iterator<int>", "Array<Array<int>>"],
+        ]
+    );
+    match body[3].node() {
+        Node::Declare { ty, .. } => assert_eq!(render(ty.node()), "Array<Array<int>>"),
+        other => panic!("expected declaration, found {:?}", other),
+    }
+
+    for code in ["let x: Array<int>> = 1;", "func f<T: Iterator<int>>>() {}"] {
+        assert!(
+            Body::from_string(Source::default(), code.to_string()).is_err(),
+            "{} should not parse",
+            code,
+        );
+    }
+}
+
+#[test]
+fn test_generics_analysis() {
+    let code = r#"
+        func id<T>(value: T) -> T { value }
+        func pick<T: int | float>(a: T, b: T) -> T { a }
+        func wrong<T>(value: T) -> T { 1 }
+
+        let _a: int = id(1);
+        let _b: string = id(1);
+        pick(1, 2);
+        pick(1, 2.5);
+        pick("a", "b");
+    "#;
+
+    let messages = analyze(code);
+
+    // `wrong` returns an int, `_b` is not a string, 2.5 is not an int and strings violate the bound
+    assert_eq!(count(&messages, AnalyzerKind::IncompatibleTypes), 4);
+    assert_eq!(count(&messages, AnalyzerKind::UnresolvedIdentifiers), 0);
+}
+
+#[test]
+fn test_class_generics_analysis() {
+    let code = r#"
+        class Box<T> {
+            value: T;
+
+            get() -> T { $value }
+            map<U>(f: (T) -> U) -> Box<U> { Box() }
+            sum() -> T where T: int | float { $value }
+        }
+
+        class Loose {
+            value: T;
+        }
+    "#;
+
+    let messages = analyze(code);
+
+    // Only `T` in `Loose` is out of scope
+    assert_eq!(count(&messages, AnalyzerKind::UnresolvedIdentifiers), 1);
+}
@@ -0,0 +1,165 @@
+use terbium::analyzer::{AnalyzerKind, AnalyzerMessageKind, PrimitiveType, Type};
+use terbium::grammar::{Body, Expr, Node, ParseInterface, Source, Target};
+
+mod interpreter;
+use interpreter::{analyze, interpret_string};
+
+#[test]
+fn test_index_and_slice() {
+    let res = interpret_string(
+        r#"
+        let mut a = [1, 2, 3, 4];
+        let s = "hello";
+
+        a[0] = 10;
+        a[-1] = a[-2] * 10;
+
+        let b = $"{a[0]} {a[-1]} {s[1]} {s[-1]}";
+        let c = $"{a[1..3]} {a[..-1]} {a[2..]} {s[1..-1]} {a[5..]}";
+        let d = try { a[4] } catch IndexError as e { e };
+        let nothing = if false { a };
+        let e = nothing?.[0];
+
+        $"{b} | {c} | {d} | {e}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        r#"10 30 e o | [2, 3] [10, 2, 3] [3, 30] ell [] | IndexError("index 4 is out of range for length 4") | null"#
+    );
+}
+
+#[test]
+fn test_index_overloads() {
+    let res = interpret_string(
+        r#"
+        let mut log = "";
+
+        class Grid {
+            op index(i) { i * 2 }
+
+            op set_index(i, value) {
+                log = $"{log}{i}={value};";
+            }
+
+            op slice(start, end) { $"{start}:{end}" }
+        }
+
+        let mut g = Grid();
+        g[3] = "x";
+        g.name = "grid";
+        g.name = $"{g.name}!";
+
+        $"{g[4]} {g[1..]} {g.name} {log}"
+    "#,
+    );
+
+    assert_eq!(res, "8 1:null grid! 3=x;");
+}
+
+#[test]
+#[should_panic(expected = "uncaught IndexError(\"index 3 is out of range for length 3\")")]
+fn test_index_out_of_range() {
+    interpret_string("let a = [1, 2, 3]; a[3];");
+}
+
+#[test]
+fn test_index_type_errors() {
+    let res = interpret_string(
+        r#"
+        let n = 1;
+        let s = "x";
+        let a = [1, 2];
+
+        let errors = [
+            try { n[0] } catch TypeError as e { e },
+            try { n[1..] } catch TypeError as e { e },
+            try { a[s..] } catch TypeError as e { e },
+            try { a[s] } catch TypeError as e { e },
+            try { n[0] = 2; } catch TypeError as e { e },
+            try { n.x = 2; } catch TypeError as e { e },
+        ];
+        $"{errors}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        concat!(
+            r#"[TypeError("1 can not be indexed by 0"), TypeError("1 can not be sliced"), "#,
+            r#"TypeError("slice bounds must be integers, not \"x\""), "#,
+            r#"TypeError("[1, 2] can not be indexed by \"x\""), "#,
+            r#"TypeError("items of 1 can not be assigned to"), "#,
+            r#"TypeError("attributes of 1 can not be assigned to")]"#,
+        )
+    );
+}
+
+#[test]
+fn test_index_parsing() {
+    assert!(matches!(
+        Expr::from_string(Source::default(), "a?.[1]".to_string()),
+        Ok(Expr::Index { optional: true, .. })
+    ));
+
+    match Expr::from_string(Source::default(), "a[..-1]".to_string()) {
+        Ok(Expr::Slice {
+            start: None,
+            end: Some(_),
+            optional: false,
+            ..
+        }) => (),
+        other => panic!("unexpected parse result: {:?}", other),
+    }
+
+    match Body::from_string(Source::default(), "a.b[0] = 1;".to_string()) {
+        Ok(Body(body, _)) => match body[0].node() {
+            Node::Assign { targets, .. } => match targets[0].node() {
+                Target::Index(subject, _) => {
+                    assert!(matches!(subject.node(), Target::Attr(_, attr) if attr == "b"));
+                }
+                other => panic!("unexpected target: {:?}", other),
+            },
+            other => panic!("unexpected node: {:?}", other),
+        },
+        Err(e) => panic!("parse error: {:?}", e),
+    }
+
+    for code in ["a[] = 1;", "a[1, 2];", "[a, b].x = 1;"] {
+        assert!(
+            Body::from_string(Source::default(), code.to_string()).is_err(),
+            "{} should not parse",
+            code,
+        );
+    }
+}
+
+#[test]
+fn test_index_analysis() {
+    let infer = |code: &str| interpreter::infer(code).0;
+    let string = || Type::Primitive(PrimitiveType::String);
+
+    assert_eq!(infer(r#""abc"[1..]"#), string());
+    assert_eq!(infer("[1, 2][0]"), Type::Primitive(PrimitiveType::Int));
+
+    // Item assignments neither rebind nor require a mutable subject
+    let code = r#"
+        let a = [1, 2];
+        a[0] = 3;
+        b.c = 4;
+    "#;
+
+    let messages = analyze(code);
+
+    assert_eq!(
+        messages
+            .iter()
+            .map(|m| m.kind.clone())
+            .filter(|k| matches!(k, AnalyzerMessageKind::Alert(_)))
+            .collect::<Vec<_>>(),
+        vec![AnalyzerMessageKind::Alert(
+            AnalyzerKind::UnresolvedIdentifiers
+        )]
+    );
+}
@@ -0,0 +1,55 @@
+use terbium::analyzer::AnalyzerKind;
+use terbium::grammar::{Body, Expr, InterpolatedPart, ParseInterface, Source};
+
+mod interpreter;
+use interpreter::{analyze, count, interpret_string};
+
+#[test]
+fn test_interpolation() {
+    let res = interpret_string(
+        r#"
+        let name = "world";
+        let x = 2.5;
+        $"hello {name}! {x + 1:>6.2}|{name:*^9}|{name:.3?}|{[1, 'a']}|{{ok}}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        r#"hello world!   3.50|**world**|"world"|[1, "a"]|{ok}"#
+    );
+}
+
+#[test]
+fn test_interpolation_spans() {
+    let code = r#"let x = $"a\n{x + 1}";"#;
+
+    match Expr::from_string(Source::default(), r#"$"a\n{x + 1}""#.to_string()) {
+        Ok(Expr::Interpolated(parts)) => match &parts[..] {
+            [InterpolatedPart::Literal(s), InterpolatedPart::Value(value, _)] => {
+                assert_eq!(s, "a\n");
+                // Spans point into the original source, after the escape sequence
+                assert_eq!(value.span().range(), 6..11);
+            }
+            parts => panic!("unexpected parts: {:?}", parts),
+        },
+        other => panic!("unexpected parse result: {:?}", other),
+    }
+
+    let errors = Body::from_string(Source::default(), code.replace("+ 1", "+")).unwrap_err();
+    assert!(errors
+        .iter()
+        .all(|e| e.span.start() >= 14 && e.span.end() <= 19));
+
+    let errors = Body::from_string(Source::default(), r#"$"{x:<<<}";"#.to_string()).unwrap_err();
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn test_interpolation_analysis() {
+    let code = r#"let name = "x"; $"{name} {missing}";"#;
+
+    let messages = analyze(code);
+
+    assert_eq!(count(&messages, AnalyzerKind::UnresolvedIdentifiers), 1);
+}
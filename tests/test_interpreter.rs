@@ -14,3 +14,15 @@ fn test_interpreter() {
 
     assert_eq!(interpreter.ctx.pop(), &TerbiumObject::Integer(2));
 }
+
+#[test]
+#[should_panic(expected = "uncaught TypeError(\"1 is not a module\")")]
+fn test_unpack_non_module() {
+    let program = Program::from_iter([
+        Instruction::LoadInt(1).into(),
+        Instruction::UnpackModule.into(),
+        Instruction::Halt.into(),
+    ]);
+    let mut interpreter = DefaultInterpreter::default();
+    interpreter.run_bytecode(&program);
+}
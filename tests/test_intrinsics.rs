@@ -0,0 +1,35 @@
+mod interpreter;
+use interpreter::interpret_string;
+
+#[test]
+fn test_intrinsics() {
+    let res = interpret_string(
+        r#"
+        class Meters {
+            op add(other) { $n + other }
+        }
+
+        let m = Meters();
+        m.n = 2;
+
+        let sum = __trb_internal_call_op(m, "add", 3);
+        let defined = __trb_internal_has_op(m, "add");
+        let missing = __trb_internal_has_op(m, "sub");
+        let repr = __trb_internal_call_op("it's", "repr");
+        let floor = __trb_internal_floor_num(-1.5);
+        let name = __trb_internal_name_of(Meters);
+        let unnamed = __trb_internal_name_of(1);
+        let type_name = __trb_internal_name_of(__trb_internal_type_of(m));
+
+        $"{sum} {defined} {missing} {repr} {floor} {name} {unnamed} {type_name}"
+    "#,
+    );
+
+    assert_eq!(res, r"5 true false 'it\'s' -2 Meters null Meters");
+}
+
+#[test]
+#[should_panic(expected = "uncaught TypeError(\"1 does not define `op next`\")")]
+fn test_call_missing_op() {
+    interpret_string(r#"__trb_internal_call_op(1, "next")"#);
+}
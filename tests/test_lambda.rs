@@ -0,0 +1,90 @@
+use terbium::analyzer::{PrimitiveType, Type};
+use terbium::interpreter::TerbiumObject;
+
+mod interpreter;
+use interpreter::{compile, execute, interpret_string};
+
+fn interpret_ints(code: &str) -> Vec<i128> {
+    let mut interpreter = execute(&compile(code));
+
+    match *interpreter.ctx.pop() {
+        TerbiumObject::Array(id) => interpreter
+            .ctx
+            .array(id)
+            .iter()
+            .map(|o| match interpreter.ctx.store.resolve(*o) {
+                TerbiumObject::Integer(i) => *i,
+                o => panic!("expected an integer, got {:?}", o),
+            })
+            .collect(),
+        ref o => panic!("expected an array, got {:?}", o),
+    }
+}
+
+#[test]
+fn test_lambda_calls() {
+    let res = interpret_string(
+        r#"
+        let add = (a: int, b: int) -> int => a + b;
+        let apply = (f, x) => f(x);
+        let nothing = () => {};
+        $"{add(2, 3)} {apply(n => n * 2, 21)} {nothing()}"
+    "#,
+    );
+
+    assert_eq!(res, "5 42 null");
+}
+
+#[test]
+fn test_lambda_captures() {
+    let res = interpret_ints(
+        r#"
+        let make_counter = () => {
+            let mut count = 0;
+            () => {
+                count = count + 1;
+                count
+            }
+        };
+        let counter = make_counter();
+        let other = make_counter();
+        counter();
+        counter();
+        other();
+
+        let mut x = 1;
+        let get = () => x;
+        let set = value => { x = value; };
+        x = 5;
+        let before = get();
+        set(7);
+
+        [counter(), other(), before, x]
+    "#,
+    );
+
+    // Each call to make_counter has its own count, which outlives the call
+    assert_eq!(res, vec![3, 2, 5, 7]);
+}
+
+#[test]
+fn test_lambda_type() {
+    let infer = |code: &str| {
+        let (ty, messages) = interpreter::infer(code);
+        (ty, messages.len())
+    };
+    let int = || Type::Primitive(PrimitiveType::Int);
+
+    assert_eq!(
+        infer("(a: int, b) => a + 1"),
+        (Type::Func(vec![int(), Type::Any], Box::new(int())), 1) // b is unused
+    );
+    assert_eq!(
+        infer("x => x"),
+        (Type::Func(vec![Type::Any], Box::new(Type::Any)), 0)
+    );
+    assert_eq!(
+        infer("() -> int => \"a\""),
+        (Type::Func(vec![], Box::new(int())), 1)
+    );
+}
@@ -1,5 +1,5 @@
 use terbium::grammar::token::*;
-use terbium::grammar::{ChumskyParser as _, ChumskyStream as Stream, Source, Span};
+use terbium::grammar::{ChumskyParser as _, ChumskyStream as Stream, Error, Source, Span};
 
 #[test]
 fn test_lexer() {
@@ -49,3 +49,109 @@ fn test_lexer() {
     );
     assert_eq!(errors, vec![]);
 }
+
+fn lex(raw: &str) -> (Option<Vec<(Token, Span)>>, Vec<Error>) {
+    get_lexer().parse_recovery(Stream::<_, Span, _>::from_iter(
+        Span::single(Source::default(), raw.chars().count()),
+        raw.chars()
+            .enumerate()
+            .map(|(i, c)| (c, Span::single(Source::default(), i))),
+    ))
+}
+
+fn lex_strings(raw: &str) -> Vec<StringLiteral> {
+    let (tokens, errors) = lex(raw);
+    assert_eq!(errors, vec![]);
+
+    tokens
+        .unwrap()
+        .into_iter()
+        .map(|(t, _)| match t {
+            Token::Literal(Literal::String(s)) => s,
+            t => panic!("expected a string literal, got {:?}", t),
+        })
+        .collect()
+}
+
+#[test]
+fn test_lexer_raw_strings() {
+    assert_eq!(
+        lex_strings(r###"r"\d+\.trb" r#"say "hi""# r##"a "# b"##"###),
+        vec![
+            StringLiteral::RawString(r"\d+\.trb".to_string()),
+            StringLiteral::RawString(r#"say "hi""#.to_string()),
+            StringLiteral::RawString(r##"a "# b"##.to_string()),
+        ],
+    );
+}
+
+#[test]
+fn test_lexer_multi_line_strings() {
+    let raw = "
+        \"\"\"
+            first
+              \"indented\"\\n
+\t
+            last\\t
+        \"\"\"
+    ";
+
+    assert_eq!(
+        lex_strings(raw),
+        vec![StringLiteral::String(
+            "first\n  \"indented\"\n\n\nlast\t".to_string()
+        )],
+    );
+
+    // Surrogates are not valid characters
+    let raw = "\"\"\"\n  \\uD800\"\"\"";
+    let (_, errors) = lex(raw);
+    assert_eq!(errors.first().map(|e| e.span.range()), Some(8..12));
+}
+
+#[test]
+fn test_lexer_numbers() {
+    let (tokens, errors) = lex("0xFF_FF 0o17 0b1010 1_000_000 6.02e23 1e-9 .5E+2 1_000.25 0..1");
+    assert_eq!(errors, vec![]);
+
+    assert_eq!(
+        tokens.map(|t| t.into_iter().map(|t| t.0).collect::<Vec<_>>()),
+        Some(vec![
+            Token::Literal(Literal::Integer(0xFFFF)),
+            Token::Literal(Literal::Integer(0o17)),
+            Token::Literal(Literal::Integer(0b1010)),
+            Token::Literal(Literal::Integer(1_000_000)),
+            Token::Literal(Literal::Float("6.02e23".to_string())),
+            Token::Literal(Literal::Float("1e-9".to_string())),
+            Token::Literal(Literal::Float(".5E+2".to_string())),
+            Token::Literal(Literal::Float("1000.25".to_string())),
+            Token::Literal(Literal::Integer(0)),
+            Token::Operator(Operator::Range),
+            Token::Literal(Literal::Integer(1)),
+        ]),
+    );
+
+    for raw in ["170141183460469231731687303715884105728", "0b102", "0x"] {
+        let (_, errors) = lex(raw);
+
+        assert_eq!(errors.len(), 1, "{}", raw);
+        assert_eq!(errors[0].span.range(), 0..raw.len());
+    }
+}
+
+#[test]
+fn test_lexer_right_shift() {
+    let (tokens, errors) = lex("1>>(2)");
+    assert_eq!(errors, vec![]);
+
+    assert_eq!(
+        tokens.map(|t| t.into_iter().map(|t| t.0).collect::<Vec<_>>()),
+        Some(vec![
+            Token::Literal(Literal::Integer(1)),
+            Token::Operator(Operator::BitRShift),
+            Token::StartBracket(Bracket::Paren),
+            Token::Literal(Literal::Integer(2)),
+            Token::EndBracket(Bracket::Paren),
+        ]),
+    );
+}
@@ -0,0 +1,41 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use terbium::grammar::{Body, ParseInterface, Source};
+
+/// Every `.trb` file under `dir`, recursively.
+fn trb_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+
+        if path.is_dir() {
+            files.extend(trb_files(&path));
+        } else if path.extension().is_some_and(|ext| ext == "trb") {
+            files.push(path);
+        }
+    }
+
+    files
+}
+
+#[test]
+fn test_parse_lib() {
+    let lib = Path::new(env!("CARGO_MANIFEST_DIR")).join("lib");
+    let files = trb_files(&lib);
+    assert!(!files.is_empty());
+
+    let failures = files
+        .into_iter()
+        .filter_map(|path| {
+            let code = fs::read_to_string(&path).unwrap();
+
+            Body::from_string(Source::from_path(&path), code)
+                .err()
+                .map(|errors| format!("{}: {:?}", path.display(), errors))
+        })
+        .collect::<Vec<_>>();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
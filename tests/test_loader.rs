@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use terbium::grammar::Source;
+use terbium::interpreter::{DefaultInterpreter, TerbiumObject};
+use terbium::loader::{LoadError, ModuleLoader};
+
+/// Creates a fresh directory containing the given files, to be used as a library directory.
+fn lib(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("terbium-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    for (path, code) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, code).unwrap();
+    }
+
+    dir
+}
+
+fn load(lib: &Path, code: &str) -> Result<ModuleLoader, LoadError> {
+    let mut loader = ModuleLoader::new(lib.to_path_buf());
+    loader.load_main(Source::default(), code.to_string(), lib)?;
+
+    Ok(loader)
+}
+
+#[test]
+fn test_require_modules() {
+    let dir = lib(
+        "require",
+        &[
+            ("builtin.trb", "require * from pkg;"),
+            ("pkg/lib.trb", "require value as answer from .consts;"),
+            ("pkg/consts.trb", "let value = 40; let _hidden = 1;"),
+        ],
+    );
+
+    let loader = load(&dir, "require value from pkg.consts; answer + value + 2")
+        .unwrap_or_else(|e| panic!("load error: {}", e));
+
+    let consts = dir.join("pkg/consts.trb").canonicalize().unwrap();
+    assert_eq!(loader.module(&consts).unwrap().exports, vec!["value"]);
+
+    let mut program = loader.program();
+    program.resolve();
+
+    let mut interpreter = DefaultInterpreter::default();
+    interpreter.run_bytecode(&program);
+
+    assert_eq!(interpreter.ctx.pop().clone(), TerbiumObject::Integer(82));
+}
+
+#[test]
+fn test_require_errors() {
+    let dir = lib(
+        "require-errors",
+        &[
+            ("a.trb", "require b;"),
+            ("b.trb", "require a;"),
+            ("c.trb", "let _hidden = 1;"),
+        ],
+    );
+
+    assert!(matches!(load(&dir, "require a;"), Err(LoadError::Cycle(chain)) if chain.len() == 3));
+    assert!(matches!(
+        load(&dir, "require missing;"),
+        Err(LoadError::NotFound { .. })
+    ));
+    assert!(matches!(
+        load(&dir, "require _hidden from c;"),
+        Err(LoadError::NotExported { .. })
+    ));
+}
+
+#[test]
+fn test_prelude() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("lib");
+    let code = r#"
+        let a = repr("a");
+        let b = try { todo("later") } catch UnimplementedError as e { e };
+        $"{a} {b}"
+    "#;
+
+    let mut loader = load(&dir, code).unwrap_or_else(|e| panic!("load error: {}", e));
+    assert!(loader.take_messages().is_empty());
+
+    let mut program = loader.program();
+    program.resolve();
+
+    let mut interpreter = DefaultInterpreter::default();
+    interpreter.run_bytecode(&program);
+
+    match *interpreter.ctx.pop() {
+        TerbiumObject::String(id) => assert_eq!(
+            interpreter.string_lookup(id),
+            r#"'a' UnimplementedError("TODO: later")"#
+        ),
+        ref o => panic!("expected a string, got {:?}", o),
+    }
+}
+
+#[test]
+fn test_load_std_traits() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("lib");
+    let code = r#"
+        require * from std.iter;
+        require * from std.rand;
+
+        $"{Iterator} {MapIterator} {Joinable} {Rng} {Random}"
+    "#;
+
+    let loader = load(&dir, code).unwrap_or_else(|e| panic!("load error: {}", e));
+
+    let mut program = loader.program();
+    program.resolve();
+
+    let mut interpreter = DefaultInterpreter::default();
+    interpreter.run_bytecode(&program);
+
+    match *interpreter.ctx.pop() {
+        TerbiumObject::String(id) => assert_eq!(
+            interpreter.string_lookup(id),
+            "<class Iterator> <class MapIterator> <class Joinable> <class Rng> <class Random>"
+        ),
+        ref o => panic!("expected a string, got {:?}", o),
+    }
+}
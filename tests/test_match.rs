@@ -0,0 +1,77 @@
+use terbium::analyzer::{AnalyzerKind, AnalyzerMessageKind};
+use terbium::interpreter::TerbiumObject;
+
+mod interpreter;
+use interpreter::{analyze, interpret};
+
+#[test]
+fn test_match() {
+    let res = interpret(
+        r#"
+        match 1 + 1 {
+            1 -> 10,
+            2 -> {
+                let message = 20;
+                message
+            },
+            else 30,
+        }
+    "#,
+    );
+
+    assert_eq!(res, TerbiumObject::Integer(20));
+}
+
+#[test]
+fn test_match_patterns() {
+    let res = interpret(
+        r#"
+        match [1, [2, 3]] {
+            1 | 2 -> 0,
+            [a, [b, c]] if a == 2 -> 1,
+            [a, [_, c]] -> a + c,
+        }
+    "#,
+    );
+    assert_eq!(res, TerbiumObject::Integer(4));
+
+    let res = interpret("match 5 { 1 | 2 -> 0, -5 -> 1 }");
+    assert_eq!(res, TerbiumObject::Null);
+}
+
+#[test]
+fn test_match_unbalanced_arms() {
+    let code = r#"
+        let x = match 1 {
+            1 -> 1,
+            2 -> true,
+            else 0,
+        };
+    "#;
+
+    let messages = analyze(code);
+
+    assert!(messages
+        .iter()
+        .any(|m| m.kind == AnalyzerMessageKind::Alert(AnalyzerKind::UnbalancedMatchArms)));
+}
+
+#[test]
+fn test_match_arm_scopes() {
+    // Each arm binds its own `x`, even once an earlier arm bound it and failed
+    let res = interpret("match [5, 6] { [x, 0] -> x, [_, x] if x > 5 -> x * 10, else 0 }");
+    assert_eq!(res, TerbiumObject::Integer(60));
+
+    // Bindings of a failed arm are gone by the time the next arm is tested
+    let res = interpret(
+        r#"
+        let x = 1;
+        match [2, 3] {
+            [x, 0] -> 0,
+            [_, y] if x == 1 -> y,
+            else -1,
+        }
+    "#,
+    );
+    assert_eq!(res, TerbiumObject::Integer(3));
+}
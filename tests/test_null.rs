@@ -0,0 +1,110 @@
+use terbium::analyzer::{AnalyzerKind, PrimitiveType, Type};
+use terbium::grammar::{Body, Expr, ParseInterface, Source};
+
+mod interpreter;
+use interpreter::{count, interpret_string};
+
+#[test]
+fn test_optional_chaining() {
+    let res = interpret_string(
+        r#"
+        let mut depth = 0;
+
+        class Node {
+            func next() {
+                depth = depth + 1;
+                if depth < 3 { Node() }
+            }
+
+            func value() -> int { depth }
+        }
+
+        let mut calls = 0;
+        let count = () => { calls = calls + 1; calls };
+        let nothing = if false { 0 };
+
+        let a = Node().next()?.next()?.value();
+        depth = 0;
+        let b = Node().next()?.next()?.next()?.next().value(count());
+        let c = nothing?.(count());
+        let d = count?.();
+        let e = Node().value!();
+        let f = try { nothing! } catch NullError as e { e };
+
+        $"{a} {b} {c} {d} {e} {f} {calls}"
+    "#,
+    );
+
+    assert_eq!(res, r#"2 null null 1 3 NullError("unexpected null") 1"#);
+}
+
+#[test]
+#[should_panic(expected = "uncaught NullError(\"unexpected null\")")]
+fn test_non_null_assertion() {
+    interpret_string("let x = if false { 1 }; x!;");
+}
+
+#[test]
+fn test_null_literal() {
+    let res = interpret_string(
+        r#"
+        let x = null;
+        let y = match x { null -> "none", else "some" };
+
+        $"{x} {y} {x == null} {0 != null}"
+    "#,
+    );
+
+    assert_eq!(res, "null none true true");
+}
+
+#[test]
+fn test_null_parsing() {
+    match Expr::from_string(Source::default(), "a?.b.c!".to_string()) {
+        Ok(Expr::NonNull(chain)) => match chain.node() {
+            Expr::Attr {
+                subject,
+                attr,
+                optional: false,
+            } => {
+                assert_eq!(attr, "c");
+                assert!(matches!(subject.node(), Expr::Attr { optional: true, .. }));
+            }
+            other => panic!("unexpected expression: {:?}", other),
+        },
+        other => panic!("unexpected parse result: {:?}", other),
+    }
+
+    assert!(matches!(
+        Expr::from_string(Source::default(), "f?.(1)".to_string()),
+        Ok(Expr::Call { optional: true, .. })
+    ));
+
+    for code in ["a?.;", "a?b;", "a.?b;"] {
+        assert!(
+            Body::from_string(Source::default(), code.to_string()).is_err(),
+            "{} should not parse",
+            code,
+        );
+    }
+}
+
+#[test]
+fn test_null_analysis() {
+    let infer = |code: &str| {
+        let (ty, messages) = interpreter::infer(code);
+        (ty, count(&messages, AnalyzerKind::UnnecessaryNullChecks))
+    };
+    let int = || Type::Primitive(PrimitiveType::Int);
+    let nullable = |ty| Type::Union(Box::new(ty), Box::new(Type::Null));
+
+    assert_eq!(
+        infer("(x: ?int) => x!"),
+        (Type::Func(vec![nullable(int())], Box::new(int())), 0)
+    );
+    assert_eq!(
+        infer("(x: int) => x!"),
+        (Type::Func(vec![int()], Box::new(int())), 1)
+    );
+    assert_eq!(infer("(() -> int => 1)?.()"), (nullable(int()), 1));
+}
@@ -0,0 +1,139 @@
+use terbium::analyzer::AnalyzerKind;
+use terbium::grammar::{Expr, Operator, ParseInterface, Source};
+
+mod interpreter;
+use interpreter::{analyze, count, interpret_string};
+
+#[test]
+fn test_arithmetic() {
+    let res = interpret_string(
+        r#"
+        // Division floors and the remainder takes the sign of the divisor
+        let a = [7 / 2, -7 / 2, 7 / -2, -7 % 3, 7 % -3, 7.0 / 2, -7.5 % 2];
+        let b = [2 ** 10, 2 ** -1, 2.0 ** -1, 3 * 1.5];
+        let c = [1 << 4, -8 >> 1, 1 + 1 << 2, 6 & 3, 6 | 3, 6 ^ 3, ~5];
+        // True division always divides as floats
+        let d = [7 /. 2, -7 /. 2, 6 /. 3, 1 /.5, 2 ** 126 > 0, 1 << 200, -1 >> 200];
+
+        let mut x = 7;
+        x %= 4;
+        x **= 2;
+        x <<= 1;
+        x /.= 4;
+
+        $"{a} {b} {c} {d} {x}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        concat!(
+            "[3, -4, -4, 2, -2, 3.5, 0.5] [1024, 0, 0.5, 4.5] [16, -4, 8, 2, 7, 5, -6] ",
+            "[3.5, -3.5, 2, 2, true, 0, -1] 4.5",
+        )
+    );
+}
+
+#[test]
+fn test_bool_and_string_operators() {
+    let res = interpret_string(
+        r#"
+        let a = "apple";
+        let b = "banana";
+
+        let bools = [true & false, true | false, true ^ true, 1 && 0, 0 || 2.5];
+        let strings = [a < b, a >= b, a + b == "applebanana", a * 2];
+
+        $"{bools} {strings}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        r#"[false, true, false, false, true] [true, false, true, "appleapple"]"#
+    );
+}
+
+#[test]
+fn test_zero_division() {
+    let res = interpret_string(
+        r#"
+        let divide = (a, b) => try { a / b } catch ZeroDivisionError as e { e };
+
+        $"{divide(1, 0)} {divide(1.0, 0)} {try { 0 ** -1 } catch ZeroDivisionError { 0 }}"
+    "#,
+    );
+
+    assert_eq!(res, r#"ZeroDivisionError("division by zero") inf 0"#);
+}
+
+#[test]
+#[should_panic(expected = "uncaught ZeroDivisionError(\"modulo by zero\")")]
+fn test_uncaught_zero_division() {
+    interpret_string("1 % 0;");
+}
+
+#[test]
+fn test_unsupported_operands() {
+    let res = interpret_string(
+        r#"
+        let s = "a";
+
+        let a = try { s - 1 } catch TypeError as e { e };
+        let b = try { ~s } catch TypeError as e { e };
+        let c = try { 1 << -1 } catch ValueError as e { e };
+        let d = try { 2 ** 127 } catch ValueError as e { e };
+        let e = try { +s } catch TypeError as e { e };
+        let f = try { -true } catch TypeError as e { e };
+
+        $"{a} {b} {c} {d} {e} {f}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        concat!(
+            r#"TypeError("unsupported operands for -: \"a\" and 1") "#,
+            r#"TypeError("unsupported operand for ~: \"a\"") "#,
+            r#"ValueError("negative shift count -1") "#,
+            r#"ValueError("2 ** 127 overflows an int") "#,
+            r#"TypeError("unsupported operand for +: \"a\"") "#,
+            r#"TypeError("unsupported operand for -: true")"#,
+        )
+    );
+}
+
+#[test]
+fn test_operator_parsing() {
+    // Shifts bind looser than sums, but tighter than comparisons
+    match Expr::from_string(Source::default(), "a + b << c < d".to_string()) {
+        Ok(Expr::BinaryExpr { operator, lhs, .. }) => {
+            assert_eq!(operator.node(), &Operator::Lt);
+            assert!(matches!(
+                lhs.node(),
+                Expr::BinaryExpr { operator, .. } if operator.node() == &Operator::BitLShift
+            ));
+        }
+        other => panic!("unexpected parse result: {:?}", other),
+    }
+
+    // `/.` is true division unless the dot starts a float
+    for (code, expected) in [("a /. b", Operator::TrueDiv), ("1/.5", Operator::Div)] {
+        match Expr::from_string(Source::default(), code.to_string()) {
+            Ok(Expr::BinaryExpr { operator, .. }) => assert_eq!(operator.node(), &expected),
+            other => panic!("unexpected parse result: {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_operator_analysis() {
+    let code = r#"
+        let a = [true ^ false, "a" < "b", "a" * 3, 1 && "a", 7 % 2 << 1];
+        let b = "a" * "b";
+    "#;
+
+    let messages = analyze(code);
+
+    assert_eq!(count(&messages, AnalyzerKind::UnsupportedOperators), 1);
+}
@@ -0,0 +1,164 @@
+use terbium::analyzer::AnalyzerKind;
+use terbium::grammar::{Body, Expr, ParamKind, ParseInterface, Source};
+
+mod interpreter;
+use interpreter::{analyze, count, interpret_string};
+
+#[test]
+fn test_param_binding() {
+    let res = interpret_string(
+        r#"
+        let f = (a, b = 2, *rest, c = 3, ?d, **extra) => [a, b, rest, c, d, extra];
+
+        let mut n = 1;
+        let g = (x = n) => x;
+        n = 5;
+
+        $"{f(1)} {f(1, 5, 6, 7, d = 4)} {f(c = 0, a = 9, x = 8)} {g()}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        r#"[1, 2, [], 3, null, []] [1, 5, [6, 7], 3, 4, []] [9, 2, [], 0, null, [["x", 8]]] 1"#
+    );
+}
+
+#[test]
+fn test_param_unknown_keyword() {
+    let res = interpret_string(
+        r#"
+        let f = (a, *args) => a;
+        let a = try { f(1, b = 2) } catch TypeError as e { e };
+        let b = try { f() } catch TypeError as e { e };
+
+        $"{a}; {b}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        r#"TypeError("unknown keyword argument `b`"); TypeError("missing argument for parameter `a`")"#
+    );
+}
+
+#[test]
+fn test_call_errors() {
+    let res = interpret_string(
+        r#"
+        class Point {}
+
+        let a = try { Point(x = 1) } catch TypeError as e { e };
+        let b = try { 1(2) } catch TypeError as e { e };
+
+        $"{a}; {b}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        r#"TypeError("class Point takes no keyword arguments"); TypeError("1 is not callable")"#
+    );
+}
+
+#[test]
+fn test_argument_splats() {
+    let res = interpret_string(
+        r#"
+        let f = (a, b = 2, *rest, c = 3, **extra) => [a, b, rest, c, extra];
+        let forward = (*args, **kwargs) => f(*args, **kwargs);
+        let items = [4, 5];
+        let options = [["c", 0], ["x", 1]];
+
+        let a = try { f(*1) } catch TypeError as e { e };
+        let b = try { f(1, **[2]) } catch TypeError as e { e };
+
+        $"{f(1, *items, 6)} {forward(1, c = 0)} {f(*[], 7, **options)} {a}; {b}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        r#"[1, 4, [5, 6], 3, []] [1, 2, [], 0, []] [7, 2, [], 0, [["x", 1]]] TypeError("1 can not be unpacked as arguments"); TypeError("2 is not a keyword argument")"#
+    );
+}
+
+#[test]
+fn test_splat_parsing() {
+    match Expr::from_string(
+        Source::default(),
+        "f(1, *args, c = 2, **kwargs)".to_string(),
+    ) {
+        Ok(Expr::Call { args, kwargs, .. }) => {
+            assert!(matches!(args[1].node(), Expr::Splat(_)));
+            assert!(matches!(args[2].node(), Expr::KeywordSplat(_)));
+            assert_eq!(kwargs.len(), 1);
+        }
+        other => panic!("unexpected parse result: {:?}", other),
+    }
+
+    assert!(Expr::from_string(Source::default(), "f(**kwargs, 1)".to_string()).is_err());
+}
+
+#[test]
+fn test_param_parsing() {
+    match Expr::from_string(Source::default(), "(a, *, b, ?c, **d) => a".to_string()) {
+        Ok(Expr::Func { params, .. }) => {
+            let specs = params
+                .iter()
+                .map(|p| {
+                    let spec = p.node().spec();
+                    (spec.name, spec.kind, spec.keyword_only, spec.optional)
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                specs,
+                vec![
+                    ("a".to_string(), ParamKind::Normal, false, false),
+                    ("b".to_string(), ParamKind::Normal, true, false),
+                    ("c".to_string(), ParamKind::Normal, true, true),
+                    ("d".to_string(), ParamKind::KeywordVariadic, false, false),
+                ]
+            );
+        }
+        other => panic!("unexpected parse result: {:?}", other),
+    }
+
+    for code in [
+        "let f = (**d, a) => a;",
+        "let f = (*a, *b) => a;",
+        "let f = (?a = 1) => a;",
+        "let f = (a = 1, b) => b;",
+        "f(a = 1, 2);",
+    ] {
+        assert!(
+            Body::from_string(Source::default(), code.to_string()).is_err(),
+            "{} should not parse",
+            code,
+        );
+    }
+}
+
+#[test]
+fn test_param_analysis() {
+    let code = r#"
+        greet("a", loud = true);
+
+        func greet(name: string, *, loud: bool = false) -> string {
+            name
+        }
+
+        greet("a", true);
+        greet(name = "a", quiet = true);
+
+        let f = (a, ?b) => a;
+        f(1);
+        f();
+        f(1, a = 2);
+    "#;
+
+    let messages = analyze(code);
+
+    assert_eq!(count(&messages, AnalyzerKind::InvalidArguments), 4);
+}
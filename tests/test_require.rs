@@ -0,0 +1,70 @@
+use terbium::analyzer::AnalyzerKind;
+use terbium::grammar::{
+    Body, Node, ParseInterface, RequireItem, RequireItems, RequirePath, Source,
+};
+
+mod interpreter;
+use interpreter::{analyze, count};
+
+const CODE: &str = r#"
+    require std;
+    require fs, io from .;
+    require repr as _repr, print from .util;
+    require * from std.iter;
+"#;
+
+fn item(name: &str, alias: Option<&str>) -> RequireItem {
+    RequireItem {
+        name: name.to_string(),
+        alias: alias.map(ToString::to_string),
+    }
+}
+
+fn path(relative: bool, segments: &[&str]) -> Option<RequirePath> {
+    Some(RequirePath {
+        relative,
+        segments: segments.iter().map(ToString::to_string).collect(),
+    })
+}
+
+#[test]
+fn test_require() {
+    let Body(body, _) = Body::from_string(Source::default(), CODE.to_string())
+        .unwrap_or_else(|e| panic!("parse error: {:?}", e));
+
+    let requires = body
+        .iter()
+        .map(|node| match node.node() {
+            Node::Require { items, from } => (items.clone(), from.clone()),
+            other => panic!("expected require, found {:?}", other),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        requires,
+        vec![
+            (RequireItems::Named(vec![item("std", None)]), None),
+            (
+                RequireItems::Named(vec![item("fs", None), item("io", None)]),
+                path(true, &[]),
+            ),
+            (
+                RequireItems::Named(vec![item("repr", Some("_repr")), item("print", None)]),
+                path(true, &["util"]),
+            ),
+            (RequireItems::Glob, path(false, &["std", "iter"])),
+        ]
+    );
+    assert_eq!(requires[2].1.as_ref().unwrap().to_string(), ".util");
+
+    assert!(Body::from_string(Source::default(), "require *;".to_string()).is_err());
+}
+
+#[test]
+fn test_require_binds_names() {
+    let code = format!("{}\nlet x = [std, fs, _repr, print];", CODE);
+
+    let messages = analyze(&code);
+
+    assert_eq!(count(&messages, AnalyzerKind::UnresolvedIdentifiers), 0);
+}
@@ -0,0 +1,144 @@
+use terbium::analyzer::AnalyzerKind;
+use terbium::grammar::{Body, Node, ParseInterface, Source};
+
+mod interpreter;
+use interpreter::{analyze, count, interpret_string};
+
+const CODE: &str = r#"
+    class Counter(Iterator<int>) {
+        private count: int = 0;
+
+        skip(n: int) -> $ { $ }
+    }
+
+    trait Iterator<T> {
+        op next() -> T;
+
+        skip(n: int) -> $ {
+            $
+        }
+    }
+"#;
+
+#[test]
+fn test_trait() {
+    let Body(body, _) = Body::from_string(Source::default(), CODE.to_string())
+        .unwrap_or_else(|e| panic!("parse error: {:?}", e));
+
+    match body[1].node() {
+        Node::Trait {
+            name,
+            generics,
+            methods,
+            ..
+        } => {
+            assert_eq!(name, "Iterator");
+            assert_eq!(generics.len(), 1);
+            assert_eq!(generics[0].node().name, "T");
+            assert!(methods[0].op && methods[0].body.is_none());
+            assert!(!methods[1].op && methods[1].body.is_some());
+        }
+        other => panic!("expected trait, found {:?}", other),
+    }
+}
+
+#[test]
+fn test_trait_bounds() {
+    let code = r#"
+        trait IntoIterator<T, I: Iterator<T>> where T: int | string {
+            op iter() -> I;
+        }
+    "#;
+    let Body(body, _) = Body::from_string(Source::default(), code.to_string())
+        .unwrap_or_else(|e| panic!("parse error: {:?}", e));
+
+    match body[0].node() {
+        Node::Trait { generics, .. } => {
+            let bounds = generics
+                .iter()
+                .map(|g| (g.node().name.as_str(), g.node().bounds.len()))
+                .collect::<Vec<_>>();
+
+            assert_eq!(bounds, vec![("T", 1), ("I", 1)]);
+        }
+        other => panic!("expected trait, found {:?}", other),
+    }
+}
+
+#[test]
+fn test_trait_missing_methods() {
+    let messages = analyze(CODE);
+
+    assert_eq!(count(&messages, AnalyzerKind::MissingTraitMethods), 1,);
+}
+
+#[test]
+fn test_trait_default_methods() {
+    let code = r#"
+        trait Sized<T> {
+            op len() -> int;
+
+            is_empty() -> bool { $len() == missing }
+
+            first() -> T { "first" }
+        }
+    "#;
+
+    let messages = analyze(code);
+
+    assert_eq!(count(&messages, AnalyzerKind::UnresolvedIdentifiers), 1);
+    assert_eq!(count(&messages, AnalyzerKind::IncompatibleTypes), 1);
+}
+
+#[test]
+fn test_nested_trait_hoisting() {
+    let code = r#"
+        func make() -> int {
+            class Counter(Iterator<int>) {
+                skip(n: int) -> $ { $ }
+            }
+
+            trait Iterator<T> {
+                op next() -> T;
+            }
+
+            1
+        }
+    "#;
+
+    let messages = analyze(code);
+
+    // The trait is known before the class extending it is visited
+    assert_eq!(count(&messages, AnalyzerKind::MissingTraitMethods), 1);
+    assert_eq!(count(&messages, AnalyzerKind::UnresolvedIdentifiers), 0);
+}
+
+#[test]
+fn test_trait_runtime() {
+    let res = interpret_string(
+        r#"
+        trait Named {
+            name() -> string;
+
+            greet() -> string { "hello " + $name() }
+        }
+
+        trait Loud {
+            shout() -> string;
+        }
+
+        class World(Named) {
+            name() -> string { "world" }
+        }
+
+        extend Loud for World {
+            shout() -> string { $greet() + "!" }
+        }
+
+        let world = World();
+        $"{world.greet()}, {world.shout()}"
+    "#,
+    );
+
+    assert_eq!(res, "hello world, hello world!");
+}
@@ -0,0 +1,156 @@
+use terbium::analyzer::{PrimitiveType, Type};
+use terbium::grammar::{Body, Expr, ParseInterface, Source, TypeExpr};
+
+mod interpreter;
+use interpreter::interpret_string;
+
+#[test]
+fn test_type_tests() {
+    let res = interpret_string(
+        r#"
+        class Shape {}
+        class Square(Shape) {}
+
+        let nothing = if false { 0 };
+        let s = "a";
+
+        let a = 1 is int;
+        let b = 1.5 is int;
+        let c = s is string | int;
+        let d = nothing is ?int;
+        let e = [1, 2] is int[];
+        let f = [1, 2] is int[3];
+        let g = Square() is Shape;
+        let h = Shape() is not Square;
+        let i = 1 is int & !string;
+
+        $"{a} {b} {c} {d} {e} {f} {g} {h} {i}"
+    "#,
+    );
+
+    assert_eq!(res, "true false true true true false true true true");
+}
+
+#[test]
+fn test_membership() {
+    let res = interpret_string(
+        r#"
+        class Small {
+            op contains(x) { x < 10 }
+        }
+
+        let word = "hello";
+        let part = "ell";
+
+        let a = 2 in [1, 2, 3];
+        let b = [1] in [[0], [1]];
+        let c = part in word;
+        // Ranges bind looser than `in`
+        let d = 5 in (0..5);
+        let e = 4 in Small();
+
+        $"{a} {b} {c} {d} {e}"
+    "#,
+    );
+
+    assert_eq!(res, "true true true false true");
+}
+
+#[test]
+fn test_unsupported_membership() {
+    let res = interpret_string(
+        r#"
+        let word = "hello";
+        let not_class = 3;
+
+        let a = try { 1 in 2 } catch TypeError as e { e };
+        let b = try { 1 in word } catch TypeError as e { e };
+        let c = try { 1 is not_class } catch TypeError as e { e };
+
+        $"{a} {b} {c}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        concat!(
+            "TypeError(\"2 does not support `in`\") ",
+            "TypeError(\"only a string can be in a string, got 1\") ",
+            "TypeError(\"expected a class, got 3\")",
+        )
+    );
+}
+
+#[test]
+fn test_type_test_parsing() {
+    match Expr::from_string(
+        Source::default(),
+        "x is not string | null == y in z".to_string(),
+    ) {
+        Ok(Expr::BinaryExpr { lhs, rhs, .. }) => {
+            match lhs.node() {
+                Expr::Is {
+                    ty, negated: true, ..
+                } => assert!(matches!(ty.node(), TypeExpr::Union(..))),
+                other => panic!("unexpected expression: {:?}", other),
+            }
+            assert!(matches!(rhs.node(), Expr::In { .. }));
+        }
+        other => panic!("unexpected parse result: {:?}", other),
+    }
+
+    for code in ["x is;", "x is not;", "x in;", "in x;"] {
+        assert!(
+            Body::from_string(Source::default(), code.to_string()).is_err(),
+            "{} should not parse",
+            code,
+        );
+    }
+}
+
+#[test]
+fn test_narrowing() {
+    let infer = |code: &str| {
+        let (ty, messages) = interpreter::infer(code);
+        (ty, messages.len())
+    };
+    let int = || Type::Primitive(PrimitiveType::Int);
+    let string = || Type::Primitive(PrimitiveType::String);
+    let union = |a, b| Type::Union(Box::new(a), Box::new(b));
+
+    assert_eq!(
+        infer("(x: ?int) => if x is null { 0 } else { x }"),
+        (
+            Type::Func(vec![union(int(), Type::Null)], Box::new(int())),
+            0
+        )
+    );
+    assert_eq!(
+        infer("(x: int | string) => if x is not string { x + 1 } else { 0 }"),
+        (Type::Func(vec![union(int(), string())], Box::new(int())), 0)
+    );
+    assert_eq!(
+        infer(
+            "(x: int | string | null) => if x is int { x } else if x is null { true } else { x }"
+        ),
+        (
+            Type::Func(
+                vec![union(union(int(), string()), Type::Null)],
+                Box::new(union(
+                    union(int(), Type::Primitive(PrimitiveType::Bool)),
+                    string()
+                )),
+            ),
+            1,
+        )
+    );
+}
+
+#[test]
+fn test_unmodeled_type_tests() {
+    // Module members, negations and `$` are accepted but narrow nothing yet
+    for ty in ["m.T", "!int", "$"] {
+        let code = format!("let m = 1; let x = m; if x is {} {{ x }} else {{ 0 }}", ty);
+        assert!(interpreter::analyze(&code).is_empty(), "{}", code);
+    }
+}
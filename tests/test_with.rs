@@ -0,0 +1,114 @@
+use terbium::analyzer::AnalyzerKind;
+use terbium::grammar::{Body, Expr, ParseInterface, Source};
+
+mod interpreter;
+use interpreter::{analyze, count, interpret_string};
+
+#[test]
+fn test_with_exits() {
+    let res = interpret_string(
+        r#"
+        let mut log = "";
+
+        class Resource {
+            op enter() {
+                log = $"{log}enter {$};";
+                $
+            }
+
+            op exit() {
+                log = $"{log}exit {$};";
+            }
+
+            func describe() -> string {
+                $"resource {$}"
+            }
+        }
+        class Failure(Error) {}
+
+        let a = with Resource("a") as r, Resource("b") { r.describe() };
+
+        let f = () => {
+            with Resource("c") { return 1; }
+        };
+        f();
+
+        for i in 0..3 {
+            with Resource(i) {
+                if i == 1 { break; }
+            }
+        }
+
+        for i in 0..2 {
+            with Resource(i + 10) {
+                if i == 0 { continue; }
+                log = $"{log}body {i};";
+            }
+        }
+
+        let b = try {
+            with Resource("d") { throw Failure; }
+        } catch Failure {
+            2
+        };
+
+        $"{a} {b} {log}"
+    "#,
+    );
+
+    assert_eq!(
+        res,
+        concat!(
+            r#"resource Resource("a") 2 "#,
+            r#"enter Resource("a");enter Resource("b");exit Resource("b");exit Resource("a");"#,
+            r#"enter Resource("c");exit Resource("c");"#,
+            "enter Resource(0);exit Resource(0);enter Resource(1);exit Resource(1);",
+            "enter Resource(10);exit Resource(10);enter Resource(11);body 1;exit Resource(11);",
+            r#"enter Resource("d");exit Resource("d");"#,
+        )
+    );
+}
+
+#[test]
+fn test_with_non_manager() {
+    let res = interpret_string(r#"try { with 1 { 2 } } catch TypeError as e { $"{e}" }"#);
+
+    assert_eq!(res, r#"TypeError("1 does not define `op enter`")"#);
+}
+
+#[test]
+fn test_with_parsing() {
+    match Expr::from_string(Source::default(), "with a() as x, b { x }".to_string()) {
+        Ok(Expr::With { managers, .. }) => {
+            let bindings = managers.into_iter().map(|(_, b)| b).collect::<Vec<_>>();
+
+            assert_eq!(bindings, vec![Some("x".to_string()), None]);
+        }
+        other => panic!("unexpected parse result: {:?}", other),
+    }
+
+    for code in ["with { 1 };", "with a as { 1 };", "with a, { 1 };"] {
+        assert!(
+            Body::from_string(Source::default(), code.to_string()).is_err(),
+            "{} should not parse",
+            code,
+        );
+    }
+}
+
+#[test]
+fn test_with_analysis() {
+    let code = r#"
+        let a = 1;
+
+        with a as f, a as g {
+            f
+        };
+        g;
+    "#;
+
+    let messages = analyze(code);
+
+    // Bindings are only visible within the body
+    assert_eq!(count(&messages, AnalyzerKind::UnresolvedIdentifiers), 1);
+}